@@ -0,0 +1,4 @@
+-- Settlement latency distribution persisted alongside the reliability score
+ALTER TABLE corridors ADD COLUMN median_settlement_latency_ms INTEGER;
+ALTER TABLE corridors ADD COLUMN p95_settlement_latency_ms INTEGER;
+ALTER TABLE corridors ADD COLUMN p99_settlement_latency_ms INTEGER;
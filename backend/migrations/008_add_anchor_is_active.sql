@@ -0,0 +1,4 @@
+-- Deactivated anchors keep their history but drop out of default listings
+ALTER TABLE anchors ADD COLUMN is_active BOOLEAN NOT NULL DEFAULT TRUE;
+
+CREATE INDEX idx_anchors_is_active ON anchors(is_active);
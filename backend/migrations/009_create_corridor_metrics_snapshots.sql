@@ -0,0 +1,19 @@
+-- One row per corridor per metrics run, so trends can be charted
+CREATE TABLE IF NOT EXISTS corridor_metrics_snapshots (
+    id TEXT PRIMARY KEY,
+    corridor_id TEXT NOT NULL REFERENCES corridors(id) ON DELETE CASCADE,
+    total_transactions BIGINT NOT NULL DEFAULT 0,
+    successful_transactions BIGINT NOT NULL DEFAULT 0,
+    failed_transactions BIGINT NOT NULL DEFAULT 0,
+    success_rate DOUBLE PRECISION NOT NULL DEFAULT 0,
+    volume_usd DOUBLE PRECISION NOT NULL DEFAULT 0,
+    median_settlement_latency_ms INTEGER,
+    p95_settlement_latency_ms INTEGER,
+    p99_settlement_latency_ms INTEGER,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_corridor_metrics_snapshots_corridor_time
+    ON corridor_metrics_snapshots(corridor_id, recorded_at DESC);
+CREATE INDEX idx_corridor_metrics_snapshots_recorded_at
+    ON corridor_metrics_snapshots(recorded_at);
@@ -0,0 +1,2 @@
+-- When the anchor last had any transaction, so dormant anchors can be flagged
+ALTER TABLE anchors ADD COLUMN last_activity_at TIMESTAMPTZ;
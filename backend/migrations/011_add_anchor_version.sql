@@ -0,0 +1,2 @@
+-- Bumped on every metrics write so concurrent writers can detect a lost update
+ALTER TABLE anchors ADD COLUMN version BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,47 @@
+-- The original tables stored timestamps as TEXT, so a value written without an
+-- offset was read back in whatever time zone the session used. Convert every
+-- timestamp column the models read as DateTime<Utc> to TIMESTAMPTZ, taking
+-- text that carries no offset to be UTC.
+DO $$
+DECLARE
+    target RECORD;
+BEGIN
+    FOR target IN
+        SELECT c.table_name, c.column_name, c.column_default
+        FROM information_schema.columns c
+        JOIN (VALUES
+            ('anchors', 'created_at'),
+            ('anchors', 'updated_at'),
+            ('assets', 'created_at'),
+            ('assets', 'updated_at'),
+            ('anchor_metrics_history', 'timestamp'),
+            ('anchor_metrics_history', 'created_at'),
+            ('corridors', 'created_at'),
+            ('corridors', 'updated_at'),
+            ('corridor_metrics', 'date'),
+            ('corridor_metrics', 'created_at'),
+            ('corridor_metrics', 'updated_at'),
+            ('metrics', 'timestamp'),
+            ('metrics', 'created_at'),
+            ('snapshots', 'timestamp'),
+            ('snapshots', 'created_at'),
+            ('payments', 'created_at'),
+            ('ingestion_state', 'updated_at')
+        ) AS wanted(table_name, column_name)
+            ON c.table_name = wanted.table_name AND c.column_name = wanted.column_name
+        WHERE c.table_schema = current_schema() AND c.data_type = 'text'
+    LOOP
+        EXECUTE format('ALTER TABLE %I ALTER COLUMN %I DROP DEFAULT',
+            target.table_name, target.column_name);
+        EXECUTE format(
+            'ALTER TABLE %1$I ALTER COLUMN %2$I TYPE TIMESTAMPTZ USING CASE '
+            || 'WHEN %2$I ~ ''\d{2}:\d{2}(:\d{2}(\.\d+)?)?\s*(Z|[+-]\d{2}(:?\d{2})?)$'' '
+            || 'THEN %2$I::TIMESTAMPTZ '
+            || 'ELSE %2$I::TIMESTAMP AT TIME ZONE ''UTC'' END',
+            target.table_name, target.column_name);
+        IF target.column_default IS NOT NULL THEN
+            EXECUTE format('ALTER TABLE %I ALTER COLUMN %I SET DEFAULT CURRENT_TIMESTAMP',
+                target.table_name, target.column_name);
+        END IF;
+    END LOOP;
+END $$;
@@ -0,0 +1,4 @@
+-- Share of attempted USD volume that settled, persisted next to the count-based
+-- reliability score. NULL for corridors and snapshots written before this column.
+ALTER TABLE corridors ADD COLUMN volume_weighted_success_rate DOUBLE PRECISION;
+ALTER TABLE corridor_metrics_snapshots ADD COLUMN volume_weighted_success_rate DOUBLE PRECISION;
@@ -76,7 +76,7 @@ pub fn compute_anchor_metrics(
 
 /// Calculate settlement time score (0-100)
 /// Lower settlement time = higher score
-fn calculate_settlement_time_score(avg_settlement_time_ms: Option<i32>) -> f64 {
+pub(crate) fn calculate_settlement_time_score(avg_settlement_time_ms: Option<i32>) -> f64 {
     const MAX_SETTLEMENT_TIME_MS: f64 = 10000.0; // 10 seconds
     const MIN_SETTLEMENT_TIME_MS: f64 = 1000.0; // 1 second
 
@@ -6,6 +6,8 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 
+use crate::database::{AnchorCursor, AnchorFilters, SortSpec, ANCHOR_SORT_COLUMNS};
+use crate::http_cache::TotalCount;
 use crate::state::AppState;
 
 pub type ApiResult<T> = Result<T, ApiError>;
@@ -47,6 +49,28 @@ pub struct ListAnchorsQuery {
     pub limit: i64,
     #[serde(default)]
     pub offset: i64,
+    /// Optional search over anchor name and Stellar account
+    #[serde(default)]
+    pub q: Option<String>,
+    /// One of `name`, `total_transactions`, `volume_usd`, `created_at`
+    #[serde(default)]
+    pub sort_by: Option<String>,
+    /// `asc` (default) or `desc`
+    #[serde(default)]
+    pub order: Option<String>,
+    /// Keyset pagination cursor from a previous `next_cursor`; pass it empty to
+    /// start from the first page. Replaces `offset`, `q` and `sort_by`.
+    #[serde(default)]
+    pub after: Option<String>,
+    /// Also list deactivated anchors, in the offset, search and cursor listings alike
+    #[serde(default)]
+    pub include_inactive: bool,
+    /// Lowest total volume in USD to include. Applies to the offset listing only.
+    #[serde(default)]
+    pub min_volume: Option<f64>,
+    /// Lowest success rate (0-100) to include. Applies to the offset listing only.
+    #[serde(default)]
+    pub min_success_rate: Option<f64>,
 }
 
 fn default_limit() -> i64 {
@@ -70,22 +94,98 @@ pub struct AnchorMetricsResponse {
 #[derive(Debug, Serialize)]
 pub struct AnchorsResponse {
     pub anchors: Vec<AnchorMetricsResponse>,
-    pub total: usize,
+    pub total: i64,
+    /// Cursor for the following page in keyset mode; absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
-/// GET /api/anchors - List all anchors with key metrics
+/// GET /api/anchors - List all anchors with key metrics, optionally filtered by `q`
 pub async fn get_anchors(
     State(app_state): State<AppState>,
     Query(params): Query<ListAnchorsQuery>,
-) -> ApiResult<Json<AnchorsResponse>> {
-    let anchors = app_state.db.list_anchors(params.limit, params.offset).await?;
+) -> ApiResult<(TotalCount, Json<AnchorsResponse>)> {
+    let sort = SortSpec::parse(
+        params.sort_by.as_deref(),
+        params.order.as_deref(),
+        ANCHOR_SORT_COLUMNS,
+    )
+    .map_err(ApiError::BadRequest)?;
+    let filters = AnchorFilters::new(params.min_volume, params.min_success_rate)
+        .map_err(ApiError::BadRequest)?;
+    let search = params.q.as_deref().map(str::trim).filter(|q| !q.is_empty());
+    let mut next_cursor = None;
+    let (anchors, total) = if let Some(after) = params.after.as_deref() {
+        if params.q.is_some() || params.sort_by.is_some() {
+            return Err(ApiError::BadRequest(
+                "after cannot be combined with q or sort_by".to_string(),
+            ));
+        }
+        let cursor = if after.is_empty() {
+            None
+        } else {
+            Some(
+                AnchorCursor::decode(after)
+                    .map_err(|e| ApiError::BadRequest(format!("Invalid cursor: {}", e)))?,
+            )
+        };
+        let anchors = app_state
+            .db
+            .list_anchors_after(cursor.as_ref(), params.limit, params.include_inactive)
+            .await?;
+        if anchors.len() as i64 >= params.limit {
+            next_cursor = anchors
+                .last()
+                .map(|a| AnchorCursor::from_anchor(a).encode());
+        }
+        let total = app_state
+            .db
+            .count_listed_anchors(params.include_inactive, &AnchorFilters::default())
+            .await?;
+        (anchors, total)
+    } else {
+        match search {
+            Some(q) => (
+                app_state
+                    .db
+                    .search_anchors(
+                        q,
+                        params.limit,
+                        params.offset,
+                        sort.as_ref(),
+                        params.include_inactive,
+                    )
+                    .await?,
+                app_state
+                    .db
+                    .count_search_anchors(q, params.include_inactive)
+                    .await?,
+            ),
+            None => (
+                app_state
+                    .db
+                    .list_anchors(
+                        params.limit,
+                        params.offset,
+                        sort.as_ref(),
+                        params.include_inactive,
+                        &filters,
+                    )
+                    .await?,
+                app_state
+                    .db
+                    .count_listed_anchors(params.include_inactive, &filters)
+                    .await?,
+            ),
+        }
+    };
 
     let mut anchor_responses = Vec::new();
 
     for anchor in anchors {
         // Get assets for each anchor to calculate asset coverage
         let anchor_id = uuid::Uuid::parse_str(&anchor.id).unwrap_or_else(|_| uuid::Uuid::nil());
-        let assets = app_state.db.get_assets_by_anchor(anchor_id).await?;
+        let assets = app_state.db.get_all_assets_by_anchor(anchor_id).await?;
 
         let failure_rate = if anchor.total_transactions > 0 {
             (anchor.failed_transactions as f64 / anchor.total_transactions as f64) * 100.0
@@ -109,12 +209,14 @@ pub async fn get_anchors(
         anchor_responses.push(anchor_response);
     }
 
-    let total = anchor_responses.len();
-
-    Ok(Json(AnchorsResponse {
-        anchors: anchor_responses,
-        total,
-    }))
+    Ok((
+        TotalCount(total),
+        Json(AnchorsResponse {
+            anchors: anchor_responses,
+            total,
+            next_cursor,
+        }),
+    ))
 }
 
 #[cfg(test)]
@@ -139,6 +241,9 @@ mod tests {
             avg_settlement_time_ms: 2000,
             reliability_score: 95.5,
             status: "green".to_string(),
+            is_active: true,
+            last_activity_at: None,
+            version: 0,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -180,6 +285,9 @@ mod tests {
             avg_settlement_time_ms: 0,
             reliability_score: 0.0,
             status: "red".to_string(),
+            is_active: true,
+            last_activity_at: None,
+            version: 0,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -207,6 +315,9 @@ mod tests {
             avg_settlement_time_ms: 5000,
             reliability_score: 80.0,
             status: "yellow".to_string(),
+            is_active: true,
+            last_activity_at: None,
+            version: 0,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -2,12 +2,13 @@ use axum::{
     extract::{Path, Query, State},
     Json,
 };
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::handlers::{ApiError, ApiResult};
 use crate::models::corridor::{Corridor, CorridorMetrics};
 use crate::models::SortBy;
+use crate::services::analytics::grade_corridor;
 use crate::state::AppState;
 
 // Response DTOs matching frontend TypeScript interfaces
@@ -29,7 +30,9 @@ pub struct CorridorResponse {
     pub liquidity_volume_24h_usd: f64,
     pub liquidity_trend: String,
     pub health_score: f64,
-    pub last_updated: String,
+    /// A–F letter grade, or `?` when there are too few transactions to judge
+    pub health_grade: char,
+    pub last_updated: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -156,10 +159,16 @@ pub async fn list_corridors(
                 successful_transactions: m.successful_transactions,
                 failed_transactions: m.failed_transactions,
                 success_rate: m.avg_success_rate,
+                // The daily aggregates don't keep attempted volume, and
+                // CorridorResponse doesn't expose the weighted rate
+                volume_weighted_success_rate: 0.0,
                 volume_usd: m.total_volume_usd,
                 avg_settlement_latency_ms: None,
                 median_settlement_latency_ms: None,
+                p95_settlement_latency_ms: None,
+                p99_settlement_latency_ms: None,
                 liquidity_depth_usd: m.total_volume_usd,
+                skipped_fx: 0,
                 created_at: m.latest_date,
                 updated_at: m.latest_date,
             })
@@ -214,7 +223,7 @@ pub async fn list_corridors(
         })
         .collect();
 
-    let corridors: Vec<CorridorResponse> = filtered_metrics
+    let mut corridors: Vec<CorridorResponse> = filtered_metrics
         .iter()
         .map(|m| {
             let health_score =
@@ -238,11 +247,20 @@ pub async fn list_corridors(
                 liquidity_volume_24h_usd: m.volume_usd * 0.1,
                 liquidity_trend,
                 health_score,
-                last_updated: m.updated_at.to_rfc3339(),
+                health_grade: grade_corridor(m),
+                last_updated: m.updated_at,
             }
         })
         .collect();
 
+    // Highest first, whichever column the dashboard sorts by
+    let sort_key = |c: &CorridorResponse| match params.sort_by {
+        SortBy::SuccessRate => c.success_rate,
+        SortBy::HealthScore => c.health_score,
+        SortBy::Volume | SortBy::Liquidity => c.liquidity_depth_usd,
+    };
+    corridors.sort_by(|a, b| sort_key(b).total_cmp(&sort_key(a)));
+
     Ok(Json(corridors))
 }
 
@@ -315,7 +333,8 @@ pub async fn get_corridor_detail(
         liquidity_volume_24h_usd: latest.volume_usd * 0.1,
         liquidity_trend,
         health_score,
-        last_updated: latest.updated_at.to_rfc3339(),
+        health_grade: grade_corridor(latest),
+        last_updated: latest.updated_at,
     };
 
     let historical_success_rate: Vec<SuccessRateDataPoint> = metrics
@@ -400,7 +419,8 @@ pub async fn get_corridor_detail(
                 liquidity_volume_24h_usd: m.volume_usd * 0.1,
                 liquidity_trend,
                 health_score,
-                last_updated: m.updated_at.to_rfc3339(),
+                health_grade: grade_corridor(m),
+                last_updated: m.updated_at,
             }
         })
         .collect();
@@ -435,10 +455,14 @@ mod tests {
             successful_transactions: 950,
             failed_transactions: 50,
             success_rate: 95.0,
+            volume_weighted_success_rate: 97.5,
             volume_usd: 1000000.0,
             avg_settlement_latency_ms: Some(400),
             median_settlement_latency_ms: Some(300),
+            p95_settlement_latency_ms: Some(900),
+            p99_settlement_latency_ms: Some(1500),
             liquidity_depth_usd: 500000.0,
+            skipped_fx: 0,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -459,7 +483,8 @@ mod tests {
             liquidity_volume_24h_usd: metrics.volume_usd * 0.1,
             liquidity_trend: "stable".to_string(),
             health_score: 95.0,
-            last_updated: metrics.updated_at.to_rfc3339(),
+            health_grade: grade_corridor(&metrics),
+            last_updated: metrics.updated_at,
         };
 
         assert_eq!(response.source_asset, "EURC");
@@ -469,5 +494,37 @@ mod tests {
         assert_eq!(response.liquidity_depth_usd, 1000000.0);
         assert!(response.id.contains("EURC:issuer2"));
         assert!(response.id.contains("USDC:issuer1"));
+        // 95% success with a 900ms p95 grades C
+        assert_eq!(response.health_grade, 'C');
+    }
+
+    #[test]
+    fn test_last_updated_serializes_as_rfc3339_utc() {
+        let updated_at = DateTime::parse_from_rfc3339("2024-03-01T12:30:45.123456+02:00")
+            .unwrap()
+            .with_timezone(&Utc);
+        let response = CorridorResponse {
+            id: "EURC:issuer2->USDC:issuer1".to_string(),
+            source_asset: "EURC".to_string(),
+            destination_asset: "USDC".to_string(),
+            success_rate: 95.0,
+            total_attempts: 1000,
+            successful_payments: 950,
+            failed_payments: 50,
+            average_latency_ms: 400.0,
+            median_latency_ms: 300.0,
+            p95_latency_ms: 900.0,
+            p99_latency_ms: 1500.0,
+            liquidity_depth_usd: 500000.0,
+            liquidity_volume_24h_usd: 50000.0,
+            liquidity_trend: "stable".to_string(),
+            health_score: 95.0,
+            health_grade: 'C',
+            last_updated: updated_at,
+        };
+
+        let json = serde_json::to_value(&response).unwrap();
+
+        assert_eq!(json["last_updated"], "2024-03-01T10:30:45.123456Z");
     }
 }
@@ -0,0 +1,6321 @@
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::future::BoxFuture;
+use rand::Rng;
+use redis::aio::MultiplexedConnection;
+use redis::AsyncCommands;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::future::Future;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{watch, Mutex, Notify, RwLock};
+use tokio::task::{JoinHandle, JoinSet};
+use tracing::Instrument;
+
+/// Why a cache operation failed
+#[derive(Debug)]
+pub enum CacheError {
+    /// Redis could not be reached, or its URL or TLS setup is unusable
+    Connection(String),
+    /// A value could not be serialized (or compressed) for storage
+    Serialization(String),
+    /// A stored value could not be decompressed or deserialized
+    Deserialization(String),
+    /// A Redis command failed
+    Redis(redis::RedisError),
+}
+
+impl std::fmt::Display for CacheError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CacheError::Connection(msg) => write!(f, "Redis connection error: {}", msg),
+            CacheError::Serialization(msg) => {
+                write!(f, "Failed to serialize value for cache: {}", msg)
+            }
+            CacheError::Deserialization(msg) => {
+                write!(f, "Failed to deserialize cached value: {}", msg)
+            }
+            CacheError::Redis(e) => write!(f, "Redis error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CacheError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CacheError::Redis(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<redis::RedisError> for CacheError {
+    fn from(err: redis::RedisError) -> Self {
+        CacheError::Redis(err)
+    }
+}
+
+pub type Result<T, E = CacheError> = std::result::Result<T, E>;
+
+/// Builders for every cache key used by the application, so the key layout
+/// lives in one place and invalidation patterns stay in sync with it.
+///
+/// Invariant: caller-supplied segments pass through [`escape_key_segment`], so
+/// no built key contains a Redis glob metacharacter (`*`, `?`, `[`, `]`, `\`)
+/// and patterns like `corridor:*` only ever match what their prefix says.
+pub struct CacheKey;
+
+impl CacheKey {
+    pub fn anchor_list(limit: i64, offset: i64, sort: &str, include_inactive: bool) -> String {
+        format!(
+            "anchor:list:{}:{}:{}:{}",
+            limit,
+            offset,
+            escape_key_segment(sort),
+            if include_inactive { "all" } else { "active" }
+        )
+    }
+
+    /// Search results page, keyed by a hash of the search term so arbitrary
+    /// user input never ends up in the key. The search is case-insensitive, so
+    /// the term is trimmed and lower-cased first and equivalent searches share a page.
+    pub fn anchor_search(
+        query: &str,
+        limit: i64,
+        offset: i64,
+        sort: &str,
+        include_inactive: bool,
+    ) -> String {
+        format!(
+            "anchor:search:{}:{}:{}:{}:{}",
+            hash_filters(&query.trim().to_lowercase()),
+            limit,
+            offset,
+            escape_key_segment(sort),
+            if include_inactive { "all" } else { "active" }
+        )
+    }
+
+    /// Keyset page starting after `cursor` (empty for the first page)
+    pub fn anchor_cursor_page(cursor: &str, limit: i64, include_inactive: bool) -> String {
+        format!(
+            "anchor:cursor:{}:{}:{}",
+            hash_filters(cursor),
+            limit,
+            if include_inactive { "all" } else { "active" }
+        )
+    }
+
+    pub fn anchor_count() -> String {
+        "anchor:count".to_string()
+    }
+
+    /// Count of anchors that haven't been deactivated
+    pub fn active_anchor_count() -> String {
+        "anchor:count:active".to_string()
+    }
+
+    pub fn anchor_data(anchor_id: &str) -> String {
+        format!("anchor:data:{}", escape_key_segment(anchor_id))
+    }
+
+    pub fn anchor_detail(anchor_id: &str) -> String {
+        format!("anchor:detail:{}", escape_key_segment(anchor_id))
+    }
+
+    pub fn anchor_by_account(stellar_account: &str) -> String {
+        format!("anchor:account:{}", escape_key_segment(stellar_account))
+    }
+
+    /// One page of an anchor's assets
+    pub fn anchor_assets(anchor_id: &str, limit: i64, offset: i64) -> String {
+        format!(
+            "anchor:assets:{}:{}:{}",
+            escape_key_segment(anchor_id),
+            limit,
+            offset
+        )
+    }
+
+    /// Matches every cached page of an anchor's assets
+    pub fn anchor_assets_pattern(anchor_id: &str) -> String {
+        format!("anchor:assets:{}:*", escape_key_segment(anchor_id))
+    }
+
+    /// Every fixed key derived from an anchor's id. Add new per-anchor keys here
+    /// so each invalidation that drops an anchor picks them up; asset pages vary
+    /// by page and are matched by `anchor_assets_pattern` instead.
+    pub fn all_keys_for_anchor(anchor_id: &str) -> Vec<String> {
+        vec![Self::anchor_data(anchor_id), Self::anchor_detail(anchor_id)]
+    }
+
+    /// Remembers that the lookup cached under `key` found nothing. It shares
+    /// `key`'s prefix, so whatever sweep invalidates `key` drops it too.
+    pub fn not_found(key: &str) -> String {
+        format!("{}:missing", key)
+    }
+
+    /// Tag grouping every cached key that belongs to one anchor
+    pub fn anchor_tag(anchor_id: &str) -> String {
+        format!("tag:anchor:{}", escape_key_segment(anchor_id))
+    }
+
+    /// Anchors issuing `asset_code`. The code is upper-cased so every spelling a
+    /// user types shares one entry, matching the case-insensitive lookup.
+    pub fn asset_anchors(asset_code: &str) -> String {
+        format!(
+            "asset:{}:anchors",
+            escape_key_segment(&asset_code.to_ascii_uppercase())
+        )
+    }
+
+    /// Corridors with an asset on either side. Like `asset_anchors` the code is
+    /// upper-cased; an issuer narrows it to one asset and is appended, so
+    /// `asset_corridors_pattern` covers every issuer's entry for the code.
+    pub fn asset_corridors(asset_code: &str, asset_issuer: Option<&str>) -> String {
+        let key = format!(
+            "asset:{}:corridors",
+            escape_key_segment(&asset_code.to_ascii_uppercase())
+        );
+        match asset_issuer {
+            Some(issuer) => format!("{}:{}", key, escape_key_segment(issuer)),
+            None => key,
+        }
+    }
+
+    /// Glob matching every `asset_corridors` key for `asset_code`
+    pub fn asset_corridors_pattern(asset_code: &str) -> String {
+        format!("{}*", Self::asset_corridors(asset_code, None))
+    }
+
+    /// Aggregated metrics for one asset. Unlike `asset_anchors` the code keeps
+    /// its case, since it names a single asset exactly.
+    pub fn asset_metrics(asset_code: &str, asset_issuer: &str) -> String {
+        format!(
+            "asset:metrics:{}:{}",
+            escape_key_segment(asset_code),
+            escape_key_segment(asset_issuer)
+        )
+    }
+
+    pub fn corridor_list(limit: i64, offset: i64, filters: &str) -> String {
+        format!(
+            "corridor:list:{}:{}:{}",
+            limit,
+            offset,
+            escape_key_segment(filters)
+        )
+    }
+
+    pub fn corridor_count() -> String {
+        "corridor:count".to_string()
+    }
+
+    pub fn corridor_detail(corridor_key: &str) -> String {
+        format!("corridor:detail:{}", escape_key_segment(corridor_key))
+    }
+
+    pub fn corridor_metrics(corridor_key: &str) -> String {
+        format!("corridor:metrics:{}", escape_key_segment(corridor_key))
+    }
+
+    /// Snapshot series covering the last `hours` of a corridor's metrics runs
+    pub fn corridor_metrics_history(corridor_id: &str, hours: i64) -> String {
+        format!(
+            "corridor:history:{}:{}",
+            escape_key_segment(corridor_id),
+            hours
+        )
+    }
+
+    /// Pattern matching every cached history window of one corridor
+    pub fn corridor_metrics_history_pattern(corridor_id: &str) -> String {
+        format!("corridor:history:{}:*", escape_key_segment(corridor_id))
+    }
+
+    /// Stored result of a create request carrying `Idempotency-Key: <key>`,
+    /// scoped per endpoint so one key reused across endpoints can't collide
+    pub fn idempotency(scope: &str, key: &str) -> String {
+        format!("idempotency:{}:{}", scope, escape_key_segment(key))
+    }
+
+    /// Held by whichever replica is running the create for an `Idempotency-Key`
+    pub fn idempotency_claim(scope: &str, key: &str) -> String {
+        format!("lock:idempotency:{}:{}", scope, escape_key_segment(key))
+    }
+
+    /// Held by whichever replica is running the metrics ingestion job
+    pub fn metrics_ingestion_lock() -> String {
+        "lock:ingestion:metrics".to_string()
+    }
+
+    /// Held while an anchor's updated row is written through to its entries
+    pub fn anchor_write_lock(anchor_id: &str) -> String {
+        format!("lock:anchor:{}:write", escape_key_segment(anchor_id))
+    }
+
+    pub fn dashboard_stats() -> String {
+        "dashboard:stats".to_string()
+    }
+
+    pub fn dashboard_overview() -> String {
+        "dashboard:overview".to_string()
+    }
+}
+
+/// Percent-encode the characters that are special in Redis glob patterns, plus
+/// `%` itself so distinct inputs never encode to the same segment
+pub fn escape_key_segment(segment: &str) -> String {
+    let mut escaped = String::with_capacity(segment.len());
+    for c in segment.chars() {
+        match c {
+            '*' | '?' | '[' | ']' | '\\' | '%' => escaped.push_str(&format!("%{:02X}", c as u32)),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Hash a filter description into a short, key-safe token
+pub fn hash_filters(filters: &str) -> String {
+    let digest = Sha256::digest(filters.as_bytes());
+    hex::encode(&digest[..8])
+}
+
+/// Which `CacheConfig` TTL an endpoint uses unless `CACHE_TTL_OVERRIDES` names it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TtlCategory {
+    Anchor,
+    Corridor,
+    Dashboard,
+    NotFound,
+}
+
+/// Logical name of every cached endpoint, as looked up with `CacheConfig::ttl`
+/// and overridden in `CACHE_TTL_OVERRIDES`
+const ENDPOINT_TTLS: &[(&str, TtlCategory)] = &[
+    ("anchor.list", TtlCategory::Anchor),
+    ("anchor.count", TtlCategory::Anchor),
+    ("anchor.search", TtlCategory::Anchor),
+    ("anchor.detail", TtlCategory::Anchor),
+    ("anchor.by_account", TtlCategory::Anchor),
+    ("anchor.data", TtlCategory::Anchor),
+    ("anchor.assets", TtlCategory::Anchor),
+    ("asset.anchors", TtlCategory::Anchor),
+    ("asset.metrics", TtlCategory::Corridor),
+    ("asset.corridors", TtlCategory::Corridor),
+    ("corridor.list", TtlCategory::Corridor),
+    ("corridor.count", TtlCategory::Corridor),
+    ("corridor.detail", TtlCategory::Corridor),
+    ("corridor.history", TtlCategory::Corridor),
+    ("dashboard.stats", TtlCategory::Dashboard),
+    ("not_found", TtlCategory::NotFound),
+];
+
+/// Per-endpoint TTLs that replace an endpoint's category TTL, parsed from
+/// `CACHE_TTL_OVERRIDES` (`anchor.by_account=60,corridor.list=120`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TtlRegistry {
+    overrides: HashMap<&'static str, usize>,
+}
+
+impl TtlRegistry {
+    /// Comma-separated `endpoint=seconds` pairs. Unknown endpoints and
+    /// non-positive or unparseable TTLs are skipped with a warning.
+    pub fn parse(raw: &str) -> Self {
+        let mut registry = Self::default();
+        for pair in raw
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+        {
+            let Some((endpoint, ttl)) = pair.split_once('=') else {
+                tracing::warn!(
+                    "Ignoring CACHE_TTL_OVERRIDES entry {:?} (expected endpoint=seconds)",
+                    pair
+                );
+                continue;
+            };
+            match ttl.trim().parse::<usize>() {
+                Ok(ttl) if ttl > 0 => registry = registry.with_override(endpoint.trim(), ttl),
+                _ => tracing::warn!(
+                    "Ignoring CACHE_TTL_OVERRIDES entry {:?} (expected a positive number of seconds)",
+                    pair
+                ),
+            }
+        }
+        registry
+    }
+
+    /// Pin `endpoint` to `ttl` seconds; an unknown endpoint is ignored with a warning
+    pub fn with_override(mut self, endpoint: &str, ttl: usize) -> Self {
+        match ENDPOINT_TTLS.iter().find(|(name, _)| *name == endpoint) {
+            Some((name, _)) => {
+                self.overrides.insert(name, ttl);
+            }
+            None => tracing::warn!("Ignoring TTL override for unknown endpoint {:?}", endpoint),
+        }
+        self
+    }
+
+    /// The override for `endpoint`, if one was configured
+    pub fn get(&self, endpoint: &str) -> Option<usize> {
+        self.overrides.get(endpoint).copied()
+    }
+}
+
+/// Page sizes cached unless `CACHE_LIST_LIMITS` says otherwise; 50 is the
+/// list endpoints' default `limit`
+pub const DEFAULT_CACHEABLE_LIST_LIMITS: [i64; 3] = [25, 50, 100];
+
+/// Cache lifetimes (and debug output) used by the cached handlers, read from
+/// the environment so each deployment can tune freshness without a rebuild
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// `CACHE_TTL_CORRIDOR`: corridor metrics change with every ingestion run
+    pub corridor_metrics_ttl: usize,
+    /// `CACHE_TTL_CORRIDOR_MIN`: shortest TTL `corridor_ttl` gives a busy corridor
+    pub corridor_ttl_min: usize,
+    /// `CACHE_TTL_CORRIDOR_MAX`: longest TTL `corridor_ttl` gives a quiet corridor
+    pub corridor_ttl_max: usize,
+    /// `CACHE_TTL_ANCHOR`: anchor data changes infrequently
+    pub anchor_data_ttl: usize,
+    /// `CACHE_TTL_DASHBOARD`: dashboard totals should refresh often
+    pub dashboard_stats_ttl: usize,
+    /// `CACHE_TTL_NOT_FOUND`: how long a 404 for a missing id is remembered
+    pub not_found_ttl: usize,
+    /// `CACHE_DEBUG_HEADERS`: report which tier served a response in `X-Cache`
+    pub debug_headers: bool,
+    /// `ALLOW_CACHE_BYPASS`: honour `?no_cache=true`. Off unless set, since
+    /// every bypassing request goes to the database.
+    pub allow_bypass: bool,
+    /// `MAX_LIST_LIMIT`: largest page the list endpoints serve; a bigger
+    /// `limit` is clamped so one request can't pull a whole table into a cache entry
+    pub max_list_limit: i64,
+    /// `CACHE_DOUBLE_DELETE_DELAY_MS`: when set, anchor and corridor
+    /// invalidations delete their keys a second time after this delay, dropping
+    /// any old value a read racing the write put back. Off unless set.
+    pub double_delete_delay: Option<Duration>,
+    /// `CACHE_TTL_OVERRIDES`: per-endpoint TTLs taking precedence over the
+    /// category TTLs above; see `CacheConfig::ttl`
+    pub ttl_overrides: TtlRegistry,
+    /// `CACHE_LIST_LIMITS`: the page sizes whose list responses are cached; see
+    /// `CacheConfig::caches_list_page`
+    pub cacheable_list_limits: Vec<i64>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            corridor_metrics_ttl: 300, // 5 minutes
+            corridor_ttl_min: 60,      // 1 minute
+            corridor_ttl_max: 3600,    // 1 hour
+            anchor_data_ttl: 600,      // 10 minutes
+            dashboard_stats_ttl: 60,   // 1 minute
+            not_found_ttl: 30,         // 30 seconds
+            debug_headers: false,
+            allow_bypass: false,
+            max_list_limit: 500,
+            double_delete_delay: None,
+            ttl_overrides: TtlRegistry::default(),
+            cacheable_list_limits: DEFAULT_CACHEABLE_LIST_LIMITS.to_vec(),
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Load TTLs from the environment; unset variables keep their defaults and
+    /// invalid ones fall back to them with a warning
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let ttl =
+            |var: &str, default: usize| parse_ttl(var, std::env::var(var).ok().as_deref(), default);
+
+        Self {
+            corridor_metrics_ttl: ttl("CACHE_TTL_CORRIDOR", defaults.corridor_metrics_ttl),
+            corridor_ttl_min: ttl("CACHE_TTL_CORRIDOR_MIN", defaults.corridor_ttl_min),
+            corridor_ttl_max: ttl("CACHE_TTL_CORRIDOR_MAX", defaults.corridor_ttl_max),
+            anchor_data_ttl: ttl("CACHE_TTL_ANCHOR", defaults.anchor_data_ttl),
+            dashboard_stats_ttl: ttl("CACHE_TTL_DASHBOARD", defaults.dashboard_stats_ttl),
+            not_found_ttl: ttl("CACHE_TTL_NOT_FOUND", defaults.not_found_ttl),
+            debug_headers: env_flag(std::env::var("CACHE_DEBUG_HEADERS").ok().as_deref()),
+            allow_bypass: env_flag(std::env::var("ALLOW_CACHE_BYPASS").ok().as_deref()),
+            max_list_limit: parse_list_limit(
+                std::env::var("MAX_LIST_LIMIT").ok().as_deref(),
+                defaults.max_list_limit,
+            ),
+            double_delete_delay: parse_double_delete_delay(
+                std::env::var("CACHE_DOUBLE_DELETE_DELAY_MS")
+                    .ok()
+                    .as_deref(),
+            ),
+            ttl_overrides: std::env::var("CACHE_TTL_OVERRIDES")
+                .map(|raw| TtlRegistry::parse(&raw))
+                .unwrap_or_default(),
+            cacheable_list_limits: parse_list_limits(
+                std::env::var("CACHE_LIST_LIMITS").ok().as_deref(),
+                defaults.cacheable_list_limits,
+            ),
+        }
+    }
+
+    /// Whether a list page of `limit` rows at `offset` is written to the cache:
+    /// only for an allowlisted `limit` and an `offset` on a page boundary. Any
+    /// other combination is loaded without being stored, so a client walking
+    /// `?limit=1&offset=0,1,2,...` can't flood the cache with near-duplicate pages.
+    pub fn caches_list_page(&self, limit: i64, offset: i64) -> bool {
+        limit > 0 && self.cacheable_list_limits.contains(&limit) && offset % limit == 0
+    }
+
+    /// TTL for the endpoint named `endpoint` (e.g. `"anchor.detail"`): its
+    /// `CACHE_TTL_OVERRIDES` entry, else the TTL of its category. A name missing
+    /// from the registry gets the shortest category TTL, erring towards freshness.
+    pub fn ttl(&self, endpoint: &str) -> usize {
+        if let Some(ttl) = self.ttl_overrides.get(endpoint) {
+            return ttl;
+        }
+        match ENDPOINT_TTLS.iter().find(|(name, _)| *name == endpoint) {
+            Some((_, TtlCategory::Anchor)) => self.anchor_data_ttl,
+            Some((_, TtlCategory::Corridor)) => self.corridor_metrics_ttl,
+            Some((_, TtlCategory::Dashboard)) => self.dashboard_stats_ttl,
+            Some((_, TtlCategory::NotFound)) => self.not_found_ttl,
+            None => {
+                debug_assert!(false, "no TTL registered for endpoint {:?}", endpoint);
+                tracing::warn!("No TTL registered for endpoint {:?}", endpoint);
+                self.anchor_data_ttl
+                    .min(self.corridor_metrics_ttl)
+                    .min(self.dashboard_stats_ttl)
+            }
+        }
+    }
+
+    /// TTL for one corridor's cached metrics: the `corridor.detail` TTL divided
+    /// by `transactions_per_day / CORRIDOR_REFERENCE_DAILY_TRANSACTIONS` and
+    /// clamped to `corridor_ttl_min..=corridor_ttl_max`, so an idle corridor gets
+    /// the maximum.
+    pub fn corridor_ttl(&self, transactions_per_day: f64) -> usize {
+        let max = self.corridor_ttl_max.max(self.corridor_ttl_min);
+        if transactions_per_day.is_nan() || transactions_per_day <= 0.0 {
+            return max;
+        }
+        let activity = transactions_per_day / CORRIDOR_REFERENCE_DAILY_TRANSACTIONS;
+        // Float-to-int `as` saturates, so a tiny activity can't overflow
+        let ttl = (self.ttl("corridor.detail") as f64 / activity) as usize;
+        ttl.clamp(self.corridor_ttl_min, max)
+    }
+}
+
+/// Daily transactions at which `CacheConfig::corridor_ttl` returns the
+/// `corridor.detail` base TTL unchanged
+pub const CORRIDOR_REFERENCE_DAILY_TRANSACTIONS: f64 = 100.0;
+
+/// `CACHE_DOUBLE_DELETE_DELAY_MS` from `raw`; unset, `0` or invalid turns it off
+fn parse_double_delete_delay(raw: Option<&str>) -> Option<Duration> {
+    let raw = raw?;
+    match raw.trim().parse::<u64>() {
+        Ok(0) => None,
+        Ok(millis) => Some(Duration::from_millis(millis)),
+        Err(_) => {
+            tracing::warn!(
+                "Ignoring invalid CACHE_DOUBLE_DELETE_DELAY_MS={:?} (expected milliseconds), double delete stays off",
+                raw
+            );
+            None
+        }
+    }
+}
+
+/// `CACHE_LIST_LIMITS` from `raw`, comma-separated positive integers. Invalid
+/// entries are skipped with a warning; if none are left, `default` is used.
+fn parse_list_limits(raw: Option<&str>, default: Vec<i64>) -> Vec<i64> {
+    let Some(raw) = raw else {
+        return default;
+    };
+    let mut limits = Vec::new();
+    for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        match entry.parse::<i64>() {
+            Ok(limit) if limit > 0 => limits.push(limit),
+            _ => tracing::warn!(
+                "Ignoring CACHE_LIST_LIMITS entry {:?} (expected a positive integer)",
+                entry
+            ),
+        }
+    }
+    if limits.is_empty() {
+        tracing::warn!(
+            "No usable limits in CACHE_LIST_LIMITS={:?}, using {:?}",
+            raw,
+            default
+        );
+        return default;
+    }
+    limits
+}
+
+/// `MAX_LIST_LIMIT` from `raw`, which must be a positive integer
+fn parse_list_limit(raw: Option<&str>, default: i64) -> i64 {
+    let Some(raw) = raw else {
+        return default;
+    };
+    match raw.trim().parse::<i64>() {
+        Ok(limit) if limit > 0 => limit,
+        _ => {
+            tracing::warn!(
+                "Ignoring invalid MAX_LIST_LIMIT={:?} (expected a positive integer), using {}",
+                raw,
+                default
+            );
+            default
+        }
+    }
+}
+
+/// A TTL in seconds from `raw`, which must be a positive integer
+fn parse_ttl(var: &str, raw: Option<&str>, default: usize) -> usize {
+    let Some(raw) = raw else {
+        return default;
+    };
+    match raw.trim().parse::<usize>() {
+        Ok(ttl) if ttl > 0 => ttl,
+        _ => {
+            tracing::warn!(
+                "Ignoring invalid {}={:?} (expected a positive number of seconds), using {}",
+                var,
+                raw,
+                default
+            );
+            default
+        }
+    }
+}
+
+/// Tier a cached value was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheTier {
+    Redis,
+    Memory,
+    /// A `Cache` implementation's default read-through, which can't tell
+    /// which tier answered
+    Unknown,
+}
+
+impl CacheTier {
+    /// Value of the `cache.tier` log field
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CacheTier::Redis => "redis",
+            CacheTier::Memory => "memory",
+            CacheTier::Unknown => "unknown",
+        }
+    }
+}
+
+/// Whether a cache-aside read was answered from the cache or had to load
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+    Hit {
+        tier: CacheTier,
+        /// When the entry was written; `None` for entries stored before
+        /// writes were timestamped
+        cached_at: Option<DateTime<Utc>>,
+    },
+    Miss,
+    /// The caller skipped the read on purpose and reloaded the value
+    Bypass,
+}
+
+impl CacheStatus {
+    /// Value for the `X-Cache` debug header
+    pub fn header_value(self) -> &'static str {
+        match self {
+            CacheStatus::Hit {
+                tier: CacheTier::Redis,
+                ..
+            } => "HIT-REDIS",
+            CacheStatus::Hit {
+                tier: CacheTier::Memory,
+                ..
+            } => "HIT-MEMORY",
+            CacheStatus::Hit {
+                tier: CacheTier::Unknown,
+                ..
+            } => "HIT",
+            CacheStatus::Miss => "MISS",
+            CacheStatus::Bypass => "BYPASS",
+        }
+    }
+
+    /// Tier that answered a hit
+    pub fn tier(self) -> Option<CacheTier> {
+        match self {
+            CacheStatus::Hit { tier, .. } => Some(tier),
+            CacheStatus::Miss | CacheStatus::Bypass => None,
+        }
+    }
+
+    /// Whole seconds the value had been cached at `now`: 0 for a value just
+    /// loaded, `None` when a hit's write time is unknown
+    pub fn age_secs(self, now: DateTime<Utc>) -> Option<i64> {
+        match self {
+            CacheStatus::Hit { cached_at, .. } => {
+                cached_at.map(|at| (now - at).num_seconds().max(0))
+            }
+            CacheStatus::Miss | CacheStatus::Bypass => Some(0),
+        }
+    }
+}
+
+/// Version segment folded into every stored key. Bump it whenever a cached
+/// model changes shape incompatibly, so entries written by older builds are
+/// simply never read again and age out on their TTL.
+pub const CACHE_VERSION: u32 = 3;
+
+/// Scheme marking `REDIS_URL` as a list of Sentinels plus a master name
+const SENTINEL_SCHEME: &str = "redis+sentinel://";
+
+/// How cached values are serialized, chosen with `CACHE_FORMAT`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheFormat {
+    #[default]
+    Json,
+    /// MessagePack with field names kept, so `serde` attributes behave as in JSON
+    MessagePack,
+}
+
+impl CacheFormat {
+    /// Parse a `CACHE_FORMAT` value: `json` or `msgpack`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "json" => Some(CacheFormat::Json),
+            "msgpack" | "messagepack" => Some(CacheFormat::MessagePack),
+            _ => None,
+        }
+    }
+
+    fn serialize<T: Serialize>(self, value: &T) -> Result<Vec<u8>> {
+        match self {
+            CacheFormat::Json => {
+                serde_json::to_vec(value).map_err(|e| CacheError::Serialization(e.to_string()))
+            }
+            CacheFormat::MessagePack => {
+                rmp_serde::to_vec_named(value).map_err(|e| CacheError::Serialization(e.to_string()))
+            }
+        }
+    }
+
+    fn deserialize<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T> {
+        match self {
+            CacheFormat::Json => serde_json::from_slice(bytes)
+                .map_err(|e| CacheError::Deserialization(e.to_string())),
+            CacheFormat::MessagePack => {
+                rmp_serde::from_slice(bytes).map_err(|e| CacheError::Deserialization(e.to_string()))
+            }
+        }
+    }
+}
+
+/// First delay between reconnection attempts; doubles on each failure
+const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(1);
+/// Ceiling on the reconnection backoff
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
+/// How often a healthy connection is pinged
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Keys Redis examines per `SCAN` round trip in `delete_pattern`
+const SCAN_BATCH_SIZE: usize = 500;
+/// Keys passed to a single `UNLINK` command
+const UNLINK_CHUNK_SIZE: usize = 100;
+/// Entries the memory fallback holds when `MEMORY_CACHE_MAX_ENTRIES` is unset
+const DEFAULT_MEMORY_CACHE_MAX_ENTRIES: usize = 10_000;
+/// Failed Redis writes remembered when `CACHE_FAILED_WRITES_LOG_SIZE` is unset
+const DEFAULT_FAILED_WRITES_LOG_SIZE: usize = 100;
+/// Serialized size above which values are gzipped when `CACHE_COMPRESS_THRESHOLD` is unset
+const DEFAULT_COMPRESS_THRESHOLD: usize = 1024;
+/// Marks a stored payload as gzipped; JSON itself never starts with a NUL byte
+const COMPRESSED_MAGIC: &[u8] = b"\0gz";
+/// Marks a stored payload as prefixed with its write time (big-endian Unix
+/// milliseconds). Entries written before this existed have no stamp.
+const STAMP_MAGIC: &[u8] = b"\0at";
+/// Marks a payload serialized as MessagePack; untagged payloads are JSON
+const MSGPACK_MAGIC: &[u8] = b"\0mp";
+/// Connections opened per Redis node when `REDIS_POOL_SIZE` is unset
+const DEFAULT_REDIS_POOL_SIZE: usize = 4;
+/// Consecutive Redis failures that open the circuit when `REDIS_BREAKER_THRESHOLD` is unset
+const DEFAULT_BREAKER_THRESHOLD: u32 = 5;
+/// Failures only add up towards opening the circuit while they fall this close together
+const BREAKER_WINDOW: Duration = Duration::from_secs(30);
+/// How long an open circuit keeps commands off Redis when
+/// `REDIS_BREAKER_COOLDOWN_SECS` is unset
+const DEFAULT_BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+/// How often the memory sweeper started by `RedisCache::new` purges expired entries
+const MEMORY_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+/// How long `shutdown` waits for background work when `CACHE_SHUTDOWN_GRACE_SECS` is unset
+const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+/// Redis pub/sub channel every instance announces its invalidations on, so the
+/// others can drop the same keys from their memory tiers
+pub const INVALIDATION_CHANNEL: &str = "cache:invalidate";
+
+/// What an invalidation removed, in stored form (namespace and key version
+/// included) so a receiving instance matches exactly the entries the sender did
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Invalidation {
+    Keys {
+        keys: Vec<String>,
+    },
+    Tag {
+        tag: String,
+    },
+    Pattern {
+        pattern: String,
+    },
+    /// `clear_everything`: the whole database was flushed
+    Everything,
+}
+
+/// Payload published on `INVALIDATION_CHANNEL`; `origin` lets an instance
+/// skip the invalidations it published itself
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct InvalidationMessage {
+    origin: String,
+    #[serde(flatten)]
+    invalidation: Invalidation,
+}
+
+/// Fixed set of connections to one Redis node, handed out round-robin. Each
+/// multiplexed connection pipelines its commands in order, so spreading ops
+/// across several keeps one slow command from holding up every other op.
+/// The pool is replaced as a whole when any connection fails its health check.
+#[derive(Clone)]
+struct RedisPool<C = MultiplexedConnection> {
+    connections: Arc<[C]>,
+    next: Arc<AtomicUsize>,
+}
+
+impl<C: Clone> RedisPool<C> {
+    /// `None` for an empty set, so a pool always has a connection to hand out
+    fn new(connections: Vec<C>) -> Option<Self> {
+        if connections.is_empty() {
+            return None;
+        }
+        Some(Self {
+            connections: connections.into(),
+            next: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    /// The next connection in turn
+    fn get(&self) -> C {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.connections.len();
+        self.connections[index].clone()
+    }
+
+    fn len(&self) -> usize {
+        self.connections.len()
+    }
+}
+
+impl RedisPool {
+    /// Probe every connection, failing on the first one that doesn't answer
+    async fn probe(&self, expect_master: bool) -> redis::RedisResult<()> {
+        for conn in self.connections.iter() {
+            probe(&mut conn.clone(), expect_master).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Counters describing cache effectiveness
+#[derive(Debug, Default)]
+pub struct CacheMetrics {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    invalidations: AtomicU64,
+    errors: AtomicU64,
+    /// The same counters broken down by key prefix (see `key_prefix`)
+    per_prefix: DashMap<String, PrefixCounters>,
+    /// Outcomes of the latest lookups, for a hit rate that reacts to sudden drops
+    recent: RecentOutcomes,
+    redis_latency: OperationLatencies,
+    memory_latency: OperationLatencies,
+    /// Current size of the memory tier, kept up to date by `MemoryTier`
+    memory_entries: AtomicU64,
+    memory_bytes: AtomicU64,
+}
+
+/// Lookups the recent-window hit rate is computed over
+pub const RECENT_WINDOW_SIZE: usize = 1000;
+/// Fewest lookups in the window before the hit rate counts as degraded, so a
+/// freshly started or idle instance doesn't alert on a handful of misses
+const MIN_RECENT_SAMPLES: u64 = 100;
+
+const OUTCOME_EMPTY: u8 = 0;
+const OUTCOME_HIT: u8 = 1;
+const OUTCOME_MISS: u8 = 2;
+
+/// Ring buffer of the last `RECENT_WINDOW_SIZE` lookup outcomes. Writers claim
+/// a slot with one atomic increment and overwrite it, so recording never blocks.
+#[derive(Debug)]
+struct RecentOutcomes {
+    slots: Box<[AtomicU8]>,
+    next: AtomicUsize,
+}
+
+impl Default for RecentOutcomes {
+    fn default() -> Self {
+        Self {
+            slots: (0..RECENT_WINDOW_SIZE)
+                .map(|_| AtomicU8::new(OUTCOME_EMPTY))
+                .collect(),
+            next: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl RecentOutcomes {
+    fn record(&self, hit: bool) {
+        let idx = self.next.fetch_add(1, Ordering::Relaxed) % self.slots.len();
+        let outcome = if hit { OUTCOME_HIT } else { OUTCOME_MISS };
+        self.slots[idx].store(outcome, Ordering::Relaxed);
+    }
+
+    /// `(hits, misses)` currently in the window
+    fn counts(&self) -> (u64, u64) {
+        self.slots.iter().fold((0, 0), |(hits, misses), slot| {
+            match slot.load(Ordering::Relaxed) {
+                OUTCOME_HIT => (hits + 1, misses),
+                OUTCOME_MISS => (hits, misses + 1),
+                _ => (hits, misses),
+            }
+        })
+    }
+
+    fn reset(&self) {
+        for slot in self.slots.iter() {
+            slot.store(OUTCOME_EMPTY, Ordering::Relaxed);
+        }
+        self.next.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Upper bounds (inclusive, in milliseconds) of the latency histogram buckets;
+/// a final `+Inf` bucket catches everything slower
+const LATENCY_BUCKETS_MS: [u64; 6] = [1, 5, 10, 50, 100, 500];
+
+/// Latency histogram with non-cumulative buckets: each one counts only the
+/// samples between the previous bound and its own
+#[derive(Debug, Default)]
+struct LatencyHistogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS_MS.len() + 1],
+}
+
+impl LatencyHistogram {
+    fn record(&self, elapsed: Duration) {
+        let millis = elapsed.as_secs_f64() * 1000.0;
+        let idx = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|&bound| millis <= bound as f64)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.buckets[idx].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn summary(&self) -> LatencySummary {
+        let counts: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|b| b.load(Ordering::Relaxed))
+            .collect();
+
+        LatencySummary {
+            count: counts.iter().sum(),
+            p50_ms: estimate_percentile(&counts, 0.50),
+            p95_ms: estimate_percentile(&counts, 0.95),
+            p99_ms: estimate_percentile(&counts, 0.99),
+            buckets: counts
+                .iter()
+                .enumerate()
+                .map(|(idx, &count)| LatencyBucket {
+                    le: LATENCY_BUCKETS_MS
+                        .get(idx)
+                        .map(|bound| format!("{}ms", bound))
+                        .unwrap_or_else(|| "+Inf".to_string()),
+                    count,
+                })
+                .collect(),
+        }
+    }
+
+    fn reset(&self) {
+        for bucket in &self.buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct OperationLatencies {
+    get: LatencyHistogram,
+    set: LatencyHistogram,
+    delete: LatencyHistogram,
+}
+
+impl OperationLatencies {
+    fn summary(&self) -> OperationLatencySummary {
+        OperationLatencySummary {
+            get: self.get.summary(),
+            set: self.set.summary(),
+            delete: self.delete.summary(),
+        }
+    }
+
+    fn reset(&self) {
+        self.get.reset();
+        self.set.reset();
+        self.delete.reset();
+    }
+}
+
+#[derive(Debug, Default)]
+struct PrefixCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    errors: AtomicU64,
+}
+
+/// Outcome of an on-demand memory-tier compaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MemoryCompaction {
+    /// Expired entries removed
+    pub purged: usize,
+    /// Entries left in the memory tier afterwards
+    pub remaining: usize,
+}
+
+/// One write Redis rejected, as kept by `RedisCache::failed_writes`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct FailedWrite {
+    pub key: String,
+    pub failed_at: DateTime<Utc>,
+    /// Redis' error kind, e.g. `IoError` or `ResponseError`
+    pub error_kind: String,
+}
+
+/// The last `capacity` failed Redis writes, oldest first. A capacity of zero
+/// keeps nothing.
+#[derive(Debug)]
+struct FailedWriteLog {
+    capacity: usize,
+    entries: std::collections::VecDeque<FailedWrite>,
+}
+
+impl FailedWriteLog {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: std::collections::VecDeque::with_capacity(capacity),
+        }
+    }
+
+    fn push(&mut self, failure: FailedWrite) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(failure);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CacheMetricsSummary {
+    pub hits: u64,
+    pub misses: u64,
+    pub invalidations: u64,
+    pub errors: u64,
+    /// Lifetime hit rate, as a percentage
+    pub hit_rate: f64,
+    /// Hit rate over the last `recent_samples` lookups (at most
+    /// `RECENT_WINDOW_SIZE`), as a percentage
+    pub recent_hit_rate: f64,
+    pub recent_samples: u64,
+    pub per_prefix: HashMap<String, PrefixStats>,
+    pub latency: LatencyReport,
+    /// Whether commands are currently reaching Redis or short-circuiting to memory
+    #[serde(default)]
+    pub redis_breaker: BreakerState,
+    /// Entries held by the memory fallback, expired ones included until purged
+    #[serde(default)]
+    pub memory_cache_entries: u64,
+    /// Approximate memory used by the fallback: the summed length of its stored payloads
+    #[serde(default)]
+    pub memory_cache_bytes: u64,
+}
+
+impl CacheMetricsSummary {
+    /// Whether the recent-window hit rate has fallen below `threshold_pct`,
+    /// e.g. from an invalidation loop or Redis flapping. Stays false until the
+    /// window holds enough lookups to mean something.
+    pub fn is_hit_rate_degraded(&self, threshold_pct: f64) -> bool {
+        self.recent_samples >= MIN_RECENT_SAMPLES && self.recent_hit_rate < threshold_pct
+    }
+}
+
+/// Operation latencies, with Redis and the memory fallback kept apart for comparison
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct LatencyReport {
+    pub redis: OperationLatencySummary,
+    pub memory: OperationLatencySummary,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct OperationLatencySummary {
+    pub get: LatencySummary,
+    pub set: LatencySummary,
+    pub delete: LatencySummary,
+}
+
+/// Bucket counts plus percentile estimates interpolated within the buckets
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct LatencySummary {
+    pub count: u64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub buckets: Vec<LatencyBucket>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct LatencyBucket {
+    /// Upper bound of the bucket, e.g. `5ms` or `+Inf`
+    pub le: String,
+    pub count: u64,
+}
+
+/// Hit/miss/error counts for one key prefix such as `anchor:list`
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PrefixStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub errors: u64,
+    pub hit_rate: f64,
+}
+
+impl CacheMetrics {
+    pub fn record_hit(&self, key: &str) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        self.prefix(key).hits.fetch_add(1, Ordering::Relaxed);
+        self.recent.record(true);
+    }
+
+    pub fn record_miss(&self, key: &str) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.prefix(key).misses.fetch_add(1, Ordering::Relaxed);
+        self.recent.record(false);
+    }
+
+    pub fn record_invalidation(&self) {
+        self.invalidations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_error(&self, key: &str) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        self.prefix(key).errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Set the memory tier's current size; these are gauges, so `reset` leaves them alone
+    fn record_memory_usage(&self, entries: usize, bytes: usize) {
+        self.memory_entries.store(entries as u64, Ordering::Relaxed);
+        self.memory_bytes.store(bytes as u64, Ordering::Relaxed);
+    }
+
+    fn prefix(&self, key: &str) -> dashmap::mapref::one::Ref<'_, String, PrefixCounters> {
+        let prefix = key_prefix(key);
+        if let Some(counters) = self.per_prefix.get(prefix) {
+            return counters;
+        }
+        self.per_prefix
+            .entry(prefix.to_string())
+            .or_default()
+            .downgrade()
+    }
+
+    /// Hit rate as a percentage of all lookups
+    pub fn hit_rate(&self) -> f64 {
+        percentage(
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+
+    pub fn summary(&self) -> CacheMetricsSummary {
+        let per_prefix = self
+            .per_prefix
+            .iter()
+            .map(|entry| {
+                let hits = entry.hits.load(Ordering::Relaxed);
+                let misses = entry.misses.load(Ordering::Relaxed);
+                let stats = PrefixStats {
+                    hits,
+                    misses,
+                    errors: entry.errors.load(Ordering::Relaxed),
+                    hit_rate: percentage(hits, misses),
+                };
+                (entry.key().clone(), stats)
+            })
+            .collect();
+        let (recent_hits, recent_misses) = self.recent.counts();
+
+        CacheMetricsSummary {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            hit_rate: self.hit_rate(),
+            recent_hit_rate: percentage(recent_hits, recent_misses),
+            recent_samples: recent_hits + recent_misses,
+            per_prefix,
+            latency: LatencyReport {
+                redis: self.redis_latency.summary(),
+                memory: self.memory_latency.summary(),
+            },
+            // The breaker belongs to the cache; `RedisCache::get_metrics` fills it in
+            redis_breaker: BreakerState::default(),
+            memory_cache_entries: self.memory_entries.load(Ordering::Relaxed),
+            memory_cache_bytes: self.memory_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Render the counters in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let summary = self.summary();
+        let mut out = String::new();
+
+        for (name, help, value) in [
+            (
+                "cache_hits_total",
+                "Cache lookups that found a value",
+                summary.hits,
+            ),
+            (
+                "cache_misses_total",
+                "Cache lookups that found nothing",
+                summary.misses,
+            ),
+            (
+                "cache_errors_total",
+                "Redis operations that failed",
+                summary.errors,
+            ),
+            (
+                "cache_invalidations_total",
+                "Cache invalidation calls",
+                summary.invalidations,
+            ),
+        ] {
+            out.push_str(&format!(
+                "# HELP {name} {help}\n# TYPE {name} counter\n{name} {value}\n"
+            ));
+        }
+
+        out.push_str(&format!(
+            "# HELP cache_hit_rate Fraction of lookups served from cache (0-1)\n\
+             # TYPE cache_hit_rate gauge\n\
+             cache_hit_rate {}\n",
+            summary.hit_rate / 100.0
+        ));
+        out.push_str(&format!(
+            "# HELP cache_recent_hit_rate Fraction of the last {} lookups served from cache (0-1)\n\
+             # TYPE cache_recent_hit_rate gauge\n\
+             cache_recent_hit_rate {}\n",
+            RECENT_WINDOW_SIZE,
+            summary.recent_hit_rate / 100.0
+        ));
+        out.push_str(&format!(
+            "# HELP cache_memory_entries Entries held by the in-memory fallback\n\
+             # TYPE cache_memory_entries gauge\n\
+             cache_memory_entries {}\n\
+             # HELP cache_memory_bytes Summed payload size of the in-memory fallback's entries\n\
+             # TYPE cache_memory_bytes gauge\n\
+             cache_memory_bytes {}\n",
+            summary.memory_cache_entries, summary.memory_cache_bytes
+        ));
+
+        out
+    }
+
+    pub fn reset(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+        self.invalidations.store(0, Ordering::Relaxed);
+        self.errors.store(0, Ordering::Relaxed);
+        self.per_prefix.clear();
+        self.recent.reset();
+        self.redis_latency.reset();
+        self.memory_latency.reset();
+    }
+}
+
+/// Where the Redis circuit breaker stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Commands go to Redis
+    #[default]
+    Closed,
+    /// Redis kept failing, so commands go straight to memory until the cooldown ends
+    Open,
+    /// The cooldown is over and a single trial command may test Redis
+    HalfOpen,
+}
+
+/// Keeps commands off a Redis that keeps failing, so a flapping instance costs
+/// a memory lookup per command instead of a failed round trip each.
+/// `threshold` consecutive failures within `window` open the circuit; once
+/// `cooldown` has passed it half-opens and lets one trial command (or the
+/// health check's probe) through, closing on success and reopening on failure.
+#[derive(Debug)]
+struct CircuitBreaker {
+    threshold: u32,
+    window: Duration,
+    cooldown: Duration,
+    /// Set while any failure is outstanding, so successes skip the lock
+    failing: AtomicBool,
+    /// Set while the circuit is open or half-open, so closed checks skip the lock
+    tripped: AtomicBool,
+    inner: std::sync::Mutex<BreakerInner>,
+}
+
+#[derive(Debug, Default)]
+struct BreakerInner {
+    /// Failures since the last success, within `window` of `first_failure`
+    failures: u32,
+    first_failure: Option<Instant>,
+    /// When the circuit last opened; `None` while it is closed
+    opened_at: Option<Instant>,
+    /// When the half-open circuit last let a trial command through
+    trial_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    fn new(threshold: u32, window: Duration, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            window,
+            cooldown,
+            failing: AtomicBool::new(false),
+            tripped: AtomicBool::new(false),
+            inner: std::sync::Mutex::new(BreakerInner::default()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BreakerInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn state(&self) -> BreakerState {
+        self.state_at(Instant::now())
+    }
+
+    fn state_at(&self, now: Instant) -> BreakerState {
+        match self.lock().opened_at {
+            None => BreakerState::Closed,
+            Some(opened_at) if now.duration_since(opened_at) < self.cooldown => BreakerState::Open,
+            Some(_) => BreakerState::HalfOpen,
+        }
+    }
+
+    /// Time left before an open circuit half-opens, if it is open
+    fn cooldown_remaining(&self) -> Option<Duration> {
+        let opened_at = self.lock().opened_at?;
+        Some(self.cooldown.saturating_sub(opened_at.elapsed()))
+    }
+
+    /// Whether a command may go to Redis. A half-open circuit admits one trial
+    /// per `cooldown`, so a trial whose outcome is never reported can't wedge it.
+    fn allow(&self) -> bool {
+        self.allow_at(Instant::now())
+    }
+
+    fn allow_at(&self, now: Instant) -> bool {
+        if !self.tripped.load(Ordering::Relaxed) {
+            return true;
+        }
+        let mut inner = self.lock();
+        let Some(opened_at) = inner.opened_at else {
+            return true;
+        };
+        if now.duration_since(opened_at) < self.cooldown {
+            return false;
+        }
+        match inner.trial_at {
+            Some(trial_at) if now.duration_since(trial_at) < self.cooldown => false,
+            _ => {
+                inner.trial_at = Some(now);
+                true
+            }
+        }
+    }
+
+    /// A Redis command succeeded: clear the failure count and close the circuit
+    fn record_success(&self) {
+        if !self.failing.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut inner = self.lock();
+        if inner.opened_at.is_some() {
+            tracing::info!("Redis is answering again, closing the circuit breaker");
+        }
+        *inner = BreakerInner::default();
+        self.tripped.store(false, Ordering::Relaxed);
+        self.failing.store(false, Ordering::Relaxed);
+    }
+
+    /// The health check reached Redis. Closes a half-open circuit but leaves an
+    /// open one alone, since a flapping Redis often answers a lone ping.
+    fn record_probe_success(&self) {
+        if self.state() != BreakerState::Open {
+            self.record_success();
+        }
+    }
+
+    /// A Redis command failed. Returns whether this failure opened the circuit.
+    fn record_failure(&self) -> bool {
+        self.record_failure_at(Instant::now())
+    }
+
+    fn record_failure_at(&self, now: Instant) -> bool {
+        let mut inner = self.lock();
+        self.failing.store(true, Ordering::Relaxed);
+        if let Some(opened_at) = inner.opened_at {
+            // A failed trial restarts the cooldown; stragglers from before the
+            // circuit opened change nothing
+            if now.duration_since(opened_at) >= self.cooldown {
+                inner.opened_at = Some(now);
+                inner.trial_at = None;
+            }
+            return false;
+        }
+
+        match inner.first_failure {
+            Some(first) if now.duration_since(first) <= self.window => inner.failures += 1,
+            _ => {
+                inner.first_failure = Some(now);
+                inner.failures = 1;
+            }
+        }
+        if inner.failures < self.threshold {
+            return false;
+        }
+        inner.opened_at = Some(now);
+        inner.trial_at = None;
+        self.tripped.store(true, Ordering::Relaxed);
+        tracing::warn!(
+            "Redis failed {} times in a row, sending cache traffic to memory for {:?}",
+            inner.failures,
+            self.cooldown
+        );
+        true
+    }
+}
+
+/// Entry held by the in-memory fallback cache
+struct MemoryCacheEntry {
+    /// Stored payload, encoded exactly as it would be in Redis
+    data: Vec<u8>,
+    expires_at: Instant,
+    /// Value of the cache's access clock when this entry was last written or read
+    last_used: u64,
+}
+
+impl MemoryCacheEntry {
+    fn is_expired(&self) -> bool {
+        Instant::now() >= self.expires_at
+    }
+}
+
+/// The memory fallback's entries plus a running total of their payload bytes.
+/// Every insert, removal, eviction and clear goes through here so the totals
+/// published to `CacheMetrics` stay exact; reads deref to the map.
+struct MemoryTier {
+    entries: HashMap<String, MemoryCacheEntry>,
+    bytes: usize,
+    metrics: Arc<CacheMetrics>,
+}
+
+impl MemoryTier {
+    fn new(metrics: Arc<CacheMetrics>) -> Self {
+        Self {
+            entries: HashMap::new(),
+            bytes: 0,
+            metrics,
+        }
+    }
+
+    /// For bumping `last_used`; replacing `data` through this would skew the byte count
+    fn get_mut(&mut self, key: &str) -> Option<&mut MemoryCacheEntry> {
+        self.entries.get_mut(key)
+    }
+
+    fn insert(&mut self, key: String, entry: MemoryCacheEntry) -> Option<MemoryCacheEntry> {
+        self.bytes += entry.data.len();
+        let previous = self.entries.insert(key, entry);
+        if let Some(previous) = &previous {
+            self.bytes -= previous.data.len();
+        }
+        self.publish();
+        previous
+    }
+
+    fn remove(&mut self, key: &str) -> Option<MemoryCacheEntry> {
+        let removed = self.entries.remove(key);
+        if let Some(removed) = &removed {
+            self.bytes -= removed.data.len();
+            self.publish();
+        }
+        removed
+    }
+
+    fn retain(&mut self, mut keep: impl FnMut(&String, &MemoryCacheEntry) -> bool) {
+        let bytes = &mut self.bytes;
+        self.entries.retain(|key, entry| {
+            let kept = keep(key, entry);
+            if !kept {
+                *bytes -= entry.data.len();
+            }
+            kept
+        });
+        self.publish();
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.bytes = 0;
+        self.publish();
+    }
+
+    fn publish(&self) {
+        self.metrics
+            .record_memory_usage(self.entries.len(), self.bytes);
+    }
+}
+
+impl std::ops::Deref for MemoryTier {
+    type Target = HashMap<String, MemoryCacheEntry>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entries
+    }
+}
+
+/// Stored form of a stale-while-revalidate value. The timestamps are wall-clock
+/// milliseconds so every instance sharing Redis agrees on them.
+#[derive(Serialize, Deserialize)]
+struct SwrEnvelope<T> {
+    value: T,
+    fresh_until: u64,
+    stale_until: u64,
+}
+
+/// Redis-backed cache with an in-memory fallback used while Redis is unavailable
+pub struct RedisCache {
+    redis_url: String,
+    redis_connection: Arc<RwLock<Option<RedisPool>>>,
+    /// Replica serving reads when `REDIS_READ_FROM_REPLICA` is set behind Sentinel
+    replica_connection: Arc<RwLock<Option<RedisPool>>>,
+    read_from_replica: bool,
+    /// Connections opened per node, from `REDIS_POOL_SIZE`
+    pool_size: usize,
+    memory_cache: Arc<RwLock<MemoryTier>>,
+    /// Memory-tier mirror of the Redis tag sets: stored tag -> stored keys
+    memory_tags: Arc<RwLock<HashMap<String, HashSet<String>>>>,
+    metrics: Arc<CacheMetrics>,
+    /// Per-key locks held while a `get_or_set` loader runs, so concurrent misses share one load
+    inflight: Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>,
+    /// Keys with a stale-while-revalidate refresh currently running in the background
+    refreshing: Arc<std::sync::Mutex<HashSet<String>>>,
+    /// Upper bound on memory fallback entries before least-recently-used ones are evicted
+    memory_max_entries: usize,
+    /// Monotonic counter stamped onto memory entries to order them by recency
+    access_clock: AtomicU64,
+    /// Prepended to every stored key (e.g. `staging:`) so environments sharing
+    /// one Redis don't collide; empty when no namespace is configured
+    namespace: String,
+    /// Key version placed after the namespace; see `CACHE_VERSION`
+    version: u32,
+    /// Serialized payloads larger than this many bytes are stored gzipped
+    compress_threshold: usize,
+    format: CacheFormat,
+    /// Woken when a Redis command fails so the health check runs immediately
+    health_check: Arc<Notify>,
+    /// Reconnection attempts made by the background health check
+    reconnect_attempts: Arc<AtomicU64>,
+    /// Skips Redis for a while after repeated failures
+    breaker: Arc<CircuitBreaker>,
+    /// Flipped by `shutdown` to stop the health check and memory sweeper
+    shutdown: watch::Sender<bool>,
+    /// How long `shutdown` waits for background work before aborting it
+    shutdown_grace: Duration,
+    /// Background health check, aborted when the cache is dropped
+    health_task: std::sync::Mutex<Option<JoinHandle<()>>>,
+    /// Periodic purge of expired memory entries, aborted when the cache is dropped
+    sweeper_task: std::sync::Mutex<Option<JoinHandle<()>>>,
+    /// Identifies this instance's messages on `INVALIDATION_CHANNEL`
+    instance_id: String,
+    /// Applies other instances' invalidations to the memory tier, aborted when
+    /// the cache is dropped
+    invalidation_task: std::sync::Mutex<Option<JoinHandle<()>>>,
+    /// Stale-while-revalidate refreshes and delayed writes still running;
+    /// `shutdown` waits for them
+    background: std::sync::Mutex<JoinSet<()>>,
+    /// Recent writes Redis rejected, for deciding what to re-warm after a blip
+    failed_writes: std::sync::Mutex<FailedWriteLog>,
+}
+
+impl RedisCache {
+    /// Connect using `REDIS_URL`, falling back to memory-only caching on failure.
+    /// A background task keeps trying to (re)connect whenever Redis is unavailable.
+    ///
+    /// `REDIS_URL` is either a single node (`redis://` or `rediss://`) or a
+    /// Sentinel deployment, `redis+sentinel://host1:26379,host2:26379/<master>`,
+    /// in which case the current master is resolved on every (re)connect so a
+    /// failover is followed automatically.
+    pub async fn new() -> Result<Self> {
+        let redis_url =
+            std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        if is_tls_url(&redis_url) && !cfg!(feature = "redis-tls") {
+            tracing::warn!(
+                "REDIS_URL uses rediss:// but this binary was built without the `redis-tls` \
+                 feature; caching will stay in memory until it is rebuilt with TLS support"
+            );
+        }
+        Ok(Self::from_url(&redis_url)
+            .await?
+            .with_auto_reconnect()
+            .with_memory_sweeper(MEMORY_SWEEP_INTERVAL)
+            .with_invalidation_subscriber())
+    }
+
+    pub async fn from_url(redis_url: &str) -> Result<Self> {
+        let pool_size = redis_pool_size_from_env();
+        let connection = Self::connect(redis_url, pool_size).await;
+
+        let sentinel = matches!(
+            parse_redis_target(redis_url),
+            Ok(RedisTarget::Sentinel { .. })
+        );
+        let wants_replica = env_flag(std::env::var("REDIS_READ_FROM_REPLICA").ok().as_deref());
+        if wants_replica && !sentinel {
+            tracing::warn!("REDIS_READ_FROM_REPLICA only applies to Sentinel URLs, ignoring it");
+        }
+        let read_from_replica = wants_replica && sentinel;
+        let replica = if read_from_replica {
+            Self::connect_node(redis_url, NodeRole::Replica, pool_size).await
+        } else {
+            None
+        };
+
+        let metrics = Arc::new(CacheMetrics::default());
+        Ok(Self {
+            redis_url: redis_url.to_string(),
+            redis_connection: Arc::new(RwLock::new(connection)),
+            replica_connection: Arc::new(RwLock::new(replica)),
+            read_from_replica,
+            pool_size,
+            memory_cache: Arc::new(RwLock::new(MemoryTier::new(Arc::clone(&metrics)))),
+            memory_tags: Arc::new(RwLock::new(HashMap::new())),
+            metrics,
+            inflight: Arc::new(Mutex::new(HashMap::new())),
+            refreshing: Arc::new(std::sync::Mutex::new(HashSet::new())),
+            memory_max_entries: memory_max_entries_from_env(),
+            access_clock: AtomicU64::new(0),
+            namespace: namespace_prefix(&std::env::var("CACHE_NAMESPACE").unwrap_or_default()),
+            version: CACHE_VERSION,
+            compress_threshold: compress_threshold_from_env(),
+            format: cache_format_from_env(),
+            health_check: Arc::new(Notify::new()),
+            reconnect_attempts: Arc::new(AtomicU64::new(0)),
+            breaker: Arc::new(CircuitBreaker::new(
+                breaker_threshold_from_env(),
+                BREAKER_WINDOW,
+                breaker_cooldown_from_env(),
+            )),
+            shutdown: watch::Sender::new(false),
+            shutdown_grace: shutdown_grace_from_env(),
+            health_task: std::sync::Mutex::new(None),
+            sweeper_task: std::sync::Mutex::new(None),
+            instance_id: uuid::Uuid::new_v4().to_string(),
+            invalidation_task: std::sync::Mutex::new(None),
+            background: std::sync::Mutex::new(JoinSet::new()),
+            failed_writes: std::sync::Mutex::new(FailedWriteLog::new(
+                failed_writes_log_size_from_env(),
+            )),
+        })
+    }
+
+    /// Override the memory fallback's entry limit
+    pub fn with_memory_max_entries(mut self, max_entries: usize) -> Self {
+        self.memory_max_entries = max_entries.max(1);
+        self
+    }
+
+    /// Override how many failed writes `failed_writes` keeps; zero keeps none
+    pub fn with_failed_writes_log_size(mut self, size: usize) -> Self {
+        *unpoisoned(self.failed_writes.get_mut()) = FailedWriteLog::new(size);
+        self
+    }
+
+    /// Override the key namespace taken from `CACHE_NAMESPACE`
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.namespace = namespace_prefix(namespace);
+        self
+    }
+
+    /// Start the background health check that reconnects with exponential
+    /// backoff (1s, 2s, 4s, ... capped at 60s) whenever Redis is unavailable
+    pub fn with_auto_reconnect(self) -> Self {
+        self.spawn_health_check(RECONNECT_BASE_DELAY, RECONNECT_MAX_DELAY)
+    }
+
+    /// Start purging expired memory entries every `interval`, so keys nobody
+    /// reads again don't linger until the next eviction
+    pub fn with_memory_sweeper(mut self, interval: Duration) -> Self {
+        let memory_cache = Arc::clone(&self.memory_cache);
+        let task = tokio::spawn(until_shutdown(self.shutdown.subscribe(), async move {
+            let mut ticks = tokio::time::interval(interval);
+            loop {
+                ticks.tick().await;
+                let compaction = compact(&memory_cache).await;
+                if compaction.purged > 0 {
+                    tracing::debug!("Purged {} expired memory cache entries", compaction.purged);
+                }
+            }
+        }));
+        if let Some(previous) = unpoisoned(self.sweeper_task.get_mut()).replace(task) {
+            previous.abort();
+        }
+        self
+    }
+
+    /// Subscribe to `INVALIDATION_CHANNEL` and apply the invalidations other
+    /// instances publish to this instance's memory tier, so an entry written
+    /// here during a Redis outage can't outlive a delete made elsewhere. The
+    /// subscription is re-established with backoff whenever it drops;
+    /// invalidations published in the meantime are missed.
+    pub fn with_invalidation_subscriber(mut self) -> Self {
+        let redis_url = self.redis_url.clone();
+        let instance_id = self.instance_id.clone();
+        let memory_cache = Arc::clone(&self.memory_cache);
+        let memory_tags = Arc::clone(&self.memory_tags);
+
+        let task = tokio::spawn(until_shutdown(self.shutdown.subscribe(), async move {
+            use futures::StreamExt;
+
+            let mut failures: u32 = 0;
+            loop {
+                match subscribe(&redis_url, INVALIDATION_CHANNEL).await {
+                    Ok(mut pubsub) => {
+                        failures = 0;
+                        tracing::debug!("Subscribed to {}", INVALIDATION_CHANNEL);
+                        let mut messages = pubsub.on_message();
+                        while let Some(message) = messages.next().await {
+                            let Ok(payload) = message.get_payload::<String>() else {
+                                continue;
+                            };
+                            apply_invalidation(&instance_id, &payload, &memory_cache, &memory_tags)
+                                .await;
+                        }
+                        tracing::warn!(
+                            "Lost the {} subscription, resubscribing",
+                            INVALIDATION_CHANNEL
+                        );
+                    }
+                    Err(e) => {
+                        tracing::debug!("Could not subscribe to {}: {}", INVALIDATION_CHANNEL, e)
+                    }
+                }
+                let delay = reconnect_delay(failures, RECONNECT_BASE_DELAY, RECONNECT_MAX_DELAY);
+                failures = failures.saturating_add(1);
+                tokio::time::sleep(delay).await;
+            }
+        }));
+        if let Some(previous) = unpoisoned(self.invalidation_task.get_mut()).replace(task) {
+            previous.abort();
+        }
+        self
+    }
+
+    /// Override how long `shutdown` waits for background work
+    pub fn with_shutdown_grace(mut self, grace: Duration) -> Self {
+        self.shutdown_grace = grace;
+        self
+    }
+
+    fn spawn_health_check(mut self, base_delay: Duration, max_delay: Duration) -> Self {
+        if let Some(task) = unpoisoned(self.health_task.get_mut()).take() {
+            task.abort();
+        }
+
+        let redis_url = self.redis_url.clone();
+        let connection = Arc::clone(&self.redis_connection);
+        let replica = Arc::clone(&self.replica_connection);
+        let read_from_replica = self.read_from_replica;
+        let pool_size = self.pool_size;
+        let expect_master = matches!(
+            parse_redis_target(&redis_url),
+            Ok(RedisTarget::Sentinel { .. })
+        );
+        let notify = Arc::clone(&self.health_check);
+        let attempts = Arc::clone(&self.reconnect_attempts);
+        let breaker = Arc::clone(&self.breaker);
+        let stop = self.shutdown.subscribe();
+
+        let task = tokio::spawn(until_shutdown(stop, async move {
+            let mut failures: u32 = 0;
+            loop {
+                let current = connection.read().await.clone();
+                match current {
+                    Some(pool) => {
+                        // An open circuit is probed as soon as it half-opens
+                        let interval = breaker
+                            .cooldown_remaining()
+                            .map_or(HEALTH_CHECK_INTERVAL, |left| {
+                                left.min(HEALTH_CHECK_INTERVAL)
+                            });
+                        tokio::select! {
+                            _ = tokio::time::sleep(interval) => {}
+                            _ = notify.notified() => {}
+                        }
+                        match pool.probe(expect_master).await {
+                            Ok(()) => breaker.record_probe_success(),
+                            Err(e) => {
+                                tracing::warn!(
+                                    "Redis health check failed ({}), falling back to memory cache",
+                                    e
+                                );
+                                breaker.record_failure();
+                                *connection.write().await = None;
+                            }
+                        }
+                        if read_from_replica {
+                            Self::refresh_replica(&redis_url, &replica, pool_size).await;
+                        }
+                    }
+                    None => {
+                        attempts.fetch_add(1, Ordering::Relaxed);
+                        match Self::connect(&redis_url, pool_size).await {
+                            Some(pool) => {
+                                *connection.write().await = Some(pool);
+                                breaker.record_probe_success();
+                                tracing::info!(
+                                    "Redis connection restored after {} failed attempts",
+                                    failures
+                                );
+                                failures = 0;
+                            }
+                            None => {
+                                let delay = reconnect_delay(failures, base_delay, max_delay);
+                                failures = failures.saturating_add(1);
+                                tracing::debug!("Retrying Redis connection in {:?}", delay);
+                                tokio::time::sleep(delay).await;
+                            }
+                        }
+                    }
+                }
+            }
+        }));
+        *unpoisoned(self.health_task.get_mut()) = Some(task);
+        self
+    }
+
+    /// Hand a fire-and-forget task (a background refresh, a delayed write) to
+    /// the cache so `shutdown` waits for it instead of dropping it mid-flight
+    pub fn spawn_tracked<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let mut background = unpoisoned(self.background.lock());
+        // Finished tasks stay in the set until reaped
+        while background.try_join_next().is_some() {}
+        // Keep the spawning request's span, and with it its request id, on what the task logs
+        background.spawn(task.in_current_span());
+    }
+
+    /// Stop the health check, memory sweeper and invalidation subscriber, then give in-flight background
+    /// refreshes and writes up to the grace period (`CACHE_SHUTDOWN_GRACE_SECS`,
+    /// 10s by default) to finish. Returns `false` when some had to be aborted.
+    pub async fn shutdown(&self) -> bool {
+        self.shutdown.send_replace(true);
+        let mut handles: Vec<JoinHandle<()>> = [
+            &self.health_task,
+            &self.sweeper_task,
+            &self.invalidation_task,
+        ]
+        .into_iter()
+        .filter_map(|task| unpoisoned(task.lock()).take())
+        .collect();
+        let mut background = std::mem::take(&mut *unpoisoned(self.background.lock()));
+
+        let drained = tokio::time::timeout(self.shutdown_grace, async {
+            for handle in &mut handles {
+                let _ = handle.await;
+            }
+            while background.join_next().await.is_some() {}
+        })
+        .await
+        .is_ok();
+
+        if drained {
+            tracing::info!("Cache background tasks stopped");
+        } else {
+            tracing::warn!(
+                "Cache shutdown grace period of {:?} ran out, aborting {} background tasks",
+                self.shutdown_grace,
+                background.len()
+            );
+            for handle in &handles {
+                handle.abort();
+            }
+            background.abort_all();
+        }
+        drained
+    }
+
+    /// Count a failed Redis command towards the circuit breaker and prompt the
+    /// health check to verify the connection
+    fn record_redis_error(&self, key: &str) {
+        self.metrics.record_error(key);
+        self.breaker.record_failure();
+        self.health_check.notify_one();
+    }
+
+    /// Remember a write Redis rejected. Writes that went to memory because
+    /// Redis was down or the breaker open never reached Redis and aren't kept.
+    fn record_failed_write(&self, key: &str, error: &redis::RedisError) {
+        unpoisoned(self.failed_writes.lock()).push(FailedWrite {
+            key: key.to_string(),
+            failed_at: Utc::now(),
+            error_kind: format!("{:?}", error.kind()),
+        });
+    }
+
+    /// The most recent writes Redis rejected, oldest first, up to
+    /// `CACHE_FAILED_WRITES_LOG_SIZE` of them
+    pub fn failed_writes(&self) -> Vec<FailedWrite> {
+        unpoisoned(self.failed_writes.lock())
+            .entries
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// How many failed writes `failed_writes` keeps
+    pub fn failed_writes_capacity(&self) -> usize {
+        unpoisoned(self.failed_writes.lock()).capacity
+    }
+
+    /// Announce an invalidation on `INVALIDATION_CHANNEL`. Without Redis there
+    /// is nobody to tell; a failed publish is logged and otherwise ignored,
+    /// since the shared Redis tier was invalidated either way.
+    async fn publish_invalidation(&self, invalidation: Invalidation) {
+        let Some(mut conn) = self.write_connection().await else {
+            return;
+        };
+        let payload = match self.invalidation_payload(invalidation) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::warn!("Failed to encode invalidation message: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = conn
+            .publish::<_, _, ()>(INVALIDATION_CHANNEL, payload)
+            .await
+        {
+            tracing::warn!("Failed to publish invalidation: {}", e);
+        }
+    }
+
+    fn invalidation_payload(&self, invalidation: Invalidation) -> serde_json::Result<String> {
+        serde_json::to_string(&InvalidationMessage {
+            origin: self.instance_id.clone(),
+            invalidation,
+        })
+    }
+
+    /// Reconnection attempts made by the background health check so far
+    pub fn reconnect_attempts(&self) -> u64 {
+        self.reconnect_attempts.load(Ordering::Relaxed)
+    }
+
+    /// Override the payload size above which values are compressed
+    pub fn with_compress_threshold(mut self, threshold: usize) -> Self {
+        self.compress_threshold = threshold;
+        self
+    }
+
+    /// Override the serialization format for values written from now on.
+    /// Entries stored in another format read as misses.
+    pub fn with_format(mut self, format: CacheFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Override the key version, mainly to exercise a `CACHE_VERSION` bump
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// The key actually stored for a logical `CacheKey` or glob pattern:
+    /// `[namespace:]v<version>:<key>`
+    fn storage_key(&self, key: &str) -> String {
+        format!("{}v{}:{}", self.namespace, self.version, key)
+    }
+
+    async fn connect(redis_url: &str, pool_size: usize) -> Option<RedisPool> {
+        Self::connect_node(redis_url, NodeRole::Primary, pool_size).await
+    }
+
+    /// Open `pool_size` connections to the node, or none at all if any fails
+    async fn connect_node(redis_url: &str, role: NodeRole, pool_size: usize) -> Option<RedisPool> {
+        let label = match role {
+            NodeRole::Primary => "Redis",
+            NodeRole::Replica => "Redis replica",
+        };
+        match resolve_client(redis_url, role).await {
+            Ok(client) => {
+                let mut connections = Vec::with_capacity(pool_size);
+                for _ in 0..pool_size {
+                    match client.get_multiplexed_tokio_connection().await {
+                        Ok(conn) => connections.push(conn),
+                        Err(e) => {
+                            tracing::warn!(
+                                "Failed to connect to {} ({}), using memory-only caching",
+                                label,
+                                e
+                            );
+                            return None;
+                        }
+                    }
+                }
+                let pool = RedisPool::new(connections)?;
+                tracing::info!(
+                    "Connected to {} for caching ({} connections)",
+                    label,
+                    pool.len()
+                );
+                Some(pool)
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Could not set up {} client ({}), using memory-only caching",
+                    label,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Keep the read replica usable: drop it if it stops answering and
+    /// re-resolve one through Sentinel while none is connected
+    async fn refresh_replica(
+        redis_url: &str,
+        replica: &RwLock<Option<RedisPool>>,
+        pool_size: usize,
+    ) {
+        let current = replica.read().await.clone();
+        match current {
+            Some(pool) => {
+                if let Err(e) = pool.probe(false).await {
+                    tracing::warn!(
+                        "Redis replica health check failed ({}), reading from master",
+                        e
+                    );
+                    *replica.write().await = None;
+                }
+            }
+            None => {
+                if let Some(pool) =
+                    Self::connect_node(redis_url, NodeRole::Replica, pool_size).await
+                {
+                    *replica.write().await = Some(pool);
+                }
+            }
+        }
+    }
+
+    /// Connection for reads: the replica when one is in use, otherwise the
+    /// master. `None` while the circuit breaker is keeping commands off Redis.
+    async fn read_connection(&self) -> Option<MultiplexedConnection> {
+        if !self.breaker.allow() {
+            return None;
+        }
+        if let Some(pool) = self.replica_connection.read().await.as_ref() {
+            return Some(pool.get());
+        }
+        self.master_connection().await
+    }
+
+    /// The next pooled connection to the master, if Redis is connected and the
+    /// circuit breaker lets commands through
+    async fn write_connection(&self) -> Option<MultiplexedConnection> {
+        if !self.breaker.allow() {
+            return None;
+        }
+        self.master_connection().await
+    }
+
+    async fn master_connection(&self) -> Option<MultiplexedConnection> {
+        self.redis_connection
+            .read()
+            .await
+            .as_ref()
+            .map(RedisPool::get)
+    }
+
+    /// Get a cached value, checking Redis first and the memory cache when Redis is unavailable
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        Ok(self.get_with_tier(key).await?.map(|(value, _)| value))
+    }
+
+    /// `get` that also reports which tier the value came from
+    pub async fn get_with_tier<T: DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Result<Option<(T, CacheTier)>> {
+        Ok(self
+            .get_with_status(key)
+            .await?
+            .and_then(|(value, status)| status.tier().map(|tier| (value, tier))))
+    }
+
+    /// `get` that reports the hit, with its tier and write time
+    pub async fn get_with_status<T: DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Result<Option<(T, CacheStatus)>> {
+        self.lookup(key, true, |data| decode_entry(data, self.format))
+            .await
+    }
+
+    /// Get the bytes stored under `key` by `set_raw`, exactly as they were
+    /// given. Reading a key written by the typed `set` returns its serialized
+    /// form (behind `MSGPACK_MAGIC` in MessagePack); callers mixing raw and
+    /// typed access to one key are responsible for the formats matching.
+    pub async fn get_raw(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let hit = self
+            .lookup(key, true, |data| {
+                let (payload, cached_at) = decode_raw_entry(data)?;
+                Ok(Some((payload.into_owned(), cached_at)))
+            })
+            .await?;
+        Ok(hit.map(|(bytes, _)| bytes))
+    }
+
+    /// Shared read path for `get` and `get_raw`, which differ only in `decode`;
+    /// `track` controls whether hits and misses are counted. Every value
+    /// returned comes with a `CacheStatus::Hit`.
+    async fn lookup<T, D>(
+        &self,
+        key: &str,
+        track: bool,
+        decode: D,
+    ) -> Result<Option<(T, CacheStatus)>>
+    where
+        D: Fn(&[u8]) -> Result<Option<Stamped<T>>>,
+    {
+        let storage_key = self.storage_key(key);
+        if let Some(mut conn) = self.read_connection().await {
+            let started = Instant::now();
+            let reply = conn.get::<_, Option<Vec<u8>>>(&storage_key).await;
+            let latency = started.elapsed();
+            self.metrics.redis_latency.get.record(latency);
+            if reply.is_ok() {
+                self.breaker.record_success();
+            }
+            match reply {
+                Ok(Some(data)) => match decode(&data) {
+                    Ok(Some((value, cached_at))) => {
+                        if track {
+                            self.metrics.record_hit(key);
+                        }
+                        log_cache_op(
+                            "get",
+                            CacheTier::Redis,
+                            "hit",
+                            key,
+                            latency,
+                            format_args!("Cache hit (redis): {}", key),
+                        );
+                        let status = CacheStatus::Hit {
+                            tier: CacheTier::Redis,
+                            cached_at,
+                        };
+                        return Ok(Some((value, status)));
+                    }
+                    Ok(None) => {
+                        // Left for the next write to overwrite, since replicas
+                        // still on the other format can read it
+                        if track {
+                            self.metrics.record_miss(key);
+                        }
+                        log_cache_op(
+                            "get",
+                            CacheTier::Redis,
+                            "miss",
+                            key,
+                            latency,
+                            format_args!("Cache miss (stored in another format): {}", key),
+                        );
+                        return Ok(None);
+                    }
+                    Err(e) => {
+                        self.record_corrupt(key, &e, track);
+                        if let Err(e) = conn.del::<_, ()>(&storage_key).await {
+                            tracing::warn!("Redis delete failed for {}: {}", key, e);
+                        }
+                        return Ok(None);
+                    }
+                },
+                Ok(None) => {
+                    if track {
+                        self.metrics.record_miss(key);
+                    }
+                    log_cache_op(
+                        "get",
+                        CacheTier::Redis,
+                        "miss",
+                        key,
+                        latency,
+                        format_args!("Cache miss: {}", key),
+                    );
+                    return Ok(None);
+                }
+                Err(e) if is_missing_value(&e) => {
+                    if track {
+                        self.metrics.record_miss(key);
+                    }
+                    log_cache_op(
+                        "get",
+                        CacheTier::Redis,
+                        "miss",
+                        key,
+                        latency,
+                        format_args!("Cache miss (no usable value): {}", key),
+                    );
+                    return Ok(None);
+                }
+                Err(e) => {
+                    self.record_redis_error(key);
+                    tracing::warn!(
+                        "Redis get failed for {} ({:?}: {}), falling back to memory cache",
+                        key,
+                        e.kind(),
+                        e
+                    );
+                }
+            }
+        }
+
+        Ok(self
+            .memory_lookup(key, &storage_key, track, &decode)
+            .await
+            .map(|(value, cached_at)| {
+                let status = CacheStatus::Hit {
+                    tier: CacheTier::Memory,
+                    cached_at,
+                };
+                (value, status)
+            }))
+    }
+
+    /// Read path for the memory fallback tier
+    async fn memory_lookup<T, D>(
+        &self,
+        key: &str,
+        storage_key: &str,
+        track: bool,
+        decode: &D,
+    ) -> Option<(T, Option<DateTime<Utc>>)>
+    where
+        D: Fn(&[u8]) -> Result<Option<Stamped<T>>>,
+    {
+        let started = Instant::now();
+        let mut memory_cache = self.memory_cache.write().await;
+        let result = match memory_cache.get_mut(storage_key) {
+            Some(entry) if !entry.is_expired() => match decode(&entry.data) {
+                Ok(Some(hit)) => {
+                    if track {
+                        self.metrics.record_hit(key);
+                    }
+                    entry.last_used = self.next_tick();
+                    log_cache_op(
+                        "get",
+                        CacheTier::Memory,
+                        "hit",
+                        key,
+                        started.elapsed(),
+                        format_args!("Cache hit (memory): {}", key),
+                    );
+                    Some(hit)
+                }
+                Ok(None) => {
+                    if track {
+                        self.metrics.record_miss(key);
+                    }
+                    log_cache_op(
+                        "get",
+                        CacheTier::Memory,
+                        "miss",
+                        key,
+                        started.elapsed(),
+                        format_args!("Cache miss (stored in another format): {}", key),
+                    );
+                    None
+                }
+                Err(e) => {
+                    memory_cache.remove(storage_key);
+                    self.record_corrupt(key, &e, track);
+                    None
+                }
+            },
+            Some(_) => {
+                memory_cache.remove(storage_key);
+                if track {
+                    self.metrics.record_miss(key);
+                }
+                log_cache_op(
+                    "get",
+                    CacheTier::Memory,
+                    "expired",
+                    key,
+                    started.elapsed(),
+                    format_args!("Cache miss (expired): {}", key),
+                );
+                None
+            }
+            None => {
+                if track {
+                    self.metrics.record_miss(key);
+                }
+                log_cache_op(
+                    "get",
+                    CacheTier::Memory,
+                    "miss",
+                    key,
+                    started.elapsed(),
+                    format_args!("Cache miss: {}", key),
+                );
+                None
+            }
+        };
+        drop(memory_cache);
+        self.metrics.memory_latency.get.record(started.elapsed());
+
+        result
+    }
+
+    /// Read several keys in one Redis `MGET`, returning a slot per key in order.
+    /// Each slot deserializes on its own, so a corrupt entry reads as `None`
+    /// without failing the rest. When Redis is unavailable or the `MGET` fails,
+    /// every slot is looked up in the memory cache instead.
+    pub async fn mget<T: DeserializeOwned>(&self, keys: &[&str]) -> Result<Vec<Option<T>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let storage_keys: Vec<String> = keys.iter().map(|key| self.storage_key(key)).collect();
+
+        if let Some(mut conn) = self.read_connection().await {
+            let started = Instant::now();
+            let reply = redis::cmd("MGET")
+                .arg(&storage_keys)
+                .query_async::<_, Vec<Option<Vec<u8>>>>(&mut conn)
+                .await;
+            self.metrics.redis_latency.get.record(started.elapsed());
+            if reply.is_ok() {
+                self.breaker.record_success();
+            }
+            match reply {
+                Ok(slots) => {
+                    let mut values = Vec::with_capacity(keys.len());
+                    let mut corrupt = Vec::new();
+                    for ((key, storage_key), slot) in keys.iter().zip(&storage_keys).zip(slots) {
+                        let value = match slot.map(|data| decode_entry(&data, self.format)) {
+                            Some(Ok(Some((value, _)))) => {
+                                self.metrics.record_hit(key);
+                                Some(value)
+                            }
+                            Some(Err(e)) => {
+                                self.record_corrupt(key, &e, true);
+                                corrupt.push(storage_key);
+                                None
+                            }
+                            Some(Ok(None)) | None => {
+                                self.metrics.record_miss(key);
+                                None
+                            }
+                        };
+                        values.push(value);
+                    }
+                    if !corrupt.is_empty() {
+                        if let Err(e) = conn.del::<_, ()>(corrupt).await {
+                            tracing::warn!("Redis delete of corrupt entries failed: {}", e);
+                        }
+                    }
+                    tracing::debug!("Cache mget (redis): {} keys", keys.len());
+                    return Ok(values);
+                }
+                Err(e) => {
+                    self.record_redis_error(keys[0]);
+                    tracing::warn!(
+                        "Redis mget failed for {} keys ({}), falling back to memory cache",
+                        keys.len(),
+                        e
+                    );
+                }
+            }
+        }
+
+        let mut values = Vec::with_capacity(keys.len());
+        for (key, storage_key) in keys.iter().zip(&storage_keys) {
+            let hit = self
+                .memory_lookup(key, storage_key, true, &|data: &[u8]| {
+                    decode_entry(data, self.format)
+                })
+                .await;
+            values.push(hit.map(|(value, _)| value));
+        }
+        Ok(values)
+    }
+
+    /// A cached value that no longer deserializes (usually a shape written by an
+    /// older build) is counted as an error and then treated as a miss; the caller
+    /// drops the key so the next write repopulates it
+    fn record_corrupt(&self, key: &str, err: &CacheError, track: bool) {
+        self.metrics.record_error(key);
+        if track {
+            self.metrics.record_miss(key);
+        }
+        tracing::warn!("Dropping undeserializable cache entry {}: {}", key, err);
+    }
+
+    /// Store a value with a TTL, writing to the memory cache when Redis is unavailable
+    pub async fn set<T: Serialize>(&self, key: &str, value: &T, ttl_secs: usize) -> Result<()> {
+        self.set_raw(key, &serialize_value(self.format, value)?, ttl_secs)
+            .await
+    }
+
+    /// Store already-serialized bytes as they are, for payloads that arrive
+    /// encoded (e.g. JSON snapshots) and would otherwise be parsed only to be
+    /// serialized again. The key is namespaced, expires and falls back to
+    /// memory like any other, and large payloads are still compressed.
+    ///
+    /// Nothing checks the bytes: a typed `get` of the key decodes them with
+    /// the configured format, so JSON written here reads back with `get` only
+    /// under `CACHE_FORMAT=json`. Callers mixing raw and typed access to one
+    /// key are responsible for the formats matching.
+    pub async fn set_raw(&self, key: &str, bytes: &[u8], ttl_secs: usize) -> Result<()> {
+        self.store(key, bytes, Duration::from_secs(ttl_secs as u64))
+            .await
+    }
+
+    /// `set` with a TTL in milliseconds, for keys that must not outlive a
+    /// fraction of a second (short locks, brief negative caching). Written
+    /// with `PSETEX`; a zero TTL is raised to 1ms, which Redis accepts.
+    pub async fn set_ms<T: Serialize>(&self, key: &str, value: &T, ttl_ms: u64) -> Result<()> {
+        self.store(
+            key,
+            &serialize_value(self.format, value)?,
+            Duration::from_millis(ttl_ms.max(1)),
+        )
+        .await
+    }
+
+    /// Write path shared by every `set`. Whole-second TTLs go out as `SETEX`,
+    /// anything finer as `PSETEX`.
+    async fn store(&self, key: &str, bytes: &[u8], ttl: Duration) -> Result<()> {
+        let data = stamp_payload(encode_payload(bytes, self.compress_threshold)?, Utc::now());
+        let storage_key = self.storage_key(key);
+
+        if let Some(mut conn) = self.write_connection().await {
+            let started = Instant::now();
+            let reply = if ttl.subsec_nanos() == 0 {
+                conn.set_ex::<_, _, ()>(&storage_key, &data, ttl.as_secs())
+                    .await
+            } else {
+                conn.pset_ex::<_, _, ()>(&storage_key, &data, ttl.as_millis() as u64)
+                    .await
+            };
+            let latency = started.elapsed();
+            self.metrics.redis_latency.set.record(latency);
+            match reply {
+                Ok(()) => {
+                    self.breaker.record_success();
+                    log_cache_op(
+                        "set",
+                        CacheTier::Redis,
+                        "stored",
+                        key,
+                        latency,
+                        format_args!("Cached (redis): {} (ttl {:?})", key, ttl),
+                    );
+                    return Ok(());
+                }
+                Err(e) => {
+                    self.record_redis_error(key);
+                    self.record_failed_write(key, &e);
+                    tracing::warn!("Redis set failed for {} ({}), using memory cache", key, e);
+                }
+            }
+        }
+
+        let started = Instant::now();
+        let mut memory_cache = self.memory_cache.write().await;
+        memory_cache.insert(
+            storage_key,
+            MemoryCacheEntry {
+                data,
+                expires_at: Instant::now() + ttl,
+                last_used: self.next_tick(),
+            },
+        );
+        if memory_cache.len() > self.memory_max_entries {
+            evict_to_capacity(&mut memory_cache, self.memory_max_entries);
+        }
+        drop(memory_cache);
+        let latency = started.elapsed();
+        self.metrics.memory_latency.set.record(latency);
+        log_cache_op(
+            "set",
+            CacheTier::Memory,
+            "stored",
+            key,
+            latency,
+            format_args!("Cached (memory): {} (ttl {:?})", key, ttl),
+        );
+
+        Ok(())
+    }
+
+    /// Drop every expired memory entry, returning how many went; the sweeper
+    /// started by `with_memory_sweeper` does this periodically
+    pub async fn purge_expired(&self) -> usize {
+        self.compact_memory().await.purged
+    }
+
+    /// Purge expired memory entries now, independently of the background sweep,
+    /// reporting how many went and how many remain
+    pub async fn compact_memory(&self) -> MemoryCompaction {
+        compact(&self.memory_cache).await
+    }
+
+    fn next_tick(&self) -> u64 {
+        self.access_clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Store a value with its TTL randomly spread by up to ±`jitter_pct` percent,
+    /// so keys written together don't all expire together
+    pub async fn set_with_jitter<T: Serialize>(
+        &self,
+        key: &str,
+        value: &T,
+        base_ttl: usize,
+        jitter_pct: f64,
+    ) -> Result<()> {
+        self.set(key, value, jittered_ttl(base_ttl, jitter_pct))
+            .await
+    }
+
+    /// Cache-aside helper: return the cached value for `key`, or run `loader`,
+    /// cache its result and return it. A failed cache write is logged but never
+    /// fails the call, since the loaded value is still good.
+    pub async fn get_or_set<T, F, Fut, E>(
+        &self,
+        key: &str,
+        ttl_secs: usize,
+        loader: F,
+    ) -> Result<T, E>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        self.get_or_set_with_jitter(key, ttl_secs, 0.0, loader)
+            .await
+    }
+
+    /// `get_or_set` that stores the loaded value with a jittered TTL.
+    ///
+    /// Concurrent misses on the same key are collapsed: the first caller runs
+    /// `loader` while the rest wait on a per-key lock and then read the value it
+    /// cached. If the loader fails, the next waiter runs its own loader.
+    pub async fn get_or_set_with_jitter<T, F, Fut, E>(
+        &self,
+        key: &str,
+        base_ttl: usize,
+        jitter_pct: f64,
+        loader: F,
+    ) -> Result<T, E>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        self.get_or_set_tagged(key, base_ttl, jitter_pct, &[], loader)
+            .await
+    }
+
+    /// `get_or_set_with_jitter` that also reports where the value came from
+    pub async fn get_or_set_with_status<T, F, Fut, E>(
+        &self,
+        key: &str,
+        base_ttl: usize,
+        jitter_pct: f64,
+        loader: F,
+    ) -> Result<(T, CacheStatus), E>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        self.get_or_set_tagged_with_status(key, base_ttl, jitter_pct, &[], loader)
+            .await
+    }
+
+    /// `get_or_set_with_jitter` that also registers a freshly loaded key under
+    /// each of `tags`, so `invalidate_tag` can later drop exactly those keys
+    pub async fn get_or_set_tagged<T, F, Fut, E>(
+        &self,
+        key: &str,
+        base_ttl: usize,
+        jitter_pct: f64,
+        tags: &[&str],
+        loader: F,
+    ) -> Result<T, E>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        self.get_or_set_tagged_with_status(key, base_ttl, jitter_pct, tags, loader)
+            .await
+            .map(|(value, _)| value)
+    }
+
+    /// `get_or_set_tagged` that also reports whether the value was a hit, and
+    /// from which tier. A value another caller cached while we waited on the
+    /// single-flight lock counts as a hit.
+    pub async fn get_or_set_tagged_with_status<T, F, Fut, E>(
+        &self,
+        key: &str,
+        base_ttl: usize,
+        jitter_pct: f64,
+        tags: &[&str],
+        loader: F,
+    ) -> Result<(T, CacheStatus), E>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        self.get_or_set_tagged_with_ttl_fn(key, |_| base_ttl, jitter_pct, tags, loader)
+            .await
+    }
+
+    /// `get_or_set_tagged_with_status` for values whose TTL depends on what
+    /// was loaded: `ttl_for` picks the base TTL from the loaded value.
+    pub async fn get_or_set_tagged_with_ttl_fn<T, F, Fut, E>(
+        &self,
+        key: &str,
+        ttl_for: impl FnOnce(&T) -> usize,
+        jitter_pct: f64,
+        tags: &[&str],
+        loader: F,
+    ) -> Result<(T, CacheStatus), E>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        if let Ok(Some(hit)) = self.get_with_status::<T>(key).await {
+            return Ok(hit);
+        }
+
+        let lock = self.inflight_lock(key).await;
+        let result = {
+            let _guard = lock.lock().await;
+
+            // Another caller may have filled the key while we waited for the lock
+            if let Ok(Some(hit)) = self
+                .lookup(key, false, |data| decode_entry(data, self.format))
+                .await
+            {
+                Ok(hit)
+            } else {
+                match loader().await {
+                    Ok(value) => {
+                        let base_ttl = ttl_for(&value);
+                        if let Err(e) = self
+                            .set_with_jitter(key, &value, base_ttl, jitter_pct)
+                            .await
+                        {
+                            tracing::warn!("Failed to cache {}: {}", key, e);
+                        }
+                        if let Err(e) = self.tag(key, tags).await {
+                            tracing::warn!("Failed to tag {}: {}", key, e);
+                        }
+                        Ok((value, CacheStatus::Miss))
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+        };
+        self.release_inflight(key, lock).await;
+
+        result
+    }
+
+    /// Run `loader` without reading the cache, then store and tag its result
+    /// exactly as a miss in `get_or_set_tagged` would. Used to force a fresh
+    /// read while still letting later callers hit the refreshed entry.
+    pub async fn refresh_tagged<T, F, Fut, E>(
+        &self,
+        key: &str,
+        base_ttl: usize,
+        jitter_pct: f64,
+        tags: &[&str],
+        loader: F,
+    ) -> Result<T, E>
+    where
+        T: Serialize,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        self.refresh_tagged_with_ttl_fn(key, |_| base_ttl, jitter_pct, tags, loader)
+            .await
+    }
+
+    /// `refresh_tagged` with the base TTL picked from the loaded value
+    pub async fn refresh_tagged_with_ttl_fn<T, F, Fut, E>(
+        &self,
+        key: &str,
+        ttl_for: impl FnOnce(&T) -> usize,
+        jitter_pct: f64,
+        tags: &[&str],
+        loader: F,
+    ) -> Result<T, E>
+    where
+        T: Serialize,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let value = loader().await?;
+        let base_ttl = ttl_for(&value);
+        if let Err(e) = self
+            .set_with_jitter(key, &value, base_ttl, jitter_pct)
+            .await
+        {
+            tracing::warn!("Failed to cache {}: {}", key, e);
+        }
+        if let Err(e) = self.tag(key, tags).await {
+            tracing::warn!("Failed to tag {}: {}", key, e);
+        }
+        tracing::debug!("Cache bypassed and refreshed: {}", key);
+
+        Ok(value)
+    }
+
+    /// Stale-while-revalidate lookup. For `fresh_secs` after a load the cached
+    /// value is returned as-is. For a further `stale_secs` it is still returned
+    /// immediately, but a background task reloads and re-caches it. Past that
+    /// window (or on a miss) the caller blocks on `loader` like `get_or_set`.
+    pub async fn get_stale_while_revalidate<T, F, Fut, E>(
+        self: &Arc<Self>,
+        key: &str,
+        fresh_secs: usize,
+        stale_secs: usize,
+        loader: F,
+    ) -> Result<T, E>
+    where
+        T: Serialize + DeserializeOwned + Send + Sync + 'static,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        E: Display + Send + 'static,
+    {
+        self.get_stale_while_revalidate_with_status(key, fresh_secs, stale_secs, loader)
+            .await
+            .map(|(value, _)| value)
+    }
+
+    /// `get_stale_while_revalidate` that also reports where the value came
+    /// from; a stale value served during revalidation counts as a hit
+    pub async fn get_stale_while_revalidate_with_status<T, F, Fut, E>(
+        self: &Arc<Self>,
+        key: &str,
+        fresh_secs: usize,
+        stale_secs: usize,
+        loader: F,
+    ) -> Result<(T, CacheStatus), E>
+    where
+        T: Serialize + DeserializeOwned + Send + Sync + 'static,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        E: Display + Send + 'static,
+    {
+        let now = unix_millis();
+        if let Ok(Some((envelope, status))) = self.get_with_status::<SwrEnvelope<T>>(key).await {
+            if now < envelope.fresh_until {
+                return Ok((envelope.value, status));
+            }
+            if now < envelope.stale_until {
+                self.spawn_revalidation(key, fresh_secs, stale_secs, loader)
+                    .await;
+                return Ok((envelope.value, status));
+            }
+        }
+
+        let value = loader().await?;
+        self.store_swr(key, &value, fresh_secs, stale_secs).await;
+        Ok((value, CacheStatus::Miss))
+    }
+
+    /// `refresh_tagged` for a stale-while-revalidate key: load now and start a
+    /// fresh window
+    pub async fn refresh_stale_while_revalidate<T, F, Fut, E>(
+        &self,
+        key: &str,
+        fresh_secs: usize,
+        stale_secs: usize,
+        loader: F,
+    ) -> Result<T, E>
+    where
+        T: Serialize,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let value = loader().await?;
+        self.store_swr(key, &value, fresh_secs, stale_secs).await;
+        Ok(value)
+    }
+
+    /// Reload `key` in the background unless a refresh for it is already running
+    async fn spawn_revalidation<T, F, Fut, E>(
+        self: &Arc<Self>,
+        key: &str,
+        fresh_secs: usize,
+        stale_secs: usize,
+        loader: F,
+    ) where
+        T: Serialize + Send + Sync + 'static,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        E: Display + Send + 'static,
+    {
+        if !unpoisoned(self.refreshing.lock()).insert(key.to_string()) {
+            return;
+        }
+        let refreshing = RefreshingGuard {
+            refreshing: Arc::clone(&self.refreshing),
+            key: key.to_string(),
+        };
+
+        let cache = Arc::clone(self);
+        self.spawn_tracked(async move {
+            let key = &refreshing.key;
+            tracing::debug!("Revalidating stale cache entry: {}", key);
+            match loader().await {
+                Ok(value) => cache.store_swr(key, &value, fresh_secs, stale_secs).await,
+                Err(e) => tracing::warn!("Background refresh of {} failed: {}", key, e),
+            }
+        });
+    }
+
+    /// Write a stale-while-revalidate envelope; Redis keeps it for the whole stale window
+    async fn store_swr<T: Serialize>(
+        &self,
+        key: &str,
+        value: &T,
+        fresh_secs: usize,
+        stale_secs: usize,
+    ) {
+        let now = unix_millis();
+        let fresh_until = now + fresh_secs as u64 * 1000;
+        let envelope = SwrEnvelope {
+            value,
+            fresh_until,
+            stale_until: fresh_until + stale_secs as u64 * 1000,
+        };
+
+        if let Err(e) = self.set(key, &envelope, fresh_secs + stale_secs).await {
+            tracing::warn!("Failed to cache {}: {}", key, e);
+        }
+    }
+
+    /// Fetch (or create) the single-flight lock for `key`
+    async fn inflight_lock(&self, key: &str) -> Arc<Mutex<()>> {
+        self.inflight
+            .lock()
+            .await
+            .entry(key.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Drop our handle on the lock for `key`, removing it from the map once no
+    /// other caller is waiting on it
+    async fn release_inflight(&self, key: &str, lock: Arc<Mutex<()>>) {
+        let mut inflight = self.inflight.lock().await;
+        drop(lock);
+        if let Some(entry) = inflight.get(key) {
+            if Arc::strong_count(entry) == 1 {
+                inflight.remove(key);
+            }
+        }
+    }
+
+    /// Whether `key` holds a value, without fetching it: `EXISTS` on Redis, or
+    /// the memory tier while Redis is unavailable
+    pub async fn exists(&self, key: &str) -> Result<bool> {
+        let storage_key = self.storage_key(key);
+        if let Some(mut conn) = self.read_connection().await {
+            match conn.exists::<_, bool>(&storage_key).await {
+                Ok(exists) => {
+                    self.breaker.record_success();
+                    return Ok(exists);
+                }
+                Err(e) => {
+                    self.record_redis_error(key);
+                    tracing::warn!(
+                        "Redis exists failed for {} ({}), checking memory cache",
+                        key,
+                        e
+                    );
+                }
+            }
+        }
+
+        let memory_cache = self.memory_cache.read().await;
+        Ok(memory_cache
+            .get(&storage_key)
+            .is_some_and(|entry| !entry.is_expired()))
+    }
+
+    /// How long `key` has left before it expires, without fetching it: `PTTL`
+    /// on Redis, or the memory tier while Redis is unavailable. `None` when the
+    /// key is missing or never expires.
+    pub async fn remaining_ttl(&self, key: &str) -> Result<Option<Duration>> {
+        let storage_key = self.storage_key(key);
+        if let Some(mut conn) = self.read_connection().await {
+            match conn.pttl::<_, i64>(&storage_key).await {
+                Ok(millis) => {
+                    self.breaker.record_success();
+                    // -2 for a missing key, -1 for one without an expiry
+                    return Ok(u64::try_from(millis).ok().map(Duration::from_millis));
+                }
+                Err(e) => {
+                    self.record_redis_error(key);
+                    tracing::warn!(
+                        "Redis PTTL failed for {} ({}), checking memory cache",
+                        key,
+                        e
+                    );
+                }
+            }
+        }
+
+        let memory_cache = self.memory_cache.read().await;
+        Ok(memory_cache
+            .get(&storage_key)
+            .and_then(|entry| entry.expires_at.checked_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero()))
+    }
+
+    /// Remove a single key from both tiers
+    pub async fn delete(&self, key: &str) -> Result<()> {
+        let storage_key = self.storage_key(key);
+        if let Some(mut conn) = self.write_connection().await {
+            let started = Instant::now();
+            let reply = conn.del::<_, ()>(&storage_key).await;
+            let latency = started.elapsed();
+            self.metrics.redis_latency.delete.record(latency);
+            match reply {
+                Ok(()) => log_cache_op(
+                    "delete",
+                    CacheTier::Redis,
+                    "deleted",
+                    key,
+                    latency,
+                    format_args!("Deleted (redis): {}", key),
+                ),
+                Err(e) => {
+                    self.record_redis_error(key);
+                    tracing::warn!("Redis delete failed for {}: {}", key, e);
+                }
+            }
+        }
+
+        let started = Instant::now();
+        self.memory_cache.write().await.remove(&storage_key);
+        let latency = started.elapsed();
+        self.metrics.memory_latency.delete.record(latency);
+        self.metrics.record_invalidation();
+        log_cache_op(
+            "delete",
+            CacheTier::Memory,
+            "deleted",
+            key,
+            latency,
+            format_args!("Invalidated cache key: {}", key),
+        );
+        self.publish_invalidation(Invalidation::Keys {
+            keys: vec![storage_key],
+        })
+        .await;
+
+        Ok(())
+    }
+
+    /// Remove several keys from both tiers, in one pipelined round trip to Redis
+    pub async fn delete_many(&self, keys: &[String]) -> Result<()> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+        let storage_keys: Vec<String> = keys.iter().map(|key| self.storage_key(key)).collect();
+
+        if let Some(mut conn) = self.write_connection().await {
+            let mut pipe = redis::pipe();
+            for storage_key in &storage_keys {
+                pipe.del(storage_key).ignore();
+            }
+            let started = Instant::now();
+            let reply = pipe.query_async::<_, ()>(&mut conn).await;
+            self.metrics.redis_latency.delete.record(started.elapsed());
+            if let Err(e) = reply {
+                self.record_redis_error(&keys[0]);
+                tracing::warn!("Redis delete failed for {} keys: {}", keys.len(), e);
+            }
+        }
+
+        let started = Instant::now();
+        let mut memory_cache = self.memory_cache.write().await;
+        for storage_key in &storage_keys {
+            memory_cache.remove(storage_key);
+        }
+        drop(memory_cache);
+        self.metrics.memory_latency.delete.record(started.elapsed());
+        self.metrics.record_invalidation();
+        tracing::debug!("Invalidated {} cache keys", keys.len());
+        self.publish_invalidation(Invalidation::Keys { keys: storage_keys })
+            .await;
+
+        Ok(())
+    }
+
+    /// Bump a counter that expires `window_secs` after its first increment and
+    /// return the new count, or `None` when there is no Redis to count in.
+    /// Counters are never mirrored to the memory tier.
+    pub async fn incr_window(&self, key: &str, window_secs: usize) -> Result<Option<u64>> {
+        let Some(mut conn) = self.write_connection().await else {
+            return Ok(None);
+        };
+        let storage_key = self.storage_key(key);
+
+        let reply: redis::RedisResult<u64> = redis::Script::new(INCR_WINDOW_SCRIPT)
+            .key(&storage_key)
+            .arg(window_secs)
+            .invoke_async(&mut conn)
+            .await;
+
+        match reply {
+            Ok(count) => Ok(Some(count)),
+            Err(e) => {
+                self.record_redis_error(key);
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Run `write` holding `lock_key` in this process and, while Redis is
+    /// reachable, across replicas, so read-compare-write sequences on shared
+    /// entries can't interleave. `Ok(None)` without running `write` when another
+    /// replica holds the lock. Without Redis only this process's memory tier is
+    /// written, so the local lock is enough.
+    pub async fn with_write_lock<T, E, F, Fut>(
+        &self,
+        lock_key: &str,
+        ttl_secs: u64,
+        write: F,
+    ) -> std::result::Result<Option<T>, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = std::result::Result<T, E>>,
+    {
+        let local = self.inflight_lock(lock_key).await;
+        let result = {
+            let _local = local.lock().await;
+            match self.try_lock(lock_key, ttl_secs).await {
+                Ok(None) => Ok(None),
+                Ok(Some(remote)) => {
+                    let result = write().await.map(Some);
+                    if let Err(e) = remote.release().await {
+                        tracing::warn!("Failed to release lock {}: {}", lock_key, e);
+                    }
+                    result
+                }
+                Err(_) => write().await.map(Some),
+            }
+        };
+        self.release_inflight(lock_key, local).await;
+        result
+    }
+
+    /// Take the distributed lock `key` for up to `ttl_secs`, or `Ok(None)` if
+    /// another holder has it. Locks only mean something when shared, so there
+    /// is no memory fallback: without Redis this returns a connection error and
+    /// the caller decides whether to proceed unlocked or skip the work.
+    pub async fn try_lock(&self, key: &str, ttl_secs: u64) -> Result<Option<LockGuard>> {
+        let Some(mut conn) = self.write_connection().await else {
+            return Err(CacheError::Connection(format!(
+                "cannot take lock {} without Redis",
+                key
+            )));
+        };
+        let storage_key = self.storage_key(key);
+        // Identifies this holder, so release never deletes a successor's lock
+        let token = uuid::Uuid::new_v4().to_string();
+
+        let reply: redis::RedisResult<Option<String>> = redis::cmd("SET")
+            .arg(&storage_key)
+            .arg(&token)
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl_secs.saturating_mul(1000))
+            .query_async(&mut conn)
+            .await;
+
+        match reply {
+            Ok(Some(_)) => Ok(Some(LockGuard {
+                conn: Some(conn),
+                storage_key,
+                token,
+            })),
+            Ok(None) => Ok(None),
+            Err(e) => {
+                self.record_redis_error(key);
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Register `key` under each tag. Tag sets are Redis sets of stored keys and
+    /// carry no TTL; members that have since expired are harmless on invalidation.
+    pub async fn tag(&self, key: &str, tags: &[&str]) -> Result<()> {
+        if tags.is_empty() {
+            return Ok(());
+        }
+        let storage_key = self.storage_key(key);
+        let storage_tags: Vec<String> = tags.iter().map(|tag| self.storage_key(tag)).collect();
+
+        if let Some(mut conn) = self.write_connection().await {
+            let mut pipe = redis::pipe();
+            for storage_tag in &storage_tags {
+                pipe.sadd(storage_tag, &storage_key).ignore();
+            }
+            if let Err(e) = pipe.query_async::<_, ()>(&mut conn).await {
+                self.record_redis_error(key);
+                tracing::warn!("Redis tag failed for {}: {}", key, e);
+            }
+        }
+
+        // Mirrored in memory as the key may be sitting in the memory tier
+        let mut memory_tags = self.memory_tags.write().await;
+        for storage_tag in storage_tags {
+            memory_tags
+                .entry(storage_tag)
+                .or_default()
+                .insert(storage_key.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Remove every key registered under `tag`, and the tag itself, from both
+    /// tiers, returning how many keys were dropped
+    pub async fn invalidate_tag(&self, tag: &str) -> Result<usize> {
+        let storage_tag = self.storage_key(tag);
+        let mut deleted_count = 0;
+
+        if let Some(mut conn) = self.write_connection().await {
+            let started = Instant::now();
+            let reply: redis::RedisResult<usize> = async {
+                let members: Vec<String> = conn.smembers(&storage_tag).await?;
+                let mut deleted = 0;
+                for chunk in members.chunks(UNLINK_CHUNK_SIZE) {
+                    deleted += conn.unlink::<_, usize>(chunk).await?;
+                }
+                conn.unlink::<_, ()>(&storage_tag).await?;
+                Ok(deleted)
+            }
+            .await;
+            self.metrics.redis_latency.delete.record(started.elapsed());
+            match reply {
+                Ok(count) => deleted_count += count,
+                Err(e) => {
+                    self.record_redis_error(tag);
+                    tracing::warn!("Redis tag invalidation failed for {}: {}", tag, e);
+                }
+            }
+        }
+
+        let members = self.memory_tags.write().await.remove(&storage_tag);
+        if let Some(members) = members {
+            let mut memory_cache = self.memory_cache.write().await;
+            for member in members {
+                if memory_cache.remove(&member).is_some() {
+                    deleted_count += 1;
+                }
+            }
+        }
+
+        self.metrics.record_invalidation();
+        tracing::debug!("Invalidated {} cache keys tagged {}", deleted_count, tag);
+        self.publish_invalidation(Invalidation::Tag { tag: storage_tag })
+            .await;
+
+        Ok(deleted_count)
+    }
+
+    /// Remove every key matching a Redis glob pattern (e.g. `anchor:*`) from both
+    /// tiers, returning how many keys were dropped. Only keys in this cache's
+    /// namespace are considered.
+    pub async fn delete_pattern(&self, pattern: &str) -> Result<usize> {
+        self.delete_storage_pattern(pattern, self.storage_key(pattern))
+            .await
+    }
+
+    /// `delete_pattern` for a glob over stored keys, `pattern` being what it
+    /// was asked for (for logs and error tracking)
+    async fn delete_storage_pattern(
+        &self,
+        pattern: &str,
+        storage_pattern: String,
+    ) -> Result<usize> {
+        let mut deleted_count = 0;
+
+        if let Some(mut conn) = self.write_connection().await {
+            match scan_and_unlink(&mut conn, &storage_pattern).await {
+                Ok(count) => deleted_count += count,
+                Err(e) => {
+                    self.record_redis_error(pattern);
+                    tracing::warn!("Redis pattern delete failed for {}: {}", pattern, e);
+                }
+            }
+        }
+
+        let mut memory_cache = self.memory_cache.write().await;
+        let before = memory_cache.len();
+        memory_cache.retain(|key, _| !glob_matches(&storage_pattern, key));
+        deleted_count += before - memory_cache.len();
+        drop(memory_cache);
+
+        self.metrics.record_invalidation();
+        tracing::debug!(
+            "Invalidated {} cache keys matching pattern: {}",
+            deleted_count,
+            pattern
+        );
+        self.publish_invalidation(Invalidation::Pattern {
+            pattern: storage_pattern,
+        })
+        .await;
+
+        Ok(deleted_count)
+    }
+
+    /// Flush this application's cache. With a namespace configured only that
+    /// namespace's keys are removed (via `SCAN`, never `FLUSHDB`), since the
+    /// Redis database may be shared with sessions, rate limits or other apps.
+    /// Without one there is no way to tell our keys apart, so this falls back
+    /// to `clear_everything`.
+    pub async fn clear_all(&self) -> Result<()> {
+        if self.namespace.is_empty() {
+            tracing::warn!(
+                "CACHE_NAMESPACE is not set: clearing the cache runs FLUSHDB and wipes \
+                 EVERY key in the Redis database, including data owned by other services"
+            );
+            return self.clear_everything().await;
+        }
+
+        // Every version's keys, so entries left by an older `CACHE_VERSION` go too
+        let namespace_glob = format!("{}*", self.namespace);
+        let deleted = self
+            .delete_storage_pattern("*", namespace_glob.clone())
+            .await?;
+        self.memory_tags
+            .write()
+            .await
+            .retain(|tag, _| !glob_matches(&namespace_glob, tag));
+        tracing::info!(
+            "Cleared {} cache entries in namespace {}",
+            deleted,
+            self.namespace
+        );
+
+        Ok(())
+    }
+
+    /// Wipe the whole Redis database with `FLUSHDB`, whatever owns the keys,
+    /// along with the memory tier. Prefer `clear_all`. The memory tier is
+    /// cleared even when the flush fails, and the flush error is returned.
+    pub async fn clear_everything(&self) -> Result<()> {
+        let flushed = match self.write_connection().await {
+            Some(mut conn) => redis::cmd("FLUSHDB").query_async::<_, ()>(&mut conn).await,
+            None => Ok(()),
+        };
+
+        self.memory_cache.write().await.clear();
+        self.memory_tags.write().await.clear();
+        if let Err(e) = flushed {
+            self.record_redis_error("*");
+            return Err(e.into());
+        }
+        tracing::warn!("Flushed the entire cache database");
+        self.publish_invalidation(Invalidation::Everything).await;
+
+        Ok(())
+    }
+
+    pub fn get_metrics(&self) -> CacheMetricsSummary {
+        CacheMetricsSummary {
+            redis_breaker: self.breaker.state(),
+            ..self.metrics.summary()
+        }
+    }
+
+    /// Zero every counter and latency histogram, returning the summary they
+    /// held just before. Lookups racing the reset may land on either side.
+    pub fn metrics_reset(&self) -> CacheMetricsSummary {
+        let summary = self.get_metrics();
+        self.metrics.reset();
+        summary
+    }
+
+    pub fn metrics_prometheus(&self) -> String {
+        let mut out = self.metrics.to_prometheus();
+        out.push_str(&format!(
+            "# HELP cache_redis_circuit_open Whether the circuit breaker is keeping commands off Redis (0/1)\n\
+             # TYPE cache_redis_circuit_open gauge\n\
+             cache_redis_circuit_open {}\n",
+            u8::from(self.breaker.state() != BreakerState::Closed)
+        ));
+        out
+    }
+
+    pub async fn is_redis_connected(&self) -> bool {
+        self.redis_connection.read().await.is_some()
+    }
+
+    /// Try to re-establish the Redis connection
+    pub async fn reconnect(&self) -> Result<()> {
+        let connection = Self::connect(&self.redis_url, self.pool_size).await;
+        let connected = connection.is_some();
+        *self.redis_connection.write().await = connection;
+        if self.read_from_replica {
+            *self.replica_connection.write().await =
+                Self::connect_node(&self.redis_url, NodeRole::Replica, self.pool_size).await;
+        }
+
+        if connected {
+            Ok(())
+        } else {
+            Err(CacheError::Connection(
+                "failed to reconnect to Redis".to_string(),
+            ))
+        }
+    }
+}
+
+impl Drop for RedisCache {
+    fn drop(&mut self) {
+        for task in [
+            &mut self.health_task,
+            &mut self.sweeper_task,
+            &mut self.invalidation_task,
+        ] {
+            if let Some(task) = unpoisoned(task.get_mut()).take() {
+                task.abort();
+            }
+        }
+    }
+}
+
+/// The cache operations the handlers rely on, so they can run against a test
+/// double instead of Redis. `RedisCache` implements every method with its own.
+/// The read-through methods have defaults built on `get` and `set` for simple
+/// implementations, which ignore jitter and tags, load without single-flight
+/// and report hits as `CacheTier::Unknown`.
+pub trait Cache: Send + Sync {
+    fn get<T>(&self, key: &str) -> impl Future<Output = Result<Option<T>>> + Send
+    where
+        T: DeserializeOwned + Send;
+
+    fn set<T>(
+        &self,
+        key: &str,
+        value: &T,
+        ttl_secs: usize,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: Serialize + Sync;
+
+    fn delete(&self, key: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Delete every key matching a glob `pattern`, returning how many went
+    fn delete_pattern(&self, pattern: &str) -> impl Future<Output = Result<usize>> + Send;
+
+    /// Attach `key` to `tags` so invalidating a tag drops it
+    fn tag(&self, _key: &str, _tags: &[&str]) -> impl Future<Output = Result<()>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Serve `key` from the cache, or run `loader` and store its result for
+    /// `ttl_for(&value)` seconds. Boxed, since an unboxed future here can't be
+    /// shown to be `Send` when `loader` borrows from the caller.
+    fn get_or_set_tagged_with_ttl_fn<'a, T, F, Fut, E>(
+        &'a self,
+        key: &'a str,
+        ttl_for: impl FnOnce(&T) -> usize + Send + 'a,
+        _jitter_pct: f64,
+        tags: &'a [&'a str],
+        loader: F,
+    ) -> BoxFuture<'a, Result<(T, CacheStatus), E>>
+    where
+        T: Serialize + DeserializeOwned + Send + Sync + 'a,
+        F: FnOnce() -> Fut + Send + 'a,
+        Fut: Future<Output = Result<T, E>> + Send + 'a,
+        E: Send + 'a,
+    {
+        Box::pin(async move {
+            if let Ok(Some(value)) = self.get::<T>(key).await {
+                let status = CacheStatus::Hit {
+                    tier: CacheTier::Unknown,
+                    cached_at: None,
+                };
+                return Ok((value, status));
+            }
+            let value = self
+                .refresh_tagged_with_ttl_fn(key, ttl_for, 0.0, tags, loader)
+                .await?;
+            Ok((value, CacheStatus::Miss))
+        })
+    }
+
+    /// Run `loader` and store its result under `key`, whatever is cached now
+    fn refresh_tagged_with_ttl_fn<'a, T, F, Fut, E>(
+        &'a self,
+        key: &'a str,
+        ttl_for: impl FnOnce(&T) -> usize + Send + 'a,
+        _jitter_pct: f64,
+        tags: &'a [&'a str],
+        loader: F,
+    ) -> BoxFuture<'a, Result<T, E>>
+    where
+        T: Serialize + Send + Sync + 'a,
+        F: FnOnce() -> Fut + Send + 'a,
+        Fut: Future<Output = Result<T, E>> + Send + 'a,
+        E: Send + 'a,
+    {
+        Box::pin(async move {
+            let value = loader().await?;
+            if let Err(e) = self.set(key, &value, ttl_for(&value)).await {
+                tracing::warn!("Failed to cache {}: {}", key, e);
+            }
+            if let Err(e) = self.tag(key, tags).await {
+                tracing::warn!("Failed to tag {}: {}", key, e);
+            }
+            Ok(value)
+        })
+    }
+}
+
+impl Cache for RedisCache {
+    fn get<T>(&self, key: &str) -> impl Future<Output = Result<Option<T>>> + Send
+    where
+        T: DeserializeOwned + Send,
+    {
+        RedisCache::get(self, key)
+    }
+
+    fn set<T>(
+        &self,
+        key: &str,
+        value: &T,
+        ttl_secs: usize,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: Serialize + Sync,
+    {
+        RedisCache::set(self, key, value, ttl_secs)
+    }
+
+    fn delete(&self, key: &str) -> impl Future<Output = Result<()>> + Send {
+        RedisCache::delete(self, key)
+    }
+
+    fn delete_pattern(&self, pattern: &str) -> impl Future<Output = Result<usize>> + Send {
+        RedisCache::delete_pattern(self, pattern)
+    }
+
+    fn tag(&self, key: &str, tags: &[&str]) -> impl Future<Output = Result<()>> + Send {
+        RedisCache::tag(self, key, tags)
+    }
+
+    fn get_or_set_tagged_with_ttl_fn<'a, T, F, Fut, E>(
+        &'a self,
+        key: &'a str,
+        ttl_for: impl FnOnce(&T) -> usize + Send + 'a,
+        jitter_pct: f64,
+        tags: &'a [&'a str],
+        loader: F,
+    ) -> BoxFuture<'a, Result<(T, CacheStatus), E>>
+    where
+        T: Serialize + DeserializeOwned + Send + Sync + 'a,
+        F: FnOnce() -> Fut + Send + 'a,
+        Fut: Future<Output = Result<T, E>> + Send + 'a,
+        E: Send + 'a,
+    {
+        Box::pin(RedisCache::get_or_set_tagged_with_ttl_fn(
+            self, key, ttl_for, jitter_pct, tags, loader,
+        ))
+    }
+
+    fn refresh_tagged_with_ttl_fn<'a, T, F, Fut, E>(
+        &'a self,
+        key: &'a str,
+        ttl_for: impl FnOnce(&T) -> usize + Send + 'a,
+        jitter_pct: f64,
+        tags: &'a [&'a str],
+        loader: F,
+    ) -> BoxFuture<'a, Result<T, E>>
+    where
+        T: Serialize + Send + Sync + 'a,
+        F: FnOnce() -> Fut + Send + 'a,
+        Fut: Future<Output = Result<T, E>> + Send + 'a,
+        E: Send + 'a,
+    {
+        Box::pin(RedisCache::refresh_tagged_with_ttl_fn(
+            self, key, ttl_for, jitter_pct, tags, loader,
+        ))
+    }
+}
+
+/// Debug line for one `get`, `set` or `delete`, with `cache.*` fields attached
+/// so log aggregators can filter by operation and tier; the message stays as
+/// readable as before
+fn log_cache_op(
+    op: &str,
+    tier: CacheTier,
+    result: &str,
+    key: &str,
+    latency: Duration,
+    message: std::fmt::Arguments<'_>,
+) {
+    tracing::debug!(
+        cache.key = key,
+        cache.op = op,
+        cache.tier = tier.as_str(),
+        cache.result = result,
+        cache.latency_ms = latency.as_secs_f64() * 1000.0,
+        "{}",
+        message
+    );
+}
+
+/// Drop every expired entry, holding the write lock only for the sweep itself
+async fn compact(memory_cache: &RwLock<MemoryTier>) -> MemoryCompaction {
+    let mut memory_cache = memory_cache.write().await;
+    let before = memory_cache.len();
+    memory_cache.retain(|_, entry| !entry.is_expired());
+    let remaining = memory_cache.len();
+    drop(memory_cache);
+
+    MemoryCompaction {
+        purged: before - remaining,
+        remaining,
+    }
+}
+
+/// Apply an `INVALIDATION_CHANNEL` payload to a memory tier, unless it came
+/// from `instance_id` itself. Returns whether it was applied.
+async fn apply_invalidation(
+    instance_id: &str,
+    payload: &str,
+    memory_cache: &RwLock<MemoryTier>,
+    memory_tags: &RwLock<HashMap<String, HashSet<String>>>,
+) -> bool {
+    let message: InvalidationMessage = match serde_json::from_str(payload) {
+        Ok(message) => message,
+        Err(e) => {
+            tracing::warn!("Ignoring malformed invalidation message: {}", e);
+            return false;
+        }
+    };
+    if message.origin == instance_id {
+        return false;
+    }
+
+    match message.invalidation {
+        Invalidation::Keys { keys } => {
+            let mut memory_cache = memory_cache.write().await;
+            for key in &keys {
+                memory_cache.remove(key);
+            }
+        }
+        Invalidation::Tag { tag } => {
+            let members = memory_tags.write().await.remove(&tag);
+            if let Some(members) = members {
+                let mut memory_cache = memory_cache.write().await;
+                for member in members {
+                    memory_cache.remove(&member);
+                }
+            }
+        }
+        Invalidation::Pattern { pattern } => {
+            memory_cache
+                .write()
+                .await
+                .retain(|key, _| !glob_matches(&pattern, key));
+        }
+        Invalidation::Everything => {
+            memory_cache.write().await.clear();
+            memory_tags.write().await.clear();
+        }
+    }
+    true
+}
+
+/// Open a pub/sub connection to the primary and subscribe it to `channel`
+async fn subscribe(redis_url: &str, channel: &str) -> Result<redis::aio::PubSub> {
+    let client = resolve_client(redis_url, NodeRole::Primary).await?;
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(channel).await?;
+    Ok(pubsub)
+}
+
+/// Run `task` until it completes or `shutdown` flips to true (or its sender is dropped)
+async fn until_shutdown(mut shutdown: watch::Receiver<bool>, task: impl Future<Output = ()>) {
+    tokio::select! {
+        _ = task => {}
+        _ = shutdown.wait_for(|stop| *stop) => {}
+    }
+}
+
+/// A key's mark in `RedisCache::refreshing`, removed when the refresh task
+/// drops it, so a loader that panics or a task aborted at shutdown doesn't
+/// leave the key unrefreshable
+struct RefreshingGuard {
+    refreshing: Arc<std::sync::Mutex<HashSet<String>>>,
+    key: String,
+}
+
+impl Drop for RefreshingGuard {
+    fn drop(&mut self) {
+        unpoisoned(self.refreshing.lock()).remove(&self.key);
+    }
+}
+
+/// A lock result whose guard is usable even if a holder panicked; the guarded
+/// task handles stay valid either way
+fn unpoisoned<T>(result: std::sync::LockResult<T>) -> T {
+    result.unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Increments a window counter and starts its expiry on the first increment in
+/// one atomic step, so a failure between the two can't leave a counter that
+/// never expires
+const INCR_WINDOW_SCRIPT: &str = r#"
+local count = redis.call("INCR", KEYS[1])
+if count == 1 then
+    redis.call("EXPIRE", KEYS[1], ARGV[1])
+end
+return count
+"#;
+
+/// Deletes the lock only while it still holds our token; after the TTL lapses
+/// another holder may own it
+const RELEASE_LOCK_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+end
+return 0
+"#;
+
+/// A lock taken with [`RedisCache::try_lock`]. Dropping it releases the lock in
+/// the background; call [`LockGuard::release`] to wait for the release.
+pub struct LockGuard {
+    /// `None` once released
+    conn: Option<MultiplexedConnection>,
+    storage_key: String,
+    token: String,
+}
+
+impl LockGuard {
+    /// Release the lock, returning whether it was still ours to delete
+    pub async fn release(mut self) -> Result<bool> {
+        let Some(mut conn) = self.conn.take() else {
+            return Ok(false);
+        };
+        release_lock(&mut conn, &self.storage_key, &self.token).await
+    }
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        let Some(mut conn) = self.conn.take() else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            // No runtime to release on; the TTL frees the lock instead
+            return;
+        };
+        let storage_key = std::mem::take(&mut self.storage_key);
+        let token = std::mem::take(&mut self.token);
+        runtime.spawn(async move {
+            if let Err(e) = release_lock(&mut conn, &storage_key, &token).await {
+                tracing::warn!("Failed to release lock {}: {}", storage_key, e);
+            }
+        });
+    }
+}
+
+async fn release_lock(
+    conn: &mut MultiplexedConnection,
+    storage_key: &str,
+    token: &str,
+) -> Result<bool> {
+    let deleted: i64 = redis::Script::new(RELEASE_LOCK_SCRIPT)
+        .key(storage_key)
+        .arg(token)
+        .invoke_async(conn)
+        .await?;
+    Ok(deleted == 1)
+}
+
+/// Delay before reconnection attempt number `failures + 1`
+fn reconnect_delay(failures: u32, base: Duration, max: Duration) -> Duration {
+    base.saturating_mul(2u32.saturating_pow(failures)).min(max)
+}
+
+/// Where `REDIS_URL` points
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RedisTarget {
+    /// A single Redis node
+    Node(String),
+    /// Sentinels (as `redis://host:port` URLs) watching the master named `master_name`
+    Sentinel {
+        sentinels: Vec<String>,
+        master_name: String,
+    },
+}
+
+/// Which node of a Sentinel deployment to connect to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NodeRole {
+    Primary,
+    Replica,
+}
+
+/// Split `redis+sentinel://host1:26379,host2:26379/mymaster` into its Sentinel
+/// hosts and master name; any other URL is a single node
+fn parse_redis_target(redis_url: &str) -> Result<RedisTarget> {
+    let Some(rest) = redis_url.strip_prefix(SENTINEL_SCHEME) else {
+        return Ok(RedisTarget::Node(redis_url.to_string()));
+    };
+
+    let (hosts, master_name) = rest.split_once('/').ok_or_else(|| {
+        CacheError::Connection(format!(
+            "Sentinel URL must end in a master name: {}host:port[,host:port]/<master>",
+            SENTINEL_SCHEME
+        ))
+    })?;
+    let master_name = master_name.trim_end_matches('/');
+    if master_name.is_empty() {
+        return Err(CacheError::Connection(
+            "Sentinel URL is missing the master name".to_string(),
+        ));
+    }
+
+    let sentinels: Vec<String> = hosts
+        .split(',')
+        .map(str::trim)
+        .filter(|host| !host.is_empty())
+        .map(|host| format!("redis://{}", host))
+        .collect();
+    if sentinels.is_empty() {
+        return Err(CacheError::Connection(
+            "Sentinel URL lists no sentinel hosts".to_string(),
+        ));
+    }
+
+    Ok(RedisTarget::Sentinel {
+        sentinels,
+        master_name: master_name.to_string(),
+    })
+}
+
+/// Client for the node `redis_url` describes, asking Sentinel for the current
+/// master (or a replica) when it names a Sentinel deployment
+async fn resolve_client(redis_url: &str, role: NodeRole) -> Result<redis::Client> {
+    match parse_redis_target(redis_url)? {
+        RedisTarget::Node(url) => redis_client(&url),
+        RedisTarget::Sentinel {
+            sentinels,
+            master_name,
+        } => {
+            let mut sentinel = redis::sentinel::Sentinel::build(sentinels)
+                .map_err(|e| CacheError::Connection(format!("Invalid Sentinel host: {}", e)))?;
+            let client = match role {
+                NodeRole::Primary => sentinel.async_master_for(&master_name, None).await,
+                NodeRole::Replica => sentinel.async_replica_for(&master_name, None).await,
+            };
+            client.map_err(|e| {
+                CacheError::Connection(format!(
+                    "Sentinel could not resolve {:?} of {}: {}",
+                    role, master_name, e
+                ))
+            })
+        }
+    }
+}
+
+/// Check that `conn` still answers. Behind Sentinel the master must also still
+/// be a master, since a failed-over master rejoins as a read-only replica.
+async fn probe(conn: &mut MultiplexedConnection, expect_master: bool) -> redis::RedisResult<()> {
+    if !expect_master {
+        return redis::cmd("PING").query_async::<_, ()>(conn).await;
+    }
+
+    let role: Vec<redis::Value> = redis::cmd("ROLE").query_async(conn).await?;
+    match role.first().map(redis::from_redis_value::<String>) {
+        Some(Ok(role)) if role == "master" => Ok(()),
+        _ => Err(redis::RedisError::from((
+            redis::ErrorKind::ReadOnly,
+            "node is no longer the master",
+        ))),
+    }
+}
+
+fn is_tls_url(redis_url: &str) -> bool {
+    redis_url.starts_with("rediss://")
+}
+
+/// Whether a boolean env var such as `REDIS_TLS_INSECURE` is switched on
+fn env_flag(raw: Option<&str>) -> bool {
+    matches!(
+        raw.map(|v| v.trim().to_ascii_lowercase()).as_deref(),
+        Some("1" | "true" | "yes")
+    )
+}
+
+/// Build a Redis client for `redis_url`. `rediss://` URLs use TLS, trusting the
+/// system roots plus `REDIS_CA_CERT` (a PEM file) when set; `REDIS_TLS_INSECURE`
+/// disables verification for self-signed test instances.
+fn redis_client(redis_url: &str) -> Result<redis::Client> {
+    if !is_tls_url(redis_url) {
+        return redis::Client::open(redis_url).map_err(invalid_url);
+    }
+    tls_redis_client(redis_url)
+}
+
+#[cfg(feature = "redis-tls")]
+fn tls_redis_client(redis_url: &str) -> Result<redis::Client> {
+    use redis::{ConnectionAddr, IntoConnectionInfo, TlsCertificates};
+
+    let mut info = redis_url.into_connection_info().map_err(invalid_url)?;
+    if env_flag(std::env::var("REDIS_TLS_INSECURE").ok().as_deref()) {
+        if let ConnectionAddr::TcpTls { insecure, .. } = &mut info.addr {
+            tracing::warn!("REDIS_TLS_INSECURE is set, Redis certificates will not be verified");
+            *insecure = true;
+        }
+    }
+
+    match std::env::var("REDIS_CA_CERT")
+        .ok()
+        .filter(|p| !p.is_empty())
+    {
+        Some(path) => {
+            let root_cert = std::fs::read(&path).map_err(|e| {
+                CacheError::Connection(format!("Failed to read REDIS_CA_CERT {}: {}", path, e))
+            })?;
+            redis::Client::build_with_tls(
+                info,
+                TlsCertificates {
+                    client_tls: None,
+                    root_cert: Some(root_cert),
+                },
+            )
+            .map_err(tls_setup_failed)
+        }
+        None => redis::Client::open(info).map_err(tls_setup_failed),
+    }
+}
+
+#[cfg(feature = "redis-tls")]
+fn tls_setup_failed(err: redis::RedisError) -> CacheError {
+    CacheError::Connection(format!("Failed to configure Redis TLS: {}", err))
+}
+
+#[cfg(not(feature = "redis-tls"))]
+fn tls_redis_client(_redis_url: &str) -> Result<redis::Client> {
+    Err(CacheError::Connection(
+        "rediss:// requires building with the `redis-tls` feature".to_string(),
+    ))
+}
+
+fn invalid_url(err: redis::RedisError) -> CacheError {
+    CacheError::Connection(format!("Invalid Redis URL: {}", err))
+}
+
+fn breaker_threshold_from_env() -> u32 {
+    std::env::var("REDIS_BREAKER_THRESHOLD")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_BREAKER_THRESHOLD)
+}
+
+fn breaker_cooldown_from_env() -> Duration {
+    std::env::var("REDIS_BREAKER_COOLDOWN_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|&n| n > 0)
+        .map_or(DEFAULT_BREAKER_COOLDOWN, Duration::from_secs)
+}
+
+fn shutdown_grace_from_env() -> Duration {
+    std::env::var("CACHE_SHUTDOWN_GRACE_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .map_or(DEFAULT_SHUTDOWN_GRACE, Duration::from_secs)
+}
+
+fn compress_threshold_from_env() -> usize {
+    std::env::var("CACHE_COMPRESS_THRESHOLD")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_COMPRESS_THRESHOLD)
+}
+
+fn cache_format_from_env() -> CacheFormat {
+    let Ok(value) = std::env::var("CACHE_FORMAT") else {
+        return CacheFormat::default();
+    };
+    CacheFormat::parse(&value).unwrap_or_else(|| {
+        tracing::warn!("Unknown CACHE_FORMAT {:?}, using json", value);
+        CacheFormat::default()
+    })
+}
+
+/// A typed value's bytes: serialized in `format`, behind `MSGPACK_MAGIC` when
+/// that is MessagePack so readers on the other format can tell
+fn serialize_value<T: Serialize>(format: CacheFormat, value: &T) -> Result<Vec<u8>> {
+    let serialized = format.serialize(value)?;
+    Ok(match format {
+        CacheFormat::Json => serialized,
+        CacheFormat::MessagePack => [MSGPACK_MAGIC, &serialized].concat(),
+    })
+}
+
+/// Inverse of `serialize_value`. `Ok(None)` when the bytes were written in a
+/// format other than `format`.
+fn deserialize_value<T: DeserializeOwned>(bytes: &[u8], format: CacheFormat) -> Result<Option<T>> {
+    let (written_as, serialized) = match bytes.strip_prefix(MSGPACK_MAGIC) {
+        Some(serialized) => (CacheFormat::MessagePack, serialized),
+        None => (CacheFormat::Json, bytes),
+    };
+    if written_as != format {
+        return Ok(None);
+    }
+    format.deserialize(serialized).map(Some)
+}
+
+/// Bytes as they are stored: gzipped behind `COMPRESSED_MAGIC` when they are
+/// larger than `threshold`, otherwise as given
+fn encode_payload(bytes: &[u8], threshold: usize) -> Result<Vec<u8>> {
+    if bytes.len() <= threshold {
+        return Ok(bytes.to_vec());
+    }
+
+    let mut encoder = GzEncoder::new(COMPRESSED_MAGIC.to_vec(), Compression::fast());
+    let compress_failed = |e: std::io::Error| CacheError::Serialization(e.to_string());
+    encoder.write_all(bytes).map_err(compress_failed)?;
+    encoder.finish().map_err(compress_failed)
+}
+
+/// Prefix an encoded payload with the time it is being stored
+fn stamp_payload(payload: Vec<u8>, cached_at: DateTime<Utc>) -> Vec<u8> {
+    let mut data = Vec::with_capacity(STAMP_MAGIC.len() + 8 + payload.len());
+    data.extend_from_slice(STAMP_MAGIC);
+    data.extend_from_slice(&cached_at.timestamp_millis().to_be_bytes());
+    data.extend_from_slice(&payload);
+    data
+}
+
+/// Split a stored entry into its write time and encoded payload; entries
+/// written before stamping was introduced come back with no time
+fn split_stamp(data: &[u8]) -> Result<(Option<DateTime<Utc>>, &[u8])> {
+    let Some(stamped) = data.strip_prefix(STAMP_MAGIC) else {
+        return Ok((None, data));
+    };
+    let truncated = || CacheError::Deserialization("truncated cache timestamp".to_string());
+    let (millis, payload) = stamped.split_first_chunk::<8>().ok_or_else(truncated)?;
+    let cached_at =
+        DateTime::from_timestamp_millis(i64::from_be_bytes(*millis)).ok_or_else(truncated)?;
+    Ok((Some(cached_at), payload))
+}
+
+/// Inverse of `encode_payload`
+fn decode_payload(data: &[u8]) -> Result<Cow<'_, [u8]>> {
+    match data.strip_prefix(COMPRESSED_MAGIC) {
+        Some(compressed) => {
+            let mut bytes = Vec::new();
+            GzDecoder::new(compressed)
+                .read_to_end(&mut bytes)
+                .map_err(|e| CacheError::Deserialization(e.to_string()))?;
+            Ok(Cow::Owned(bytes))
+        }
+        None => Ok(Cow::Borrowed(data)),
+    }
+}
+
+/// A value read back from an entry, with the entry's write time when stamped
+type Stamped<T> = (T, Option<DateTime<Utc>>);
+
+/// Inverse of `stamp_payload(encode_payload(..))`: the bytes given to `set_raw`
+/// and their write time
+fn decode_raw_entry(data: &[u8]) -> Result<Stamped<Cow<'_, [u8]>>> {
+    let (cached_at, payload) = split_stamp(data)?;
+    Ok((decode_payload(payload)?, cached_at))
+}
+
+/// A typed entry as `set` stored it. `Ok(None)` when it was written in a
+/// format other than `format`.
+fn decode_entry<T: DeserializeOwned>(
+    data: &[u8],
+    format: CacheFormat,
+) -> Result<Option<(T, Option<DateTime<Utc>>)>> {
+    let (bytes, cached_at) = decode_raw_entry(data)?;
+    Ok(deserialize_value(&bytes, format)?.map(|value| (value, cached_at)))
+}
+
+fn redis_pool_size_from_env() -> usize {
+    std::env::var("REDIS_POOL_SIZE")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_REDIS_POOL_SIZE)
+}
+
+fn failed_writes_log_size_from_env() -> usize {
+    std::env::var("CACHE_FAILED_WRITES_LOG_SIZE")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_FAILED_WRITES_LOG_SIZE)
+}
+
+fn memory_max_entries_from_env() -> usize {
+    std::env::var("MEMORY_CACHE_MAX_ENTRIES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_MEMORY_CACHE_MAX_ENTRIES)
+}
+
+/// Normalise a configured namespace into the `name:` prefix stored keys start with
+fn namespace_prefix(namespace: &str) -> String {
+    let namespace = namespace.trim().trim_end_matches(':');
+    if namespace.is_empty() {
+        String::new()
+    } else {
+        format!("{}:", namespace)
+    }
+}
+
+/// Shrink the memory cache to `max_entries`, dropping expired entries first and
+/// then the least recently used ones
+fn evict_to_capacity(memory_cache: &mut MemoryTier, max_entries: usize) {
+    memory_cache.retain(|_, entry| !entry.is_expired());
+
+    let excess = memory_cache.len().saturating_sub(max_entries);
+    if excess == 0 {
+        return;
+    }
+
+    let mut by_recency: Vec<(u64, String)> = memory_cache
+        .iter()
+        .map(|(key, entry)| (entry.last_used, key.clone()))
+        .collect();
+    by_recency.sort_unstable();
+
+    for (_, key) in by_recency.into_iter().take(excess) {
+        memory_cache.remove(&key);
+    }
+    tracing::debug!(
+        "Evicted {} least recently used memory cache entries",
+        excess
+    );
+}
+
+/// Group a key by its first two segments (`anchor:data:<id>` -> `anchor:data`);
+/// keys with fewer segments are their own prefix
+fn key_prefix(key: &str) -> &str {
+    match key.match_indices(':').nth(1) {
+        Some((idx, _)) => &key[..idx],
+        None => key,
+    }
+}
+
+/// Estimate the `quantile` (0-1) latency in milliseconds from per-bucket counts,
+/// interpolating linearly inside the bucket it falls in. Samples in the `+Inf`
+/// bucket are reported at the largest finite bound.
+fn estimate_percentile(counts: &[u64], quantile: f64) -> f64 {
+    let total: u64 = counts.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+
+    let rank = quantile * total as f64;
+    let mut seen = 0u64;
+    for (idx, &count) in counts.iter().enumerate() {
+        if count == 0 {
+            continue;
+        }
+        if (seen + count) as f64 >= rank {
+            let Some(&upper) = LATENCY_BUCKETS_MS.get(idx) else {
+                return LATENCY_BUCKETS_MS[LATENCY_BUCKETS_MS.len() - 1] as f64;
+            };
+            let lower = if idx == 0 {
+                0
+            } else {
+                LATENCY_BUCKETS_MS[idx - 1]
+            };
+            let fraction = (rank - seen as f64) / count as f64;
+            return lower as f64 + (upper - lower) as f64 * fraction;
+        }
+        seen += count;
+    }
+
+    LATENCY_BUCKETS_MS[LATENCY_BUCKETS_MS.len() - 1] as f64
+}
+
+/// `hits` as a percentage of `hits + misses`
+fn percentage(hits: u64, misses: u64) -> f64 {
+    let total = hits + misses;
+    if total == 0 {
+        0.0
+    } else {
+        (hits as f64 / total as f64) * 100.0
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Spread `base_ttl` uniformly across ±`jitter_pct` percent, never going below one second
+fn jittered_ttl(base_ttl: usize, jitter_pct: f64) -> usize {
+    let spread = (base_ttl as f64 * jitter_pct.abs() / 100.0).round() as i64;
+    if spread == 0 {
+        return base_ttl;
+    }
+
+    let offset = rand::thread_rng().gen_range(-spread..=spread);
+    (base_ttl as i64 + offset).max(1) as usize
+}
+
+/// Walk the keyspace with `SCAN` (rather than the blocking `KEYS`) and `UNLINK`
+/// every key matching `pattern`, so eviction happens off Redis' main thread
+async fn scan_and_unlink(
+    conn: &mut MultiplexedConnection,
+    pattern: &str,
+) -> redis::RedisResult<usize> {
+    let mut deleted_count = 0;
+    let mut cursor: u64 = 0;
+
+    loop {
+        let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(pattern)
+            .arg("COUNT")
+            .arg(SCAN_BATCH_SIZE)
+            .query_async(conn)
+            .await?;
+
+        if !keys.is_empty() {
+            // One pipelined round trip per scanned batch instead of one per key
+            let mut pipe = redis::pipe();
+            for chunk in keys.chunks(UNLINK_CHUNK_SIZE) {
+                pipe.cmd("UNLINK").arg(chunk);
+            }
+            let unlinked: Vec<usize> = pipe.query_async(conn).await?;
+            deleted_count += unlinked.iter().sum::<usize>();
+        }
+
+        if next_cursor == 0 {
+            return Ok(deleted_count);
+        }
+        cursor = next_cursor;
+    }
+}
+
+/// Whether a Redis error only means the key holds nothing we can read, as opposed
+/// to a connection, I/O or server failure that should count as a cache error
+fn is_missing_value(err: &redis::RedisError) -> bool {
+    err.kind() == redis::ErrorKind::TypeError
+}
+
+/// Match a key against a Redis-style glob pattern supporting `*` and `?`
+fn glob_matches(pattern: &str, key: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let key: Vec<char> = key.chars().collect();
+
+    let (mut p, mut k) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while k < key.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == key[k]) {
+            p += 1;
+            k += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, k));
+            p += 1;
+        } else if let Some((star_p, star_k)) = star {
+            p = star_p + 1;
+            k = star_k + 1;
+            star = Some((star_p, star_k + 1));
+        } else {
+            return false;
+        }
+    }
+
+    while p < pattern.len() && pattern[p] == '*' {
+        p += 1;
+    }
+
+    p == pattern.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cache pointed at a closed port so every operation exercises the memory tier
+    async fn memory_only_cache() -> RedisCache {
+        RedisCache::from_url("redis://127.0.0.1:1").await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_memory_fallback_set_and_get() {
+        let cache = memory_only_cache().await;
+        assert!(!cache.is_redis_connected().await);
+
+        cache.set("anchor:count", &42i64, 60).await.unwrap();
+        let value: Option<i64> = cache.get("anchor:count").await.unwrap();
+        assert_eq!(value, Some(42));
+
+        let missing: Option<i64> = cache.get("corridor:count").await.unwrap();
+        assert_eq!(missing, None);
+
+        let metrics = cache.get_metrics();
+        assert_eq!(metrics.hits, 1);
+        assert_eq!(metrics.misses, 1);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_reset_returns_previous_counts_and_starts_over() {
+        let cache = memory_only_cache().await;
+        cache.set("anchor:count", &42i64, 60).await.unwrap();
+        let _: Option<i64> = cache.get("anchor:count").await.unwrap();
+        let _: Option<i64> = cache.get("corridor:count").await.unwrap();
+
+        let before = cache.metrics_reset();
+        assert_eq!((before.hits, before.misses), (1, 1));
+        let after = cache.get_metrics();
+        assert_eq!((after.hits, after.misses), (0, 0));
+
+        let _: Option<i64> = cache.get("corridor:count").await.unwrap();
+        let metrics = cache.get_metrics();
+        assert_eq!((metrics.hits, metrics.misses), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn test_get_or_set_only_loads_on_miss() {
+        use std::sync::atomic::AtomicUsize;
+
+        let cache = memory_only_cache().await;
+        let loads = AtomicUsize::new(0);
+
+        for _ in 0..3 {
+            let value: i64 = cache
+                .get_or_set("anchor:count", 60, || async {
+                    loads.fetch_add(1, Ordering::SeqCst);
+                    Ok::<_, anyhow::Error>(7)
+                })
+                .await
+                .unwrap();
+            assert_eq!(value, 7);
+        }
+
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        let metrics = cache.get_metrics();
+        assert_eq!(metrics.misses, 1);
+        assert_eq!(metrics.hits, 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_get_or_set_single_flight_runs_loader_once() {
+        use std::sync::atomic::AtomicUsize;
+
+        let cache = Arc::new(memory_only_cache().await);
+        let loads = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(tokio::sync::Barrier::new(50));
+
+        let handles: Vec<_> = (0..50)
+            .map(|_| {
+                let cache = Arc::clone(&cache);
+                let loads = Arc::clone(&loads);
+                let barrier = Arc::clone(&barrier);
+                tokio::spawn(async move {
+                    barrier.wait().await;
+                    cache
+                        .get_or_set("anchor:detail:hot", 60, || async {
+                            loads.fetch_add(1, Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(50)).await;
+                            Ok::<_, anyhow::Error>(7u32)
+                        })
+                        .await
+                        .unwrap()
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), 7);
+        }
+
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert!(cache.inflight.lock().await.is_empty());
+    }
+
+    /// Plant an envelope whose fresh/stale windows end `fresh_ms`/`stale_ms` from now
+    async fn plant_swr(cache: &RedisCache, key: &str, value: u32, fresh_ms: i64, stale_ms: i64) {
+        let now = unix_millis() as i64;
+        let envelope = SwrEnvelope {
+            value,
+            fresh_until: (now + fresh_ms) as u64,
+            stale_until: (now + stale_ms) as u64,
+        };
+        cache.set(key, &envelope, 60).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_swr_serves_fresh_value_without_loading() {
+        let cache = Arc::new(memory_only_cache().await);
+        plant_swr(&cache, "dashboard:stats", 1, 30_000, 60_000).await;
+
+        let value = cache
+            .get_stale_while_revalidate("dashboard:stats", 60, 300, || async {
+                Err::<u32, _>(anyhow::anyhow!("loader should not run"))
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(value, 1);
+    }
+
+    #[tokio::test]
+    async fn test_swr_serves_stale_value_and_refreshes_in_background() {
+        let cache = Arc::new(memory_only_cache().await);
+        plant_swr(&cache, "dashboard:stats", 1, -1_000, 60_000).await;
+
+        let value = cache
+            .get_stale_while_revalidate("dashboard:stats", 60, 300, || async {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                Ok::<_, anyhow::Error>(2u32)
+            })
+            .await
+            .unwrap();
+        assert_eq!(value, 1, "stale value should be returned without waiting");
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let refreshed: SwrEnvelope<u32> = cache.get("dashboard:stats").await.unwrap().unwrap();
+        assert_eq!(refreshed.value, 2);
+        assert!(refreshed.fresh_until > unix_millis());
+        assert!(unpoisoned(cache.refreshing.lock()).is_empty());
+    }
+
+    async fn panicking_load() -> anyhow::Result<u32> {
+        panic!("refresh panicked")
+    }
+
+    #[tokio::test]
+    async fn test_swr_refresh_that_panics_can_be_retried() {
+        let cache = Arc::new(memory_only_cache().await);
+        plant_swr(&cache, "dashboard:stats", 1, -1_000, 60_000).await;
+
+        let value = cache
+            .get_stale_while_revalidate("dashboard:stats", 60, 300, panicking_load)
+            .await
+            .unwrap();
+        assert_eq!(value, 1);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(unpoisoned(cache.refreshing.lock()).is_empty());
+
+        // The key isn't stuck as refreshing, so the next stale read reloads it
+        cache
+            .get_stale_while_revalidate("dashboard:stats", 60, 300, || async {
+                Ok::<_, anyhow::Error>(2u32)
+            })
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let refreshed: SwrEnvelope<u32> = cache.get("dashboard:stats").await.unwrap().unwrap();
+        assert_eq!(refreshed.value, 2);
+    }
+
+    #[tokio::test]
+    async fn test_swr_blocks_and_reloads_past_stale_window() {
+        let cache = Arc::new(memory_only_cache().await);
+        plant_swr(&cache, "dashboard:stats", 1, -60_000, -1_000).await;
+
+        let value = cache
+            .get_stale_while_revalidate("dashboard:stats", 60, 300, || async {
+                Ok::<_, anyhow::Error>(3u32)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(value, 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_or_set_does_not_cache_loader_errors() {
+        let cache = memory_only_cache().await;
+
+        let result: Result<i64, anyhow::Error> = cache
+            .get_or_set("anchor:count", 60, || async { anyhow::bail!("db down") })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(cache.get::<i64>("anchor:count").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_delete_many_removes_only_listed_keys() {
+        let cache = memory_only_cache().await;
+        let keys = CacheKey::all_keys_for_anchor("a1");
+        for key in keys.iter().chain([&CacheKey::anchor_data("a2")]) {
+            cache.set(key, &1, 60).await.unwrap();
+        }
+
+        cache.delete_many(&keys).await.unwrap();
+        cache.delete_many(&[]).await.unwrap();
+
+        for key in &keys {
+            assert_eq!(cache.get::<i32>(key).await.unwrap(), None, "{}", key);
+        }
+        assert_eq!(
+            cache
+                .get::<i32>(&CacheKey::anchor_data("a2"))
+                .await
+                .unwrap(),
+            Some(1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_pattern_only_removes_matching_keys() {
+        let cache = memory_only_cache().await;
+        cache
+            .set(&CacheKey::anchor_list(50, 0, "default", false), &1, 60)
+            .await
+            .unwrap();
+        cache.set(&CacheKey::anchor_count(), &1, 60).await.unwrap();
+        cache
+            .set(&CacheKey::corridor_count(), &1, 60)
+            .await
+            .unwrap();
+
+        let deleted = cache.delete_pattern("anchor:*").await.unwrap();
+        assert_eq!(deleted, 2);
+
+        assert_eq!(
+            cache
+                .get::<i32>(&CacheKey::anchor_list(50, 0, "default", false))
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            cache.get::<i32>(&CacheKey::anchor_count()).await.unwrap(),
+            None
+        );
+        assert_eq!(
+            cache.get::<i32>(&CacheKey::corridor_count()).await.unwrap(),
+            Some(1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_pattern_removes_thousands_of_keys() {
+        // Uses REDIS_URL when a server is reachable, otherwise the memory tier
+        let cache = RedisCache::new().await.unwrap();
+        let prefix = format!("test:scan:{}", uuid::Uuid::new_v4());
+
+        for i in 0..3000 {
+            cache
+                .set(&format!("{}:{}", prefix, i), &i, 60)
+                .await
+                .unwrap();
+        }
+        let survivor = format!("test:keep:{}", uuid::Uuid::new_v4());
+        cache.set(&survivor, &1, 60).await.unwrap();
+
+        let deleted = cache
+            .delete_pattern(&format!("{}:*", prefix))
+            .await
+            .unwrap();
+        assert_eq!(deleted, 3000);
+
+        for i in [0, 499, 500, 1500, 2999] {
+            let key = format!("{}:{}", prefix, i);
+            assert_eq!(cache.get::<i32>(&key).await.unwrap(), None);
+        }
+        assert_eq!(cache.get::<i32>(&survivor).await.unwrap(), Some(1));
+        cache.delete(&survivor).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_set_with_jitter_spreads_expiry_within_band() {
+        let cache = memory_only_cache().await;
+        let before = Instant::now();
+
+        for i in 0..10 {
+            cache
+                .set_with_jitter(&format!("anchor:detail:{}", i), &i, 600, 10.0)
+                .await
+                .unwrap();
+        }
+
+        let memory_cache = cache.memory_cache.read().await;
+        let ttls: std::collections::HashSet<u64> = memory_cache
+            .values()
+            .map(|entry| entry.expires_at.duration_since(before).as_secs())
+            .collect();
+
+        assert!(ttls.len() > 1, "expiries should not all be identical");
+        for ttl in ttls {
+            assert!((539..=660).contains(&ttl), "ttl {} outside 600s ± 10%", ttl);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_memory_cache_evicts_least_recently_used_over_cap() {
+        let cache = memory_only_cache().await.with_memory_max_entries(5);
+
+        for i in 0..5 {
+            cache
+                .set(&format!("anchor:data:{}", i), &i, 60)
+                .await
+                .unwrap();
+        }
+        // Touch the oldest key so it becomes the most recently used
+        assert_eq!(cache.get::<i32>("anchor:data:0").await.unwrap(), Some(0));
+
+        for i in 5..8 {
+            cache
+                .set(&format!("anchor:data:{}", i), &i, 60)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(cache.memory_cache.read().await.len(), 5);
+        for evicted in 1..4 {
+            let key = format!("anchor:data:{}", evicted);
+            assert_eq!(
+                cache.get::<i32>(&key).await.unwrap(),
+                None,
+                "{} should be evicted",
+                key
+            );
+        }
+        for kept in [0, 4, 5, 6, 7] {
+            let key = format!("anchor:data:{}", kept);
+            assert_eq!(cache.get::<i32>(&key).await.unwrap(), Some(kept));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_memory_byte_count_follows_sets_deletes_and_evictions() {
+        let cache = memory_only_cache().await.with_memory_max_entries(2);
+        let stored_len = |cache: &RedisCache, key: &str| {
+            let entries = Arc::clone(&cache.memory_cache);
+            let key = cache.storage_key(key);
+            async move { entries.read().await[&key].data.len() as u64 }
+        };
+        assert_eq!(cache.get_metrics().memory_cache_bytes, 0);
+
+        cache
+            .set("anchor:data:1", &"x".repeat(200), 60)
+            .await
+            .unwrap();
+        let first = stored_len(&cache, "anchor:data:1").await;
+        assert!(first >= 200);
+        cache.set("anchor:data:2", &"short", 60).await.unwrap();
+        let second = stored_len(&cache, "anchor:data:2").await;
+        let metrics = cache.get_metrics();
+        assert_eq!(metrics.memory_cache_entries, 2);
+        assert_eq!(metrics.memory_cache_bytes, first + second);
+
+        // Overwriting replaces the old payload's bytes rather than adding to them
+        cache.set("anchor:data:1", &"y", 60).await.unwrap();
+        let first = stored_len(&cache, "anchor:data:1").await;
+        assert_eq!(cache.get_metrics().memory_cache_bytes, first + second);
+
+        cache.delete("anchor:data:2").await.unwrap();
+        let metrics = cache.get_metrics();
+        assert_eq!(metrics.memory_cache_entries, 1);
+        assert_eq!(metrics.memory_cache_bytes, first);
+
+        // Over the cap, the least recently used entry's bytes go with it
+        cache.set("anchor:data:3", &"z", 60).await.unwrap();
+        cache.set("anchor:data:4", &"w", 60).await.unwrap();
+        let remaining =
+            stored_len(&cache, "anchor:data:3").await + stored_len(&cache, "anchor:data:4").await;
+        let metrics = cache.get_metrics();
+        assert_eq!(metrics.memory_cache_entries, 2);
+        assert_eq!(metrics.memory_cache_bytes, remaining);
+
+        // A metrics reset clears counters, not the size of what's stored
+        cache.metrics_reset();
+        assert_eq!(cache.get_metrics().memory_cache_bytes, remaining);
+
+        cache.clear_everything().await.unwrap();
+        assert_eq!(cache.get_metrics().memory_cache_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_the_memory_sweeper() {
+        let cache = memory_only_cache()
+            .await
+            .with_memory_sweeper(Duration::from_millis(10));
+        let short_key = cache.storage_key("anchor:data:short");
+        let swept = |cache: &RedisCache| {
+            let entries = Arc::clone(&cache.memory_cache);
+            let key = short_key.clone();
+            async move { !entries.read().await.contains_key(&key) }
+        };
+
+        cache.set_ms("anchor:data:short", &1, 10).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(swept(&cache).await);
+
+        // Returns true only once the sweeper's task has exited on its own
+        assert!(cache.shutdown().await);
+        assert!(cache.sweeper_task.lock().unwrap().is_none());
+
+        cache.set_ms("anchor:data:short", &1, 10).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!swept(&cache).await);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_tracked_writes() {
+        let cache = Arc::new(memory_only_cache().await);
+        let writer = Arc::clone(&cache);
+        cache.spawn_tracked(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            writer.set("anchor:data:late", &7, 60).await.unwrap();
+        });
+
+        assert!(cache.shutdown().await);
+        assert_eq!(cache.get::<i32>("anchor:data:late").await.unwrap(), Some(7));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_aborts_work_past_the_grace_period() {
+        let cache = memory_only_cache()
+            .await
+            .with_shutdown_grace(Duration::from_millis(20));
+        let finished = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&finished);
+        cache.spawn_tracked(async move {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            flag.store(true, Ordering::SeqCst);
+        });
+
+        assert!(!cache.shutdown().await);
+        assert!(!finished.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_purge_expired_removes_only_expired_entries() {
+        let cache = memory_only_cache().await;
+        cache.set("anchor:data:live", &1, 60).await.unwrap();
+        cache.set("anchor:data:dead", &2, 60).await.unwrap();
+        cache
+            .memory_cache
+            .write()
+            .await
+            .get_mut(&cache.storage_key("anchor:data:dead"))
+            .unwrap()
+            .expires_at = Instant::now();
+
+        assert_eq!(cache.purge_expired().await, 1);
+        assert_eq!(cache.get::<i32>("anchor:data:live").await.unwrap(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_compact_memory_purges_entries_past_their_ttl() {
+        let cache = memory_only_cache().await;
+        cache.set("anchor:data:live", &1, 60).await.unwrap();
+        cache.set_ms("anchor:data:short", &2, 50).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let compaction = cache.compact_memory().await;
+
+        assert_eq!(
+            compaction,
+            MemoryCompaction {
+                purged: 1,
+                remaining: 1
+            }
+        );
+        assert!(!cache
+            .memory_cache
+            .read()
+            .await
+            .contains_key(&cache.storage_key("anchor:data:short")));
+        assert_eq!(cache.get::<i32>("anchor:data:live").await.unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_metrics_prometheus_exposition() {
+        let metrics = CacheMetrics::default();
+        metrics.record_hit("anchor:data:1");
+        metrics.record_hit("anchor:data:1");
+        metrics.record_hit("anchor:data:2");
+        metrics.record_miss("anchor:data:3");
+        metrics.record_error("anchor:data:3");
+
+        let text = metrics.to_prometheus();
+
+        assert!(text.contains("# TYPE cache_hits_total counter\ncache_hits_total 3\n"));
+        assert!(text.contains("# TYPE cache_misses_total counter\ncache_misses_total 1\n"));
+        assert!(text.contains("cache_errors_total 1\n"));
+        assert!(text.contains("cache_invalidations_total 0\n"));
+        assert!(text.contains("# TYPE cache_hit_rate gauge\ncache_hit_rate 0.75\n"));
+        assert!(text.contains("# TYPE cache_recent_hit_rate gauge\ncache_recent_hit_rate 0.75\n"));
+        assert!(text.lines().all(|line| !line.is_empty()));
+    }
+
+    #[test]
+    fn test_miss_burst_drops_recent_hit_rate_before_lifetime() {
+        let metrics = CacheMetrics::default();
+        for _ in 0..5000 {
+            metrics.record_hit("anchor:data:1");
+        }
+        assert!(!metrics.summary().is_hit_rate_degraded(60.0));
+
+        let burst = RECENT_WINDOW_SIZE / 2;
+        for _ in 0..burst {
+            metrics.record_miss("anchor:data:1");
+        }
+        let summary = metrics.summary();
+
+        assert_eq!(summary.recent_samples, RECENT_WINDOW_SIZE as u64);
+        assert_eq!(summary.recent_hit_rate, 50.0);
+        // 5000 of 5500 lookups were hits, so the lifetime rate barely moved
+        assert!(summary.hit_rate > 90.0, "{}", summary.hit_rate);
+        assert!(summary.is_hit_rate_degraded(60.0));
+        assert!(!summary.is_hit_rate_degraded(50.0));
+    }
+
+    #[test]
+    fn test_hit_rate_not_degraded_until_window_has_enough_samples() {
+        let metrics = CacheMetrics::default();
+        for _ in 0..MIN_RECENT_SAMPLES - 1 {
+            metrics.record_miss("anchor:data:1");
+        }
+        assert!(!metrics.summary().is_hit_rate_degraded(50.0));
+
+        metrics.record_miss("anchor:data:1");
+        assert!(metrics.summary().is_hit_rate_degraded(50.0));
+
+        metrics.reset();
+        let summary = metrics.summary();
+        assert_eq!((summary.recent_samples, summary.recent_hit_rate), (0, 0.0));
+        assert!(!summary.is_hit_rate_degraded(50.0));
+    }
+
+    #[test]
+    fn test_metrics_are_tracked_per_prefix() {
+        let metrics = CacheMetrics::default();
+        metrics.record_hit("anchor:list:50:0");
+        metrics.record_miss("anchor:list:50:50");
+        metrics.record_miss("corridor:metrics:USDC:XLM");
+        metrics.record_error("corridor:metrics:USDC:XLM");
+        metrics.record_hit("dashboard:stats");
+
+        let per_prefix = metrics.summary().per_prefix;
+
+        assert_eq!(per_prefix.len(), 3);
+        let anchors = &per_prefix["anchor:list"];
+        assert_eq!((anchors.hits, anchors.misses, anchors.errors), (1, 1, 0));
+        assert_eq!(anchors.hit_rate, 50.0);
+        let corridors = &per_prefix["corridor:metrics"];
+        assert_eq!(
+            (corridors.hits, corridors.misses, corridors.errors),
+            (0, 1, 1)
+        );
+        assert_eq!(per_prefix["dashboard:stats"].hits, 1);
+
+        metrics.reset();
+        assert!(metrics.summary().per_prefix.is_empty());
+    }
+
+    #[test]
+    fn test_key_prefix_handles_short_keys() {
+        assert_eq!(key_prefix("anchor:data:123"), "anchor:data");
+        assert_eq!(key_prefix("anchor:count"), "anchor:count");
+        assert_eq!(key_prefix("dashboard"), "dashboard");
+        assert_eq!(key_prefix(""), "");
+        assert_eq!(key_prefix("corridor::x"), "corridor:");
+    }
+
+    #[test]
+    fn test_latency_histogram_buckets_and_percentiles() {
+        let histogram = LatencyHistogram::default();
+        for _ in 0..90 {
+            histogram.record(Duration::from_micros(500));
+        }
+        for _ in 0..9 {
+            histogram.record(Duration::from_millis(30));
+        }
+        histogram.record(Duration::from_secs(2));
+
+        let summary = histogram.summary();
+
+        assert_eq!(summary.count, 100);
+        let counts: Vec<u64> = summary.buckets.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![90, 0, 0, 9, 0, 0, 1]);
+        assert_eq!(summary.buckets.last().unwrap().le, "+Inf");
+        assert!(summary.p50_ms <= 1.0);
+        assert!(summary.p95_ms > 10.0 && summary.p95_ms <= 50.0);
+        assert!(summary.p99_ms > 10.0 && summary.p99_ms <= 50.0);
+        assert_eq!(estimate_percentile(&counts, 1.0), 500.0);
+        assert_eq!(estimate_percentile(&[0; 7], 0.5), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_memory_operations_record_latency_separately() {
+        let cache = memory_only_cache().await;
+        cache.set("anchor:data:1", &1, 60).await.unwrap();
+        let _ = cache.get::<i32>("anchor:data:1").await.unwrap();
+        cache.delete("anchor:data:1").await.unwrap();
+
+        let latency = cache.get_metrics().latency;
+
+        assert_eq!(latency.memory.get.count, 1);
+        assert_eq!(latency.memory.set.count, 1);
+        assert_eq!(latency.memory.delete.count, 1);
+        assert_eq!(latency.redis.get.count, 0);
+    }
+
+    #[test]
+    fn test_jittered_ttl_without_jitter_is_exact() {
+        assert_eq!(jittered_ttl(600, 0.0), 600);
+        assert!(jittered_ttl(1, 50.0) >= 1);
+    }
+
+    #[test]
+    fn test_redis_errors_are_not_misses() {
+        let type_error = redis::RedisError::from((redis::ErrorKind::TypeError, "not a string"));
+        assert!(is_missing_value(&type_error));
+
+        let io_error = redis::RedisError::from(std::io::Error::new(
+            std::io::ErrorKind::ConnectionReset,
+            "connection reset",
+        ));
+        assert!(!is_missing_value(&io_error));
+
+        let client_error = redis::RedisError::from((redis::ErrorKind::ClientError, "bad client"));
+        assert!(!is_missing_value(&client_error));
+    }
+
+    #[test]
+    fn test_anchor_search_keys_do_not_collide() {
+        let circle = CacheKey::anchor_search("circle", 50, 0, "default", false);
+
+        assert_eq!(
+            circle,
+            CacheKey::anchor_search("circle", 50, 0, "default", false)
+        );
+        assert_ne!(
+            circle,
+            CacheKey::anchor_search("circles", 50, 0, "default", false)
+        );
+        assert_ne!(
+            circle,
+            CacheKey::anchor_search("circle", 50, 50, "default", false)
+        );
+        assert_ne!(
+            circle,
+            CacheKey::anchor_search("circle", 50, 0, "name:asc", false)
+        );
+        assert_ne!(
+            circle,
+            CacheKey::anchor_search("circle", 50, 0, "default", true)
+        );
+        assert_ne!(
+            CacheKey::anchor_cursor_page("", 50, false),
+            CacheKey::anchor_cursor_page("", 50, true)
+        );
+        assert_ne!(circle, CacheKey::anchor_list(50, 0, "default", false));
+        assert!(glob_matches("anchor:*", &circle));
+        assert!(!CacheKey::anchor_search("a b:*", 1, 0, "default", false).contains(' '));
+    }
+
+    #[test]
+    fn test_equivalent_anchor_searches_share_a_key() {
+        let circle = CacheKey::anchor_search("circle", 50, 0, "default", false);
+
+        assert_eq!(
+            circle,
+            CacheKey::anchor_search("  circle\t", 50, 0, "default", false)
+        );
+        assert_eq!(
+            circle,
+            CacheKey::anchor_search("Circle", 50, 0, "default", false)
+        );
+        assert_ne!(
+            circle,
+            CacheKey::anchor_search("circ le", 50, 0, "default", false)
+        );
+        assert!(circle.starts_with("anchor:search:"));
+
+        let hostile = CacheKey::anchor_search(&"x".repeat(100_000), 50, 0, "default", false);
+        assert!(hostile.len() < 64, "{}", hostile);
+    }
+
+    #[tokio::test]
+    async fn test_namespaces_do_not_see_each_others_keys() {
+        let key = CacheKey::anchor_list(50, 0, "default", false);
+
+        // Re-namespacing one cache keeps its storage, standing in for two
+        // environments sharing a Redis instance
+        let staging = memory_only_cache().await.with_namespace("staging");
+        staging.set(&key, &1i64, 60).await.unwrap();
+
+        let production = staging.with_namespace("production");
+        assert_eq!(production.get::<i64>(&key).await.unwrap(), None);
+        production.set(&key, &2i64, 60).await.unwrap();
+        assert_eq!(production.delete_pattern("anchor:*").await.unwrap(), 1);
+
+        let staging = production.with_namespace("staging:");
+        assert_eq!(staging.get::<i64>(&key).await.unwrap(), Some(1));
+        assert_eq!(
+            staging.storage_key(&key),
+            format!("staging:v{}:{}", CACHE_VERSION, key)
+        );
+        assert_eq!(
+            staging.with_namespace("").storage_key(&key),
+            format!("v{}:{}", CACHE_VERSION, key)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_version_bump_turns_existing_keys_into_misses() {
+        let key = CacheKey::anchor_detail("anchor-1");
+        let cache = memory_only_cache().await.with_namespace("staging");
+        cache.set(&key, &"old shape", 60).await.unwrap();
+
+        let bumped = cache.with_version(CACHE_VERSION + 1);
+        assert_eq!(bumped.get::<String>(&key).await.unwrap(), None);
+
+        // Invalidation globs land after the version segment, so they still
+        // match the current version's keys and leave the old ones alone
+        bumped.set(&key, &"new shape", 60).await.unwrap();
+        assert!(glob_matches(
+            &bumped.storage_key("anchor:*"),
+            &bumped.storage_key(&key)
+        ));
+        assert_eq!(bumped.delete_pattern("anchor:*").await.unwrap(), 1);
+
+        let previous = bumped.with_version(CACHE_VERSION);
+        assert_eq!(
+            previous.get::<String>(&key).await.unwrap().as_deref(),
+            Some("old shape")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_entry_reads_as_miss_and_is_removed() {
+        let cache = memory_only_cache().await;
+        let key = CacheKey::anchor_detail("anchor-1");
+        cache.memory_cache.write().await.insert(
+            cache.storage_key(&key),
+            MemoryCacheEntry {
+                data: b"{not json".to_vec(),
+                expires_at: Instant::now() + Duration::from_secs(60),
+                last_used: 0,
+            },
+        );
+
+        assert_eq!(cache.get::<i64>(&key).await.unwrap(), None);
+        assert!(!cache
+            .memory_cache
+            .read()
+            .await
+            .contains_key(&cache.storage_key(&key)));
+
+        let metrics = cache.get_metrics();
+        assert_eq!(metrics.errors, 1);
+        assert_eq!(metrics.hits, 0);
+        assert_eq!(metrics.misses, 1);
+    }
+
+    #[tokio::test]
+    async fn test_mget_returns_a_slot_per_key() {
+        let cache = memory_only_cache().await;
+        cache.set("dashboard:stats", &1i64, 60).await.unwrap();
+        cache.set("dashboard:overview", &2i64, 60).await.unwrap();
+        cache
+            .set("corridor:count", &"not a number", 60)
+            .await
+            .unwrap();
+
+        let values: Vec<Option<i64>> = cache
+            .mget(&[
+                "dashboard:stats",
+                "anchor:count",
+                "dashboard:overview",
+                "corridor:count",
+            ])
+            .await
+            .unwrap();
+        assert_eq!(values, vec![Some(1), None, Some(2), None]);
+        assert!(cache.mget::<i64>(&[]).await.unwrap().is_empty());
+
+        let metrics = cache.get_metrics();
+        assert_eq!(metrics.hits, 2);
+        assert_eq!(metrics.misses, 2);
+        assert_eq!(metrics.errors, 1);
+    }
+
+    #[tokio::test]
+    async fn test_large_values_are_stored_compressed() {
+        let cache = memory_only_cache().await.with_compress_threshold(1024);
+        let assets: Vec<String> = (0..500).map(|i| format!("ASSET{}:GISSUER", i)).collect();
+        let raw_len = serde_json::to_vec(&assets).unwrap().len();
+
+        cache.set("anchor:assets:1", &assets, 60).await.unwrap();
+        cache.set("anchor:count", &42i64, 60).await.unwrap();
+
+        {
+            let memory_cache = cache.memory_cache.read().await;
+            let stored = |key: &str| {
+                let data = &memory_cache[&cache.storage_key(key)].data;
+                split_stamp(data).unwrap().1.to_vec()
+            };
+            let large = stored("anchor:assets:1");
+            assert!(large.starts_with(COMPRESSED_MAGIC));
+            assert!(large.len() < raw_len);
+            assert_eq!(stored("anchor:count").as_slice(), b"42");
+        }
+
+        let read: Option<Vec<String>> = cache.get("anchor:assets:1").await.unwrap();
+        assert_eq!(read, Some(assets));
+        assert_eq!(cache.get::<i64>("anchor:count").await.unwrap(), Some(42));
+    }
+
+    #[tokio::test]
+    async fn test_millisecond_ttl_expires_in_memory_fallback() {
+        let cache = memory_only_cache().await;
+        cache.set_ms("lock:probe", &"held", 200).await.unwrap();
+        assert_eq!(
+            cache.get::<String>("lock:probe").await.unwrap().as_deref(),
+            Some("held")
+        );
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(cache.get::<String>("lock:probe").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_raw_bytes_round_trip_verbatim() {
+        let cache = memory_only_cache()
+            .await
+            .with_namespace("raw")
+            .with_compress_threshold(1024);
+        let small = br#"{"name":"Snapshot Anchor"}"#.to_vec();
+        let large = serde_json::to_vec(&format_probe(500)).unwrap();
+
+        cache.set_raw("snapshot:small", &small, 60).await.unwrap();
+        cache.set_raw("snapshot:large", &large, 60).await.unwrap();
+        assert_eq!(cache.get_raw("snapshot:small").await.unwrap(), Some(small));
+        assert_eq!(
+            cache.get_raw("snapshot:large").await.unwrap(),
+            Some(large.clone())
+        );
+        assert_eq!(cache.get_raw("snapshot:missing").await.unwrap(), None);
+
+        {
+            let memory_cache = cache.memory_cache.read().await;
+            let stored = &memory_cache[&cache.storage_key("snapshot:large")].data;
+            assert!(split_stamp(stored).unwrap().1.starts_with(COMPRESSED_MAGIC));
+        }
+        // Raw JSON reads back through the typed API under the JSON format
+        let typed: Option<FormatProbe> = cache.get("snapshot:large").await.unwrap();
+        assert_eq!(typed, Some(format_probe(500)));
+        assert_eq!(
+            cache
+                .with_namespace("other")
+                .get_raw("snapshot:small")
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct FormatProbe {
+        name: String,
+        updated_at: DateTime<Utc>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        home_domain: Option<String>,
+        assets: Vec<String>,
+    }
+
+    fn format_probe(assets: usize) -> FormatProbe {
+        FormatProbe {
+            name: "Probe Anchor".to_string(),
+            updated_at: DateTime::from_timestamp_millis(1_700_000_000_000).unwrap(),
+            home_domain: None,
+            assets: (0..assets).map(|i| format!("ASSET{}:GISSUER", i)).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_values_round_trip_in_each_format() {
+        for format in [CacheFormat::Json, CacheFormat::MessagePack] {
+            let cache = memory_only_cache()
+                .await
+                .with_format(format)
+                .with_compress_threshold(1024);
+            // Small stays plain, large goes through gzip
+            for (key, probe) in [
+                ("probe:small", format_probe(2)),
+                ("probe:large", format_probe(500)),
+            ] {
+                cache.set(key, &probe, 60).await.unwrap();
+                let read: Option<FormatProbe> = cache.get(key).await.unwrap();
+                assert_eq!(read, Some(probe), "{:?}", format);
+
+                let memory_cache = cache.memory_cache.read().await;
+                let (payload, _) =
+                    decode_raw_entry(&memory_cache[&cache.storage_key(key)].data).unwrap();
+                assert_eq!(
+                    payload.starts_with(MSGPACK_MAGIC),
+                    format == CacheFormat::MessagePack
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entries_in_another_format_read_as_misses() {
+        let cache = memory_only_cache().await.with_format(CacheFormat::Json);
+        cache.set("probe:json", &format_probe(2), 60).await.unwrap();
+
+        let cache = cache.with_format(CacheFormat::MessagePack);
+        assert_eq!(cache.get::<FormatProbe>("probe:json").await.unwrap(), None);
+        cache
+            .set("probe:msgpack", &format_probe(2), 60)
+            .await
+            .unwrap();
+
+        let cache = cache.with_format(CacheFormat::Json);
+        assert_eq!(
+            cache.get::<FormatProbe>("probe:msgpack").await.unwrap(),
+            None
+        );
+        let slots = cache
+            .mget::<FormatProbe>(&["probe:json", "probe:msgpack"])
+            .await
+            .unwrap();
+        assert_eq!(slots, vec![Some(format_probe(2)), None]);
+
+        let metrics = cache.get_metrics();
+        assert_eq!(metrics.errors, 0);
+        assert_eq!(metrics.misses, 3);
+    }
+
+    #[test]
+    fn test_parse_cache_format() {
+        assert_eq!(CacheFormat::parse("json"), Some(CacheFormat::Json));
+        assert_eq!(
+            CacheFormat::parse(" MsgPack "),
+            Some(CacheFormat::MessagePack)
+        );
+        assert_eq!(CacheFormat::parse("xml"), None);
+    }
+
+    fn test_breaker() -> CircuitBreaker {
+        CircuitBreaker::new(3, Duration::from_secs(10), Duration::from_secs(30))
+    }
+
+    #[test]
+    fn test_breaker_opens_after_consecutive_failures_in_window() {
+        let breaker = test_breaker();
+        let start = Instant::now();
+
+        assert!(!breaker.record_failure_at(start));
+        assert!(!breaker.record_failure_at(start + Duration::from_secs(1)));
+        assert_eq!(breaker.state_at(start), BreakerState::Closed);
+        assert!(breaker.record_failure_at(start + Duration::from_secs(2)));
+
+        let later = start + Duration::from_secs(3);
+        assert_eq!(breaker.state_at(later), BreakerState::Open);
+        assert!(!breaker.allow_at(later));
+    }
+
+    #[test]
+    fn test_breaker_ignores_spread_out_or_interrupted_failures() {
+        let breaker = test_breaker();
+        let start = Instant::now();
+
+        // Too far apart to count together
+        for n in 0..3 {
+            breaker.record_failure_at(start + Duration::from_secs(11 * n));
+        }
+        assert_eq!(
+            breaker.state_at(start + Duration::from_secs(23)),
+            BreakerState::Closed
+        );
+
+        // A success in between resets the run
+        breaker.record_failure_at(start);
+        breaker.record_failure_at(start);
+        breaker.record_success();
+        assert!(!breaker.record_failure_at(start));
+        assert!(breaker.allow_at(start));
+    }
+
+    #[test]
+    fn test_breaker_half_opens_for_one_trial_and_closes_on_success() {
+        let breaker = test_breaker();
+        let start = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure_at(start);
+        }
+
+        let cooled = start + Duration::from_secs(30);
+        assert_eq!(breaker.state_at(cooled), BreakerState::HalfOpen);
+        assert!(breaker.allow_at(cooled));
+        assert!(!breaker.allow_at(cooled), "only one trial at a time");
+
+        // A failed trial restarts the cooldown
+        breaker.record_failure_at(cooled);
+        assert_eq!(
+            breaker.state_at(cooled + Duration::from_secs(1)),
+            BreakerState::Open
+        );
+
+        let retried = cooled + Duration::from_secs(30);
+        assert!(breaker.allow_at(retried));
+        breaker.record_success();
+        assert_eq!(breaker.state_at(retried), BreakerState::Closed);
+        assert!(breaker.allow_at(retried));
+    }
+
+    #[tokio::test]
+    async fn test_open_breaker_shows_in_metrics_and_closes_on_probe() {
+        let mut cache = memory_only_cache().await;
+        cache.breaker = Arc::new(CircuitBreaker::new(
+            2,
+            Duration::from_secs(10),
+            Duration::from_millis(50),
+        ));
+        cache.record_redis_error("anchor:1");
+        cache.record_redis_error("anchor:1");
+
+        assert_eq!(cache.get_metrics().redis_breaker, BreakerState::Open);
+        assert!(cache
+            .metrics_prometheus()
+            .contains("cache_redis_circuit_open 1\n"));
+        // A probe while still cooling down doesn't close it
+        cache.breaker.record_probe_success();
+        assert_eq!(cache.get_metrics().redis_breaker, BreakerState::Open);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(cache.get_metrics().redis_breaker, BreakerState::HalfOpen);
+        cache.breaker.record_probe_success();
+        assert_eq!(cache.get_metrics().redis_breaker, BreakerState::Closed);
+        // Commands fall back to memory the whole time
+        cache.set("anchor:1", &1u8, 60).await.unwrap();
+        assert_eq!(cache.get::<u8>("anchor:1").await.unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_reconnect_delay_doubles_up_to_cap() {
+        let delays: Vec<u64> = (0..8)
+            .map(|n| reconnect_delay(n, RECONNECT_BASE_DELAY, RECONNECT_MAX_DELAY).as_secs())
+            .collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 16, 32, 60, 60]);
+        assert_eq!(
+            reconnect_delay(u32::MAX, RECONNECT_BASE_DELAY, RECONNECT_MAX_DELAY),
+            RECONNECT_MAX_DELAY
+        );
+    }
+
+    #[tokio::test]
+    async fn test_health_check_retries_failed_connection() {
+        let cache = memory_only_cache()
+            .await
+            .spawn_health_check(Duration::from_millis(5), Duration::from_millis(20));
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(cache.reconnect_attempts() >= 2);
+        assert!(!cache.is_redis_connected().await);
+
+        // Dropping the cache stops the task
+        let attempts = Arc::clone(&cache.reconnect_attempts);
+        drop(cache);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let settled = attempts.load(Ordering::Relaxed);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(attempts.load(Ordering::Relaxed), settled);
+    }
+
+    #[test]
+    fn test_corridor_ttl_scales_inversely_with_activity() {
+        let config = CacheConfig::default();
+
+        // Busy corridors bottom out at the minimum, quiet ones top out at the maximum
+        assert_eq!(config.corridor_ttl(50_000.0), config.corridor_ttl_min);
+        assert_eq!(config.corridor_ttl(3.0), config.corridor_ttl_max);
+        assert_eq!(config.corridor_ttl(0.0), config.corridor_ttl_max);
+        assert_eq!(
+            config.corridor_ttl(CORRIDOR_REFERENCE_DAILY_TRANSACTIONS),
+            config.corridor_metrics_ttl
+        );
+        assert_eq!(config.corridor_ttl(250.0), 120);
+
+        let inverted = CacheConfig {
+            corridor_ttl_min: 900,
+            corridor_ttl_max: 60,
+            ..config
+        };
+        assert_eq!(inverted.corridor_ttl(50_000.0), 900);
+        assert_eq!(inverted.corridor_ttl(1.0), 900);
+    }
+
+    #[test]
+    fn test_ttl_overrides_replace_the_endpoint_category_ttl() {
+        let config = CacheConfig {
+            ttl_overrides: TtlRegistry::parse(
+                " anchor.by_account=60, corridor.list = 120,anchor.nope=5,anchor.detail=0,junk",
+            ),
+            ..CacheConfig::default()
+        };
+
+        assert_eq!(config.ttl("anchor.by_account"), 60);
+        assert_eq!(config.ttl("corridor.list"), 120);
+        // Invalid entries leave the category TTL in place
+        assert_eq!(config.ttl("anchor.detail"), config.anchor_data_ttl);
+        assert_eq!(config.ttl("anchor.list"), config.anchor_data_ttl);
+        assert_eq!(config.ttl("corridor.history"), config.corridor_metrics_ttl);
+        assert_eq!(config.ttl("not_found"), config.not_found_ttl);
+        assert_eq!(config.ttl_overrides.get("anchor.nope"), None);
+    }
+
+    #[tokio::test]
+    async fn test_configured_override_is_the_ttl_a_set_uses() {
+        let config = CacheConfig {
+            ttl_overrides: TtlRegistry::default().with_override("anchor.by_account", 5),
+            ..CacheConfig::default()
+        };
+        let cache = memory_only_cache().await;
+        let key = CacheKey::anchor_by_account("GABC");
+
+        cache
+            .set(&key, &1, config.ttl("anchor.by_account"))
+            .await
+            .unwrap();
+
+        let memory_cache = cache.memory_cache.read().await;
+        let remaining = memory_cache[&cache.storage_key(&key)]
+            .expires_at
+            .saturating_duration_since(Instant::now());
+        assert!(remaining <= Duration::from_secs(5), "{:?}", remaining);
+        assert!(remaining > Duration::from_secs(3), "{:?}", remaining);
+    }
+
+    #[test]
+    fn test_only_allowlisted_list_pages_are_cacheable() {
+        let config = CacheConfig::default();
+        assert!(config.caches_list_page(50, 0));
+        assert!(config.caches_list_page(50, 150));
+        assert!(config.caches_list_page(100, 200));
+        assert!(!config.caches_list_page(50, 1));
+        assert!(!config.caches_list_page(1, 0));
+        assert!(!config.caches_list_page(0, 0));
+    }
+
+    #[test]
+    fn test_parse_ttl_falls_back_on_invalid_values() {
+        assert_eq!(parse_ttl("CACHE_TTL_ANCHOR", None, 600), 600);
+        assert_eq!(parse_ttl("CACHE_TTL_ANCHOR", Some("120"), 600), 120);
+        assert_eq!(parse_ttl("CACHE_TTL_ANCHOR", Some(" 30 "), 600), 30);
+        assert_eq!(parse_ttl("CACHE_TTL_ANCHOR", Some("0"), 600), 600);
+        assert_eq!(parse_ttl("CACHE_TTL_ANCHOR", Some("-5"), 600), 600);
+        assert_eq!(parse_ttl("CACHE_TTL_ANCHOR", Some("ten"), 600), 600);
+        assert_eq!(parse_list_limit(Some("1000"), 500), 1000);
+        assert_eq!(parse_list_limit(Some("0"), 500), 500);
+        assert_eq!(parse_list_limit(Some("lots"), 500), 500);
+        assert_eq!(parse_list_limits(Some("20, 40,x,-5"), vec![50]), [20, 40]);
+        assert_eq!(parse_list_limits(Some("none"), vec![50]), [50]);
+        assert_eq!(parse_double_delete_delay(None), None);
+        assert_eq!(parse_double_delete_delay(Some("0")), None);
+        assert_eq!(
+            parse_double_delete_delay(Some("500")),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(parse_double_delete_delay(Some("soon")), None);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_tag_drops_only_tagged_keys() {
+        let cache = memory_only_cache().await;
+        let (one, two) = (CacheKey::anchor_tag("1"), CacheKey::anchor_tag("2"));
+
+        for (key, tag) in [
+            (CacheKey::anchor_detail("1"), &one),
+            (CacheKey::anchor_assets("1", 50, 0), &one),
+            (CacheKey::anchor_detail("2"), &two),
+        ] {
+            let _: i64 = cache
+                .get_or_set_tagged(&key, 60, 0.0, &[tag], || async {
+                    Ok::<_, anyhow::Error>(1)
+                })
+                .await
+                .unwrap();
+        }
+        cache
+            .set(&CacheKey::anchor_list(50, 0, "default", false), &1i64, 60)
+            .await
+            .unwrap();
+
+        assert_eq!(cache.invalidate_tag(&one).await.unwrap(), 2);
+        assert_eq!(
+            cache
+                .get::<i64>(&CacheKey::anchor_detail("1"))
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            cache
+                .get::<i64>(&CacheKey::anchor_detail("2"))
+                .await
+                .unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            cache
+                .get::<i64>(&CacheKey::anchor_list(50, 0, "default", false))
+                .await
+                .unwrap(),
+            Some(1)
+        );
+        assert_eq!(cache.invalidate_tag(&one).await.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_tls_settings() {
+        assert!(is_tls_url("rediss://cache.example.com:6380"));
+        assert!(!is_tls_url("redis://127.0.0.1:6379"));
+
+        assert!(env_flag(Some("true")));
+        assert!(env_flag(Some(" 1 ")));
+        assert!(!env_flag(Some("false")));
+        assert!(!env_flag(None));
+    }
+
+    #[test]
+    fn test_parse_redis_target() {
+        assert_eq!(
+            parse_redis_target("redis://127.0.0.1:6379").unwrap(),
+            RedisTarget::Node("redis://127.0.0.1:6379".to_string())
+        );
+        assert_eq!(
+            parse_redis_target("redis+sentinel://s1:26379, s2:26379/mymaster").unwrap(),
+            RedisTarget::Sentinel {
+                sentinels: vec![
+                    "redis://s1:26379".to_string(),
+                    "redis://s2:26379".to_string()
+                ],
+                master_name: "mymaster".to_string(),
+            }
+        );
+        assert!(parse_redis_target("redis+sentinel://s1:26379").is_err());
+        assert!(parse_redis_target("redis+sentinel://s1:26379/").is_err());
+        assert!(parse_redis_target("redis+sentinel:///mymaster").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_sentinel_falls_back_to_memory() {
+        let cache = RedisCache::from_url("redis+sentinel://127.0.0.1:1,127.0.0.1:2/mymaster")
+            .await
+            .unwrap();
+        assert!(!cache.is_redis_connected().await);
+        assert!(cache.read_connection().await.is_none());
+
+        cache.set("anchor:count", &5i64, 60).await.unwrap();
+        assert_eq!(cache.get::<i64>("anchor:count").await.unwrap(), Some(5));
+    }
+
+    #[cfg(not(feature = "redis-tls"))]
+    #[tokio::test]
+    async fn test_tls_url_without_feature_falls_back_to_memory() {
+        let cache = RedisCache::from_url("rediss://127.0.0.1:1").await.unwrap();
+        assert!(!cache.is_redis_connected().await);
+
+        cache.set("anchor:count", &3i64, 60).await.unwrap();
+        assert_eq!(cache.get::<i64>("anchor:count").await.unwrap(), Some(3));
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("anchor:*", "anchor:list:50:0"));
+        assert!(glob_matches("anchor:list:*:0", "anchor:list:50:0"));
+        assert!(glob_matches("anchor:?ount", "anchor:count"));
+        assert!(!glob_matches("anchor:*", "corridor:list:50:0"));
+        assert!(!glob_matches("anchor:count", "anchor:count:extra"));
+    }
+
+    #[test]
+    fn test_asset_anchors_key_ignores_case() {
+        assert_eq!(CacheKey::asset_anchors("usdc"), "asset:USDC:anchors");
+        assert_eq!(
+            CacheKey::asset_anchors("UsDc"),
+            CacheKey::asset_anchors("USDC")
+        );
+    }
+
+    #[test]
+    fn test_asset_corridors_keys_fall_under_the_code_pattern() {
+        let by_code = CacheKey::asset_corridors("usdc", None);
+        let by_issuer = CacheKey::asset_corridors("USDC", Some("GISSUER"));
+        assert_eq!(by_code, "asset:USDC:corridors");
+        assert_eq!(by_issuer, "asset:USDC:corridors:GISSUER");
+
+        let pattern = CacheKey::asset_corridors_pattern("Usdc");
+        assert!(glob_matches(&pattern, &by_code));
+        assert!(glob_matches(&pattern, &by_issuer));
+        assert!(!glob_matches(&pattern, &CacheKey::asset_anchors("USDC")));
+        assert!(!glob_matches(
+            &pattern,
+            &CacheKey::asset_corridors("USDCX", None)
+        ));
+    }
+
+    #[test]
+    fn test_anchor_asset_pages_have_their_own_keys() {
+        let first = CacheKey::anchor_assets("a1", 50, 0);
+        let second = CacheKey::anchor_assets("a1", 50, 50);
+        assert_eq!(first, "anchor:assets:a1:50:0");
+        assert_ne!(first, second);
+        assert_ne!(first, CacheKey::anchor_assets("a1", 25, 0));
+
+        let pattern = CacheKey::anchor_assets_pattern("a1");
+        assert!(glob_matches(&pattern, &first));
+        assert!(glob_matches(&pattern, &second));
+        assert!(!glob_matches(
+            &pattern,
+            &CacheKey::anchor_assets("a10", 50, 0)
+        ));
+        assert!(!glob_matches(&pattern, &CacheKey::anchor_detail("a1")));
+    }
+
+    #[test]
+    fn test_escape_key_segment_encodes_glob_metacharacters() {
+        assert_eq!(
+            escape_key_segment("USDC:issuer->EURC:issuer"),
+            "USDC:issuer->EURC:issuer"
+        );
+        assert_eq!(escape_key_segment("a*b?c[d]e\\f"), "a%2Ab%3Fc%5Bd%5De%5Cf");
+        // `%` is encoded too, so an already-encoded input can't collide
+        assert_ne!(escape_key_segment("%2A"), escape_key_segment("*"));
+    }
+
+    #[test]
+    fn test_corridor_key_with_wildcard_cannot_match_other_keys() {
+        let hostile = CacheKey::corridor_metrics("*");
+        assert!(!hostile.contains('*'));
+        assert!(!glob_matches(
+            &hostile,
+            &CacheKey::corridor_metrics("USDC:issuer->EURC:issuer")
+        ));
+        assert!(glob_matches("corridor:*", &hostile));
+        assert!(!glob_matches("anchor:*", &hostile));
+    }
+
+    #[tokio::test]
+    async fn test_delete_pattern_from_escaped_key_only_drops_that_key() {
+        let cache = memory_only_cache().await;
+        let hostile = CacheKey::corridor_detail("USDC:*");
+        let other = CacheKey::corridor_detail("USDC:issuer->EURC:issuer");
+        cache.set(&hostile, &1, 60).await.unwrap();
+        cache.set(&other, &2, 60).await.unwrap();
+
+        assert_eq!(cache.delete_pattern(&hostile).await.unwrap(), 1);
+        assert_eq!(cache.get::<i32>(&other).await.unwrap(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_get_or_set_reports_miss_then_memory_hit() {
+        let cache = memory_only_cache().await;
+        let load = || async { Ok::<_, anyhow::Error>(7u32) };
+
+        let (value, status) = cache
+            .get_or_set_tagged_with_status("anchor:detail:1", 60, 0.0, &[], load)
+            .await
+            .unwrap();
+        assert_eq!((value, status), (7, CacheStatus::Miss));
+
+        let (_, status) = cache
+            .get_or_set_tagged_with_status("anchor:detail:1", 60, 0.0, &[], load)
+            .await
+            .unwrap();
+        assert!(
+            matches!(
+                status,
+                CacheStatus::Hit {
+                    tier: CacheTier::Memory,
+                    cached_at: Some(_)
+                }
+            ),
+            "{:?}",
+            status
+        );
+        assert_eq!(status.header_value(), "HIT-MEMORY");
+        assert_eq!(
+            cache.get_with_tier::<u32>("anchor:detail:1").await.unwrap(),
+            Some((7, CacheTier::Memory))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_runs_loader_on_hit_and_writes_back() {
+        let cache = memory_only_cache().await;
+        cache.set("anchor:detail:1", &1u32, 60).await.unwrap();
+
+        let loads = AtomicU64::new(0);
+        let value = cache
+            .refresh_tagged("anchor:detail:1", 60, 0.0, &[], || async {
+                loads.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, anyhow::Error>(2u32)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(value, 2);
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.get::<u32>("anchor:detail:1").await.unwrap(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_clear_all_keeps_keys_outside_the_namespace() {
+        let key = CacheKey::dashboard_stats();
+        let other = memory_only_cache().await.with_namespace("sessions");
+        other.set(&key, &1i64, 60).await.unwrap();
+
+        let app = other.with_namespace("app");
+        app.set(&key, &2i64, 60).await.unwrap();
+        app.clear_all().await.unwrap();
+        assert_eq!(app.get::<i64>(&key).await.unwrap(), None);
+
+        let other = app.with_namespace("sessions");
+        assert_eq!(other.get::<i64>(&key).await.unwrap(), Some(1));
+
+        other.clear_everything().await.unwrap();
+        assert_eq!(other.get::<i64>(&key).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_clear_all_removes_every_version_in_the_namespace() {
+        let key = CacheKey::dashboard_stats();
+        let old = memory_only_cache()
+            .await
+            .with_namespace("app")
+            .with_version(1);
+        old.set(&key, &1i64, 60).await.unwrap();
+
+        let current = old.with_version(2);
+        current.set(&key, &2i64, 60).await.unwrap();
+        current.clear_all().await.unwrap();
+
+        let old = current.with_version(1);
+        assert_eq!(old.get::<i64>(&key).await.unwrap(), None);
+    }
+
+    /// Address of a stand-in Redis that answers every command with an error
+    async fn refusing_redis() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("redis://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    while let Ok(read @ 1..) = socket.read(&mut buf).await {
+                        // Each command arrives as a RESP array starting a line with `*`
+                        let commands = (0..read)
+                            .filter(|&i| buf[i] == b'*' && (i == 0 || buf[i - 1] == b'\n'))
+                            .count();
+                        let reply = "-ERR refused\r\n".repeat(commands);
+                        if socket.write_all(reply.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_failed_flush_still_clears_the_memory_tier() {
+        let cache = RedisCache::from_url(&refusing_redis().await).await.unwrap();
+        assert!(cache.is_redis_connected().await);
+        // The refused write falls back to the memory tier
+        cache
+            .set(&CacheKey::dashboard_stats(), &1i64, 60)
+            .await
+            .unwrap();
+        assert_eq!(cache.memory_cache.read().await.len(), 1);
+
+        assert!(cache.clear_everything().await.is_err());
+        assert_eq!(cache.memory_cache.read().await.len(), 0);
+    }
+
+    #[test]
+    fn test_decode_failure_is_a_deserialization_error() {
+        let err = decode_entry::<u32>(b"not json", CacheFormat::Json).unwrap_err();
+        assert!(matches!(err, CacheError::Deserialization(_)), "{:?}", err);
+
+        let mut truncated = COMPRESSED_MAGIC.to_vec();
+        truncated.extend_from_slice(b"garbage");
+        let err = decode_entry::<u32>(&truncated, CacheFormat::Json).unwrap_err();
+        assert!(matches!(err, CacheError::Deserialization(_)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_unserializable_value_is_a_serialization_error() {
+        let cache = memory_only_cache().await;
+        // JSON object keys must be strings
+        let value: HashMap<(u8, u8), u8> = HashMap::from([((1, 2), 3)]);
+        let err = cache.set("anchor:count", &value, 60).await.unwrap_err();
+        assert!(matches!(err, CacheError::Serialization(_)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_bad_urls_and_failed_reconnects_are_connection_errors() {
+        let err = parse_redis_target("redis+sentinel://host:26379").unwrap_err();
+        assert!(matches!(err, CacheError::Connection(_)), "{:?}", err);
+        let err = redis_client("not a url").unwrap_err();
+        assert!(matches!(err, CacheError::Connection(_)), "{:?}", err);
+
+        let cache = memory_only_cache().await;
+        let err = cache.reconnect().await.unwrap_err();
+        assert!(matches!(err, CacheError::Connection(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn test_redis_errors_convert_and_expose_their_source() {
+        let err: CacheError =
+            redis::RedisError::from((redis::ErrorKind::ResponseError, "boom")).into();
+        assert!(matches!(err, CacheError::Redis(_)), "{:?}", err);
+        assert!(std::error::Error::source(&err).is_some());
+
+        // Still usable with `?` in anyhow code
+        let wrapped: anyhow::Error = err.into();
+        assert!(wrapped.downcast_ref::<CacheError>().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_pool_spreads_concurrent_ops_round_robin() {
+        assert!(RedisPool::<usize>::new(Vec::new()).is_none());
+
+        let pool = RedisPool::new(vec![0usize, 1, 2, 3]).unwrap();
+        let handles: Vec<_> = (0..400)
+            .map(|_| {
+                let pool = pool.clone();
+                tokio::spawn(async move { pool.get() })
+            })
+            .collect();
+
+        let mut per_connection = [0; 4];
+        for handle in handles {
+            per_connection[handle.await.unwrap()] += 1;
+        }
+        assert_eq!(per_connection, [100; 4]);
+    }
+
+    #[tokio::test]
+    async fn test_many_concurrent_gets_complete_without_serializing() {
+        // Uses REDIS_URL when a server is reachable, otherwise the memory tier
+        let cache = Arc::new(RedisCache::new().await.unwrap());
+        if let Some(pool) = cache.redis_connection.read().await.as_ref() {
+            assert_eq!(pool.len(), cache.pool_size);
+        }
+        let key = format!("test:pool:{}", uuid::Uuid::new_v4());
+        cache.set(&key, &7u32, 60).await.unwrap();
+
+        let started = Instant::now();
+        let gets = (0..500).map(|_| {
+            let cache = Arc::clone(&cache);
+            let key = key.clone();
+            tokio::spawn(async move { cache.get::<u32>(&key).await })
+        });
+        for result in futures::future::join_all(gets).await {
+            assert_eq!(result.unwrap().unwrap(), Some(7));
+        }
+        let elapsed = started.elapsed();
+
+        // Generous bound; a head-of-line stall behind one connection blows well past it
+        assert!(elapsed < Duration::from_secs(5), "took {:?}", elapsed);
+        cache.delete(&key).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_exists_and_remaining_ttl_from_memory() {
+        let cache = memory_only_cache().await;
+        cache.set("anchor:data:inspect", &1, 60).await.unwrap();
+
+        assert!(cache.exists("anchor:data:inspect").await.unwrap());
+        let remaining = cache
+            .remaining_ttl("anchor:data:inspect")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(remaining <= Duration::from_secs(60));
+        assert!(remaining > Duration::from_secs(55), "{:?}", remaining);
+
+        assert!(!cache.exists("anchor:data:missing").await.unwrap());
+        assert_eq!(
+            cache.remaining_ttl("anchor:data:missing").await.unwrap(),
+            None
+        );
+
+        // An expired entry that hasn't been swept yet doesn't count
+        let storage_key = cache.storage_key("anchor:data:inspect");
+        cache
+            .memory_cache
+            .write()
+            .await
+            .get_mut(&storage_key)
+            .unwrap()
+            .expires_at = Instant::now();
+        assert!(!cache.exists("anchor:data:inspect").await.unwrap());
+        assert_eq!(
+            cache.remaining_ttl("anchor:data:inspect").await.unwrap(),
+            None
+        );
+    }
+
+    /// `Cache` with only the required methods, over a map of JSON values
+    #[derive(Default)]
+    struct MapCache {
+        entries: std::sync::Mutex<HashMap<String, serde_json::Value>>,
+        sets: AtomicUsize,
+    }
+
+    impl Cache for MapCache {
+        async fn get<T: DeserializeOwned + Send>(&self, key: &str) -> Result<Option<T>> {
+            let entry = self.entries.lock().unwrap().get(key).cloned();
+            Ok(entry.and_then(|value| serde_json::from_value(value).ok()))
+        }
+
+        async fn set<T: Serialize + Sync>(&self, key: &str, value: &T, _ttl: usize) -> Result<()> {
+            self.sets.fetch_add(1, Ordering::Relaxed);
+            let value = serde_json::to_value(value).unwrap();
+            self.entries.lock().unwrap().insert(key.to_string(), value);
+            Ok(())
+        }
+
+        async fn delete(&self, key: &str) -> Result<()> {
+            self.entries.lock().unwrap().remove(key);
+            Ok(())
+        }
+
+        async fn delete_pattern(&self, _pattern: &str) -> Result<usize> {
+            Ok(0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_read_through_sets_on_a_miss_only() {
+        let cache = MapCache::default();
+        let loads = AtomicUsize::new(0);
+        let read = || {
+            cache.get_or_set_tagged_with_ttl_fn(
+                "anchor:data:mock",
+                |_| 60,
+                10.0,
+                &[],
+                || async {
+                    loads.fetch_add(1, Ordering::Relaxed);
+                    Ok::<_, CacheError>(7i64)
+                },
+            )
+        };
+
+        assert_eq!(read().await.unwrap(), (7, CacheStatus::Miss));
+        let (value, status) = read().await.unwrap();
+        assert_eq!(value, 7);
+        assert_eq!(
+            status,
+            CacheStatus::Hit {
+                tier: CacheTier::Unknown,
+                cached_at: None,
+            }
+        );
+        assert_eq!(status.header_value(), "HIT");
+        assert_eq!(loads.load(Ordering::Relaxed), 1);
+        assert_eq!(cache.sets.load(Ordering::Relaxed), 1);
+    }
+
+    /// Cache on `REDIS_URL`, or `None` when no server is reachable
+    async fn connected_cache() -> Option<RedisCache> {
+        let cache = RedisCache::new().await.unwrap();
+        cache.is_redis_connected().await.then_some(cache)
+    }
+
+    #[tokio::test]
+    async fn test_failed_write_log_keeps_the_latest_failures_in_order() {
+        let cache = memory_only_cache().await.with_failed_writes_log_size(2);
+        let reset = redis::RedisError::from((redis::ErrorKind::IoError, "connection reset"));
+        for key in ["anchor:data:1", "anchor:data:2", "anchor:data:3"] {
+            cache.record_failed_write(key, &reset);
+        }
+
+        let failures = cache.failed_writes();
+        let keys: Vec<&str> = failures.iter().map(|f| f.key.as_str()).collect();
+        assert_eq!(keys, ["anchor:data:2", "anchor:data:3"]);
+        assert!(failures.iter().all(|f| f.error_kind == "IoError"));
+        assert!(failures[0].failed_at <= failures[1].failed_at);
+
+        let disabled = memory_only_cache().await.with_failed_writes_log_size(0);
+        disabled.record_failed_write("anchor:data:1", &reset);
+        assert!(disabled.failed_writes().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_memory_fallback_writes_are_not_failures() {
+        let cache = memory_only_cache().await;
+        cache.set("anchor:data:fallback", &1, 60).await.unwrap();
+
+        assert!(cache.failed_writes().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rejected_redis_writes_are_logged_in_order() {
+        let Some(cache) = connected_cache().await else {
+            return;
+        };
+        let keys: Vec<String> = (0..3)
+            .map(|i| format!("anchor:data:rejected-{}-{}", i, uuid::Uuid::new_v4()))
+            .collect();
+
+        // Redis refuses a zero expiry, so each write fails there and lands in memory
+        for key in &keys {
+            cache.set(key, &1, 0).await.unwrap();
+        }
+
+        let failures = cache.failed_writes();
+        let logged: Vec<&String> = failures.iter().map(|f| &f.key).collect();
+        assert_eq!(logged, keys.iter().collect::<Vec<_>>());
+        assert!(failures.iter().all(|f| f.error_kind == "ResponseError"));
+    }
+
+    #[tokio::test]
+    async fn test_exists_and_remaining_ttl_from_redis() {
+        let Some(cache) = connected_cache().await else {
+            return;
+        };
+        let key = format!("anchor:data:inspect-{}", uuid::Uuid::new_v4());
+        cache.set(&key, &1, 120).await.unwrap();
+
+        assert!(cache.exists(&key).await.unwrap());
+        let remaining = cache.remaining_ttl(&key).await.unwrap().unwrap();
+        assert!(remaining <= Duration::from_secs(120));
+        assert!(remaining > Duration::from_secs(110), "{:?}", remaining);
+
+        cache.delete(&key).await.unwrap();
+        assert!(!cache.exists(&key).await.unwrap());
+        assert_eq!(cache.remaining_ttl(&key).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_window_counter_expires_from_its_first_increment() {
+        let Some(cache) = connected_cache().await else {
+            return;
+        };
+        let key = format!("ratelimit:test-{}", uuid::Uuid::new_v4());
+
+        assert_eq!(cache.incr_window(&key, 60).await.unwrap(), Some(1));
+        let first = cache.remaining_ttl(&key).await.unwrap().unwrap();
+        assert!(first <= Duration::from_secs(60));
+        // Later increments keep the expiry the first one set
+        assert_eq!(cache.incr_window(&key, 120).await.unwrap(), Some(2));
+        let second = cache.remaining_ttl(&key).await.unwrap().unwrap();
+        assert!(second <= first, "{:?} > {:?}", second, first);
+
+        cache.delete(&key).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_window_counter_without_redis_is_none() {
+        let cache = memory_only_cache().await;
+        assert_eq!(cache.incr_window("ratelimit:test", 60).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_try_lock_without_redis_is_a_connection_error() {
+        let cache = memory_only_cache().await;
+        let result = cache.try_lock("test:lock", 10).await;
+        assert!(matches!(result, Err(CacheError::Connection(_))));
+    }
+
+    #[tokio::test]
+    async fn test_contended_lock_is_not_acquired_until_released() {
+        // Locks have no memory tier, so this needs a reachable REDIS_URL
+        let Some(cache) = connected_cache().await else {
+            return;
+        };
+        let key = format!("test:lock:{}", uuid::Uuid::new_v4());
+
+        let guard = cache.try_lock(&key, 10).await.unwrap().unwrap();
+        assert!(cache.try_lock(&key, 10).await.unwrap().is_none());
+
+        assert!(guard.release().await.unwrap());
+        let relocked = cache.try_lock(&key, 10).await.unwrap();
+        assert!(relocked.unwrap().release().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_write_lock_runs_locally_without_redis() {
+        let cache = memory_only_cache().await;
+        let written = cache
+            .with_write_lock("test:lock:write", 10, || async { Ok::<_, CacheError>(7) })
+            .await
+            .unwrap();
+        assert_eq!(written, Some(7));
+        assert!(cache.inflight.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_write_lock_held_by_another_replica_skips_the_write() {
+        let Some(cache) = connected_cache().await else {
+            return;
+        };
+        let key = format!("test:lock:{}", uuid::Uuid::new_v4());
+
+        let other = cache.try_lock(&key, 10).await.unwrap().unwrap();
+        let written = cache
+            .with_write_lock(&key, 10, || async { Ok::<_, CacheError>(7) })
+            .await
+            .unwrap();
+        assert_eq!(written, None);
+        assert!(other.release().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_dropped_lock_guard_releases_the_lock() {
+        let Some(cache) = connected_cache().await else {
+            return;
+        };
+        let key = format!("test:lock:{}", uuid::Uuid::new_v4());
+
+        drop(cache.try_lock(&key, 10).await.unwrap().unwrap());
+
+        // The release runs on a spawned task
+        let mut relocked = None;
+        for _ in 0..50 {
+            relocked = cache.try_lock(&key, 10).await.unwrap();
+            if relocked.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(relocked
+            .expect("lock was never released")
+            .release()
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_expired_lock_release_keeps_the_new_holders_lock() {
+        let Some(cache) = connected_cache().await else {
+            return;
+        };
+        let key = format!("test:lock:{}", uuid::Uuid::new_v4());
+
+        let stale = cache.try_lock(&key, 1).await.unwrap().unwrap();
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let current = cache.try_lock(&key, 10).await.unwrap().unwrap();
+
+        assert!(!stale.release().await.unwrap());
+        assert!(cache.try_lock(&key, 10).await.unwrap().is_none());
+        assert!(current.release().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_unstamped_legacy_entries_read_with_unknown_age() {
+        let cache = memory_only_cache().await;
+        let key = CacheKey::anchor_count();
+        cache.memory_cache.write().await.insert(
+            cache.storage_key(&key),
+            MemoryCacheEntry {
+                data: b"42".to_vec(),
+                expires_at: Instant::now() + Duration::from_secs(60),
+                last_used: 0,
+            },
+        );
+
+        let (value, status) = cache.get_with_status::<i64>(&key).await.unwrap().unwrap();
+        assert_eq!(value, 42);
+        assert_eq!(
+            status,
+            CacheStatus::Hit {
+                tier: CacheTier::Memory,
+                cached_at: None
+            }
+        );
+        assert_eq!(status.age_secs(Utc::now()), None);
+
+        cache.set(&key, &43i64, 60).await.unwrap();
+        let (_, status) = cache.get_with_status::<i64>(&key).await.unwrap().unwrap();
+        assert_eq!(status.age_secs(Utc::now()), Some(0));
+    }
+
+    async fn memory_entry(cache: &RedisCache, key: &str) -> bool {
+        cache
+            .memory_cache
+            .read()
+            .await
+            .contains_key(&cache.storage_key(key))
+    }
+
+    async fn cache_in_memory(cache: &RedisCache, key: &str) {
+        cache.memory_cache.write().await.insert(
+            cache.storage_key(key),
+            MemoryCacheEntry {
+                data: b"1".to_vec(),
+                expires_at: Instant::now() + Duration::from_secs(60),
+                last_used: 0,
+            },
+        );
+    }
+
+    async fn receive(receiver: &RedisCache, payload: &str) -> bool {
+        apply_invalidation(
+            &receiver.instance_id,
+            payload,
+            &receiver.memory_cache,
+            &receiver.memory_tags,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_invalidations_from_another_instance_clear_the_memory_tier() {
+        let sender = memory_only_cache().await.with_namespace("shared");
+        let receiver = memory_only_cache().await.with_namespace("shared");
+        let deleted = CacheKey::anchor_detail("anchor-1");
+        let kept = CacheKey::anchor_detail("anchor-2");
+        cache_in_memory(&receiver, &deleted).await;
+        cache_in_memory(&receiver, &kept).await;
+
+        let payload = sender
+            .invalidation_payload(Invalidation::Keys {
+                keys: vec![sender.storage_key(&deleted)],
+            })
+            .unwrap();
+
+        assert!(receive(&receiver, &payload).await);
+        assert!(!memory_entry(&receiver, &deleted).await);
+        assert!(memory_entry(&receiver, &kept).await);
+    }
+
+    #[tokio::test]
+    async fn test_an_instance_ignores_its_own_invalidations() {
+        let cache = memory_only_cache().await;
+        let key = CacheKey::anchor_detail("anchor-1");
+        cache_in_memory(&cache, &key).await;
+
+        let payload = cache
+            .invalidation_payload(Invalidation::Everything)
+            .unwrap();
+
+        assert!(!receive(&cache, &payload).await);
+        assert!(memory_entry(&cache, &key).await);
+        assert!(!receive(&cache, "not json").await);
+    }
+
+    #[tokio::test]
+    async fn test_tag_and_pattern_invalidations_apply_remotely() {
+        let sender = memory_only_cache().await;
+        let receiver = memory_only_cache().await;
+        cache_in_memory(&receiver, "anchor:data:1").await;
+        cache_in_memory(&receiver, "anchor:data:2").await;
+        cache_in_memory(&receiver, "corridor:data:1").await;
+        receiver
+            .tag("anchor:data:1", &["tag:anchor:1"])
+            .await
+            .unwrap();
+
+        let tag = sender
+            .invalidation_payload(Invalidation::Tag {
+                tag: sender.storage_key("tag:anchor:1"),
+            })
+            .unwrap();
+        assert!(receive(&receiver, &tag).await);
+        assert!(!memory_entry(&receiver, "anchor:data:1").await);
+        assert!(memory_entry(&receiver, "anchor:data:2").await);
+
+        let pattern = sender
+            .invalidation_payload(Invalidation::Pattern {
+                pattern: sender.storage_key("anchor:*"),
+            })
+            .unwrap();
+        assert!(receive(&receiver, &pattern).await);
+        assert!(!memory_entry(&receiver, "anchor:data:2").await);
+        assert!(memory_entry(&receiver, "corridor:data:1").await);
+    }
+
+    #[tokio::test]
+    async fn test_delete_reaches_another_instance_over_pub_sub() {
+        // Pub/sub needs a reachable REDIS_URL shared by both instances
+        let (Some(sender), Some(receiver)) = (connected_cache().await, connected_cache().await)
+        else {
+            return;
+        };
+        let key = format!("test:pubsub:{}", uuid::Uuid::new_v4());
+        cache_in_memory(&receiver, &key).await;
+        cache_in_memory(&sender, &key).await;
+
+        // The receiver subscribes in the background, so keep deleting until it is listening
+        let started = Instant::now();
+        while memory_entry(&receiver, &key).await {
+            assert!(
+                started.elapsed() < Duration::from_secs(5),
+                "invalidation never arrived"
+            );
+            sender.delete(&key).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(!memory_entry(&sender, &key).await);
+    }
+
+    /// `cache.*` fields of every event logged while it is the default subscriber
+    #[derive(Clone, Default)]
+    struct CapturedFields(Arc<std::sync::Mutex<Vec<HashMap<String, String>>>>);
+
+    struct FieldRecorder<'a>(&'a mut HashMap<String, String>);
+
+    impl tracing::field::Visit for FieldRecorder<'_> {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for CapturedFields {
+        fn on_event(
+            &self,
+            event: &tracing::Event<'_>,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut fields = HashMap::new();
+            event.record(&mut FieldRecorder(&mut fields));
+            if fields.contains_key("cache.op") {
+                self.0.lock().unwrap().push(fields);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_operations_log_structured_fields() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let captured = CapturedFields::default();
+        let _default =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(captured.clone()));
+        let cache = memory_only_cache().await;
+        let key = CacheKey::anchor_detail("anchor-1");
+
+        cache.set(&key, &1, 60).await.unwrap();
+        cache.get::<i32>(&key).await.unwrap();
+        cache.delete(&key).await.unwrap();
+        cache.get::<i32>(&key).await.unwrap();
+
+        let events = captured.0.lock().unwrap();
+        let summary: Vec<(&str, &str, &str)> = events
+            .iter()
+            .map(|fields| {
+                (
+                    fields["cache.op"].as_str(),
+                    fields["cache.tier"].as_str(),
+                    fields["cache.result"].as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("set", "memory", "stored"),
+                ("get", "memory", "hit"),
+                ("delete", "memory", "deleted"),
+                ("get", "memory", "miss"),
+            ]
+        );
+        assert!(events.iter().all(|fields| fields["cache.key"] == key
+            && fields["cache.latency_ms"].parse::<f64>().is_ok()
+            && fields.contains_key("message")));
+    }
+}
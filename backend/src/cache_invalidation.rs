@@ -0,0 +1,347 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, OwnedSemaphorePermit, Semaphore};
+
+use crate::cache::{CacheConfig, CacheKey, RedisCache, Result};
+use crate::cached_handlers::{cached_corridor_page, cached_dashboard_stats};
+use crate::database::{CorridorFilters, Database};
+use crate::handlers::default_limit;
+use crate::http_cache::CacheBypass;
+use crate::models::corridor::Corridor;
+
+/// Most `/api/events` streams open at once
+pub const MAX_EVENT_SUBSCRIBERS: usize = 100;
+/// Events buffered per subscriber before a slow one starts missing them
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Published after an invalidation so live clients know to refetch
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CacheEvent {
+    /// One anchor's cached entries were dropped
+    AnchorUpdated { anchor_id: String },
+    /// One corridor's cached metrics were dropped
+    CorridorMetricsUpdated { corridor_key: String },
+    /// A metrics ingestion run finished and corridor/dashboard caches were dropped
+    IngestionComplete { completed_at: DateTime<Utc> },
+}
+
+impl CacheEvent {
+    /// SSE event name, the same as the payload's `type`
+    pub fn name(&self) -> &'static str {
+        match self {
+            CacheEvent::AnchorUpdated { .. } => "anchor_updated",
+            CacheEvent::CorridorMetricsUpdated { .. } => "corridor_metrics_updated",
+            CacheEvent::IngestionComplete { .. } => "ingestion_complete",
+        }
+    }
+}
+
+/// A receiver of `CacheEvent`s holding one of the `MAX_EVENT_SUBSCRIBERS` slots
+/// until it is dropped
+pub struct EventSubscription {
+    pub receiver: broadcast::Receiver<CacheEvent>,
+    _permit: OwnedSemaphorePermit,
+}
+
+/// Invalidates cached entries after writes so readers don't see stale data
+pub struct CacheInvalidationService {
+    cache: Arc<RedisCache>,
+    events: broadcast::Sender<CacheEvent>,
+    subscriber_slots: Arc<Semaphore>,
+    /// Delay before the second delete of a double delete; `None` deletes once
+    double_delete_delay: Option<Duration>,
+}
+
+impl CacheInvalidationService {
+    pub fn new(cache: Arc<RedisCache>) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            cache,
+            events,
+            subscriber_slots: Arc::new(Semaphore::new(MAX_EVENT_SUBSCRIBERS)),
+            double_delete_delay: None,
+        }
+    }
+
+    /// Delete anchor and corridor keys again `delay` after each invalidation
+    /// (see `CacheConfig::double_delete_delay`); `None` turns it off
+    pub fn with_double_delete(mut self, delay: Option<Duration>) -> Self {
+        self.double_delete_delay = delay;
+        self
+    }
+
+    /// Delete `keys` (and `tag`'s members) now and, with double delete on, once
+    /// more after the delay in a spawned task. A read that raced the write may
+    /// have put the old value back in between; the second pass drops it.
+    async fn delete_twice(&self, tag: Option<String>, keys: Vec<String>) -> Result<()> {
+        if let Some(tag) = &tag {
+            self.cache.invalidate_tag(tag).await?;
+        }
+        self.cache.delete_many(&keys).await?;
+
+        let Some(delay) = self.double_delete_delay else {
+            return Ok(());
+        };
+        let cache = Arc::clone(&self.cache);
+        self.cache.spawn_tracked(async move {
+            tokio::time::sleep(delay).await;
+            if let Some(tag) = &tag {
+                if let Err(e) = cache.invalidate_tag(tag).await {
+                    tracing::warn!("Delayed invalidation of tag {} failed: {}", tag, e);
+                }
+            }
+            if let Err(e) = cache.delete_many(&keys).await {
+                tracing::warn!("Delayed delete of {} cache keys failed: {}", keys.len(), e);
+            }
+        });
+        Ok(())
+    }
+
+    /// Subscribe to events published from here on, or `None` when
+    /// `MAX_EVENT_SUBSCRIBERS` subscriptions are already open
+    pub fn subscribe(&self) -> Option<EventSubscription> {
+        let permit = Arc::clone(&self.subscriber_slots)
+            .try_acquire_owned()
+            .ok()?;
+        Some(EventSubscription {
+            receiver: self.events.subscribe(),
+            _permit: permit,
+        })
+    }
+
+    fn publish(&self, event: CacheEvent) {
+        // Sending only fails when no one is subscribed
+        let _ = self.events.send(event);
+    }
+
+    /// Drop every cached entry for a single anchor, including keys it was
+    /// tagged on that can't be derived from the id (e.g. lookups by account)
+    pub async fn invalidate_anchor(&self, anchor_id: &str) -> Result<()> {
+        self.delete_twice(
+            Some(CacheKey::anchor_tag(anchor_id)),
+            CacheKey::all_keys_for_anchor(anchor_id),
+        )
+        .await?;
+        self.publish(CacheEvent::AnchorUpdated {
+            anchor_id: anchor_id.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Tell live clients an anchor changed when its entries were rewritten in
+    /// place rather than invalidated
+    pub fn publish_anchor_updated(&self, anchor_id: &str) {
+        self.publish(CacheEvent::AnchorUpdated {
+            anchor_id: anchor_id.to_string(),
+        });
+    }
+
+    /// Drop anchor list pages, search results and the count, leaving per-anchor
+    /// entries alone
+    pub async fn invalidate_anchor_lists(&self) -> Result<()> {
+        let mut deleted = 0;
+        for pattern in ["anchor:list:*", "anchor:search:*", "anchor:cursor:*"] {
+            deleted += self.cache.delete_pattern(pattern).await?;
+        }
+        self.cache.delete(&CacheKey::anchor_count()).await?;
+        self.cache.delete(&CacheKey::active_anchor_count()).await?;
+        tracing::debug!("Invalidated {} anchor list cache keys", deleted);
+        Ok(())
+    }
+
+    /// Drop every anchor entry, including list pages, counts and the
+    /// per-asset anchor lists
+    pub async fn invalidate_anchors(&self) -> Result<()> {
+        let mut deleted = 0;
+        for pattern in ["anchor:*", "asset:*"] {
+            deleted += self.cache.delete_pattern(pattern).await?;
+        }
+        tracing::debug!("Invalidated {} anchor cache keys", deleted);
+        Ok(())
+    }
+
+    /// Drop the list of anchors issuing `asset_code`
+    pub async fn invalidate_asset(&self, asset_code: &str) -> Result<()> {
+        self.cache
+            .delete(&CacheKey::asset_anchors(asset_code))
+            .await
+    }
+
+    /// Drop the cached corridor lists for both assets of a corridor, under every
+    /// issuer, since the corridor shows up in each
+    pub async fn invalidate_corridor_assets(&self, corridor: &Corridor) -> Result<()> {
+        let mut deleted = 0;
+        for code in [&corridor.asset_a_code, &corridor.asset_b_code] {
+            deleted += self
+                .cache
+                .delete_pattern(&CacheKey::asset_corridors_pattern(code))
+                .await?;
+        }
+        tracing::debug!("Invalidated {} asset corridor cache keys", deleted);
+        Ok(())
+    }
+
+    /// Drop an asset's cached metrics, along with a remembered 404 for them
+    pub async fn invalidate_asset_metrics(
+        &self,
+        asset_code: &str,
+        asset_issuer: &str,
+    ) -> Result<()> {
+        let key = CacheKey::asset_metrics(asset_code, asset_issuer);
+        let not_found = CacheKey::not_found(&key);
+        self.cache.delete_many(&[key, not_found]).await?;
+        Ok(())
+    }
+
+    /// Drop cached metrics and detail for a single corridor
+    pub async fn invalidate_corridor(&self, corridor_key: &str) -> Result<()> {
+        self.delete_twice(
+            None,
+            vec![
+                CacheKey::corridor_metrics(corridor_key),
+                CacheKey::corridor_detail(corridor_key),
+            ],
+        )
+        .await?;
+        self.publish(CacheEvent::CorridorMetricsUpdated {
+            corridor_key: corridor_key.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Drop the cached detail served by id from `/api/corridors/:id`
+    pub async fn invalidate_corridor_detail(&self, corridor_id: &str) -> Result<()> {
+        self.cache
+            .delete(&CacheKey::corridor_detail(corridor_id))
+            .await
+    }
+
+    /// Drop every cached history window of one corridor
+    pub async fn invalidate_corridor_history(&self, corridor_id: &str) -> Result<()> {
+        let deleted = self
+            .cache
+            .delete_pattern(&CacheKey::corridor_metrics_history_pattern(corridor_id))
+            .await?;
+        tracing::debug!("Invalidated {} corridor history cache keys", deleted);
+        Ok(())
+    }
+
+    /// Drop every corridor entry, including list pages, counts and histories
+    pub async fn invalidate_corridors(&self) -> Result<()> {
+        let deleted = self.cache.delete_pattern("corridor:*").await?;
+        tracing::debug!("Invalidated {} corridor cache keys", deleted);
+        Ok(())
+    }
+
+    pub async fn invalidate_dashboard(&self) -> Result<()> {
+        self.cache.delete(&CacheKey::dashboard_stats()).await?;
+        self.cache.delete(&CacheKey::dashboard_overview()).await?;
+        Ok(())
+    }
+
+    /// Recompute the first corridor page, the corridor count and the dashboard
+    /// stats and write them back with fresh TTLs, so readers after a batch
+    /// ingestion hit warm keys instead of paying for the recompute. Other corridor
+    /// pages keep their entries until they expire; use `invalidate_corridors`
+    /// after ad-hoc writes. Each key is refreshed independently and a failure is
+    /// logged and skipped. Returns how many keys were rewritten.
+    pub async fn refresh_corridors(&self, db: &Arc<Database>, config: &CacheConfig) -> usize {
+        // The page loader reuses a cached count, so drop it to have it recomputed
+        if let Err(e) = self.cache.delete(&CacheKey::corridor_count()).await {
+            tracing::warn!("Failed to drop corridor count before refresh: {}", e);
+        }
+        // Histories are only recomputed on demand, so just drop them
+        if let Err(e) = self.cache.delete_pattern("corridor:history:*").await {
+            tracing::warn!("Failed to drop corridor histories before refresh: {}", e);
+        }
+
+        let bypass = CacheBypass(true);
+        let mut refreshed = 0;
+        let unfiltered = CorridorFilters::default();
+        match cached_corridor_page(
+            db,
+            &self.cache,
+            config,
+            bypass,
+            default_limit(),
+            0,
+            None,
+            &unfiltered,
+        )
+        .await
+        {
+            // The page also fills the total count key it reports
+            Ok(_) => refreshed += 2,
+            Err(e) => tracing::warn!("Failed to refresh corridor list: {:?}", e),
+        }
+        match cached_dashboard_stats(db, &self.cache, config, bypass).await {
+            Ok(_) => refreshed += 1,
+            Err(e) => tracing::warn!("Failed to refresh dashboard stats: {:?}", e),
+        }
+
+        tracing::info!("Refreshed {} corridor and dashboard cache keys", refreshed);
+        refreshed
+    }
+
+    /// Called once a metrics ingestion run has written new data. The corridor
+    /// sweep also drops every `corridor:history:*` window.
+    pub async fn on_metrics_ingestion_complete(&self) -> Result<()> {
+        tracing::info!("Metrics ingestion complete, invalidating corridor and dashboard caches");
+        self.invalidate_corridors().await?;
+        self.invalidate_dashboard().await?;
+        self.publish(CacheEvent::IngestionComplete {
+            completed_at: Utc::now(),
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn memory_only_cache() -> Arc<RedisCache> {
+        Arc::new(RedisCache::from_url("redis://127.0.0.1:1").await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_double_delete_drops_value_restored_after_first_delete() {
+        let cache = memory_only_cache().await;
+        let service = CacheInvalidationService::new(Arc::clone(&cache))
+            .with_double_delete(Some(Duration::from_millis(20)));
+        let anchor_key = CacheKey::anchor_detail("a1");
+        let corridor_key = CacheKey::corridor_metrics("USDC:XLM");
+        cache.set(&anchor_key, &"old", 60).await.unwrap();
+
+        service.invalidate_anchor("a1").await.unwrap();
+        service.invalidate_corridor("USDC:XLM").await.unwrap();
+        assert_eq!(cache.get::<String>(&anchor_key).await.unwrap(), None);
+
+        // A read that loaded before the commit writes the old value back
+        cache.set(&anchor_key, &"old", 60).await.unwrap();
+        cache.set(&corridor_key, &"old", 60).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        assert_eq!(cache.get::<String>(&anchor_key).await.unwrap(), None);
+        assert_eq!(cache.get::<String>(&corridor_key).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_single_delete_by_default() {
+        let cache = memory_only_cache().await;
+        let service = CacheInvalidationService::new(Arc::clone(&cache));
+        let key = CacheKey::anchor_detail("a1");
+
+        service.invalidate_anchor("a1").await.unwrap();
+        cache.set(&key, &"repopulated", 60).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(
+            cache.get::<String>(&key).await.unwrap().as_deref(),
+            Some("repopulated")
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::cache::{CacheConfig, RedisCache};
+use crate::cached_handlers::{cached_anchor_page, cached_corridor_page, cached_dashboard_stats};
+use crate::database::{AnchorFilters, CorridorFilters, Database};
+use crate::handlers::{default_limit, ApiResult};
+use crate::http_cache::CacheBypass;
+
+/// Pre-loads the hottest cache keys so the first requests after a deploy
+/// don't all fall through to the database
+pub struct CacheWarmer;
+
+impl CacheWarmer {
+    /// Fill the first anchor and corridor pages and the dashboard stats through
+    /// the handlers' own loaders, concurrently. Returns how many keys were populated;
+    /// failures are logged and skipped.
+    pub async fn warm(db: Arc<Database>, cache: Arc<RedisCache>, config: CacheConfig) -> usize {
+        let started = Instant::now();
+        let limit = default_limit();
+        let bypass = CacheBypass::default();
+        let unfiltered_anchors = AnchorFilters::default();
+        let unfiltered_corridors = CorridorFilters::default();
+
+        let (anchors, corridors, dashboard) = tokio::join!(
+            cached_anchor_page(
+                &db,
+                &cache,
+                &config,
+                bypass,
+                limit,
+                0,
+                None,
+                false,
+                &unfiltered_anchors,
+            ),
+            cached_corridor_page(
+                &db,
+                &cache,
+                &config,
+                bypass,
+                limit,
+                0,
+                None,
+                &unfiltered_corridors,
+            ),
+            cached_dashboard_stats(&db, &cache, &config, bypass),
+        );
+
+        // A list page also fills the total count key it reports
+        let populated = [
+            ("anchors", keys_or_log(anchors, 2)),
+            ("corridors", keys_or_log(corridors, 2)),
+            ("dashboard", keys_or_log(dashboard, 1)),
+        ];
+        for (entity, keys) in &populated {
+            if keys.is_none() {
+                tracing::warn!("Cache warming skipped {}", entity);
+            }
+        }
+        let total = populated.iter().filter_map(|(_, keys)| *keys).sum();
+
+        tracing::info!(
+            "Cache warming populated {} keys in {:?}",
+            total,
+            started.elapsed()
+        );
+        total
+    }
+}
+
+fn keys_or_log<T>(result: ApiResult<T>, keys: usize) -> Option<usize> {
+    match result {
+        Ok(_) => Some(keys),
+        Err(e) => {
+            tracing::warn!("Cache warming load failed: {:?}", e);
+            None
+        }
+    }
+}
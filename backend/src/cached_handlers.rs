@@ -0,0 +1,2229 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::broadcast::{broadcast_anchor_update, broadcast_corridor_update};
+use crate::cache::{
+    hash_filters, Cache, CacheConfig, CacheKey, CacheMetricsSummary, CacheStatus, FailedWrite,
+    MemoryCompaction, RedisCache,
+};
+use crate::database::{
+    AnchorFilters, AnchorMetricsUpdate, CorridorFilters, Database, SortSpec, Upserted,
+};
+use crate::handlers::{
+    validate_create_corridor, validate_metrics, validate_stellar_account, AnchorAssetsQuery,
+    ApiError, ApiResult, AssetCorridorsQuery, BatchUpdateMetricsItem, CorridorHistoryQuery,
+    CorridorHistoryResponse, CreateAssetRequest, DeleteAnchorQuery, ListAnchorsQuery,
+    ListAnchorsResponse, ListCorridorsQuery, ListCorridorsResponse, UpdateCorridorMetricsFromTxns,
+    UpdateMetricsRequest,
+};
+use crate::http_cache::{CacheBypass, CachedJson, IdempotencyKey};
+use crate::models::corridor::Corridor;
+use crate::models::corridor::CorridorMetrics;
+use crate::models::{
+    Anchor, AnchorDetailResponse, Asset, AssetMetrics, CorridorDetailResponse,
+    CorridorMetricsSnapshot, CreateAnchorRequest, CreateCorridorRequest, DashboardStats,
+};
+use crate::services::analytics::{
+    compute_corridor_metrics, detect_anomaly, volume_trend, Anomaly, CorridorTransaction,
+};
+use crate::state::AppState;
+
+/// How long past freshness a dashboard value is still served while it refreshes
+const DASHBOARD_STATS_STALE_TTL: usize = 120; // 2 minutes
+/// Spread applied to every TTL so entries written together don't expire together
+const TTL_JITTER_PCT: f64 = 10.0;
+/// How long a create's result is replayed for a repeated `Idempotency-Key`
+const IDEMPOTENCY_TTL: usize = 24 * 60 * 60; // 24 hours
+/// How long a replica's claim on an `Idempotency-Key` lasts while its create runs
+const IDEMPOTENCY_CLAIM_TTL_SECS: u64 = 30;
+/// How long a duplicate waits for another replica's result before answering 409
+const IDEMPOTENCY_WAIT: Duration = Duration::from_secs(5);
+const IDEMPOTENCY_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Longest an anchor write-through may hold the anchor's write lock
+const ANCHOR_WRITE_LOCK_TTL_SECS: u64 = 5;
+
+/// Cache-aside read shared by the GET handlers: serve `key` from the cache or
+/// load it, unless the request asked to bypass the cache, in which case the
+/// loader always runs and its result replaces the cached entry
+async fn read_through<C, T, F, Fut>(
+    cache: &C,
+    bypass: CacheBypass,
+    key: &str,
+    ttl: usize,
+    tags: &[&str],
+    loader: F,
+) -> ApiResult<(T, CacheStatus)>
+where
+    C: Cache,
+    T: Serialize + DeserializeOwned + Send + Sync,
+    F: FnOnce() -> Fut + Send,
+    Fut: Future<Output = ApiResult<T>> + Send,
+{
+    read_through_with_ttl_fn(cache, bypass, key, |_| ttl, tags, loader).await
+}
+
+/// `read_through` for one offset page of a list. A page that isn't `cacheable`
+/// (see `CacheConfig::caches_list_page`) is loaded without reading or writing
+/// the cache and reported as a miss.
+async fn read_through_page<C, T, F, Fut>(
+    cache: &C,
+    bypass: CacheBypass,
+    cacheable: bool,
+    key: &str,
+    ttl: usize,
+    tags: &[&str],
+    loader: F,
+) -> ApiResult<(T, CacheStatus)>
+where
+    C: Cache,
+    T: Serialize + DeserializeOwned + Send + Sync,
+    F: FnOnce() -> Fut + Send,
+    Fut: Future<Output = ApiResult<T>> + Send,
+{
+    if !cacheable {
+        tracing::debug!("Not caching uncommon list page {}", key);
+        return Ok((loader().await?, CacheStatus::Miss));
+    }
+    read_through(cache, bypass, key, ttl, tags, loader).await
+}
+
+/// `read_through` for entries whose TTL depends on the loaded value
+async fn read_through_with_ttl_fn<C, T, F, Fut>(
+    cache: &C,
+    bypass: CacheBypass,
+    key: &str,
+    ttl_for: impl FnOnce(&T) -> usize + Send,
+    tags: &[&str],
+    loader: F,
+) -> ApiResult<(T, CacheStatus)>
+where
+    C: Cache,
+    T: Serialize + DeserializeOwned + Send + Sync,
+    F: FnOnce() -> Fut + Send,
+    Fut: Future<Output = ApiResult<T>> + Send,
+{
+    if bypass.0 {
+        let value = cache
+            .refresh_tagged_with_ttl_fn(key, ttl_for, TTL_JITTER_PCT, tags, loader)
+            .await?;
+        return Ok((value, CacheStatus::Bypass));
+    }
+
+    cache
+        .get_or_set_tagged_with_ttl_fn(key, ttl_for, TTL_JITTER_PCT, tags, loader)
+        .await
+}
+
+/// `read_through` for single-row lookups that 404 when the row is missing. The
+/// 404 is remembered under `CacheKey::not_found(key)` for the `not_found` TTL, so
+/// repeated probes for ids that don't exist are answered without the database.
+/// Creates clear it through the same invalidation that drops `key`.
+async fn read_through_existing<C, T, F, Fut>(
+    cache: &C,
+    config: &CacheConfig,
+    bypass: CacheBypass,
+    key: &str,
+    ttl: usize,
+    tags: &[&str],
+    loader: F,
+) -> ApiResult<(T, CacheStatus)>
+where
+    C: Cache,
+    T: Serialize + DeserializeOwned + Send + Sync,
+    F: FnOnce() -> Fut + Send,
+    Fut: Future<Output = ApiResult<T>> + Send,
+{
+    let not_found_key = CacheKey::not_found(key);
+    if !bypass.0 {
+        if let Ok(Some(message)) = cache.get::<String>(&not_found_key).await {
+            return Err(ApiError::NotFound(message));
+        }
+    }
+
+    match read_through(cache, bypass, key, ttl, tags, loader).await {
+        Err(ApiError::NotFound(message)) => {
+            if let Err(e) = cache
+                .set(&not_found_key, &message, config.ttl("not_found"))
+                .await
+            {
+                tracing::warn!("Failed to cache not-found for {}: {}", key, e);
+            }
+            Err(ApiError::NotFound(message))
+        }
+        // A bypassing read that found the row retires any stale 404
+        Ok(found) if bypass.0 => {
+            if let Err(e) = cache.delete(&not_found_key).await {
+                tracing::warn!("Failed to drop cached not-found for {}: {}", key, e);
+            }
+            Ok(found)
+        }
+        result => result,
+    }
+}
+
+/// A create's result as stored under its `Idempotency-Key`, with the
+/// fingerprint of the request that produced it
+#[derive(Serialize, Deserialize)]
+struct IdempotentReplay<T> {
+    fingerprint: String,
+    response: T,
+}
+
+/// Hash of a create's path and body, so a reused `Idempotency-Key` can be told
+/// apart from a retry of the same request
+fn request_fingerprint(path: &str, body: &impl Serialize) -> String {
+    let body = serde_json::to_string(body).unwrap_or_default();
+    hash_filters(&format!("{}\n{}", path, body))
+}
+
+/// Run a create at most once per `Idempotency-Key`: a repeat within
+/// `IDEMPOTENCY_TTL` returns the stored result instead of inserting again. The
+/// first request claims the key in Redis before running `create`, so a
+/// duplicate reaching another replica meanwhile waits up to `IDEMPOTENCY_WAIT`
+/// for the stored result and gets 409 if the first is still running. Without
+/// Redis the claim is the cache's in-process per-key lock. A repeat whose
+/// `fingerprint` differs from the first request's is a client reusing the key
+/// and gets 409. A failed create stores nothing, so it can be retried with the
+/// same key. Without a key `create` just runs.
+async fn idempotent<T, F, Fut>(
+    cache: &RedisCache,
+    scope: &str,
+    idempotency: IdempotencyKey,
+    fingerprint: String,
+    create: F,
+) -> ApiResult<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = ApiResult<T>>,
+{
+    let Some(key) = idempotency.0 else {
+        return create().await;
+    };
+    let store_key = CacheKey::idempotency(scope, &key);
+    let run = || async {
+        let response = create().await?;
+        Ok::<_, ApiError>(IdempotentReplay {
+            fingerprint: fingerprint.clone(),
+            response,
+        })
+    };
+
+    let stored = cache
+        .get::<IdempotentReplay<T>>(&store_key)
+        .await
+        .ok()
+        .flatten();
+    let replay = match stored {
+        Some(replay) => Some(replay),
+        None => match cache
+            .try_lock(
+                &CacheKey::idempotency_claim(scope, &key),
+                IDEMPOTENCY_CLAIM_TTL_SECS,
+            )
+            .await
+        {
+            Ok(Some(claim)) => {
+                // The first request may have finished between the read and the claim
+                let replay = match cache.get::<IdempotentReplay<T>>(&store_key).await {
+                    Ok(Some(replay)) => replay,
+                    _ => {
+                        let replay = run().await?;
+                        if let Err(e) = cache.set(&store_key, &replay, IDEMPOTENCY_TTL).await {
+                            tracing::warn!("Failed to store idempotent result {}: {}", key, e);
+                        }
+                        replay
+                    }
+                };
+                if let Err(e) = claim.release().await {
+                    tracing::warn!("Failed to release idempotency claim {}: {}", key, e);
+                }
+                Some(replay)
+            }
+            Ok(None) => wait_for_replay(cache, &store_key).await,
+            Err(_) => {
+                let (replay, _) = cache
+                    .get_or_set_with_status(&store_key, IDEMPOTENCY_TTL, 0.0, run)
+                    .await?;
+                Some(replay)
+            }
+        },
+    };
+    let Some(replay) = replay else {
+        return Err(ApiError::Conflict(
+            "A request with this Idempotency-Key is still in progress".to_string(),
+        ));
+    };
+
+    if replay.fingerprint != fingerprint {
+        return Err(ApiError::Conflict(
+            "Idempotency-Key was already used for a different request".to_string(),
+        ));
+    }
+
+    Ok(replay.response)
+}
+
+/// Poll for the result another replica is storing under `store_key`, giving up
+/// after `IDEMPOTENCY_WAIT`
+async fn wait_for_replay<T: DeserializeOwned>(
+    cache: &RedisCache,
+    store_key: &str,
+) -> Option<IdempotentReplay<T>> {
+    let deadline = tokio::time::Instant::now() + IDEMPOTENCY_WAIT;
+    while tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(IDEMPOTENCY_POLL_INTERVAL).await;
+        if let Ok(Some(replay)) = cache.get(store_key).await {
+            tracing::debug!("Replayed another replica's create for {}", store_key);
+            return Some(replay);
+        }
+    }
+    None
+}
+
+/// Status for the `X-Cache` debug header, or `None` unless `CACHE_DEBUG_HEADERS` is on
+fn debug_status<C>(app_state: &AppState<C>, status: CacheStatus) -> Option<CacheStatus> {
+    app_state.cache_config.debug_headers.then_some(status)
+}
+
+/// Anchor count, cached so list pages don't run a second query on every call.
+/// Counts only active anchors unless `include_inactive` is set.
+async fn cached_anchor_count(
+    db: &Database,
+    cache: &RedisCache,
+    config: &CacheConfig,
+    include_inactive: bool,
+) -> ApiResult<i64> {
+    let key = if include_inactive {
+        CacheKey::anchor_count()
+    } else {
+        CacheKey::active_anchor_count()
+    };
+    let count = cache
+        .get_or_set_with_jitter(&key, config.ttl("anchor.count"), TTL_JITTER_PCT, || {
+            let unfiltered = AnchorFilters::default();
+            async move { db.count_listed_anchors(include_inactive, &unfiltered).await }
+        })
+        .await?;
+
+    Ok(count)
+}
+
+/// Total corridor count, cached alongside the corridor list pages
+async fn cached_corridor_count(
+    db: &Database,
+    cache: &RedisCache,
+    config: &CacheConfig,
+) -> ApiResult<i64> {
+    let count = cache
+        .get_or_set_with_jitter(
+            &CacheKey::corridor_count(),
+            config.ttl("corridor.count"),
+            TTL_JITTER_PCT,
+            || db.count_corridors(),
+        )
+        .await?;
+
+    Ok(count)
+}
+
+/// One offset page of anchors through the `anchor:list` key. Shared with the
+/// cache warmer so both fill the same key with the same shape. Each distinct
+/// filter set gets its own key; only unfiltered pages share the cached count.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn cached_anchor_page(
+    db: &Database,
+    cache: &RedisCache,
+    config: &CacheConfig,
+    bypass: CacheBypass,
+    limit: i64,
+    offset: i64,
+    sort: Option<&SortSpec>,
+    include_inactive: bool,
+    filters: &AnchorFilters,
+) -> ApiResult<(ListAnchorsResponse, CacheStatus)> {
+    let cache_key =
+        CacheKey::anchor_list(limit, offset, &filters.cache_token(sort), include_inactive);
+    let page = read_through_page(
+        cache,
+        bypass,
+        config.caches_list_page(limit, offset),
+        &cache_key,
+        config.ttl("anchor.list"),
+        &[],
+        || async {
+            let anchors = db
+                .list_anchors(limit, offset, sort, include_inactive, filters)
+                .await?;
+            let total = if filters.is_empty() {
+                cached_anchor_count(db, cache, config, include_inactive).await?
+            } else {
+                db.count_listed_anchors(include_inactive, filters).await?
+            };
+            Ok::<_, ApiError>(ListAnchorsResponse::offset_page(anchors, total, offset))
+        },
+    )
+    .await?;
+
+    Ok(page)
+}
+
+/// One offset page of corridors through the `corridor:list` key. Each distinct
+/// filter set gets its own key; only unfiltered pages share the cached count.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn cached_corridor_page(
+    db: &Database,
+    cache: &RedisCache,
+    config: &CacheConfig,
+    bypass: CacheBypass,
+    limit: i64,
+    offset: i64,
+    sort: Option<&SortSpec>,
+    filters: &CorridorFilters,
+) -> ApiResult<(ListCorridorsResponse, CacheStatus)> {
+    let cache_key = CacheKey::corridor_list(limit, offset, &filters.cache_token(sort));
+    let page = read_through_page(
+        cache,
+        bypass,
+        config.caches_list_page(limit, offset),
+        &cache_key,
+        config.ttl("corridor.list"),
+        &[],
+        || async {
+            let corridors = db.list_corridors(limit, offset, sort, filters).await?;
+            let total = if filters.is_empty() {
+                cached_corridor_count(db, cache, config).await?
+            } else {
+                db.count_filtered_corridors(filters).await?
+            };
+            Ok::<_, ApiError>(ListCorridorsResponse::page(corridors, total, offset))
+        },
+    )
+    .await?;
+
+    Ok(page)
+}
+
+/// Dashboard totals through the `dashboard:stats` key, served stale while a
+/// background refresh runs
+pub(crate) async fn cached_dashboard_stats(
+    db: &Arc<Database>,
+    cache: &Arc<RedisCache>,
+    config: &CacheConfig,
+    bypass: CacheBypass,
+) -> ApiResult<(DashboardStats, CacheStatus)> {
+    let db = Arc::clone(db);
+    let key = CacheKey::dashboard_stats();
+    let (fresh, stale) = (config.ttl("dashboard.stats"), DASHBOARD_STATS_STALE_TTL);
+    let loader = move || async move { db.dashboard_stats().await };
+
+    if bypass.0 {
+        let stats = cache
+            .refresh_stale_while_revalidate(&key, fresh, stale, loader)
+            .await?;
+        return Ok((stats, CacheStatus::Bypass));
+    }
+    let stats = cache
+        .get_stale_while_revalidate_with_status(&key, fresh, stale, loader)
+        .await?;
+
+    Ok(stats)
+}
+
+/// GET /api/v2/anchors - List anchors (cached), optionally filtered by `q`
+#[utoipa::path(
+    get,
+    path = "/api/v2/anchors",
+    tag = "anchors",
+    params(ListAnchorsQuery, ("no_cache" = Option<bool>, Query, description = "Skip the cache and reload, when `ALLOW_CACHE_BYPASS` is on")),
+    responses(
+        (status = 200, description = "A page of anchors", body = ListAnchorsResponse),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        ApiError
+    )
+)]
+pub async fn list_anchors_cached(
+    State(app_state): State<AppState>,
+    Query(params): Query<ListAnchorsQuery>,
+    headers: HeaderMap,
+    bypass: CacheBypass,
+) -> ApiResult<CachedJson<ListAnchorsResponse>> {
+    let ttl = app_state.cache_config.ttl("anchor.list");
+    let page = params.page(app_state.cache_config.max_list_limit)?;
+    if let Some(cursor) = params.cursor()? {
+        let cache_key = CacheKey::anchor_cursor_page(
+            params.after.as_deref().unwrap_or_default(),
+            page.limit,
+            params.include_inactive,
+        );
+        let (response, status) =
+            read_through(&*app_state.cache, bypass, &cache_key, ttl, &[], || async {
+                let anchors = app_state
+                    .db
+                    .list_anchors_after(cursor.as_ref(), page.limit, params.include_inactive)
+                    .await?;
+                let total = cached_anchor_count(
+                    &app_state.db,
+                    &app_state.cache,
+                    &app_state.cache_config,
+                    params.include_inactive,
+                )
+                .await?;
+                Ok::<_, ApiError>(ListAnchorsResponse::keyset_page(anchors, total, page.limit))
+            })
+            .await?;
+        let total = response.total;
+        return Ok(CachedJson::new(response, ttl, &headers)
+            .with_cache_age(status)
+            .with_cache_status(debug_status(&app_state, status))
+            .with_limit_clamped(page.clamped_limit())
+            .with_total_count(total));
+    }
+
+    let sort = params.sort()?;
+    let filters = params.filters()?;
+    if let Some(q) = params.search_term() {
+        let (response, status) = search_anchors_cached(
+            &app_state,
+            bypass,
+            q,
+            page.limit,
+            page.offset,
+            sort,
+            params.include_inactive,
+        )
+        .await?;
+        let total = response.total;
+        return Ok(CachedJson::new(response, ttl, &headers)
+            .with_cache_age(status)
+            .with_cache_status(debug_status(&app_state, status))
+            .with_limit_clamped(page.clamped_limit())
+            .with_total_count(total));
+    }
+
+    let (response, status) = cached_anchor_page(
+        &app_state.db,
+        &app_state.cache,
+        &app_state.cache_config,
+        bypass,
+        page.limit,
+        page.offset,
+        sort.as_ref(),
+        params.include_inactive,
+        &filters,
+    )
+    .await?;
+
+    let total = response.total;
+    Ok(CachedJson::new(response, ttl, &headers)
+        .with_cache_age(status)
+        .with_cache_status(debug_status(&app_state, status))
+        .with_limit_clamped(page.clamped_limit())
+        .with_total_count(total))
+}
+
+async fn search_anchors_cached(
+    app_state: &AppState,
+    bypass: CacheBypass,
+    q: &str,
+    limit: i64,
+    offset: i64,
+    sort: Option<SortSpec>,
+    include_inactive: bool,
+) -> ApiResult<(ListAnchorsResponse, CacheStatus)> {
+    let ttl = app_state.cache_config.ttl("anchor.search");
+    let cache_key = CacheKey::anchor_search(
+        q,
+        limit,
+        offset,
+        &SortSpec::cache_token(sort.as_ref()),
+        include_inactive,
+    );
+    let cacheable = app_state.cache_config.caches_list_page(limit, offset);
+    let page = read_through_page(
+        &*app_state.cache,
+        bypass,
+        cacheable,
+        &cache_key,
+        ttl,
+        &[],
+        || async {
+            let anchors = app_state
+                .db
+                .search_anchors(q, limit, offset, sort.as_ref(), include_inactive)
+                .await?;
+            let total = app_state
+                .db
+                .count_search_anchors(q, include_inactive)
+                .await?;
+            Ok::<_, ApiError>(ListAnchorsResponse::offset_page(anchors, total, offset))
+        },
+    )
+    .await?;
+
+    Ok(page)
+}
+
+/// One anchor's detail through the `anchor:detail` key. Shared with
+/// `warm_cache` so a warmed entry is exactly what the handler would store.
+pub(crate) async fn cached_anchor_detail<C: Cache>(
+    app_state: &AppState<C>,
+    id: Uuid,
+    bypass: CacheBypass,
+) -> ApiResult<(AnchorDetailResponse, CacheStatus)> {
+    let cache_key = CacheKey::anchor_detail(&id.to_string());
+    let tag = CacheKey::anchor_tag(&id.to_string());
+    read_through_existing(
+        &*app_state.cache,
+        &app_state.cache_config,
+        bypass,
+        &cache_key,
+        app_state.cache_config.ttl("anchor.detail"),
+        &[&tag],
+        || async {
+            app_state
+                .db
+                .get_anchor_detail(id)
+                .await?
+                .ok_or_else(|| ApiError::NotFound(format!("Anchor with id {} not found", id)))
+        },
+    )
+    .await
+}
+
+/// GET /api/anchors/:id - Get detailed anchor information (cached)
+#[utoipa::path(
+    get,
+    path = "/api/anchors/{id}",
+    tag = "anchors",
+    params(("id" = Uuid, Path, description = "Anchor id"), ("no_cache" = Option<bool>, Query, description = "Skip the cache and reload, when `ALLOW_CACHE_BYPASS` is on")),
+    responses(
+        (status = 200, description = "The anchor with its assets and metrics history", body = AnchorDetailResponse),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        ApiError
+    )
+)]
+pub async fn get_anchor_cached<C: Cache>(
+    State(app_state): State<AppState<C>>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    bypass: CacheBypass,
+) -> ApiResult<CachedJson<AnchorDetailResponse>> {
+    let (anchor_detail, status) = cached_anchor_detail(&app_state, id, bypass).await?;
+
+    Ok(CachedJson::new(
+        anchor_detail,
+        app_state.cache_config.ttl("anchor.detail"),
+        &headers,
+    )
+    .with_cache_age(status)
+    .with_cache_status(debug_status(&app_state, status)))
+}
+
+/// GET /api/anchors/account/:stellar_account - Get anchor by Stellar account (cached)
+#[utoipa::path(
+    get,
+    path = "/api/anchors/account/{stellar_account}",
+    tag = "anchors",
+    params(("stellar_account" = String, Path, description = "Anchor's Stellar account"), ("no_cache" = Option<bool>, Query, description = "Skip the cache and reload, when `ALLOW_CACHE_BYPASS` is on")),
+    responses(
+        (status = 200, description = "The anchor", body = Anchor),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        ApiError
+    )
+)]
+pub async fn get_anchor_by_account_cached<C: Cache>(
+    State(app_state): State<AppState<C>>,
+    Path(stellar_account): Path<String>,
+    headers: HeaderMap,
+    bypass: CacheBypass,
+) -> ApiResult<CachedJson<Anchor>> {
+    let ttl = app_state.cache_config.ttl("anchor.by_account");
+    let cache_key = CacheKey::anchor_by_account(&stellar_account);
+    let (anchor, status) = read_through_existing(
+        &*app_state.cache,
+        &app_state.cache_config,
+        bypass,
+        &cache_key,
+        ttl,
+        &[],
+        || async {
+            let anchor = app_state
+                .db
+                .get_anchor_by_stellar_account(&stellar_account)
+                .await?
+                .ok_or_else(|| {
+                    ApiError::NotFound(format!(
+                        "Anchor with stellar account {} not found",
+                        stellar_account
+                    ))
+                })?;
+            // The id is only known once loaded, so tag here rather than up front
+            app_state
+                .cache
+                .tag(&cache_key, &[&CacheKey::anchor_tag(&anchor.id)])
+                .await?;
+            Ok::<_, ApiError>(anchor)
+        },
+    )
+    .await?;
+
+    Ok(CachedJson::new(anchor, ttl, &headers)
+        .with_cache_age(status)
+        .with_cache_status(debug_status(&app_state, status)))
+}
+
+/// Look up an anchor row through the `anchor:data` key, returning 404 if it doesn't exist
+async fn require_anchor_cached<C: Cache>(app_state: &AppState<C>, id: Uuid) -> ApiResult<Anchor> {
+    let ttl = app_state.cache_config.ttl("anchor.data");
+    let tag = CacheKey::anchor_tag(&id.to_string());
+    let key = CacheKey::anchor_data(&id.to_string());
+    let loader = || async {
+        app_state
+            .db
+            .get_anchor_by_id(id)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Anchor with id {} not found", id)))
+    };
+    let (anchor, _) = app_state
+        .cache
+        .get_or_set_tagged_with_ttl_fn(&key, |_| ttl, TTL_JITTER_PCT, &[&tag], loader)
+        .await?;
+    Ok(anchor)
+}
+
+/// Resolve many anchors at once through their `anchor:data` keys, for callers
+/// that would otherwise run `require_anchor_cached` in a loop. Unknown ids are
+/// left out of the map.
+pub async fn get_anchors_batch_cached(
+    app_state: &AppState,
+    ids: &[Uuid],
+) -> ApiResult<HashMap<Uuid, Anchor>> {
+    anchors_batch_through_cache(
+        &app_state.cache,
+        app_state.cache_config.ttl("anchor.data"),
+        ids,
+        |missing| async move { Ok(app_state.db.get_anchors_batch(&missing).await?) },
+    )
+    .await
+}
+
+/// One `MGET` for every id's `anchor:data` key, then a single `load` of the
+/// misses (deduplicated), whose results are cached and tagged like
+/// `require_anchor_cached` would. `load` is not called when everything hits.
+pub async fn anchors_batch_through_cache<F, Fut>(
+    cache: &RedisCache,
+    ttl: usize,
+    ids: &[Uuid],
+    load: F,
+) -> ApiResult<HashMap<Uuid, Anchor>>
+where
+    F: FnOnce(Vec<Uuid>) -> Fut,
+    Fut: Future<Output = ApiResult<HashMap<Uuid, Anchor>>>,
+{
+    let keys: Vec<String> = ids
+        .iter()
+        .map(|id| CacheKey::anchor_data(&id.to_string()))
+        .collect();
+    let key_refs: Vec<&str> = keys.iter().map(String::as_str).collect();
+    let cached = cache.mget::<Anchor>(&key_refs).await.unwrap_or_else(|e| {
+        tracing::warn!("Cache mget failed for {} anchors: {}", ids.len(), e);
+        ids.iter().map(|_| None).collect()
+    });
+
+    let mut anchors = HashMap::with_capacity(ids.len());
+    let mut missing = Vec::new();
+    for (id, slot) in ids.iter().zip(cached) {
+        match slot {
+            Some(anchor) => {
+                anchors.insert(*id, anchor);
+            }
+            None => missing.push(*id),
+        }
+    }
+    missing.sort_unstable();
+    missing.dedup();
+    if missing.is_empty() {
+        return Ok(anchors);
+    }
+
+    for (id, anchor) in load(missing).await? {
+        let id_str = id.to_string();
+        let key = CacheKey::anchor_data(&id_str);
+        if let Err(e) = cache
+            .set_with_jitter(&key, &anchor, ttl, TTL_JITTER_PCT)
+            .await
+        {
+            tracing::warn!("Failed to cache {}: {}", key, e);
+        }
+        if let Err(e) = cache.tag(&key, &[&CacheKey::anchor_tag(&id_str)]).await {
+            tracing::warn!("Failed to tag {}: {}", key, e);
+        }
+        anchors.insert(id, anchor);
+    }
+
+    Ok(anchors)
+}
+
+/// POST /api/anchors - Create a new anchor and invalidate anchor caches.
+/// Honors `Idempotency-Key`.
+#[utoipa::path(
+    post,
+    path = "/api/anchors",
+    tag = "anchors",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Repeats within 24 hours return the first result; a different request under the same key gets 409")),
+    request_body = CreateAnchorRequest,
+    responses((status = 200, description = "The created anchor", body = Anchor), ApiError),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_anchor_cached(
+    State(app_state): State<AppState>,
+    idempotency: IdempotencyKey,
+    Json(req): Json<CreateAnchorRequest>,
+) -> ApiResult<Json<Anchor>> {
+    if req.name.is_empty() {
+        return Err(ApiError::BadRequest("Name cannot be empty".to_string()));
+    }
+
+    if req.stellar_account.is_empty() {
+        return Err(ApiError::BadRequest(
+            "Stellar account cannot be empty".to_string(),
+        ));
+    }
+    validate_stellar_account(&req.stellar_account)?;
+
+    let fingerprint = request_fingerprint("/api/anchors", &req);
+    let anchor = idempotent(
+        &app_state.cache,
+        "anchor",
+        idempotency,
+        fingerprint,
+        || async {
+            let anchor = app_state.db.create_anchor(req).await?;
+
+            // Also drops cached 404s for this anchor's id and account
+            if let Err(e) = app_state.cache_invalidation.invalidate_anchors().await {
+                tracing::warn!("Failed to invalidate anchor caches: {}", e);
+            }
+            if let Err(e) = app_state.cache_invalidation.invalidate_dashboard().await {
+                tracing::warn!("Failed to invalidate dashboard caches: {}", e);
+            }
+
+            broadcast_anchor_update(&app_state.ws_state, &anchor);
+
+            Ok(anchor)
+        },
+    )
+    .await?;
+
+    Ok(Json(anchor))
+}
+
+/// PUT /api/anchors - Create or update the anchor for a Stellar account and
+/// invalidate anchor caches. Answers `201 Created` for a new anchor, `200 OK`
+/// for an updated one.
+#[utoipa::path(
+    put,
+    path = "/api/anchors",
+    tag = "anchors",
+    request_body = CreateAnchorRequest,
+    responses(
+        (status = 200, description = "The updated anchor", body = Anchor),
+        (status = 201, description = "The created anchor", body = Anchor),
+        ApiError
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn upsert_anchor_cached(
+    State(app_state): State<AppState>,
+    Json(req): Json<CreateAnchorRequest>,
+) -> ApiResult<(StatusCode, Json<Anchor>)> {
+    if req.name.is_empty() {
+        return Err(ApiError::BadRequest("Name cannot be empty".to_string()));
+    }
+
+    if req.stellar_account.is_empty() {
+        return Err(ApiError::BadRequest(
+            "Stellar account cannot be empty".to_string(),
+        ));
+    }
+    validate_stellar_account(&req.stellar_account)?;
+
+    let (anchor, upserted) = app_state.db.upsert_anchor(req).await?;
+
+    if let Err(e) = app_state.cache_invalidation.invalidate_anchors().await {
+        tracing::warn!("Failed to invalidate anchor caches: {}", e);
+    }
+    if let Err(e) = app_state.cache_invalidation.invalidate_dashboard().await {
+        tracing::warn!("Failed to invalidate dashboard caches: {}", e);
+    }
+
+    broadcast_anchor_update(&app_state.ws_state, &anchor);
+
+    let status = match upserted {
+        Upserted::Created => StatusCode::CREATED,
+        Upserted::Updated => StatusCode::OK,
+    };
+    Ok((status, Json(anchor)))
+}
+
+/// Overwrite one anchor's `anchor:data`, `anchor:by_account` and `anchor:detail`
+/// entries with a freshly written row instead of dropping them, so the next read
+/// is a hit, and drop the `asset:<code>:anchors` lists it appears in. The detail
+/// is rebuilt around `anchor` from its assets and metrics history; the
+/// `anchor:assets` pages are left alone, since metrics don't touch them.
+///
+/// Runs under the anchor's write lock and leaves the entries alone when they
+/// already hold this version or a newer one, so concurrent updates can't leave
+/// the older row cached. `Ok(false)` when another replica holds the lock and
+/// nothing was written; the caller should invalidate instead.
+async fn write_through_anchor(app_state: &AppState, id: Uuid, anchor: &Anchor) -> ApiResult<bool> {
+    let detail = app_state.db.anchor_detail_for(id, anchor.clone()).await?;
+    let lock_key = CacheKey::anchor_write_lock(&anchor.id);
+    let written = app_state
+        .cache
+        .with_write_lock(&lock_key, ANCHOR_WRITE_LOCK_TTL_SECS, || {
+            write_anchor_entries(app_state, anchor, &detail)
+        })
+        .await?;
+
+    Ok(written.is_some())
+}
+
+async fn write_anchor_entries(
+    app_state: &AppState,
+    anchor: &Anchor,
+    detail: &AnchorDetailResponse,
+) -> ApiResult<()> {
+    let cache = &app_state.cache;
+    let config = &app_state.cache_config;
+    let tag = CacheKey::anchor_tag(&anchor.id);
+
+    for asset in &detail.assets {
+        cache
+            .delete(&CacheKey::asset_anchors(&asset.asset_code))
+            .await?;
+    }
+
+    let data_key = CacheKey::anchor_data(&anchor.id);
+    let cached: Option<Anchor> = cache.get(&data_key).await?;
+    if cached.is_some_and(|cached| cached.version >= anchor.version) {
+        tracing::debug!(
+            "Anchor {} v{} is already cached at that version or newer",
+            anchor.id,
+            anchor.version
+        );
+        return Ok(());
+    }
+
+    cache
+        .set_with_jitter(&data_key, anchor, config.ttl("anchor.data"), TTL_JITTER_PCT)
+        .await?;
+    let by_account_key = CacheKey::anchor_by_account(&anchor.stellar_account);
+    cache
+        .set_with_jitter(
+            &by_account_key,
+            anchor,
+            config.ttl("anchor.by_account"),
+            TTL_JITTER_PCT,
+        )
+        .await?;
+    let detail_key = CacheKey::anchor_detail(&anchor.id);
+    cache
+        .set_with_jitter(
+            &detail_key,
+            detail,
+            config.ttl("anchor.detail"),
+            TTL_JITTER_PCT,
+        )
+        .await?;
+    // Tagged again so a later invalidation of the anchor still finds them
+    for key in [&data_key, &by_account_key, &detail_key] {
+        cache.tag(key, &[&tag]).await?;
+    }
+
+    Ok(())
+}
+
+/// PUT /api/anchors/:id/metrics - Update anchor metrics, writing the anchor's own
+/// entries through to the cache and invalidating the list pages it appears on
+#[utoipa::path(
+    put,
+    path = "/api/anchors/{id}/metrics",
+    tag = "anchors",
+    params(("id" = Uuid, Path, description = "Anchor id")),
+    request_body = UpdateMetricsRequest,
+    responses((status = 200, description = "The updated anchor", body = Anchor), ApiError),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_anchor_metrics_cached(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateMetricsRequest>,
+) -> ApiResult<Json<Anchor>> {
+    validate_metrics(&req)?;
+    require_anchor_cached(&app_state, id).await?;
+
+    let anchor = app_state
+        .db
+        .update_anchor_metrics_versioned(&AnchorMetricsUpdate {
+            anchor_id: id,
+            total_transactions: req.total_transactions,
+            successful_transactions: req.successful_transactions,
+            failed_transactions: req.failed_transactions,
+            avg_settlement_time_ms: req.avg_settlement_time_ms,
+            volume_usd: req.volume_usd,
+            expected_version: req.expected_version,
+        })
+        .await?;
+
+    let not_written = match write_through_anchor(&app_state, id, &anchor).await {
+        Ok(true) => None,
+        Ok(false) => Some("another replica holds its write lock".to_string()),
+        Err(e) => Some(format!("{:?}", e)),
+    };
+    match not_written {
+        None => app_state
+            .cache_invalidation
+            .publish_anchor_updated(&anchor.id),
+        Some(reason) => {
+            // Whatever was or wasn't written, dropping the entries can't leave them stale
+            tracing::warn!(
+                "Failed to write anchor {} through to the cache, invalidating instead: {}",
+                anchor.id,
+                reason
+            );
+            if let Err(e) = app_state
+                .cache_invalidation
+                .invalidate_anchor(&anchor.id)
+                .await
+            {
+                tracing::warn!("Failed to invalidate anchor {} caches: {}", anchor.id, e);
+            }
+        }
+    }
+    if let Err(e) = app_state.cache_invalidation.invalidate_anchor_lists().await {
+        tracing::warn!("Failed to invalidate anchor list caches: {}", e);
+    }
+    if let Err(e) = app_state.cache_invalidation.invalidate_dashboard().await {
+        tracing::warn!("Failed to invalidate dashboard caches: {}", e);
+    }
+
+    broadcast_anchor_update(&app_state.ws_state, &anchor);
+
+    Ok(Json(anchor))
+}
+
+/// Most anchors a single batch update may carry
+const MAX_ANCHOR_METRICS_BATCH: usize = 1000;
+
+/// Outcome of one item in a batch metrics update
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct BatchUpdateMetricsResult {
+    pub id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<Anchor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct BatchUpdateMetricsResponse {
+    pub updated: usize,
+    pub failed: usize,
+    pub results: Vec<BatchUpdateMetricsResult>,
+}
+
+/// PUT /api/anchors/metrics:batch - Update many anchors' metrics in one
+/// transaction, reporting failures per item and invalidating caches once
+#[utoipa::path(
+    put,
+    path = "/api/anchors/metrics:batch",
+    tag = "anchors",
+    request_body = Vec<BatchUpdateMetricsItem>,
+    responses(
+        (status = 200, description = "Per-item outcome of the batch", body = BatchUpdateMetricsResponse),
+        ApiError
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_anchor_metrics_batch_cached(
+    State(app_state): State<AppState>,
+    Json(items): Json<Vec<BatchUpdateMetricsItem>>,
+) -> ApiResult<Json<BatchUpdateMetricsResponse>> {
+    if items.len() > MAX_ANCHOR_METRICS_BATCH {
+        return Err(ApiError::BadRequest(format!(
+            "Batch of {} updates exceeds the limit of {}",
+            items.len(),
+            MAX_ANCHOR_METRICS_BATCH
+        )));
+    }
+    for item in &items {
+        validate_metrics(&item.metrics).map_err(|e| match e {
+            ApiError::BadRequest(message) => {
+                ApiError::BadRequest(format!("Update for anchor {}: {}", item.id, message))
+            }
+            other => other,
+        })?;
+    }
+
+    let updates: Vec<AnchorMetricsUpdate> = items
+        .into_iter()
+        .map(|item| AnchorMetricsUpdate {
+            anchor_id: item.id,
+            total_transactions: item.metrics.total_transactions,
+            successful_transactions: item.metrics.successful_transactions,
+            failed_transactions: item.metrics.failed_transactions,
+            avg_settlement_time_ms: item.metrics.avg_settlement_time_ms,
+            volume_usd: item.metrics.volume_usd,
+            expected_version: item.metrics.expected_version,
+        })
+        .collect();
+
+    let outcomes = app_state.db.update_anchor_metrics_batch(&updates).await?;
+
+    let results: Vec<BatchUpdateMetricsResult> = updates
+        .iter()
+        .zip(outcomes)
+        .map(|(update, outcome)| {
+            let id = update.anchor_id;
+            match outcome {
+                Ok(Some(anchor)) => BatchUpdateMetricsResult {
+                    id,
+                    anchor: Some(anchor),
+                    error: None,
+                },
+                Ok(None) => BatchUpdateMetricsResult {
+                    id,
+                    anchor: None,
+                    error: Some(format!("Anchor with id {} not found", id)),
+                },
+                Err(e) => BatchUpdateMetricsResult {
+                    id,
+                    anchor: None,
+                    error: Some(e.to_string()),
+                },
+            }
+        })
+        .collect();
+    let updated = results.iter().filter(|r| r.anchor.is_some()).count();
+
+    if updated > 0 {
+        if let Err(e) = app_state.cache_invalidation.invalidate_anchors().await {
+            tracing::warn!("Failed to invalidate anchor caches: {}", e);
+        }
+        if let Err(e) = app_state.cache_invalidation.invalidate_dashboard().await {
+            tracing::warn!("Failed to invalidate dashboard caches: {}", e);
+        }
+    }
+
+    for anchor in results.iter().filter_map(|r| r.anchor.as_ref()) {
+        broadcast_anchor_update(&app_state.ws_state, anchor);
+    }
+
+    Ok(Json(BatchUpdateMetricsResponse {
+        updated,
+        failed: results.len() - updated,
+        results,
+    }))
+}
+
+/// DELETE /api/anchors/:id - Delete an anchor and every cache entry it appears in.
+/// An anchor that still has issued assets is kept (409 Conflict) unless
+/// `?force=true` is given, in which case its assets are deleted with it.
+#[utoipa::path(
+    delete,
+    path = "/api/anchors/{id}",
+    tag = "anchors",
+    params(("id" = Uuid, Path, description = "Anchor id"), DeleteAnchorQuery),
+    responses((status = 204, description = "The anchor was deleted"), ApiError),
+    security(("bearer_auth" = []))
+)]
+pub async fn delete_anchor_cached(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<DeleteAnchorQuery>,
+) -> ApiResult<StatusCode> {
+    if app_state.db.get_anchor_by_id(id).await?.is_none() {
+        return Err(ApiError::NotFound(format!(
+            "Anchor with id {} not found",
+            id
+        )));
+    }
+
+    let assets = app_state.db.count_assets_by_anchor(id).await?;
+    if assets > 0 && !params.force {
+        return Err(ApiError::Conflict(format!(
+            "Anchor {} still has {} issued asset(s); pass force=true to delete them too",
+            id, assets
+        )));
+    }
+
+    // Read before the delete cascades them away, to drop their metrics after
+    let deleted_assets = if assets > 0 {
+        app_state.db.get_all_assets_by_anchor(id).await?
+    } else {
+        Vec::new()
+    };
+
+    if !app_state.db.delete_anchor(id).await? {
+        return Err(ApiError::NotFound(format!(
+            "Anchor with id {} not found",
+            id
+        )));
+    }
+
+    for asset in &deleted_assets {
+        if let Err(e) = app_state
+            .cache_invalidation
+            .invalidate_asset_metrics(&asset.asset_code, &asset.asset_issuer)
+            .await
+        {
+            tracing::warn!(
+                "Failed to invalidate metrics for asset {}:{}: {}",
+                asset.asset_code,
+                asset.asset_issuer,
+                e
+            );
+        }
+    }
+
+    let id = id.to_string();
+    if let Err(e) = app_state.cache_invalidation.invalidate_anchor(&id).await {
+        tracing::warn!("Failed to invalidate anchor {} caches: {}", id, e);
+    }
+    if let Err(e) = app_state.cache_invalidation.invalidate_anchor_lists().await {
+        tracing::warn!("Failed to invalidate anchor list caches: {}", e);
+    }
+    if let Err(e) = app_state.cache_invalidation.invalidate_dashboard().await {
+        tracing::warn!("Failed to invalidate dashboard caches: {}", e);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/anchors/:id/deactivate - Hide an anchor from default listings,
+/// keeping its history
+#[utoipa::path(
+    post,
+    path = "/api/anchors/{id}/deactivate",
+    tag = "anchors",
+    params(("id" = Uuid, Path, description = "Anchor id")),
+    responses((status = 200, description = "The deactivated anchor", body = Anchor), ApiError),
+    security(("bearer_auth" = []))
+)]
+pub async fn deactivate_anchor_cached(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Anchor>> {
+    let anchor = app_state.db.deactivate_anchor(id).await?;
+    anchor_activity_changed(&app_state, id, anchor).await
+}
+
+/// POST /api/anchors/:id/reactivate - Return a deactivated anchor to default listings
+#[utoipa::path(
+    post,
+    path = "/api/anchors/{id}/reactivate",
+    tag = "anchors",
+    params(("id" = Uuid, Path, description = "Anchor id")),
+    responses((status = 200, description = "The reactivated anchor", body = Anchor), ApiError),
+    security(("bearer_auth" = []))
+)]
+pub async fn reactivate_anchor_cached(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Anchor>> {
+    let anchor = app_state.db.reactivate_anchor(id).await?;
+    anchor_activity_changed(&app_state, id, anchor).await
+}
+
+/// Shared tail of deactivate/reactivate: 404 for an unknown id, otherwise drop
+/// the anchor's entries and every list page it may have moved in or out of
+async fn anchor_activity_changed(
+    app_state: &AppState,
+    id: Uuid,
+    anchor: Option<Anchor>,
+) -> ApiResult<Json<Anchor>> {
+    let Some(anchor) = anchor else {
+        return Err(ApiError::NotFound(format!(
+            "Anchor with id {} not found",
+            id
+        )));
+    };
+
+    if let Err(e) = app_state
+        .cache_invalidation
+        .invalidate_anchor(&anchor.id)
+        .await
+    {
+        tracing::warn!("Failed to invalidate anchor {} caches: {}", anchor.id, e);
+    }
+    if let Err(e) = app_state.cache_invalidation.invalidate_anchor_lists().await {
+        tracing::warn!("Failed to invalidate anchor list caches: {}", e);
+    }
+
+    broadcast_anchor_update(&app_state.ws_state, &anchor);
+
+    Ok(Json(anchor))
+}
+
+/// One page of an anchor's assets through its `anchor:assets` key, 404 if the
+/// anchor doesn't exist. Pages outside `CacheConfig::caches_list_page` are
+/// loaded without the cache.
+pub(crate) async fn cached_anchor_assets<C: Cache>(
+    app_state: &AppState<C>,
+    id: Uuid,
+    limit: i64,
+    offset: i64,
+    bypass: CacheBypass,
+) -> ApiResult<(Vec<Asset>, CacheStatus)> {
+    let cache_key = CacheKey::anchor_assets(&id.to_string(), limit, offset);
+    let tag = CacheKey::anchor_tag(&id.to_string());
+    read_through_page(
+        &*app_state.cache,
+        bypass,
+        app_state.cache_config.caches_list_page(limit, offset),
+        &cache_key,
+        app_state.cache_config.ttl("anchor.assets"),
+        &[&tag],
+        || async {
+            require_anchor_cached(app_state, id).await?;
+            let assets = app_state.db.get_assets_by_anchor(id, limit, offset).await?;
+            Ok::<_, ApiError>(assets)
+        },
+    )
+    .await
+}
+
+/// GET /api/anchors/:id/assets - Get a page of an anchor's assets (cached)
+#[utoipa::path(
+    get,
+    path = "/api/anchors/{id}/assets",
+    tag = "anchors",
+    params(("id" = Uuid, Path, description = "Anchor id"), AnchorAssetsQuery, ("no_cache" = Option<bool>, Query, description = "Skip the cache and reload, when `ALLOW_CACHE_BYPASS` is on")),
+    responses(
+        (status = 200, description = "A page of the assets the anchor issues", body = Vec<Asset>),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        ApiError
+    )
+)]
+pub async fn get_anchor_assets_cached<C: Cache>(
+    State(app_state): State<AppState<C>>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<AnchorAssetsQuery>,
+    headers: HeaderMap,
+    bypass: CacheBypass,
+) -> ApiResult<CachedJson<Vec<Asset>>> {
+    let page = params.page(app_state.cache_config.max_list_limit)?;
+    let (assets, status) =
+        cached_anchor_assets(&app_state, id, page.limit, page.offset, bypass).await?;
+
+    Ok(CachedJson::new(
+        assets,
+        app_state.cache_config.ttl("anchor.assets"),
+        &headers,
+    )
+    .with_cache_age(status)
+    .with_cache_status(debug_status(&app_state, status))
+    .with_limit_clamped(page.clamped_limit()))
+}
+
+/// POST /api/anchors/:id/assets - Add asset to anchor and invalidate its caches.
+/// Honors `Idempotency-Key`.
+#[utoipa::path(
+    post,
+    path = "/api/anchors/{id}/assets",
+    tag = "anchors",
+    params(("id" = Uuid, Path, description = "Anchor id"), ("Idempotency-Key" = Option<String>, Header, description = "Repeats within 24 hours return the first result; a different request under the same key gets 409")),
+    request_body = CreateAssetRequest,
+    responses((status = 200, description = "The created asset", body = Asset), ApiError),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_anchor_asset_cached(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    idempotency: IdempotencyKey,
+    Json(req): Json<CreateAssetRequest>,
+) -> ApiResult<Json<Asset>> {
+    require_anchor_cached(&app_state, id).await?;
+    validate_stellar_account(&req.asset_issuer)?;
+
+    let fingerprint = request_fingerprint(&format!("/api/anchors/{}/assets", id), &req);
+    let asset = idempotent(
+        &app_state.cache,
+        "asset",
+        idempotency,
+        fingerprint,
+        || async {
+            let asset = app_state
+                .db
+                .create_asset(id, req.asset_code, req.asset_issuer)
+                .await?;
+
+            let anchor_keys = CacheKey::all_keys_for_anchor(&id.to_string());
+            if let Err(e) = app_state.cache.delete_many(&anchor_keys).await {
+                tracing::warn!("Failed to invalidate anchor {} caches: {}", id, e);
+            }
+            let asset_pages = CacheKey::anchor_assets_pattern(&id.to_string());
+            if let Err(e) = app_state.cache.delete_pattern(&asset_pages).await {
+                tracing::warn!("Failed to invalidate anchor {} asset pages: {}", id, e);
+            }
+            if let Err(e) = app_state
+                .cache_invalidation
+                .invalidate_asset(&asset.asset_code)
+                .await
+            {
+                tracing::warn!(
+                    "Failed to invalidate anchors for asset {}: {}",
+                    asset.asset_code,
+                    e
+                );
+            }
+            if let Err(e) = app_state
+                .cache_invalidation
+                .invalidate_asset_metrics(&asset.asset_code, &asset.asset_issuer)
+                .await
+            {
+                tracing::warn!(
+                    "Failed to invalidate metrics for asset {}:{}: {}",
+                    asset.asset_code,
+                    asset.asset_issuer,
+                    e
+                );
+            }
+
+            Ok(asset)
+        },
+    )
+    .await?;
+
+    Ok(Json(asset))
+}
+
+/// GET /api/assets/:code/anchors - Anchors issuing an asset code (cached).
+///
+/// Stellar asset codes are case-sensitive, but lookups here are not: `usdc`
+/// returns the issuers of `USDC`, and codes differing only in case are listed
+/// together. Each cached list is tagged with its anchors so updating or
+/// deleting one of them drops it.
+#[utoipa::path(
+    get,
+    path = "/api/assets/{code}/anchors",
+    tag = "anchors",
+    params(("code" = String, Path, description = "Asset code, matched case-insensitively"), ("no_cache" = Option<bool>, Query, description = "Skip the cache and reload, when `ALLOW_CACHE_BYPASS` is on")),
+    responses(
+        (status = 200, description = "Anchors issuing the asset", body = Vec<Anchor>),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        ApiError
+    )
+)]
+pub async fn get_anchors_by_asset_cached(
+    State(app_state): State<AppState>,
+    Path(code): Path<String>,
+    headers: HeaderMap,
+    bypass: CacheBypass,
+) -> ApiResult<CachedJson<Vec<Anchor>>> {
+    validate_asset_code(&code)?;
+
+    let ttl = app_state.cache_config.ttl("asset.anchors");
+    let cache_key = CacheKey::asset_anchors(&code);
+    let (anchors, status) =
+        read_through(&*app_state.cache, bypass, &cache_key, ttl, &[], || async {
+            let anchors = app_state.db.find_anchors_by_asset_code(&code).await?;
+            let tags: Vec<String> = anchors
+                .iter()
+                .map(|anchor| CacheKey::anchor_tag(&anchor.id))
+                .collect();
+            let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+            app_state.cache.tag(&cache_key, &tags).await?;
+            Ok::<_, ApiError>(anchors)
+        })
+        .await?;
+
+    Ok(CachedJson::new(anchors, ttl, &headers)
+        .with_cache_age(status)
+        .with_cache_status(debug_status(&app_state, status)))
+}
+
+/// GET /api/assets/:code/:issuer/metrics - Success rate, volume and latency
+/// over an asset's payments (cached). Unknown assets 404, and the 404 is
+/// remembered until the asset is created.
+#[utoipa::path(
+    get,
+    path = "/api/assets/{code}/{issuer}/metrics",
+    tag = "anchors",
+    params(("code" = String, Path, description = "Asset code"), ("issuer" = String, Path, description = "Issuing Stellar account"), ("no_cache" = Option<bool>, Query, description = "Skip the cache and reload, when `ALLOW_CACHE_BYPASS` is on")),
+    responses(
+        (status = 200, description = "The asset's metrics", body = AssetMetrics),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        ApiError
+    )
+)]
+pub async fn get_asset_metrics_cached(
+    State(app_state): State<AppState>,
+    Path((code, issuer)): Path<(String, String)>,
+    headers: HeaderMap,
+    bypass: CacheBypass,
+) -> ApiResult<CachedJson<AssetMetrics>> {
+    validate_asset_code(&code)?;
+    validate_stellar_account(&issuer)?;
+
+    // Payments arrive with ingestion, so this ages like corridor metrics
+    let ttl = app_state.cache_config.ttl("asset.metrics");
+    let cache_key = CacheKey::asset_metrics(&code, &issuer);
+    let (metrics, status) = read_through_existing(
+        &*app_state.cache,
+        &app_state.cache_config,
+        bypass,
+        &cache_key,
+        ttl,
+        &[],
+        || async {
+            app_state
+                .db
+                .get_asset_metrics(&code, &issuer)
+                .await?
+                .ok_or_else(|| ApiError::NotFound(format!("Asset {}:{} not found", code, issuer)))
+        },
+    )
+    .await?;
+
+    Ok(CachedJson::new(metrics, ttl, &headers)
+        .with_cache_age(status)
+        .with_cache_status(debug_status(&app_state, status)))
+}
+
+/// GET /api/assets/:code/corridors?issuer= - Corridors with the asset as
+/// source or destination (cached). The code matches case-insensitively; pass
+/// `issuer` to match one asset rather than every asset sharing the code.
+#[utoipa::path(
+    get,
+    path = "/api/assets/{code}/corridors",
+    tag = "corridors",
+    params(("code" = String, Path, description = "Asset code, matched case-insensitively"), AssetCorridorsQuery, ("no_cache" = Option<bool>, Query, description = "Skip the cache and reload, when `ALLOW_CACHE_BYPASS` is on")),
+    responses(
+        (status = 200, description = "Corridors with the asset on either side", body = Vec<Corridor>),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        ApiError
+    )
+)]
+pub async fn get_corridors_by_asset_cached(
+    State(app_state): State<AppState>,
+    Path(code): Path<String>,
+    Query(params): Query<AssetCorridorsQuery>,
+    headers: HeaderMap,
+    bypass: CacheBypass,
+) -> ApiResult<CachedJson<Vec<Corridor>>> {
+    validate_asset_code(&code)?;
+    let issuer = params.issuer();
+
+    let ttl = app_state.cache_config.ttl("asset.corridors");
+    let cache_key = CacheKey::asset_corridors(&code, issuer);
+    let (corridors, status) =
+        read_through(&*app_state.cache, bypass, &cache_key, ttl, &[], || async {
+            Ok::<_, ApiError>(app_state.db.list_corridors_by_asset(&code, issuer).await?)
+        })
+        .await?;
+
+    Ok(CachedJson::new(corridors, ttl, &headers)
+        .with_cache_age(status)
+        .with_cache_status(debug_status(&app_state, status)))
+}
+
+fn validate_asset_code(code: &str) -> ApiResult<()> {
+    if code.is_empty() || code.len() > 12 || !code.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(ApiError::BadRequest(format!(
+            "Invalid asset code {:?}: expected 1-12 letters or digits",
+            code
+        )));
+    }
+    Ok(())
+}
+
+/// GET /api/v2/corridors - List corridors (cached)
+#[utoipa::path(
+    get,
+    path = "/api/v2/corridors",
+    tag = "corridors",
+    params(ListCorridorsQuery, ("no_cache" = Option<bool>, Query, description = "Skip the cache and reload, when `ALLOW_CACHE_BYPASS` is on")),
+    responses(
+        (status = 200, description = "A page of corridors", body = ListCorridorsResponse),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        ApiError
+    )
+)]
+pub async fn list_corridors_cached(
+    State(app_state): State<AppState>,
+    Query(params): Query<ListCorridorsQuery>,
+    headers: HeaderMap,
+    bypass: CacheBypass,
+) -> ApiResult<CachedJson<ListCorridorsResponse>> {
+    let ttl = app_state.cache_config.ttl("corridor.list");
+    let page = params.page(app_state.cache_config.max_list_limit)?;
+    let sort = params.sort()?;
+    let filters = params.filters()?;
+    let (response, status) = cached_corridor_page(
+        &app_state.db,
+        &app_state.cache,
+        &app_state.cache_config,
+        bypass,
+        page.limit,
+        page.offset,
+        sort.as_ref(),
+        &filters,
+    )
+    .await?;
+
+    let total = response.total;
+    Ok(CachedJson::new(response, ttl, &headers)
+        .with_cache_age(status)
+        .with_cache_status(debug_status(&app_state, status))
+        .with_limit_clamped(page.clamped_limit())
+        .with_total_count(total))
+}
+
+/// One corridor's detail through the `corridor:detail` key. Busy corridors
+/// expire quickly and quiet ones linger; see `CacheConfig::corridor_ttl`.
+pub(crate) async fn cached_corridor_detail(
+    app_state: &AppState,
+    id: Uuid,
+    bypass: CacheBypass,
+) -> ApiResult<(CorridorDetailResponse, CacheStatus)> {
+    let config = &app_state.cache_config;
+    let cache_key = CacheKey::corridor_detail(&id.to_string());
+    read_through_with_ttl_fn(
+        &*app_state.cache,
+        bypass,
+        &cache_key,
+        |detail: &CorridorDetailResponse| config.corridor_ttl(detail.daily_transactions()),
+        &[],
+        || async {
+            app_state
+                .db
+                .get_corridor_detail(id)
+                .await?
+                .ok_or_else(|| ApiError::NotFound(format!("Corridor with id {} not found", id)))
+        },
+    )
+    .await
+}
+
+/// GET /api/corridors/:id - Corridor metrics and recent totals (cached). The
+/// route is shared with the per-key aggregate view, so a segment that isn't a
+/// UUID is treated as a corridor key and served by `api::corridors`.
+#[utoipa::path(
+    get,
+    path = "/api/corridors/{corridor}",
+    tag = "corridors",
+    params(("corridor" = String, Path, description = "Corridor id"), ("no_cache" = Option<bool>, Query, description = "Skip the cache and reload, when `ALLOW_CACHE_BYPASS` is on")),
+    responses(
+        (status = 200, description = "The corridor with its recent totals", body = CorridorDetailResponse),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        ApiError
+    )
+)]
+pub async fn get_corridor_cached(
+    State(app_state): State<AppState>,
+    Path(corridor): Path<String>,
+    headers: HeaderMap,
+    bypass: CacheBypass,
+) -> ApiResult<Response> {
+    let Ok(id) = Uuid::parse_str(&corridor) else {
+        return crate::api::corridors::get_corridor_detail(State(app_state), Path(corridor))
+            .await
+            .map(IntoResponse::into_response);
+    };
+
+    let (detail, status) = cached_corridor_detail(&app_state, id, bypass).await?;
+
+    let ttl = app_state
+        .cache_config
+        .corridor_ttl(detail.daily_transactions());
+    Ok(
+        CachedJson::<CorridorDetailResponse>::new(detail, ttl, &headers)
+            .with_cache_age(status)
+            .with_cache_status(debug_status(&app_state, status))
+            .into_response(),
+    )
+}
+
+/// GET /api/corridors/:id/history?hours=24 - Metrics snapshots from the last
+/// `hours`, oldest first (cached)
+#[utoipa::path(
+    get,
+    path = "/api/corridors/{id}/history",
+    tag = "corridors",
+    params(("id" = Uuid, Path, description = "Corridor id"), CorridorHistoryQuery, ("no_cache" = Option<bool>, Query, description = "Skip the cache and reload, when `ALLOW_CACHE_BYPASS` is on")),
+    responses(
+        (status = 200, description = "Metrics snapshots, oldest first, and their volume trend", body = CorridorHistoryResponse),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        ApiError
+    )
+)]
+pub async fn get_corridor_history_cached(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<CorridorHistoryQuery>,
+    headers: HeaderMap,
+    bypass: CacheBypass,
+) -> ApiResult<CachedJson<CorridorHistoryResponse>> {
+    let hours = params.hours()?;
+    let ttl = app_state.cache_config.ttl("corridor.history");
+    let cache_key = CacheKey::corridor_metrics_history(&id.to_string(), hours);
+    let (history, status) =
+        read_through(&*app_state.cache, bypass, &cache_key, ttl, &[], || async {
+            if app_state.db.get_corridor_by_id(id).await?.is_none() {
+                return Err(ApiError::NotFound(format!(
+                    "Corridor with id {} not found",
+                    id
+                )));
+            }
+            let since = chrono::Utc::now() - chrono::Duration::hours(hours);
+            Ok(app_state.db.get_corridor_metrics_history(id, since).await?)
+        })
+        .await?;
+
+    // Derived from the cached series on the way out, so it's always consistent with it
+    let response = CorridorHistoryResponse {
+        trend: volume_trend(&history),
+        snapshots: history,
+    };
+    Ok(CachedJson::new(response, ttl, &headers)
+        .with_cache_age(status)
+        .with_cache_status(debug_status(&app_state, status)))
+}
+
+/// POST /api/corridors - Create a new corridor and invalidate corridor caches.
+/// Honors `Idempotency-Key`.
+#[utoipa::path(
+    post,
+    path = "/api/corridors",
+    tag = "corridors",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Repeats within 24 hours return the first result; a different request under the same key gets 409")),
+    request_body = CreateCorridorRequest,
+    responses((status = 200, description = "The created corridor", body = Corridor), ApiError),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_corridor_cached(
+    State(app_state): State<AppState>,
+    idempotency: IdempotencyKey,
+    Json(req): Json<CreateCorridorRequest>,
+) -> ApiResult<Json<Corridor>> {
+    validate_create_corridor(&req)?;
+
+    let fingerprint = request_fingerprint("/api/corridors", &req);
+    let corridor = idempotent(
+        &app_state.cache,
+        "corridor",
+        idempotency,
+        fingerprint,
+        || async {
+            let corridor = app_state.db.create_corridor(req).await?;
+
+            // Re-creating an existing corridor upserts it, so this also drops its
+            // cached detail along with the list pages
+            if let Err(e) = app_state.cache_invalidation.invalidate_corridors().await {
+                tracing::warn!("Failed to invalidate corridor caches: {}", e);
+            }
+            if let Err(e) = app_state
+                .cache_invalidation
+                .invalidate_corridor_assets(&corridor)
+                .await
+            {
+                tracing::warn!("Failed to invalidate asset corridor caches: {}", e);
+            }
+            if let Err(e) = app_state.cache_invalidation.invalidate_dashboard().await {
+                tracing::warn!("Failed to invalidate dashboard caches: {}", e);
+            }
+
+            broadcast_corridor_update(&app_state.ws_state, &corridor);
+
+            Ok(corridor)
+        },
+    )
+    .await?;
+
+    Ok(Json(corridor))
+}
+
+/// Updated corridor plus any sharp health changes against the previous run
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct CorridorMetricsUpdateResponse {
+    #[serde(flatten)]
+    pub corridor: Corridor,
+    pub warnings: Vec<Anomaly>,
+}
+
+/// The numbers a snapshot recorded, laid over `current` so the two compare
+fn snapshot_metrics(
+    snapshot: CorridorMetricsSnapshot,
+    current: &CorridorMetrics,
+) -> CorridorMetrics {
+    CorridorMetrics {
+        date: snapshot.recorded_at,
+        total_transactions: snapshot.total_transactions,
+        successful_transactions: snapshot.successful_transactions,
+        failed_transactions: snapshot.failed_transactions,
+        success_rate: snapshot.success_rate,
+        volume_weighted_success_rate: snapshot.volume_weighted_success_rate.unwrap_or_default(),
+        volume_usd: snapshot.volume_usd,
+        median_settlement_latency_ms: snapshot.median_settlement_latency_ms,
+        p95_settlement_latency_ms: snapshot.p95_settlement_latency_ms,
+        p99_settlement_latency_ms: snapshot.p99_settlement_latency_ms,
+        ..current.clone()
+    }
+}
+
+/// PUT /api/corridors/:id/metrics-from-transactions - Recompute metrics and invalidate corridor caches
+#[utoipa::path(
+    put,
+    path = "/api/corridors/{id}/metrics-from-transactions",
+    tag = "corridors",
+    params(("id" = Uuid, Path, description = "Corridor id")),
+    request_body = UpdateCorridorMetricsFromTxns,
+    responses(
+        (status = 200, description = "The corridor and any anomalies", body = CorridorMetricsUpdateResponse),
+        ApiError
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_corridor_metrics_from_transactions_cached(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateCorridorMetricsFromTxns>,
+) -> ApiResult<Json<CorridorMetricsUpdateResponse>> {
+    let Some(existing) = app_state.db.get_corridor_by_id(id).await? else {
+        return Err(ApiError::NotFound(format!(
+            "Corridor with id {} not found",
+            id
+        )));
+    };
+    let txs: Vec<CorridorTransaction> = req
+        .transactions
+        .into_iter()
+        .map(CorridorTransaction::from)
+        .collect();
+
+    let metrics = compute_corridor_metrics(&txs, None, 1.0);
+
+    // The last persisted run is the baseline, so a run whose write fails never
+    // becomes one and clearing the cache doesn't turn detection off
+    let previous = match app_state.db.latest_corridor_metrics_snapshot(id).await {
+        Ok(previous) => previous,
+        Err(e) => {
+            tracing::warn!("Failed to read corridor {} metrics baseline: {}", id, e);
+            None
+        }
+    };
+    let warnings = previous
+        .map(|previous| {
+            detect_anomaly(
+                &snapshot_metrics(previous, &metrics),
+                &metrics,
+                &app_state.anomaly_thresholds,
+            )
+        })
+        .unwrap_or_default();
+    for warning in &warnings {
+        tracing::warn!(
+            "Corridor {} anomaly: {:?}",
+            existing.to_string_key(),
+            warning
+        );
+    }
+    let corridor = app_state
+        .db
+        .update_corridor_metrics(id, metrics.clone())
+        .await?;
+
+    if let Err(e) = app_state
+        .db
+        .record_corridor_metrics_snapshot(id, &metrics)
+        .await
+    {
+        tracing::warn!("Failed to record corridor {} metrics snapshot: {}", id, e);
+    }
+    if let Err(e) = app_state
+        .cache_invalidation
+        .invalidate_corridor_history(&id.to_string())
+        .await
+    {
+        tracing::warn!("Failed to invalidate corridor history cache: {}", e);
+    }
+
+    if let Err(e) = app_state
+        .cache_invalidation
+        .invalidate_corridor_detail(&id.to_string())
+        .await
+    {
+        tracing::warn!("Failed to invalidate corridor detail cache: {}", e);
+    }
+    if let Err(e) = app_state
+        .cache_invalidation
+        .invalidate_corridor(&corridor.to_string_key())
+        .await
+    {
+        tracing::warn!("Failed to invalidate corridor cache: {}", e);
+    }
+    if let Err(e) = app_state.cache_invalidation.invalidate_corridors().await {
+        tracing::warn!("Failed to invalidate corridor caches: {}", e);
+    }
+    if let Err(e) = app_state
+        .cache_invalidation
+        .invalidate_corridor_assets(&corridor)
+        .await
+    {
+        tracing::warn!("Failed to invalidate asset corridor caches: {}", e);
+    }
+    if let Err(e) = app_state.cache_invalidation.invalidate_dashboard().await {
+        tracing::warn!("Failed to invalidate dashboard caches: {}", e);
+    }
+
+    broadcast_corridor_update(&app_state.ws_state, &corridor);
+
+    Ok(Json(CorridorMetricsUpdateResponse { corridor, warnings }))
+}
+
+/// GET /api/dashboard/stats - Network-wide totals (cached, stale-while-revalidate)
+#[utoipa::path(
+    get,
+    path = "/api/dashboard/stats",
+    tag = "dashboard",
+    params(("no_cache" = Option<bool>, Query, description = "Skip the cache and reload, when `ALLOW_CACHE_BYPASS` is on")),
+    responses(
+        (status = 200, description = "Network-wide totals", body = DashboardStats),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        ApiError
+    )
+)]
+pub async fn get_dashboard_stats_cached(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    bypass: CacheBypass,
+) -> ApiResult<CachedJson<DashboardStats>> {
+    let (stats, status) = cached_dashboard_stats(
+        &app_state.db,
+        &app_state.cache,
+        &app_state.cache_config,
+        bypass,
+    )
+    .await?;
+
+    Ok(CachedJson::new(
+        stats,
+        app_state.cache_config.ttl("dashboard.stats"),
+        &headers,
+    )
+    .with_cache_age(status)
+    .with_cache_status(debug_status(&app_state, status)))
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CacheStatsResponse {
+    pub redis_connected: bool,
+    pub metrics: CacheMetricsSummary,
+}
+
+/// GET /api/cache/stats - Cache hit/miss statistics
+#[utoipa::path(
+    get,
+    path = "/api/cache/stats",
+    tag = "cache",
+    responses((status = 200, description = "Cache hit/miss statistics", body = CacheStatsResponse))
+)]
+pub async fn get_cache_stats(State(app_state): State<AppState>) -> Json<CacheStatsResponse> {
+    Json(CacheStatsResponse {
+        redis_connected: app_state.cache.is_redis_connected().await,
+        metrics: app_state.cache.get_metrics(),
+    })
+}
+
+/// GET /metrics - Cache counters in the Prometheus text format
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "cache",
+    responses((status = 200, description = "Cache metrics in the Prometheus text format", body = String, content_type = "text/plain"))
+)]
+pub async fn get_cache_metrics_prometheus(State(app_state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        app_state.cache.metrics_prometheus(),
+    )
+}
+
+/// POST /api/cache/metrics/reset - Zero the cache counters, returning what they
+/// were so the reset can be logged
+#[utoipa::path(
+    post,
+    path = "/api/cache/metrics/reset",
+    tag = "cache",
+    responses((status = 200, description = "The counts as they were before the reset", body = CacheMetricsSummary)),
+    security(("bearer_auth" = []))
+)]
+pub async fn reset_cache_metrics(State(app_state): State<AppState>) -> Json<CacheMetricsSummary> {
+    let summary = app_state.cache.metrics_reset();
+    tracing::info!(
+        "Cache metrics reset: {} hits, {} misses, {:.1}% hit rate",
+        summary.hits,
+        summary.misses,
+        summary.hit_rate
+    );
+
+    Json(summary)
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CacheInspectQuery {
+    /// Cache key as the handlers use it, without the namespace or version prefix
+    pub key: String,
+}
+
+/// Whether a cache key is set and how long it has left
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CacheKeyInspection {
+    pub key: String,
+    pub exists: bool,
+    /// Milliseconds until expiry; absent for missing keys and keys without a TTL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining_ttl_ms: Option<u64>,
+}
+
+/// GET /api/cache/inspect?key=... - Report whether a key is cached and its
+/// remaining TTL, without reading the value
+#[utoipa::path(
+    get,
+    path = "/api/cache/inspect",
+    tag = "cache",
+    params(CacheInspectQuery),
+    responses(
+        (status = 200, description = "Whether the key is set and its remaining TTL", body = CacheKeyInspection),
+        ApiError
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn inspect_cache_key(
+    State(app_state): State<AppState>,
+    Query(query): Query<CacheInspectQuery>,
+) -> ApiResult<Json<CacheKeyInspection>> {
+    let key = query.key.trim();
+    if key.is_empty() {
+        return Err(ApiError::BadRequest("key must not be empty".to_string()));
+    }
+
+    let exists = app_state.cache.exists(key).await?;
+    let remaining_ttl = app_state.cache.remaining_ttl(key).await?;
+
+    Ok(Json(CacheKeyInspection {
+        key: key.to_string(),
+        exists,
+        remaining_ttl_ms: remaining_ttl.map(|ttl| ttl.as_millis() as u64),
+    }))
+}
+
+/// Recent cache writes Redis rejected
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CacheFailuresResponse {
+    /// How many failures are kept (`CACHE_FAILED_WRITES_LOG_SIZE`); older ones are dropped
+    pub capacity: usize,
+    /// Oldest first
+    pub failures: Vec<FailedWrite>,
+}
+
+/// GET /api/cache/failures - The last writes Redis rejected, to see which keys
+/// missed the shared tier during a blip and may need re-warming
+#[utoipa::path(
+    get,
+    path = "/api/cache/failures",
+    tag = "cache",
+    responses((status = 200, description = "Recent failed Redis writes, oldest first", body = CacheFailuresResponse)),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_cache_failures(State(app_state): State<AppState>) -> Json<CacheFailuresResponse> {
+    Json(CacheFailuresResponse {
+        capacity: app_state.cache.failed_writes_capacity(),
+        failures: app_state.cache.failed_writes(),
+    })
+}
+
+/// POST /api/cache/memory/compact - Purge expired memory-tier entries now
+#[utoipa::path(
+    post,
+    path = "/api/cache/memory/compact",
+    tag = "cache",
+    responses((status = 200, description = "Expired entries purged and the memory tier's size afterwards", body = MemoryCompaction)),
+    security(("bearer_auth" = []))
+)]
+pub async fn compact_memory_cache(State(app_state): State<AppState>) -> Json<MemoryCompaction> {
+    let compaction = app_state.cache.compact_memory().await;
+    tracing::info!(
+        "Memory cache compacted: {} expired entries purged, {} remaining",
+        compaction.purged,
+        compaction.remaining
+    );
+
+    Json(compaction)
+}
+
+/// Most ids one warm request may name, anchors and corridors together
+const MAX_WARM_IDS: usize = 500;
+/// Entities loaded from the database at once while warming
+const WARM_CONCURRENCY: usize = 8;
+
+/// Anchors and corridors whose detail entries `warm_cache` should load
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct WarmCacheRequest {
+    #[serde(default)]
+    pub anchor_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub corridor_ids: Vec<Uuid>,
+}
+
+/// Outcome of warming one entity
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct WarmCacheResult {
+    pub id: Uuid,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct WarmCacheResponse {
+    pub warmed: usize,
+    pub failed: usize,
+    pub anchors: Vec<WarmCacheResult>,
+    pub corridors: Vec<WarmCacheResult>,
+}
+
+impl WarmCacheResult {
+    fn from_outcome(id: Uuid, outcome: ApiResult<()>) -> Self {
+        match outcome {
+            Ok(()) => Self {
+                id,
+                ok: true,
+                error: None,
+            },
+            Err(
+                ApiError::NotFound(message)
+                | ApiError::BadRequest(message)
+                | ApiError::Conflict(message)
+                | ApiError::InternalError(message)
+                | ApiError::ServiceUnavailable(message),
+            ) => Self {
+                id,
+                ok: false,
+                error: Some(message),
+            },
+        }
+    }
+}
+
+/// POST /api/cache/warm - Reload the named anchors' detail and assets entries
+/// and the named corridors' detail entries, reporting failures per id
+#[utoipa::path(
+    post,
+    path = "/api/cache/warm",
+    tag = "cache",
+    request_body = WarmCacheRequest,
+    responses(
+        (status = 200, description = "Per-id outcome of the warm", body = WarmCacheResponse),
+        ApiError
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn warm_cache(
+    State(app_state): State<AppState>,
+    Json(request): Json<WarmCacheRequest>,
+) -> ApiResult<Json<WarmCacheResponse>> {
+    let requested = request.anchor_ids.len() + request.corridor_ids.len();
+    if requested > MAX_WARM_IDS {
+        return Err(ApiError::BadRequest(format!(
+            "Warm request for {} ids exceeds the limit of {}",
+            requested, MAX_WARM_IDS
+        )));
+    }
+
+    // Loads run concurrently but hold a permit each, so a large request can't
+    // take every database connection at once
+    let permits = tokio::sync::Semaphore::new(WARM_CONCURRENCY);
+    let app_state = &app_state;
+    let permits = &permits;
+    let anchors = futures::future::join_all(request.anchor_ids.iter().map(|&id| async move {
+        let _permit = permits
+            .acquire()
+            .await
+            .expect("warm semaphore is never closed");
+        let outcome = async {
+            cached_anchor_detail(app_state, id, CacheBypass(true)).await?;
+            let first_page = AnchorAssetsQuery::default();
+            cached_anchor_assets(
+                app_state,
+                id,
+                first_page.limit,
+                first_page.offset,
+                CacheBypass(true),
+            )
+            .await?;
+            Ok(())
+        }
+        .await;
+        WarmCacheResult::from_outcome(id, outcome)
+    }));
+    let corridors = futures::future::join_all(request.corridor_ids.iter().map(|&id| async move {
+        let _permit = permits
+            .acquire()
+            .await
+            .expect("warm semaphore is never closed");
+        let outcome = cached_corridor_detail(app_state, id, CacheBypass(true))
+            .await
+            .map(|_| ());
+        WarmCacheResult::from_outcome(id, outcome)
+    }));
+    let (anchors, corridors) = futures::future::join(anchors, corridors).await;
+
+    let warmed = anchors
+        .iter()
+        .chain(&corridors)
+        .filter(|result| result.ok)
+        .count();
+    let failed = requested - warmed;
+    tracing::info!(
+        "Cache warm: {} of {} entities loaded, {} failed",
+        warmed,
+        requested,
+        failed
+    );
+
+    Ok(Json(WarmCacheResponse {
+        warmed,
+        failed,
+        anchors,
+        corridors,
+    }))
+}
+
+/// POST /api/cache/clear - Flush every cache entry
+#[utoipa::path(
+    post,
+    path = "/api/cache/clear",
+    tag = "cache",
+    responses((status = 200, description = "The cache was cleared", body = Object), ApiError),
+    security(("bearer_auth" = []))
+)]
+pub async fn clear_cache(State(app_state): State<AppState>) -> ApiResult<Json<serde_json::Value>> {
+    app_state.cache.clear_all().await?;
+
+    Ok(Json(serde_json::json!({ "status": "cleared" })))
+}
@@ -1,13 +1,55 @@
-use anyhow::Result;
-use chrono::Utc;
-use sqlx::PgPool;
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use sqlx::{Acquire, FromRow, PgConnection, PgExecutor, PgPool, Row};
+use std::collections::{BTreeMap, HashMap};
 use uuid::Uuid;
 
 use crate::analytics::compute_anchor_metrics;
+use crate::cache::hash_filters;
+use crate::models::validate_amount;
 use crate::models::{
-    Anchor, AnchorDetailResponse, AnchorMetricsHistory, Asset, CorridorRecord, CreateAnchorRequest,
-    MetricRecord, SnapshotRecord,
+    Anchor, AnchorDetailResponse, AnchorMetricsHistory, Asset, AssetMetrics,
+    CorridorDetailResponse, CorridorMetricsSnapshot, CorridorRecord, CorridorTransactionSummary,
+    CreateAnchorRequest, DashboardStats, MetricRecord, SnapshotRecord,
 };
+use crate::services::analytics::{
+    compute_anchor_reliability, compute_corridor_metrics, CorridorTransaction,
+};
+
+/// A write clashed with a unique constraint. Returned inside the `anyhow`
+/// error so handlers can downcast it to a `409 Conflict`.
+#[derive(Debug)]
+pub struct UniqueViolation(pub String);
+
+impl std::fmt::Display for UniqueViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for UniqueViolation {}
+
+/// A conditional update's `expected_version` no longer matches the row.
+/// Returned inside the `anyhow` error so handlers can downcast it to a `409 Conflict`.
+#[derive(Debug)]
+pub struct VersionConflict(pub String);
+
+impl std::fmt::Display for VersionConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for VersionConflict {}
+
+/// Whether `upsert_anchor` inserted a new row or updated an existing one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Upserted {
+    Created,
+    Updated,
+}
 
 /// Parameters for updating anchor from RPC data
 pub struct AnchorRpcUpdate {
@@ -21,6 +63,19 @@ pub struct AnchorRpcUpdate {
     pub status: String,
 }
 
+/// One anchor's new counters, as applied by `update_anchor_metrics_batch`
+#[derive(Debug, Clone)]
+pub struct AnchorMetricsUpdate {
+    pub anchor_id: Uuid,
+    pub total_transactions: i64,
+    pub successful_transactions: i64,
+    pub failed_transactions: i64,
+    pub avg_settlement_time_ms: Option<i32>,
+    pub volume_usd: Option<f64>,
+    /// Apply only if the row is still at this `version`; `None` writes unconditionally
+    pub expected_version: Option<i64>,
+}
+
 /// Parameters for recording anchor metrics history
 pub struct AnchorMetricsParams {
     pub anchor_id: Uuid,
@@ -34,19 +89,400 @@ pub struct AnchorMetricsParams {
     pub volume_usd: Option<f64>,
 }
 
+/// Columns anchor listings may be sorted by, as (API name, SQL column)
+pub const ANCHOR_SORT_COLUMNS: &[(&str, &str)] = &[
+    ("name", "name"),
+    ("total_transactions", "total_transactions"),
+    ("volume_usd", "total_volume_usd"),
+    ("created_at", "created_at"),
+];
+
+/// Columns corridor listings may be sorted by, as (API name, SQL column)
+pub const CORRIDOR_SORT_COLUMNS: &[(&str, &str)] = &[
+    ("reliability_score", "reliability_score"),
+    ("created_at", "created_at"),
+    ("updated_at", "updated_at"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    fn as_sql(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// A validated `ORDER BY`. The column always comes from one of the allowlists
+/// above, so it is safe to interpolate into SQL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortSpec {
+    api_name: &'static str,
+    column: &'static str,
+    order: SortOrder,
+}
+
+impl SortSpec {
+    /// Validate `sort_by`/`order` query parameters against `allowed`. Returns
+    /// `Ok(None)` when no sort was requested, so callers keep their default order.
+    pub fn parse(
+        sort_by: Option<&str>,
+        order: Option<&str>,
+        allowed: &[(&'static str, &'static str)],
+    ) -> std::result::Result<Option<Self>, String> {
+        let order = match order.map(|o| o.to_ascii_lowercase()).as_deref() {
+            None | Some("asc") => SortOrder::Asc,
+            Some("desc") => SortOrder::Desc,
+            Some(other) => {
+                return Err(format!(
+                    "Invalid order '{}', expected 'asc' or 'desc'",
+                    other
+                ))
+            }
+        };
+
+        let Some(sort_by) = sort_by.filter(|s| !s.is_empty()) else {
+            return Ok(None);
+        };
+
+        allowed
+            .iter()
+            .find(|(api_name, _)| *api_name == sort_by)
+            .map(|&(api_name, column)| {
+                Some(SortSpec {
+                    api_name,
+                    column,
+                    order,
+                })
+            })
+            .ok_or_else(|| {
+                let names: Vec<&str> = allowed.iter().map(|(name, _)| *name).collect();
+                format!(
+                    "Invalid sort_by '{}', expected one of: {}",
+                    sort_by,
+                    names.join(", ")
+                )
+            })
+    }
+
+    /// Stable token identifying this sort, for use in cache keys
+    pub fn cache_token(sort: Option<&SortSpec>) -> String {
+        match sort {
+            Some(spec) => format!("{}:{}", spec.api_name, spec.order.as_sql().to_lowercase()),
+            None => "default".to_string(),
+        }
+    }
+
+    /// `ORDER BY` body, with `id` as a tie-breaker so pages are deterministic
+    fn order_by(sort: Option<&SortSpec>, default: &'static str) -> String {
+        match sort {
+            Some(spec) => format!(
+                "{} {}, id {}",
+                spec.column,
+                spec.order.as_sql(),
+                spec.order.as_sql()
+            ),
+            None => default.to_string(),
+        }
+    }
+}
+
+/// Optional narrowing of a corridor listing. An asset filter is a code, matched
+/// case-insensitively, or `CODE:ISSUER` to pin the issuer too.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CorridorFilters {
+    pub source_asset: Option<String>,
+    pub dest_asset: Option<String>,
+    /// Lowest `reliability_score` (the success rate, 0-100) to include
+    pub min_success_rate: Option<f64>,
+}
+
+impl CorridorFilters {
+    /// Trim the asset filters, upper-case their codes and drop empty ones, so
+    /// filters that mean the same compare (and hash) the same
+    pub fn normalized(self) -> Self {
+        let asset = |asset: Option<String>| {
+            let asset = asset?;
+            let asset = asset.trim();
+            if asset.is_empty() {
+                return None;
+            }
+            Some(match asset.split_once(':') {
+                Some((code, issuer)) => {
+                    format!("{}:{}", code.trim().to_ascii_uppercase(), issuer.trim())
+                }
+                None => asset.to_ascii_uppercase(),
+            })
+        };
+        Self {
+            source_asset: asset(self.source_asset),
+            dest_asset: asset(self.dest_asset),
+            min_success_rate: self.min_success_rate,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// `key=value` pairs of the set filters, sorted by key
+    pub fn canonical(&self) -> String {
+        let mut pairs = BTreeMap::new();
+        if let Some(asset) = &self.source_asset {
+            pairs.insert("source_asset", asset.clone());
+        }
+        if let Some(asset) = &self.dest_asset {
+            pairs.insert("dest_asset", asset.clone());
+        }
+        if let Some(rate) = self.min_success_rate {
+            pairs.insert("min_success_rate", rate.to_string());
+        }
+        pairs
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    /// Cache key segment for a listing under these filters and `sort`. An
+    /// unfiltered listing keeps the bare sort token.
+    pub fn cache_token(&self, sort: Option<&SortSpec>) -> String {
+        let sort_token = SortSpec::cache_token(sort);
+        if self.is_empty() {
+            return sort_token;
+        }
+        format!("{}:{}", sort_token, hash_filters(&self.canonical()))
+    }
+
+    fn binds(&self) -> CorridorFilterBinds {
+        let split = |asset: &Option<String>| match asset.as_deref().map(|a| a.split_once(':')) {
+            None => (None, None),
+            Some(None) => (asset.clone(), None),
+            Some(Some((code, issuer))) => (Some(code.to_string()), Some(issuer.to_string())),
+        };
+        let (source_code, source_issuer) = split(&self.source_asset);
+        let (dest_code, dest_issuer) = split(&self.dest_asset);
+        CorridorFilterBinds {
+            source_code,
+            source_issuer,
+            dest_code,
+            dest_issuer,
+            min_success_rate: self.min_success_rate,
+        }
+    }
+}
+
+/// `CorridorFilters` split into the parameters of `CORRIDOR_FILTER_SQL`
+struct CorridorFilterBinds {
+    source_code: Option<String>,
+    source_issuer: Option<String>,
+    dest_code: Option<String>,
+    dest_issuer: Option<String>,
+    min_success_rate: Option<f64>,
+}
+
+/// `WHERE` clause taking `CorridorFilterBinds` as `$1` to `$5`, in field order
+const CORRIDOR_FILTER_SQL: &str = r#"
+    WHERE ($1::TEXT IS NULL OR UPPER(source_asset_code) = $1)
+      AND ($2::TEXT IS NULL OR source_asset_issuer = $2)
+      AND ($3::TEXT IS NULL OR UPPER(destination_asset_code) = $3)
+      AND ($4::TEXT IS NULL OR destination_asset_issuer = $4)
+      AND ($5::FLOAT8 IS NULL OR reliability_score >= $5)
+"#;
+
+/// Optional floors on an anchor listing, checked against the stored aggregate columns
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AnchorFilters {
+    /// Lowest `total_volume_usd` to include
+    pub min_volume: Option<f64>,
+    /// Lowest success rate (successful / total transactions, 0-100) to include;
+    /// anchors without transactions have none and are left out
+    pub min_success_rate: Option<f64>,
+}
+
+impl AnchorFilters {
+    /// Check the floors: `min_volume` a non-negative number, `min_success_rate`
+    /// within 0-100
+    pub fn new(min_volume: Option<f64>, min_success_rate: Option<f64>) -> Result<Self, String> {
+        if let Some(volume) = min_volume {
+            validate_amount("min_volume", volume)?;
+        }
+        if let Some(rate) = min_success_rate {
+            if !(0.0..=100.0).contains(&rate) {
+                return Err(format!(
+                    "min_success_rate must be between 0 and 100, got {}",
+                    rate
+                ));
+            }
+        }
+        Ok(Self {
+            min_volume,
+            min_success_rate,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// `key=value` pairs of the set filters, sorted by key
+    pub fn canonical(&self) -> String {
+        let mut pairs = BTreeMap::new();
+        if let Some(volume) = self.min_volume {
+            pairs.insert("min_volume", volume.to_string());
+        }
+        if let Some(rate) = self.min_success_rate {
+            pairs.insert("min_success_rate", rate.to_string());
+        }
+        pairs
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    /// Cache key segment for a listing under these filters and `sort`. An
+    /// unfiltered listing keeps the bare sort token.
+    pub fn cache_token(&self, sort: Option<&SortSpec>) -> String {
+        let sort_token = SortSpec::cache_token(sort);
+        if self.is_empty() {
+            return sort_token;
+        }
+        format!("{}:{}", sort_token, hash_filters(&self.canonical()))
+    }
+}
+
+/// Conditions taking `AnchorFilters` as `$1` (`min_volume`) and `$2`
+/// (`min_success_rate`), to follow a `WHERE`
+const ANCHOR_FILTER_SQL: &str = r#"
+    ($1::FLOAT8 IS NULL OR total_volume_usd >= $1)
+    AND ($2::FLOAT8 IS NULL
+         OR successful_transactions * 100.0::FLOAT8 / NULLIF(total_transactions, 0) >= $2)
+"#;
+
+/// Keyset position in the anchor list: the `(created_at, id)` of the last row
+/// already returned. Clients only ever see it as an opaque string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnchorCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: String,
+}
+
+impl AnchorCursor {
+    pub fn from_anchor(anchor: &Anchor) -> Self {
+        Self {
+            created_at: anchor.created_at,
+            id: anchor.id.clone(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let raw = format!(
+            "{}|{}",
+            self.created_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Nanos, true),
+            self.id
+        );
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let raw = URL_SAFE_NO_PAD
+            .decode(cursor)
+            .context("Cursor is not valid base64")?;
+        let raw = String::from_utf8(raw).context("Cursor is not valid UTF-8")?;
+        let (created_at, id) = raw
+            .split_once('|')
+            .context("Cursor is missing its id component")?;
+        let created_at = DateTime::parse_from_rfc3339(created_at)
+            .context("Cursor has an invalid timestamp")?
+            .with_timezone(&Utc);
+
+        Ok(Self {
+            created_at,
+            id: id.to_string(),
+        })
+    }
+}
+
+/// Default ordering for anchor listings
+const ANCHOR_DEFAULT_ORDER: &str = "reliability_score DESC, updated_at DESC";
+
+/// Days of daily corridor metrics summed into a corridor's detail
+pub const CORRIDOR_DETAIL_WINDOW_DAYS: i64 = 7;
+
+/// Days corridor metrics snapshots are kept when
+/// `CORRIDOR_HISTORY_RETENTION_DAYS` is unset
+pub const DEFAULT_CORRIDOR_HISTORY_RETENTION_DAYS: i64 = 30;
+
+/// `CORRIDOR_HISTORY_RETENTION_DAYS`, falling back to the default when unset or
+/// not a positive number
+pub fn corridor_history_retention_days_from_env() -> i64 {
+    std::env::var("CORRIDOR_HISTORY_RETENTION_DAYS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_CORRIDOR_HISTORY_RETENTION_DAYS)
+}
+
+/// Days without activity after which an anchor counts as stale when
+/// `ANCHOR_STALE_AFTER_DAYS` is unset
+pub const DEFAULT_ANCHOR_STALE_AFTER_DAYS: i64 = 7;
+
+/// `ANCHOR_STALE_AFTER_DAYS`, falling back to the default when unset or not a
+/// positive number
+pub fn anchor_stale_after_days_from_env() -> i64 {
+    std::env::var("ANCHOR_STALE_AFTER_DAYS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_ANCHOR_STALE_AFTER_DAYS)
+}
+
+/// Whether an anchor has gone `stale_after_days` without activity. One never
+/// seen active is measured from its creation, so new anchors aren't flagged
+/// before ingestion has had a chance to see them.
+pub fn is_anchor_stale(anchor: &Anchor, now: DateTime<Utc>, stale_after_days: i64) -> bool {
+    let last_seen = anchor.last_activity_at.unwrap_or(anchor.created_at);
+    now - last_seen > chrono::Duration::days(stale_after_days)
+}
+
 pub struct Database {
     pool: PgPool,
+    /// `ANCHOR_STALE_AFTER_DAYS`, used for `AnchorDetailResponse::is_stale`
+    anchor_stale_after_days: i64,
 }
 
 impl Database {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            anchor_stale_after_days: anchor_stale_after_days_from_env(),
+        }
+    }
+
+    /// Override `ANCHOR_STALE_AFTER_DAYS`
+    pub fn with_anchor_stale_after_days(mut self, days: i64) -> Self {
+        self.anchor_stale_after_days = days.max(1);
+        self
     }
 
     pub fn pool(&self) -> &PgPool {
         &self.pool
     }
 
+    /// Cheapest round trip that proves the database is answering
+    pub async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
     pub fn corridor_aggregates(&self) -> crate::db::aggregates::CorridorAggregates {
         crate::db::aggregates::CorridorAggregates::new(self.pool.clone())
     }
@@ -66,11 +502,51 @@ impl Database {
         .bind(&req.stellar_account)
         .bind(&req.home_domain)
         .fetch_one(&self.pool)
-        .await?;
+        .await
+        .map_err(|e| match &e {
+            // The UNIQUE constraint also settles two concurrent creates
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                anyhow::Error::new(UniqueViolation(format!(
+                    "An anchor with Stellar account {} already exists",
+                    req.stellar_account
+                )))
+            }
+            _ => e.into(),
+        })?;
 
         Ok(anchor)
     }
 
+    /// Create the anchor for `req.stellar_account`, or overwrite the name and
+    /// home domain of the one already registered for it, in a single statement
+    pub async fn upsert_anchor(&self, req: CreateAnchorRequest) -> Result<(Anchor, Upserted)> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO anchors (id, name, stellar_account, home_domain)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (stellar_account) DO UPDATE SET
+                name = EXCLUDED.name,
+                home_domain = EXCLUDED.home_domain,
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING *, (xmax = 0) AS inserted
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&req.name)
+        .bind(&req.stellar_account)
+        .bind(&req.home_domain)
+        .fetch_one(&self.pool)
+        .await?;
+
+        // Postgres leaves `xmax` at 0 on a row this statement inserted
+        let upserted = if row.try_get::<bool, _>("inserted")? {
+            Upserted::Created
+        } else {
+            Upserted::Updated
+        };
+        Ok((Anchor::from_row(&row)?, upserted))
+    }
+
     pub async fn get_anchor_by_id(&self, id: Uuid) -> Result<Option<Anchor>> {
         let anchor = sqlx::query_as::<_, Anchor>(
             r#"
@@ -84,6 +560,28 @@ impl Database {
         Ok(anchor)
     }
 
+    /// Load every anchor in `ids` with a single query, keyed by id. Ids with
+    /// no anchor are left out of the map.
+    pub async fn get_anchors_batch(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Anchor>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let ids: Vec<String> = ids.iter().map(Uuid::to_string).collect();
+        let anchors = sqlx::query_as::<_, Anchor>(
+            r#"
+            SELECT * FROM anchors WHERE id = ANY($1)
+            "#,
+        )
+        .bind(&ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(anchors
+            .into_iter()
+            .filter_map(|anchor| Some((Uuid::parse_str(&anchor.id).ok()?, anchor)))
+            .collect())
+    }
+
     pub async fn get_anchor_by_stellar_account(
         &self,
         stellar_account: &str,
@@ -100,14 +598,28 @@ impl Database {
         Ok(anchor)
     }
 
-    pub async fn list_anchors(&self, limit: i64, offset: i64) -> Result<Vec<Anchor>> {
-        let anchors = sqlx::query_as::<_, Anchor>(
+    /// Deactivated anchors are skipped unless `include_inactive` is set
+    pub async fn list_anchors(
+        &self,
+        limit: i64,
+        offset: i64,
+        sort: Option<&SortSpec>,
+        include_inactive: bool,
+        filters: &AnchorFilters,
+    ) -> Result<Vec<Anchor>> {
+        let anchors = sqlx::query_as::<_, Anchor>(&format!(
             r#"
             SELECT * FROM anchors
-            ORDER BY reliability_score DESC, updated_at DESC
-            LIMIT $1 OFFSET $2
+            WHERE {} AND (is_active OR $3)
+            ORDER BY {}
+            LIMIT $4 OFFSET $5
             "#,
-        )
+            ANCHOR_FILTER_SQL,
+            SortSpec::order_by(sort, ANCHOR_DEFAULT_ORDER)
+        ))
+        .bind(filters.min_volume)
+        .bind(filters.min_success_rate)
+        .bind(include_inactive)
         .bind(limit)
         .bind(offset)
         .fetch_all(&self.pool)
@@ -116,68 +628,238 @@ impl Database {
         Ok(anchors)
     }
 
-    pub async fn update_anchor_metrics(
+    /// Case-insensitive substring match on `name`, or prefix match on `stellar_account`
+    /// (strkeys are upper case, so the query is upper-cased for that side).
+    /// Deactivated anchors are skipped unless `include_inactive` is set.
+    pub async fn search_anchors(
         &self,
-        anchor_id: Uuid,
-        total_transactions: i64,
-        successful_transactions: i64,
-        failed_transactions: i64,
-        avg_settlement_time_ms: Option<i32>,
-        volume_usd: Option<f64>,
-    ) -> Result<Anchor> {
-        // Compute metrics
-        let metrics = compute_anchor_metrics(
-            total_transactions,
-            successful_transactions,
-            failed_transactions,
-            avg_settlement_time_ms,
-        );
+        query: &str,
+        limit: i64,
+        offset: i64,
+        sort: Option<&SortSpec>,
+        include_inactive: bool,
+    ) -> Result<Vec<Anchor>> {
+        let escaped = escape_like(query);
+        let anchors = sqlx::query_as::<_, Anchor>(&format!(
+            r#"
+            SELECT * FROM anchors
+            WHERE (name ILIKE $1 OR stellar_account LIKE $2) AND (is_active OR $5)
+            ORDER BY {}
+            LIMIT $3 OFFSET $4
+            "#,
+            SortSpec::order_by(sort, ANCHOR_DEFAULT_ORDER)
+        ))
+        .bind(format!("%{}%", escaped))
+        .bind(format!("{}%", escaped.to_uppercase()))
+        .bind(limit)
+        .bind(offset)
+        .bind(include_inactive)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(anchors)
+    }
+
+    /// Row count matching `search_anchors` with the same `include_inactive`
+    pub async fn count_search_anchors(&self, query: &str, include_inactive: bool) -> Result<i64> {
+        let escaped = escape_like(query);
+        let count: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM anchors
+            WHERE (name ILIKE $1 OR stellar_account LIKE $2) AND (is_active OR $3)
+            "#,
+        )
+        .bind(format!("%{}%", escaped))
+        .bind(format!("{}%", escaped.to_uppercase()))
+        .bind(include_inactive)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count.0)
+    }
+
+    /// Keyset pagination over anchors ordered by `(created_at, id)`. Rows inserted
+    /// while a client is paging land after existing ones, so no row is skipped or
+    /// repeated the way it can be with `OFFSET`. Deactivated anchors are
+    /// skipped unless `include_inactive` is set.
+    pub async fn list_anchors_after(
+        &self,
+        cursor: Option<&AnchorCursor>,
+        limit: i64,
+        include_inactive: bool,
+    ) -> Result<Vec<Anchor>> {
+        let anchors = match cursor {
+            Some(cursor) => {
+                sqlx::query_as::<_, Anchor>(
+                    r#"
+                    SELECT * FROM anchors
+                    WHERE (created_at, id) > ($1, $2) AND (is_active OR $4)
+                    ORDER BY created_at ASC, id ASC
+                    LIMIT $3
+                    "#,
+                )
+                .bind(cursor.created_at)
+                .bind(&cursor.id)
+                .bind(limit)
+                .bind(include_inactive)
+                .fetch_all(&self.pool)
+                .await?
+            }
+            None => {
+                sqlx::query_as::<_, Anchor>(
+                    r#"
+                    SELECT * FROM anchors
+                    WHERE is_active OR $2
+                    ORDER BY created_at ASC, id ASC
+                    LIMIT $1
+                    "#,
+                )
+                .bind(limit)
+                .bind(include_inactive)
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+
+        Ok(anchors)
+    }
+
+    pub async fn count_anchors(&self) -> Result<i64> {
+        let count: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM anchors
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count.0)
+    }
+
+    /// Row count matching `list_anchors` with the same `include_inactive` and filters
+    pub async fn count_listed_anchors(
+        &self,
+        include_inactive: bool,
+        filters: &AnchorFilters,
+    ) -> Result<i64> {
+        let count: (i64,) = sqlx::query_as(&format!(
+            r#"
+            SELECT COUNT(*) FROM anchors WHERE {} AND (is_active OR $3)
+            "#,
+            ANCHOR_FILTER_SQL
+        ))
+        .bind(filters.min_volume)
+        .bind(filters.min_success_rate)
+        .bind(include_inactive)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count.0)
+    }
+
+    /// Hide an anchor from default listings while keeping its metrics and
+    /// assets. Returns `None` for an unknown id.
+    pub async fn deactivate_anchor(&self, id: Uuid) -> Result<Option<Anchor>> {
+        self.set_anchor_active(id, false).await
+    }
+
+    /// Undo `deactivate_anchor`. Returns `None` for an unknown id.
+    pub async fn reactivate_anchor(&self, id: Uuid) -> Result<Option<Anchor>> {
+        self.set_anchor_active(id, true).await
+    }
 
-        // Update anchor
+    async fn set_anchor_active(&self, id: Uuid, is_active: bool) -> Result<Option<Anchor>> {
         let anchor = sqlx::query_as::<_, Anchor>(
             r#"
             UPDATE anchors
-            SET total_transactions = $1,
-                successful_transactions = $2,
-                failed_transactions = $3,
-                avg_settlement_time_ms = $4,
-                reliability_score = $5,
-                status = $6,
-                total_volume_usd = COALESCE($7, total_volume_usd),
-                updated_at = $8
-            WHERE id = $9
+            SET is_active = $1, updated_at = CURRENT_TIMESTAMP
+            WHERE id = $2
             RETURNING *
             "#,
         )
-        .bind(total_transactions)
-        .bind(successful_transactions)
-        .bind(failed_transactions)
-        .bind(avg_settlement_time_ms.unwrap_or(0))
-        .bind(metrics.reliability_score)
-        .bind(metrics.status.as_str())
-        .bind(volume_usd.unwrap_or(0.0))
-        .bind(Utc::now())
-        .bind(anchor_id.to_string())
-        .fetch_one(&self.pool)
+        .bind(is_active)
+        .bind(id.to_string())
+        .fetch_optional(&self.pool)
         .await?;
 
-        // Record metrics history
-        self.record_anchor_metrics_history(AnchorMetricsParams {
+        Ok(anchor)
+    }
+
+    pub async fn update_anchor_metrics(
+        &self,
+        anchor_id: Uuid,
+        total_transactions: i64,
+        successful_transactions: i64,
+        failed_transactions: i64,
+        avg_settlement_time_ms: Option<i32>,
+        volume_usd: Option<f64>,
+    ) -> Result<Anchor> {
+        self.update_anchor_metrics_versioned(&AnchorMetricsUpdate {
             anchor_id,
-            success_rate: metrics.success_rate,
-            failure_rate: metrics.failure_rate,
-            reliability_score: metrics.reliability_score,
             total_transactions,
             successful_transactions,
             failed_transactions,
             avg_settlement_time_ms,
             volume_usd,
+            expected_version: None,
         })
-        .await?;
+        .await
+    }
+
+    /// Apply one metrics update, failing with `VersionConflict` if it carries an
+    /// `expected_version` the row has already moved past
+    pub async fn update_anchor_metrics_versioned(
+        &self,
+        update: &AnchorMetricsUpdate,
+    ) -> Result<Anchor> {
+        let mut conn = self.pool.acquire().await?;
+        let anchor = apply_anchor_metrics(&mut conn, update)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
 
         Ok(anchor)
     }
 
+    /// Apply many metrics updates in one transaction. Each item runs under its
+    /// own savepoint, so an unknown id (`Ok(None)`) or a failing row is
+    /// reported in its slot without rolling back the others.
+    pub async fn update_anchor_metrics_batch(
+        &self,
+        updates: &[AnchorMetricsUpdate],
+    ) -> Result<Vec<Result<Option<Anchor>>>> {
+        let mut tx = self.pool.begin().await?;
+        let mut results = Vec::with_capacity(updates.len());
+
+        for update in updates {
+            let mut item = tx.begin().await?;
+            let result = apply_anchor_metrics(&mut item, update).await;
+            if matches!(result, Ok(Some(_))) {
+                item.commit().await?;
+            } else {
+                item.rollback().await?;
+            }
+            results.push(result);
+        }
+
+        tx.commit().await?;
+        Ok(results)
+    }
+
+    /// Delete an anchor, returning whether a row was removed. Its assets and
+    /// metrics history go with it through `ON DELETE CASCADE`.
+    pub async fn delete_anchor(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM anchors WHERE id = $1
+            "#,
+        )
+        .bind(id.to_string())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     // Asset operations
     pub async fn create_asset(
         &self,
@@ -188,36 +870,112 @@ impl Database {
         let id = Uuid::new_v4().to_string();
         let asset = sqlx::query_as::<_, Asset>(
             r#"
-            INSERT INTO assets (id, anchor_id, asset_code, asset_issuer)
-            VALUES ($1, $2, $3, $4)
-            ON CONFLICT (asset_code, asset_issuer) DO UPDATE
-            SET anchor_id = EXCLUDED.anchor_id,
-                updated_at = CURRENT_TIMESTAMP
-            RETURNING *
+            INSERT INTO assets (id, anchor_id, asset_code, asset_issuer)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (asset_code, asset_issuer) DO UPDATE
+            SET anchor_id = EXCLUDED.anchor_id,
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(anchor_id.to_string())
+        .bind(&asset_code)
+        .bind(&asset_issuer)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(asset)
+    }
+
+    /// One page of the assets an anchor issues, ordered by code then issuer
+    pub async fn get_assets_by_anchor(
+        &self,
+        anchor_id: Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Asset>> {
+        let assets = sqlx::query_as::<_, Asset>(
+            r#"
+            SELECT * FROM assets WHERE anchor_id = $1
+            ORDER BY asset_code ASC, asset_issuer ASC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(anchor_id.to_string())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(assets)
+    }
+
+    /// Every asset an anchor issues, for callers that need the whole set
+    pub async fn get_all_assets_by_anchor(&self, anchor_id: Uuid) -> Result<Vec<Asset>> {
+        let assets = sqlx::query_as::<_, Asset>(
+            r#"
+            SELECT * FROM assets WHERE anchor_id = $1
+            ORDER BY asset_code ASC, asset_issuer ASC
+            "#,
+        )
+        .bind(anchor_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(assets)
+    }
+
+    /// Success rate, volume and latency over every payment recorded in an
+    /// asset, or `None` when no such asset is registered
+    pub async fn get_asset_metrics(
+        &self,
+        asset_code: &str,
+        asset_issuer: &str,
+    ) -> Result<Option<AssetMetrics>> {
+        let asset = sqlx::query_as::<_, Asset>(
+            r#"
+            SELECT * FROM assets WHERE asset_code = $1 AND asset_issuer = $2
+            "#,
+        )
+        .bind(asset_code)
+        .bind(asset_issuer)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(asset) = asset else {
+            return Ok(None);
+        };
+
+        let amounts: Vec<f64> = sqlx::query_scalar(
+            r#"
+            SELECT amount::DOUBLE PRECISION FROM payments
+            WHERE asset_code = $1 AND asset_issuer = $2
             "#,
         )
-        .bind(id)
-        .bind(anchor_id.to_string())
-        .bind(&asset_code)
-        .bind(&asset_issuer)
-        .fetch_one(&self.pool)
+        .bind(asset_code)
+        .bind(asset_issuer)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(asset)
+        Ok(Some(asset_metrics(asset, &amounts)))
     }
 
-    pub async fn get_assets_by_anchor(&self, anchor_id: Uuid) -> Result<Vec<Asset>> {
-        let assets = sqlx::query_as::<_, Asset>(
+    /// Anchors issuing an asset with this code, matched case-insensitively so
+    /// `usdc` finds `USDC`. Codes that differ only in case are returned together.
+    pub async fn find_anchors_by_asset_code(&self, code: &str) -> Result<Vec<Anchor>> {
+        let anchors = sqlx::query_as::<_, Anchor>(
             r#"
-            SELECT * FROM assets WHERE anchor_id = $1
-            ORDER BY asset_code ASC
+            SELECT DISTINCT anchors.* FROM anchors
+            JOIN assets ON assets.anchor_id = anchors.id
+            WHERE UPPER(assets.asset_code) = UPPER($1)
+            ORDER BY anchors.reliability_score DESC, anchors.updated_at DESC
             "#,
         )
-        .bind(anchor_id.to_string())
+        .bind(code)
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(assets)
+        Ok(anchors)
     }
 
     pub async fn count_assets_by_anchor(&self, anchor_id: Uuid) -> Result<i64> {
@@ -245,7 +1003,8 @@ impl Database {
                 avg_settlement_time_ms = $5,
                 reliability_score = $6,
                 status = $7,
-                updated_at = $8
+                updated_at = $8,
+                version = version + 1
             WHERE stellar_account = $9
             "#,
         )
@@ -264,38 +1023,30 @@ impl Database {
         Ok(())
     }
 
-    // Metrics history operations
-    pub async fn record_anchor_metrics_history(
-        &self,
-        params: AnchorMetricsParams,
-    ) -> Result<AnchorMetricsHistory> {
-        let id = Uuid::new_v4().to_string();
-        let history = sqlx::query_as::<_, AnchorMetricsHistory>(
+    /// Record that the anchor was active at `ts`. Never moves
+    /// `last_activity_at` backwards, so replaying older data is harmless.
+    pub async fn touch_anchor_last_seen(&self, id: Uuid, ts: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
             r#"
-            INSERT INTO anchor_metrics_history (
-                id, anchor_id, timestamp, success_rate, failure_rate, reliability_score,
-                total_transactions, successful_transactions, failed_transactions,
-                avg_settlement_time_ms, volume_usd
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-            RETURNING *
+            UPDATE anchors
+            SET last_activity_at = GREATEST(last_activity_at, $1)
+            WHERE id = $2
             "#,
         )
-        .bind(id)
-        .bind(params.anchor_id.to_string())
-        .bind(Utc::now())
-        .bind(params.success_rate)
-        .bind(params.failure_rate)
-        .bind(params.reliability_score)
-        .bind(params.total_transactions)
-        .bind(params.successful_transactions)
-        .bind(params.failed_transactions)
-        .bind(params.avg_settlement_time_ms.unwrap_or(0))
-        .bind(params.volume_usd.unwrap_or(0.0))
-        .fetch_one(&self.pool)
+        .bind(ts)
+        .bind(id.to_string())
+        .execute(&self.pool)
         .await?;
 
-        Ok(history)
+        Ok(())
+    }
+
+    // Metrics history operations
+    pub async fn record_anchor_metrics_history(
+        &self,
+        params: AnchorMetricsParams,
+    ) -> Result<AnchorMetricsHistory> {
+        insert_anchor_metrics_history(&self.pool, params).await
     }
 
     pub async fn get_anchor_metrics_history(
@@ -325,17 +1076,38 @@ impl Database {
             None => return Ok(None),
         };
 
-        let assets = self.get_assets_by_anchor(anchor_id).await?;
+        Ok(Some(self.anchor_detail_for(anchor_id, anchor).await?))
+    }
+
+    /// The detail view around an anchor row already in hand, loading only its
+    /// assets and metrics history
+    pub async fn anchor_detail_for(
+        &self,
+        anchor_id: Uuid,
+        anchor: Anchor,
+    ) -> Result<AnchorDetailResponse> {
+        let assets = self.get_all_assets_by_anchor(anchor_id).await?;
         let metrics_history = self.get_anchor_metrics_history(anchor_id, 30).await?;
 
-        Ok(Some(AnchorDetailResponse {
+        let reliability = compute_anchor_reliability(&anchor);
+        let is_stale = is_anchor_stale(&anchor, Utc::now(), self.anchor_stale_after_days);
+
+        Ok(AnchorDetailResponse {
+            last_activity_at: anchor.last_activity_at,
+            version: anchor.version,
             anchor,
             assets,
             metrics_history,
-        }))
+            reliability,
+            is_stale,
+        })
     }
 
     // Corridor operations
+
+    /// Upsert a corridor. With `initial_transactions` its first metrics and
+    /// history snapshot are written in the same transaction, so a failure
+    /// rolls the corridor back too.
     pub async fn create_corridor(
         &self,
         req: crate::models::CreateCorridorRequest,
@@ -347,8 +1119,10 @@ impl Database {
             req.dest_asset_issuer,
         );
 
+        let mut tx = self.pool.begin().await?;
+
         // Ensure the corridor exists in the database
-        sqlx::query(
+        let corridor_id: String = sqlx::query_scalar(
             r#"
             INSERT INTO corridors (
                 id, source_asset_code, source_asset_issuer,
@@ -357,6 +1131,7 @@ impl Database {
             VALUES ($1, $2, $3, $4, $5)
             ON CONFLICT (source_asset_code, source_asset_issuer, destination_asset_code, destination_asset_issuer)
             DO UPDATE SET updated_at = CURRENT_TIMESTAMP
+            RETURNING id
             "#,
         )
         .bind(Uuid::new_v4().to_string())
@@ -364,9 +1139,16 @@ impl Database {
         .bind(&corridor.asset_a_issuer)
         .bind(&corridor.asset_b_code)
         .bind(&corridor.asset_b_issuer)
-        .execute(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
 
+        if let Some(transactions) = req.initial_transactions {
+            let metrics = initial_corridor_metrics(transactions)?;
+            set_corridor_metrics(&mut *tx, &corridor_id, &metrics).await?;
+            insert_corridor_metrics_snapshot(&mut *tx, &corridor_id, &metrics).await?;
+        }
+
+        tx.commit().await?;
         Ok(corridor)
     }
 
@@ -374,12 +1156,22 @@ impl Database {
         &self,
         limit: i64,
         offset: i64,
+        sort: Option<&SortSpec>,
+        filters: &CorridorFilters,
     ) -> Result<Vec<crate::models::corridor::Corridor>> {
-        let records = sqlx::query_as::<_, CorridorRecord>(
+        let binds = filters.binds();
+        let records = sqlx::query_as::<_, CorridorRecord>(&format!(
             r#"
-            SELECT * FROM corridors ORDER BY reliability_score DESC LIMIT $1 OFFSET $2
+            SELECT * FROM corridors {} ORDER BY {} LIMIT $6 OFFSET $7
             "#,
-        )
+            CORRIDOR_FILTER_SQL,
+            SortSpec::order_by(sort, "reliability_score DESC")
+        ))
+        .bind(binds.source_code)
+        .bind(binds.source_issuer)
+        .bind(binds.dest_code)
+        .bind(binds.dest_issuer)
+        .bind(binds.min_success_rate)
         .bind(limit)
         .bind(offset)
         .fetch_all(&self.pool)
@@ -398,6 +1190,98 @@ impl Database {
             .collect())
     }
 
+    /// Corridors with the asset on either side. The code matches
+    /// case-insensitively, like `find_anchors_by_asset_code`. Without an issuer
+    /// every asset with that code matches (USDC from any issuer); with one only
+    /// that exact asset does, since the same code from another issuer is a
+    /// different asset.
+    pub async fn list_corridors_by_asset(
+        &self,
+        code: &str,
+        issuer: Option<&str>,
+    ) -> Result<Vec<crate::models::corridor::Corridor>> {
+        let records = sqlx::query_as::<_, CorridorRecord>(
+            r#"
+            SELECT * FROM corridors
+            WHERE (UPPER(source_asset_code) = UPPER($1)
+                    AND ($2::TEXT IS NULL OR source_asset_issuer = $2))
+               OR (UPPER(destination_asset_code) = UPPER($1)
+                    AND ($2::TEXT IS NULL OR destination_asset_issuer = $2))
+            ORDER BY reliability_score DESC, id ASC
+            "#,
+        )
+        .bind(code)
+        .bind(issuer)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|r| {
+                crate::models::corridor::Corridor::new(
+                    r.source_asset_code,
+                    r.source_asset_issuer,
+                    r.destination_asset_code,
+                    r.destination_asset_issuer,
+                )
+            })
+            .collect())
+    }
+
+    /// Dashboard totals in a single round trip
+    pub async fn dashboard_stats(&self) -> Result<DashboardStats> {
+        let stats = sqlx::query_as::<_, DashboardStats>(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM anchors) AS total_anchors,
+                (SELECT COUNT(*) FROM corridors) AS total_corridors,
+                COALESCE(
+                    SUM(successful_transactions)::FLOAT8
+                        / NULLIF(SUM(total_transactions), 0)::FLOAT8 * 100,
+                    0
+                ) AS overall_success_rate,
+                COALESCE(SUM(total_volume_usd), 0)::FLOAT8 AS total_volume_usd
+            FROM anchors
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(stats)
+    }
+
+    /// Corridors matching `filters`, for the total of a filtered listing
+    pub async fn count_filtered_corridors(&self, filters: &CorridorFilters) -> Result<i64> {
+        let binds = filters.binds();
+        let count: (i64,) = sqlx::query_as(&format!(
+            r#"
+            SELECT COUNT(*) FROM corridors {}
+            "#,
+            CORRIDOR_FILTER_SQL
+        ))
+        .bind(binds.source_code)
+        .bind(binds.source_issuer)
+        .bind(binds.dest_code)
+        .bind(binds.dest_issuer)
+        .bind(binds.min_success_rate)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count.0)
+    }
+
+    pub async fn count_corridors(&self) -> Result<i64> {
+        let count: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM corridors
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count.0)
+    }
+
     pub async fn get_corridor_by_id(
         &self,
         id: Uuid,
@@ -421,25 +1305,72 @@ impl Database {
         }))
     }
 
-    pub async fn update_corridor_metrics(
-        &self,
-        id: Uuid,
-        metrics: crate::models::corridor::CorridorMetrics,
-    ) -> Result<crate::models::corridor::Corridor> {
+    /// A corridor's stored metrics plus totals over its last
+    /// `CORRIDOR_DETAIL_WINDOW_DAYS` of daily metrics
+    pub async fn get_corridor_detail(&self, id: Uuid) -> Result<Option<CorridorDetailResponse>> {
         let record = sqlx::query_as::<_, CorridorRecord>(
             r#"
-            UPDATE corridors
-            SET reliability_score = $1,
-                updated_at = CURRENT_TIMESTAMP
-            WHERE id = $2
-            RETURNING *
+            SELECT * FROM corridors WHERE id = $1
             "#,
         )
-        .bind(metrics.success_rate)
         .bind(id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(record) = record else {
+            return Ok(None);
+        };
+
+        let corridor = crate::models::corridor::Corridor::new(
+            record.source_asset_code,
+            record.source_asset_issuer,
+            record.destination_asset_code,
+            record.destination_asset_issuer,
+        );
+
+        let since = Utc::now() - chrono::Duration::days(CORRIDOR_DETAIL_WINDOW_DAYS);
+        let recent_transactions = sqlx::query_as::<_, CorridorTransactionSummary>(
+            r#"
+            SELECT
+                COALESCE(SUM(total_transactions), 0)::BIGINT AS total_transactions,
+                COALESCE(SUM(successful_transactions), 0)::BIGINT AS successful_transactions,
+                COALESCE(SUM(failed_transactions), 0)::BIGINT AS failed_transactions,
+                COALESCE(
+                    SUM(successful_transactions)::FLOAT8
+                        / NULLIF(SUM(total_transactions), 0)::FLOAT8 * 100,
+                    0
+                ) AS success_rate,
+                COALESCE(SUM(volume_usd), 0)::FLOAT8 AS volume_usd
+            FROM corridor_metrics
+            WHERE corridor_key = $1 AND date >= $2
+            "#,
+        )
+        .bind(corridor.to_string_key())
+        .bind(since)
         .fetch_one(&self.pool)
         .await?;
 
+        Ok(Some(CorridorDetailResponse {
+            id: record.id,
+            corridor,
+            status: record.status,
+            reliability_score: record.reliability_score,
+            volume_weighted_success_rate: record.volume_weighted_success_rate,
+            median_settlement_latency_ms: record.median_settlement_latency_ms,
+            p95_settlement_latency_ms: record.p95_settlement_latency_ms,
+            p99_settlement_latency_ms: record.p99_settlement_latency_ms,
+            recent_window_days: CORRIDOR_DETAIL_WINDOW_DAYS,
+            recent_transactions,
+        }))
+    }
+
+    pub async fn update_corridor_metrics(
+        &self,
+        id: Uuid,
+        metrics: crate::models::corridor::CorridorMetrics,
+    ) -> Result<crate::models::corridor::Corridor> {
+        let record = set_corridor_metrics(&self.pool, &id.to_string(), &metrics).await?;
+
         Ok(crate::models::corridor::Corridor::new(
             record.source_asset_code,
             record.source_asset_issuer,
@@ -448,6 +1379,71 @@ impl Database {
         ))
     }
 
+    /// Append the metrics a run computed for a corridor to its history
+    pub async fn record_corridor_metrics_snapshot(
+        &self,
+        corridor_id: Uuid,
+        metrics: &crate::models::corridor::CorridorMetrics,
+    ) -> Result<CorridorMetricsSnapshot> {
+        insert_corridor_metrics_snapshot(&self.pool, &corridor_id.to_string(), metrics).await
+    }
+
+    /// Most recent snapshot recorded for a corridor, if any run was recorded
+    pub async fn latest_corridor_metrics_snapshot(
+        &self,
+        corridor_id: Uuid,
+    ) -> Result<Option<CorridorMetricsSnapshot>> {
+        let snapshot = sqlx::query_as::<_, CorridorMetricsSnapshot>(
+            r#"
+            SELECT * FROM corridor_metrics_snapshots
+            WHERE corridor_id = $1
+            ORDER BY recorded_at DESC, id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(corridor_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(snapshot)
+    }
+
+    /// Snapshots recorded for a corridor since `since`, oldest first
+    pub async fn get_corridor_metrics_history(
+        &self,
+        corridor_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<CorridorMetricsSnapshot>> {
+        let snapshots = sqlx::query_as::<_, CorridorMetricsSnapshot>(
+            r#"
+            SELECT * FROM corridor_metrics_snapshots
+            WHERE corridor_id = $1 AND recorded_at >= $2
+            ORDER BY recorded_at ASC, id ASC
+            "#,
+        )
+        .bind(corridor_id.to_string())
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(snapshots)
+    }
+
+    /// Drop snapshots older than `retention_days`, returning how many were removed
+    pub async fn prune_corridor_metrics_snapshots(&self, retention_days: i64) -> Result<u64> {
+        let cutoff = Utc::now() - chrono::Duration::days(retention_days);
+        let result = sqlx::query(
+            r#"
+            DELETE FROM corridor_metrics_snapshots WHERE recorded_at < $1
+            "#,
+        )
+        .bind(cutoff)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     // Generic Metric operations
     pub async fn record_metric(
         &self,
@@ -664,3 +1660,485 @@ impl Database {
             .await
     }
 }
+
+/// Escape `LIKE` wildcards so user input only ever matches literally
+fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Write a metrics time-series row for an anchor
+async fn insert_anchor_metrics_history(
+    executor: impl PgExecutor<'_>,
+    params: AnchorMetricsParams,
+) -> Result<AnchorMetricsHistory> {
+    let id = Uuid::new_v4().to_string();
+    let history = sqlx::query_as::<_, AnchorMetricsHistory>(
+        r#"
+        INSERT INTO anchor_metrics_history (
+            id, anchor_id, timestamp, success_rate, failure_rate, reliability_score,
+            total_transactions, successful_transactions, failed_transactions,
+            avg_settlement_time_ms, volume_usd
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(params.anchor_id.to_string())
+    .bind(Utc::now())
+    .bind(params.success_rate)
+    .bind(params.failure_rate)
+    .bind(params.reliability_score)
+    .bind(params.total_transactions)
+    .bind(params.successful_transactions)
+    .bind(params.failed_transactions)
+    .bind(params.avg_settlement_time_ms.unwrap_or(0))
+    .bind(params.volume_usd.unwrap_or(0.0))
+    .fetch_one(executor)
+    .await?;
+
+    Ok(history)
+}
+
+/// Aggregate an asset's payment amounts. Payments don't record a status or
+/// settlement timing yet, so each counts as successful and the latency fields
+/// stay empty until they do.
+fn asset_metrics(asset: Asset, amounts: &[f64]) -> AssetMetrics {
+    let transactions: Vec<CorridorTransaction> = amounts
+        .iter()
+        .map(|&amount| CorridorTransaction {
+            successful: true,
+            settlement_latency_ms: None,
+            amount_usd: amount,
+            occurred_at: None,
+            currency: None,
+        })
+        .collect();
+    let metrics = compute_corridor_metrics(&transactions, None, 1.0);
+
+    AssetMetrics {
+        asset_code: asset.asset_code,
+        asset_issuer: asset.asset_issuer,
+        total_transactions: metrics.total_transactions,
+        successful_transactions: metrics.successful_transactions,
+        failed_transactions: metrics.failed_transactions,
+        success_rate: metrics.success_rate,
+        volume: metrics.volume_usd,
+        avg_settlement_latency_ms: metrics.avg_settlement_latency_ms,
+        median_settlement_latency_ms: metrics.median_settlement_latency_ms,
+        p95_settlement_latency_ms: metrics.p95_settlement_latency_ms,
+        p99_settlement_latency_ms: metrics.p99_settlement_latency_ms,
+    }
+}
+
+/// Compute a new corridor's first metrics, rejecting transactions that fail
+/// validation
+fn initial_corridor_metrics(
+    transactions: Vec<crate::models::CorridorTransactionDto>,
+) -> Result<crate::models::corridor::CorridorMetrics> {
+    let transactions = transactions
+        .into_iter()
+        .map(|dto| {
+            dto.validate().map_err(anyhow::Error::msg)?;
+            Ok(CorridorTransaction::from(dto))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(compute_corridor_metrics(&transactions, None, 1.0))
+}
+
+/// Persist a corridor's reliability score, volume-weighted success rate and
+/// latency percentiles
+async fn set_corridor_metrics(
+    executor: impl PgExecutor<'_>,
+    corridor_id: &str,
+    metrics: &crate::models::corridor::CorridorMetrics,
+) -> Result<CorridorRecord> {
+    let record = sqlx::query_as::<_, CorridorRecord>(
+        r#"
+        UPDATE corridors
+        SET reliability_score = $1,
+            median_settlement_latency_ms = $2,
+            p95_settlement_latency_ms = $3,
+            p99_settlement_latency_ms = $4,
+            volume_weighted_success_rate = $5,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = $6
+        RETURNING *
+        "#,
+    )
+    .bind(metrics.success_rate)
+    .bind(metrics.median_settlement_latency_ms)
+    .bind(metrics.p95_settlement_latency_ms)
+    .bind(metrics.p99_settlement_latency_ms)
+    .bind(metrics.volume_weighted_success_rate)
+    .bind(corridor_id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(record)
+}
+
+/// Write a metrics history row for a corridor
+async fn insert_corridor_metrics_snapshot(
+    executor: impl PgExecutor<'_>,
+    corridor_id: &str,
+    metrics: &crate::models::corridor::CorridorMetrics,
+) -> Result<CorridorMetricsSnapshot> {
+    let snapshot = sqlx::query_as::<_, CorridorMetricsSnapshot>(
+        r#"
+        INSERT INTO corridor_metrics_snapshots (
+            id, corridor_id, total_transactions, successful_transactions,
+            failed_transactions, success_rate, volume_usd,
+            median_settlement_latency_ms, p95_settlement_latency_ms,
+            p99_settlement_latency_ms, volume_weighted_success_rate
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(corridor_id)
+    .bind(metrics.total_transactions)
+    .bind(metrics.successful_transactions)
+    .bind(metrics.failed_transactions)
+    .bind(metrics.success_rate)
+    .bind(metrics.volume_usd)
+    .bind(metrics.median_settlement_latency_ms)
+    .bind(metrics.p95_settlement_latency_ms)
+    .bind(metrics.p99_settlement_latency_ms)
+    .bind(metrics.volume_weighted_success_rate)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(snapshot)
+}
+
+/// Overwrite an anchor's counters, recompute its score and record the change in
+/// its history. `None` when no anchor has the id.
+async fn apply_anchor_metrics(
+    conn: &mut PgConnection,
+    update: &AnchorMetricsUpdate,
+) -> Result<Option<Anchor>> {
+    let metrics = compute_anchor_metrics(
+        update.total_transactions,
+        update.successful_transactions,
+        update.failed_transactions,
+        update.avg_settlement_time_ms,
+    );
+
+    let anchor = sqlx::query_as::<_, Anchor>(
+        r#"
+        UPDATE anchors
+        SET total_transactions = $1,
+            successful_transactions = $2,
+            failed_transactions = $3,
+            avg_settlement_time_ms = $4,
+            reliability_score = $5,
+            status = $6,
+            total_volume_usd = COALESCE($7, total_volume_usd),
+            updated_at = $8,
+            version = version + 1
+        WHERE id = $9 AND ($10::BIGINT IS NULL OR version = $10)
+        RETURNING *
+        "#,
+    )
+    .bind(update.total_transactions)
+    .bind(update.successful_transactions)
+    .bind(update.failed_transactions)
+    .bind(update.avg_settlement_time_ms.unwrap_or(0))
+    .bind(metrics.reliability_score)
+    .bind(metrics.status.as_str())
+    .bind(update.volume_usd.unwrap_or(0.0))
+    .bind(Utc::now())
+    .bind(update.anchor_id.to_string())
+    .bind(update.expected_version)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some(anchor) = anchor else {
+        // Nothing matched: either there's no such anchor or the version moved on
+        let Some(expected) = update.expected_version else {
+            return Ok(None);
+        };
+        let current: Option<(i64,)> = sqlx::query_as("SELECT version FROM anchors WHERE id = $1")
+            .bind(update.anchor_id.to_string())
+            .fetch_optional(&mut *conn)
+            .await?;
+        return match current {
+            Some((current,)) => Err(anyhow::Error::new(VersionConflict(format!(
+                "Anchor {} is at version {}, not the expected {}",
+                update.anchor_id, current, expected
+            )))),
+            None => Ok(None),
+        };
+    };
+
+    insert_anchor_metrics_history(
+        &mut *conn,
+        AnchorMetricsParams {
+            anchor_id: update.anchor_id,
+            success_rate: metrics.success_rate,
+            failure_rate: metrics.failure_rate,
+            reliability_score: metrics.reliability_score,
+            total_transactions: update.total_transactions,
+            successful_transactions: update.successful_transactions,
+            failed_transactions: update.failed_transactions,
+            avg_settlement_time_ms: update.avg_settlement_time_ms,
+            volume_usd: update.volume_usd,
+        },
+    )
+    .await?;
+
+    Ok(Some(anchor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_sort_spec_accepts_allowlisted_columns() {
+        let spec = SortSpec::parse(Some("volume_usd"), Some("DESC"), ANCHOR_SORT_COLUMNS)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            SortSpec::order_by(Some(&spec), ANCHOR_DEFAULT_ORDER),
+            "total_volume_usd DESC, id DESC"
+        );
+        assert_eq!(SortSpec::cache_token(Some(&spec)), "volume_usd:desc");
+
+        let asc = SortSpec::parse(Some("name"), None, ANCHOR_SORT_COLUMNS)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            SortSpec::order_by(Some(&asc), ANCHOR_DEFAULT_ORDER),
+            "name ASC, id ASC"
+        );
+    }
+
+    #[test]
+    fn test_sort_spec_defaults_when_absent() {
+        let spec = SortSpec::parse(None, Some("desc"), ANCHOR_SORT_COLUMNS).unwrap();
+        assert_eq!(spec, None);
+        assert_eq!(
+            SortSpec::order_by(None, ANCHOR_DEFAULT_ORDER),
+            ANCHOR_DEFAULT_ORDER
+        );
+        assert_eq!(SortSpec::cache_token(None), "default");
+    }
+
+    #[test]
+    fn test_sort_spec_rejects_unknown_columns_and_orders() {
+        assert!(
+            SortSpec::parse(Some("name; DROP TABLE anchors"), None, ANCHOR_SORT_COLUMNS).is_err()
+        );
+        assert!(SortSpec::parse(Some("reliability_score"), None, ANCHOR_SORT_COLUMNS).is_err());
+        assert!(SortSpec::parse(Some("name"), Some("sideways"), ANCHOR_SORT_COLUMNS).is_err());
+        assert!(SortSpec::parse(Some("reliability_score"), None, CORRIDOR_SORT_COLUMNS).is_ok());
+    }
+
+    fn corridor_filters(query: &str) -> CorridorFilters {
+        let uri: axum::http::Uri = format!("/api/corridors?{}", query).parse().unwrap();
+        let axum::extract::Query(params) =
+            axum::extract::Query::<crate::handlers::ListCorridorsQuery>::try_from_uri(&uri)
+                .unwrap();
+        params.filters().unwrap()
+    }
+
+    #[test]
+    fn test_different_corridor_filters_get_different_cache_tokens() {
+        let by_source = corridor_filters("source_asset=USDC");
+        let by_dest = corridor_filters("dest_asset=USDC");
+        let with_floor = corridor_filters("source_asset=USDC&min_success_rate=90");
+
+        let tokens: HashSet<String> = [&by_source, &by_dest, &with_floor]
+            .iter()
+            .map(|filters| filters.cache_token(None))
+            .collect();
+        assert_eq!(tokens.len(), 3);
+        assert!(!tokens.contains(&SortSpec::cache_token(None)));
+        assert_eq!(corridor_filters("").cache_token(None), "default");
+    }
+
+    #[test]
+    fn test_equivalent_corridor_filters_share_a_cache_token() {
+        let filters =
+            corridor_filters("source_asset=usdc&dest_asset=EURC:GISSUER&min_success_rate=90");
+        let reordered = corridor_filters(
+            "min_success_rate=90.0&dest_asset=%20eurc:GISSUER%20&source_asset=USDC",
+        );
+
+        assert_eq!(filters, reordered);
+        assert_eq!(
+            filters.canonical(),
+            "dest_asset=EURC:GISSUER&min_success_rate=90&source_asset=USDC"
+        );
+        assert_eq!(filters.cache_token(None), reordered.cache_token(None));
+        // Blank filters are no filters
+        assert!(corridor_filters("source_asset=%20%20").is_empty());
+    }
+
+    fn anchor_filters(query: &str) -> AnchorFilters {
+        let uri: axum::http::Uri = format!("/api/anchors?{}", query).parse().unwrap();
+        let axum::extract::Query(params) =
+            axum::extract::Query::<crate::handlers::ListAnchorsQuery>::try_from_uri(&uri).unwrap();
+        params.filters().unwrap()
+    }
+
+    #[test]
+    fn test_anchor_filters_get_their_own_cache_keys() {
+        let unfiltered = anchor_filters("");
+        let by_volume = anchor_filters("min_volume=1000000");
+        let by_rate = anchor_filters("min_success_rate=95");
+        let both = anchor_filters("min_volume=1000000&min_success_rate=95");
+
+        assert!(unfiltered.is_empty());
+        assert_eq!(unfiltered.cache_token(None), SortSpec::cache_token(None));
+        let keys: HashSet<String> = [&unfiltered, &by_volume, &by_rate, &both]
+            .iter()
+            .map(|filters| {
+                crate::cache::CacheKey::anchor_list(50, 0, &filters.cache_token(None), false)
+            })
+            .collect();
+        assert_eq!(keys.len(), 4);
+        assert!(keys.iter().all(|key| key.starts_with("anchor:list:")));
+        assert_eq!(
+            both.cache_token(None),
+            anchor_filters("min_success_rate=95.0&min_volume=1e6").cache_token(None)
+        );
+    }
+
+    #[test]
+    fn test_anchor_filters_reject_out_of_range_floors() {
+        for (volume, rate) in [
+            (None, Some(100.5)),
+            (None, Some(-1.0)),
+            (Some(-10.0), None),
+            (Some(f64::NAN), None),
+        ] {
+            assert!(
+                AnchorFilters::new(volume, rate).is_err(),
+                "{:?} {:?}",
+                volume,
+                rate
+            );
+        }
+        assert_eq!(
+            AnchorFilters::new(None, Some(101.0)).unwrap_err(),
+            "min_success_rate must be between 0 and 100, got 101"
+        );
+        assert!(AnchorFilters::new(Some(0.0), Some(100.0)).is_ok());
+        assert!(AnchorFilters::new(None, Some(0.0)).is_ok());
+    }
+
+    #[test]
+    fn test_corridor_filter_binds_split_code_and_issuer() {
+        let binds = corridor_filters("source_asset=USDC:GISSUER&dest_asset=eurc").binds();
+        assert_eq!(binds.source_code.as_deref(), Some("USDC"));
+        assert_eq!(binds.source_issuer.as_deref(), Some("GISSUER"));
+        assert_eq!(binds.dest_code.as_deref(), Some("EURC"));
+        assert_eq!(binds.dest_issuer, None);
+    }
+
+    #[test]
+    fn test_asset_metrics_aggregates_payments() {
+        let asset = Asset {
+            id: Uuid::new_v4().to_string(),
+            anchor_id: Uuid::new_v4().to_string(),
+            asset_code: "USDC".to_string(),
+            asset_issuer: "GISSUER".to_string(),
+            total_supply: None,
+            num_holders: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        let metrics = asset_metrics(asset.clone(), &[100.0, 250.5, 49.5]);
+        assert_eq!(metrics.asset_code, "USDC");
+        assert_eq!(metrics.total_transactions, 3);
+        assert_eq!(metrics.successful_transactions, 3);
+        assert_eq!(metrics.success_rate, 100.0);
+        assert_eq!(metrics.volume, 400.0);
+        assert_eq!(metrics.median_settlement_latency_ms, None);
+
+        let empty = asset_metrics(asset, &[]);
+        assert_eq!(empty.total_transactions, 0);
+        assert_eq!(empty.volume, 0.0);
+    }
+
+    #[test]
+    fn test_anchor_cursor_round_trips() {
+        let cursor = AnchorCursor {
+            created_at: DateTime::parse_from_rfc3339("2024-03-01T12:30:45.123456789Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            id: Uuid::new_v4().to_string(),
+        };
+
+        let encoded = cursor.encode();
+        assert!(!encoded.contains('|'));
+        assert_eq!(AnchorCursor::decode(&encoded).unwrap(), cursor);
+    }
+
+    #[test]
+    fn test_anchor_cursor_rejects_garbage() {
+        assert!(AnchorCursor::decode("not base64!").is_err());
+        assert!(AnchorCursor::decode(&URL_SAFE_NO_PAD.encode("no-separator")).is_err());
+        assert!(AnchorCursor::decode(&URL_SAFE_NO_PAD.encode("yesterday|some-id")).is_err());
+    }
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("100%_done\\"), "100\\%\\_done\\\\");
+        assert_eq!(escape_like("circle"), "circle");
+    }
+
+    fn anchor_seen(created_at: DateTime<Utc>, last_activity_at: Option<DateTime<Utc>>) -> Anchor {
+        Anchor {
+            id: Uuid::new_v4().to_string(),
+            name: "Dormant".to_string(),
+            stellar_account: "GDORMANT".to_string(),
+            home_domain: None,
+            total_transactions: 0,
+            successful_transactions: 0,
+            failed_transactions: 0,
+            total_volume_usd: 0.0,
+            avg_settlement_time_ms: 0,
+            reliability_score: 0.0,
+            status: "green".to_string(),
+            is_active: true,
+            last_activity_at,
+            version: 0,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    #[test]
+    fn test_anchor_staleness_counts_from_last_activity_or_creation() {
+        let now = Utc::now();
+        let days_ago = |days| now - chrono::Duration::days(days);
+
+        assert!(is_anchor_stale(
+            &anchor_seen(days_ago(90), Some(days_ago(8))),
+            now,
+            7
+        ));
+        assert!(!is_anchor_stale(
+            &anchor_seen(days_ago(90), Some(days_ago(6))),
+            now,
+            7
+        ));
+        assert!(!is_anchor_stale(&anchor_seen(days_ago(2), None), now, 7));
+        assert!(is_anchor_stale(&anchor_seen(days_ago(30), None), now, 7));
+    }
+}
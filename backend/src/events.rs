@@ -0,0 +1,140 @@
+use axum::{
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::stream::{self, Stream};
+use std::convert::Infallible;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::cache_invalidation::{CacheEvent, EventSubscription};
+use crate::handlers::{ApiError, ApiResult};
+use crate::state::AppState;
+
+/// GET /api/events - Server-Sent Events stream of cache invalidations, one
+/// named event per `CacheEvent` with its JSON as the data. The subscription
+/// (and its slot under `MAX_EVENT_SUBSCRIBERS`) is released when the client
+/// disconnects and axum drops the stream.
+pub async fn cache_events(
+    State(app_state): State<AppState>,
+) -> ApiResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    let subscription = app_state.cache_invalidation.subscribe().ok_or_else(|| {
+        ApiError::ServiceUnavailable("Too many event stream subscribers".to_string())
+    })?;
+    Ok(Sse::new(event_stream(subscription)).keep_alive(KeepAlive::default()))
+}
+
+fn event_stream(subscription: EventSubscription) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(subscription, |mut subscription| async move {
+        loop {
+            match subscription.receiver.recv().await {
+                Ok(event) => return Some((Ok(to_sse(&event)), subscription)),
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!("Event stream subscriber lagged, skipped {} events", missed);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+fn to_sse(event: &CacheEvent) -> Event {
+    let data = serde_json::to_string(event).unwrap_or_else(|_| "{}".to_string());
+    Event::default().event(event.name()).data(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::{CacheConfig, RedisCache};
+    use crate::cache_invalidation::MAX_EVENT_SUBSCRIBERS;
+    use crate::database::Database;
+    use crate::ingestion::DataIngestionService;
+    use crate::rpc::StellarRpcClient;
+    use crate::services::analytics::AnomalyThresholds;
+    use crate::websocket::WsState;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+        routing::get,
+        Router,
+    };
+    use futures::StreamExt;
+    use sqlx::postgres::PgPoolOptions;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    async fn test_state() -> AppState {
+        // Nothing here touches the database, so a lazy pool to nowhere is enough
+        let pool = PgPoolOptions::new()
+            .connect_lazy("postgres://postgres@127.0.0.1:1/stellar_insights")
+            .unwrap();
+        let db = Arc::new(Database::new(pool));
+        let rpc_client = Arc::new(StellarRpcClient::new_with_defaults(true));
+        let ingestion = Arc::new(DataIngestionService::new(rpc_client, Arc::clone(&db)));
+        // Closed port so the cache runs against its memory tier
+        let cache = Arc::new(RedisCache::from_url("redis://127.0.0.1:1").await.unwrap());
+        AppState::new(
+            db,
+            Arc::new(WsState::new()),
+            ingestion,
+            cache,
+            CacheConfig::default(),
+            AnomalyThresholds::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_invalidation_is_streamed_as_named_event() {
+        let state = test_state().await;
+        let app = Router::new()
+            .route("/api/events", get(cache_events))
+            .with_state(state.clone());
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/events")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+        state
+            .cache_invalidation
+            .invalidate_anchor("anchor-1")
+            .await
+            .unwrap();
+
+        let mut body = response.into_body().into_data_stream();
+        let frame = tokio::time::timeout(Duration::from_secs(5), body.next())
+            .await
+            .expect("no event within 5s")
+            .unwrap()
+            .unwrap();
+        let frame = String::from_utf8(frame.to_vec()).unwrap();
+        assert!(frame.contains("event: anchor_updated\n"), "{}", frame);
+        assert!(
+            frame.contains(r#"data: {"type":"anchor_updated","anchor_id":"anchor-1"}"#),
+            "{}",
+            frame
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_are_capped_and_slots_freed_on_drop() {
+        let state = test_state().await;
+        let subscriptions: Vec<_> = (0..MAX_EVENT_SUBSCRIBERS)
+            .map(|_| state.cache_invalidation.subscribe().unwrap())
+            .collect();
+
+        let rejected = cache_events(State(state.clone())).await;
+        assert!(matches!(rejected, Err(ApiError::ServiceUnavailable(_))));
+
+        drop(subscriptions);
+        assert!(cache_events(State(state)).await.is_ok());
+    }
+}
@@ -5,12 +5,21 @@ use axum::{
     Json,
 };
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use uuid::Uuid;
 
 use crate::broadcast::{broadcast_anchor_update, broadcast_corridor_update};
+use crate::cache::CacheError;
+use crate::database::{
+    AnchorCursor, AnchorFilters, AnchorMetricsUpdate, CorridorFilters, SortSpec, UniqueViolation, VersionConflict, ANCHOR_SORT_COLUMNS, CORRIDOR_SORT_COLUMNS,
+};
 use crate::models::corridor::Corridor;
-use crate::models::{AnchorDetailResponse, CreateAnchorRequest, CreateCorridorRequest};
-use crate::services::analytics::{compute_corridor_metrics, CorridorTransaction};
+pub use crate::models::CorridorTransactionDto;
+use crate::models::{
+    validate_amount, validate_latency_ms, AnchorDetailResponse, CorridorMetricsSnapshot,
+    CreateAnchorRequest, CreateCorridorRequest,
+};
+use crate::services::analytics::{compute_corridor_metrics, CorridorTransaction, Trend};
 use crate::state::AppState;
 
 pub type ApiResult<T> = Result<T, ApiError>;
@@ -19,7 +28,15 @@ pub type ApiResult<T> = Result<T, ApiError>;
 pub enum ApiError {
     NotFound(String),
     BadRequest(String),
+    Conflict(String),
     InternalError(String),
+    ServiceUnavailable(String),
+}
+
+/// Body of every error response
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
 }
 
 impl IntoResponse for ApiError {
@@ -27,16 +44,73 @@ impl IntoResponse for ApiError {
         let (status, message) = match self {
             ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::Conflict(msg) => (StatusCode::CONFLICT, msg),
             ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            ApiError::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
         };
 
-        (status, Json(serde_json::json!({ "error": message }))).into_response()
+        (status, Json(ErrorResponse { error: message })).into_response()
+    }
+}
+
+/// Documents each `ApiError` variant as the status it is sent with
+impl utoipa::IntoResponses for ApiError {
+    fn responses() -> std::collections::BTreeMap<
+        String,
+        utoipa::openapi::RefOr<utoipa::openapi::response::Response>,
+    > {
+        [
+            (StatusCode::BAD_REQUEST, "`BadRequest`: the request failed validation"),
+            (StatusCode::NOT_FOUND, "`NotFound`: no such anchor, corridor or asset"),
+            (StatusCode::CONFLICT, "`Conflict`: the write clashes with existing data"),
+            (StatusCode::INTERNAL_SERVER_ERROR, "`InternalError`: the server failed"),
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "`ServiceUnavailable`: a dependency such as Redis is down",
+            ),
+        ]
+        .into_iter()
+        .map(|(status, description)| {
+            let response = utoipa::openapi::ResponseBuilder::new()
+                .description(description)
+                .content(
+                    "application/json",
+                    utoipa::openapi::ContentBuilder::new()
+                        .schema(utoipa::openapi::Ref::from_schema_name("ErrorResponse"))
+                        .build(),
+                )
+                .build();
+            (status.as_u16().to_string(), response.into())
+        })
+        .collect()
     }
 }
 
 impl From<anyhow::Error> for ApiError {
     fn from(err: anyhow::Error) -> Self {
-        ApiError::InternalError(err.to_string())
+        let err = match err.downcast::<UniqueViolation>() {
+            Ok(conflict) => return ApiError::Conflict(conflict.0),
+            Err(err) => err,
+        };
+        match err.downcast::<VersionConflict>() {
+            Ok(conflict) => ApiError::Conflict(conflict.0),
+            Err(err) => ApiError::InternalError(err.to_string()),
+        }
+    }
+}
+
+/// Redis being unreachable is a transient outage (503); a value that won't
+/// round-trip through the cache is a bug (500)
+impl From<CacheError> for ApiError {
+    fn from(err: CacheError) -> Self {
+        match err {
+            CacheError::Connection(_) | CacheError::Redis(_) => {
+                ApiError::ServiceUnavailable(err.to_string())
+            }
+            CacheError::Serialization(_) | CacheError::Deserialization(_) => {
+                ApiError::InternalError(err.to_string())
+            }
+        }
     }
 }
 
@@ -46,36 +120,349 @@ impl From<sqlx::Error> for ApiError {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListAnchorsQuery {
+    /// Page size, clamped to `MAX_LIST_LIMIT`
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+    /// Optional search over anchor name and Stellar account
+    #[serde(default)]
+    pub q: Option<String>,
+    /// One of `name`, `total_transactions`, `volume_usd`, `created_at`
+    #[serde(default)]
+    pub sort_by: Option<String>,
+    /// `asc` (default) or `desc`
+    #[serde(default)]
+    pub order: Option<String>,
+    /// Keyset pagination cursor from a previous `next_cursor`; pass it empty to
+    /// start from the first page. Replaces `offset`, `q` and `sort_by`.
+    #[serde(default)]
+    pub after: Option<String>,
+    /// Also list deactivated anchors, in the offset, search and cursor listings alike
+    #[serde(default)]
+    pub include_inactive: bool,
+    /// Lowest total volume in USD to include. Applies to the offset listing only.
+    #[serde(default)]
+    pub min_volume: Option<f64>,
+    /// Lowest success rate (0-100) to include. Applies to the offset listing only.
+    #[serde(default)]
+    pub min_success_rate: Option<f64>,
+}
+
+impl ListAnchorsQuery {
+    /// `Some(cursor)` when the caller asked for keyset pagination, where the inner
+    /// `None` means the first page
+    pub fn cursor(&self) -> ApiResult<Option<Option<AnchorCursor>>> {
+        let Some(after) = self.after.as_deref() else {
+            return Ok(None);
+        };
+        if self.q.is_some() || self.sort_by.is_some() {
+            return Err(ApiError::BadRequest(
+                "after cannot be combined with q or sort_by".to_string(),
+            ));
+        }
+        if after.is_empty() {
+            return Ok(Some(None));
+        }
+
+        AnchorCursor::decode(after)
+            .map(|cursor| Some(Some(cursor)))
+            .map_err(|e| ApiError::BadRequest(format!("Invalid cursor: {}", e)))
+    }
+
+    /// The validated page bounds, with `limit` clamped to `max_limit`
+    pub fn page(&self, max_limit: i64) -> ApiResult<PageBounds> {
+        PageBounds::new(self.limit, self.offset, max_limit)
+    }
+
+    /// The trimmed search term, or `None` when the listing is unfiltered
+    pub fn search_term(&self) -> Option<&str> {
+        self.q.as_deref().map(str::trim).filter(|q| !q.is_empty())
+    }
+
+    /// The validated sort, or `None` for the default ordering
+    pub fn sort(&self) -> ApiResult<Option<SortSpec>> {
+        SortSpec::parse(
+            self.sort_by.as_deref(),
+            self.order.as_deref(),
+            ANCHOR_SORT_COLUMNS,
+        )
+        .map_err(ApiError::BadRequest)
+    }
+
+    /// The validated volume and success-rate floors; empty when none were given
+    pub fn filters(&self) -> ApiResult<AnchorFilters> {
+        AnchorFilters::new(self.min_volume, self.min_success_rate).map_err(ApiError::BadRequest)
+    }
+}
+
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteAnchorQuery {
+    /// Also delete the anchor's issued assets instead of refusing with 409
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AnchorAssetsQuery {
+    /// Page size, clamped to `MAX_LIST_LIMIT`
     #[serde(default = "default_limit")]
     pub limit: i64,
     #[serde(default)]
     pub offset: i64,
 }
 
-fn default_limit() -> i64 {
+impl AnchorAssetsQuery {
+    /// The validated page bounds, with `limit` clamped to `max_limit`
+    pub fn page(&self, max_limit: i64) -> ApiResult<PageBounds> {
+        PageBounds::new(self.limit, self.offset, max_limit)
+    }
+}
+
+impl Default for AnchorAssetsQuery {
+    /// The first page, as served when no `limit` or `offset` is given
+    fn default() -> Self {
+        Self {
+            limit: default_limit(),
+            offset: 0,
+        }
+    }
+}
+
+pub(crate) fn default_limit() -> i64 {
     50
 }
 
-#[derive(Debug, Serialize)]
+/// A list request's `limit` and `offset` once checked against the maximum page size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageBounds {
+    pub limit: i64,
+    pub offset: i64,
+    /// Whether the requested `limit` was over the maximum and cut down to it
+    pub clamped: bool,
+}
+
+impl PageBounds {
+    /// Reject a negative `limit` or `offset` and clamp `limit` to `max_limit`
+    pub fn new(limit: i64, offset: i64, max_limit: i64) -> ApiResult<Self> {
+        if limit < 0 {
+            return Err(ApiError::BadRequest(format!(
+                "limit cannot be negative, got {}",
+                limit
+            )));
+        }
+        if offset < 0 {
+            return Err(ApiError::BadRequest(format!(
+                "offset cannot be negative, got {}",
+                offset
+            )));
+        }
+        Ok(Self {
+            limit: limit.min(max_limit),
+            offset,
+            clamped: limit > max_limit,
+        })
+    }
+
+    /// The limit served, when it differs from the one requested
+    pub fn clamped_limit(&self) -> Option<i64> {
+        self.clamped.then_some(self.limit)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct ListAnchorsResponse {
     pub anchors: Vec<crate::models::Anchor>,
-    pub total: usize,
+    pub total: i64,
+    /// Whether another page follows this one
+    pub has_more: bool,
+    /// Offset of the following page in offset mode; `null` on the last page
+    pub next_offset: Option<i64>,
+    /// Cursor for the following page in keyset mode; absent on the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Offset of the page after one that started at `offset` and returned
+/// `returned` of `total` rows, if any rows remain
+fn next_offset(offset: i64, returned: usize, total: i64) -> Option<i64> {
+    let end = offset + returned as i64;
+    (end < total).then_some(end)
+}
+
+impl ListAnchorsResponse {
+    /// Build an offset page, with its position taken from `offset` and `total`
+    pub fn offset_page(anchors: Vec<crate::models::Anchor>, total: i64, offset: i64) -> Self {
+        let next_offset = next_offset(offset, anchors.len(), total);
+        Self {
+            anchors,
+            total,
+            has_more: next_offset.is_some(),
+            next_offset,
+            next_cursor: None,
+        }
+    }
+
+    /// Build a keyset page, handing out a cursor only when the page came back full
+    pub fn keyset_page(anchors: Vec<crate::models::Anchor>, total: i64, limit: i64) -> Self {
+        let next_cursor = if anchors.len() as i64 >= limit {
+            anchors
+                .last()
+                .map(|anchor| AnchorCursor::from_anchor(anchor).encode())
+        } else {
+            None
+        };
+
+        Self {
+            anchors,
+            total,
+            has_more: next_cursor.is_some(),
+            next_offset: None,
+            next_cursor,
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListCorridorsQuery {
+    /// Page size, clamped to `MAX_LIST_LIMIT`
     #[serde(default = "default_limit")]
     pub limit: i64,
     #[serde(default)]
     pub offset: i64,
+    /// One of `reliability_score`, `created_at`, `updated_at`
+    #[serde(default)]
+    pub sort_by: Option<String>,
+    /// `asc` (default) or `desc`
+    #[serde(default)]
+    pub order: Option<String>,
+    /// Source asset code, or `CODE:ISSUER`
+    #[serde(default)]
+    pub source_asset: Option<String>,
+    /// Destination asset code, or `CODE:ISSUER`
+    #[serde(default)]
+    pub dest_asset: Option<String>,
+    /// Lowest success rate (0-100) to include
+    #[serde(default)]
+    pub min_success_rate: Option<f64>,
+}
+
+impl ListCorridorsQuery {
+    /// The validated page bounds, with `limit` clamped to `max_limit`
+    pub fn page(&self, max_limit: i64) -> ApiResult<PageBounds> {
+        PageBounds::new(self.limit, self.offset, max_limit)
+    }
+
+    /// The validated, normalized filters; empty when none were given
+    pub fn filters(&self) -> ApiResult<CorridorFilters> {
+        if let Some(rate) = self.min_success_rate {
+            if !(0.0..=100.0).contains(&rate) {
+                return Err(ApiError::BadRequest(format!(
+                    "min_success_rate must be between 0 and 100, got {}",
+                    rate
+                )));
+            }
+        }
+        Ok(CorridorFilters {
+            source_asset: self.source_asset.clone(),
+            dest_asset: self.dest_asset.clone(),
+            min_success_rate: self.min_success_rate,
+        }
+        .normalized())
+    }
+
+    /// The validated sort, or `None` for the default ordering
+    pub fn sort(&self) -> ApiResult<Option<SortSpec>> {
+        SortSpec::parse(
+            self.sort_by.as_deref(),
+            self.order.as_deref(),
+            CORRIDOR_SORT_COLUMNS,
+        )
+        .map_err(ApiError::BadRequest)
+    }
+}
+
+/// Longest window `/api/corridors/:id/history` serves
+pub const MAX_CORRIDOR_HISTORY_HOURS: i64 = 24 * 30;
+
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AssetCorridorsQuery {
+    /// Only match the asset from this issuer; without it every issuer's asset
+    /// with the code matches
+    pub issuer: Option<String>,
+}
+
+impl AssetCorridorsQuery {
+    /// The issuer filter, with a blank value meaning none
+    pub fn issuer(&self) -> Option<&str> {
+        self.issuer
+            .as_deref()
+            .map(str::trim)
+            .filter(|issuer| !issuer.is_empty())
+    }
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CorridorHistoryQuery {
+    /// Hours of snapshots to return, 1 to `MAX_CORRIDOR_HISTORY_HOURS`
+    #[serde(default = "default_history_hours")]
+    pub hours: i64,
+}
+
+fn default_history_hours() -> i64 {
+    24
+}
+
+impl CorridorHistoryQuery {
+    pub fn hours(&self) -> ApiResult<i64> {
+        if !(1..=MAX_CORRIDOR_HISTORY_HOURS).contains(&self.hours) {
+            return Err(ApiError::BadRequest(format!(
+                "hours must be between 1 and {}",
+                MAX_CORRIDOR_HISTORY_HOURS
+            )));
+        }
+        Ok(self.hours)
+    }
 }
 
-#[derive(Debug, Serialize)]
+/// Body of `GET /api/corridors/:id/history`
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CorridorHistoryResponse {
+    /// Volume direction across `snapshots`; see `volume_trend`
+    pub trend: Trend,
+    /// Oldest first
+    pub snapshots: Vec<CorridorMetricsSnapshot>,
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct ListCorridorsResponse {
     pub corridors: Vec<Corridor>,
-    pub total: usize,
+    pub total: i64,
+    /// Whether another page follows this one
+    pub has_more: bool,
+    /// Offset of the following page; `null` on the last page
+    pub next_offset: Option<i64>,
+}
+
+impl ListCorridorsResponse {
+    /// Build a page, with its position taken from `offset` and `total`
+    pub fn page(corridors: Vec<Corridor>, total: i64, offset: i64) -> Self {
+        let next_offset = next_offset(offset, corridors.len(), total);
+        Self {
+            corridors,
+            total,
+            has_more: next_offset.is_some(),
+            next_offset,
+        }
+    }
 }
 
 /// GET /api/anchors - List all anchors with their metrics
@@ -83,10 +470,65 @@ pub async fn list_anchors(
     State(app_state): State<AppState>,
     Query(params): Query<ListAnchorsQuery>,
 ) -> ApiResult<Json<ListAnchorsResponse>> {
-    let anchors = app_state.db.list_anchors(params.limit, params.offset).await?;
-    let total = anchors.len();
+    let page = params.page(app_state.cache_config.max_list_limit)?;
+    if let Some(cursor) = params.cursor()? {
+        let anchors = app_state
+            .db
+            .list_anchors_after(cursor.as_ref(), page.limit, params.include_inactive)
+            .await?;
+        let total = app_state
+            .db
+            .count_listed_anchors(params.include_inactive, &AnchorFilters::default())
+            .await?;
+        return Ok(Json(ListAnchorsResponse::keyset_page(
+            anchors,
+            total,
+            page.limit,
+        )));
+    }
+
+    let sort = params.sort()?;
+    let filters = params.filters()?;
+    let (anchors, total) = match params.search_term() {
+        Some(q) => (
+            app_state
+                .db
+                .search_anchors(
+                    q,
+                    page.limit,
+                    page.offset,
+                    sort.as_ref(),
+                    params.include_inactive,
+                )
+                .await?,
+            app_state
+                .db
+                .count_search_anchors(q, params.include_inactive)
+                .await?,
+        ),
+        None => (
+            app_state
+                .db
+                .list_anchors(
+                    page.limit,
+                    page.offset,
+                    sort.as_ref(),
+                    params.include_inactive,
+                    &filters,
+                )
+                .await?,
+            app_state
+                .db
+                .count_listed_anchors(params.include_inactive, &filters)
+                .await?,
+        ),
+    };
 
-    Ok(Json(ListAnchorsResponse { anchors, total }))
+    Ok(Json(ListAnchorsResponse::offset_page(
+        anchors,
+        total,
+        page.offset,
+    )))
 }
 
 /// GET /api/anchors/:id - Get detailed anchor information
@@ -120,6 +562,80 @@ pub async fn get_anchor_by_account(
     Ok(Json(anchor))
 }
 
+/// Length of a Stellar public key in its `G...` strkey form
+const STELLAR_ACCOUNT_LEN: usize = 56;
+/// Strkey version byte of an ed25519 public key, which puts `G` first
+const STRKEY_ACCOUNT_VERSION: u8 = 6 << 3;
+
+/// Checks `account` is a Stellar public key: 56 base32 characters decoding to
+/// the account version byte, a 32-byte ed25519 key and a CRC16-XModem checksum
+pub fn validate_stellar_account(account: &str) -> ApiResult<()> {
+    let invalid = |reason: &str| {
+        Err(ApiError::BadRequest(format!(
+            "Invalid Stellar account '{}': {}",
+            account, reason
+        )))
+    };
+
+    if account.len() != STELLAR_ACCOUNT_LEN {
+        return invalid(&format!(
+            "expected {} characters, got {}",
+            STELLAR_ACCOUNT_LEN,
+            account.len()
+        ));
+    }
+    if !account.starts_with('G') {
+        return invalid("public keys start with 'G'");
+    }
+    let Some(bytes) = decode_base32(account) else {
+        return invalid("only A-Z and 2-7 are allowed");
+    };
+    let (payload, checksum) = bytes.split_at(bytes.len() - 2);
+    if payload[0] != STRKEY_ACCOUNT_VERSION {
+        return invalid("not an account public key");
+    }
+    if crc16_xmodem(payload) != u16::from_le_bytes([checksum[0], checksum[1]]) {
+        return invalid("checksum mismatch");
+    }
+    Ok(())
+}
+
+/// RFC 4648 base32 without padding; `None` on a character outside the alphabet
+fn decode_base32(input: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(input.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for c in input.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'2'..=b'7' => c - b'2' + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | u32::from(value);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+fn crc16_xmodem(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for &byte in data {
+        crc ^= u16::from(byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
 /// POST /api/anchors - Create a new anchor
 pub async fn create_anchor(
     State(app_state): State<AppState>,
@@ -134,6 +650,7 @@ pub async fn create_anchor(
             "Stellar account cannot be empty".to_string(),
         ));
     }
+    validate_stellar_account(&req.stellar_account)?;
 
     let anchor = app_state.db.create_anchor(req).await?;
 
@@ -144,13 +661,53 @@ pub async fn create_anchor(
 }
 
 /// PUT /api/anchors/:id/metrics - Update anchor metrics
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct UpdateMetricsRequest {
     pub total_transactions: i64,
     pub successful_transactions: i64,
     pub failed_transactions: i64,
     pub avg_settlement_time_ms: Option<i32>,
     pub volume_usd: Option<f64>,
+    /// Apply only if the anchor is still at this `version`, else 409 Conflict
+    #[serde(default)]
+    pub expected_version: Option<i64>,
+}
+
+/// Sanity checks for a metrics update: counts are non-negative and add up, and
+/// the volume and settlement time are real, non-negative numbers
+pub fn validate_metrics(req: &UpdateMetricsRequest) -> ApiResult<()> {
+    let counts = [
+        ("total_transactions", req.total_transactions),
+        ("successful_transactions", req.successful_transactions),
+        ("failed_transactions", req.failed_transactions),
+    ];
+    if let Some((field, count)) = counts.into_iter().find(|(_, count)| *count < 0) {
+        return Err(ApiError::BadRequest(format!(
+            "{} cannot be negative, got {}",
+            field, count
+        )));
+    }
+    let settled = req
+        .successful_transactions
+        .saturating_add(req.failed_transactions);
+    if settled > req.total_transactions {
+        return Err(ApiError::BadRequest(format!(
+            "successful_transactions + failed_transactions ({} + {}) exceeds total_transactions ({})",
+            req.successful_transactions, req.failed_transactions, req.total_transactions
+        )));
+    }
+    if let Some(volume) = req.volume_usd {
+        validate_amount("volume_usd", volume).map_err(ApiError::BadRequest)?;
+    }
+    validate_latency_ms(req.avg_settlement_time_ms).map_err(ApiError::BadRequest)
+}
+
+/// One item of `PUT /api/anchors/metrics:batch`
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct BatchUpdateMetricsItem {
+    pub id: Uuid,
+    #[serde(flatten)]
+    pub metrics: UpdateMetricsRequest,
 }
 
 pub async fn update_anchor_metrics(
@@ -158,6 +715,8 @@ pub async fn update_anchor_metrics(
     Path(id): Path<Uuid>,
     Json(req): Json<UpdateMetricsRequest>,
 ) -> ApiResult<Json<crate::models::Anchor>> {
+    validate_metrics(&req)?;
+
     // Verify anchor exists
     if app_state.db.get_anchor_by_id(id).await?.is_none() {
         return Err(ApiError::NotFound(format!(
@@ -167,14 +726,15 @@ pub async fn update_anchor_metrics(
     }
 
     let anchor = app_state.db
-        .update_anchor_metrics(
-            id,
-            req.total_transactions,
-            req.successful_transactions,
-            req.failed_transactions,
-            req.avg_settlement_time_ms,
-            req.volume_usd,
-        )
+        .update_anchor_metrics_versioned(&AnchorMetricsUpdate {
+            anchor_id: id,
+            total_transactions: req.total_transactions,
+            successful_transactions: req.successful_transactions,
+            failed_transactions: req.failed_transactions,
+            avg_settlement_time_ms: req.avg_settlement_time_ms,
+            volume_usd: req.volume_usd,
+            expected_version: req.expected_version,
+        })
         .await?;
 
     // Broadcast the anchor update to WebSocket clients
@@ -196,13 +756,13 @@ pub async fn get_anchor_assets(
         )));
     }
 
-    let assets = app_state.db.get_assets_by_anchor(id).await?;
+    let assets = app_state.db.get_all_assets_by_anchor(id).await?;
 
     Ok(Json(assets))
 }
 
 /// POST /api/anchors/:id/assets - Add asset to anchor
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct CreateAssetRequest {
     pub asset_code: String,
     pub asset_issuer: String,
@@ -220,6 +780,7 @@ pub async fn create_anchor_asset(
             id
         )));
     }
+    validate_stellar_account(&req.asset_issuer)?;
 
     let asset = app_state.db
         .create_asset(id, req.asset_code, req.asset_issuer)
@@ -237,21 +798,92 @@ pub async fn health_check() -> impl IntoResponse {
     }))
 }
 
+/// How long readiness waits on the database before reporting it down
+const READINESS_DB_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyStatus {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub database: DependencyStatus,
+    /// Reported but never fails readiness: without Redis the cache degrades to memory
+    pub redis: DependencyStatus,
+}
+
+/// GET /healthz - Liveness: 200 whenever the process can answer at all
+pub async fn liveness_check() -> impl IntoResponse {
+    Json(serde_json::json!({ "status": "alive" }))
+}
+
+/// GET /readyz - Readiness: 503 unless the database answers `SELECT 1`
+pub async fn readiness_check(
+    State(app_state): State<AppState>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let database = match tokio::time::timeout(READINESS_DB_TIMEOUT, app_state.db.ping()).await {
+        Ok(Ok(())) => DependencyStatus::Up,
+        Ok(Err(e)) => {
+            tracing::warn!("Readiness check: database unreachable: {}", e);
+            DependencyStatus::Down
+        }
+        Err(_) => {
+            tracing::warn!(
+                "Readiness check: database did not answer within {:?}",
+                READINESS_DB_TIMEOUT
+            );
+            DependencyStatus::Down
+        }
+    };
+    let redis = if app_state.cache.is_redis_connected().await {
+        DependencyStatus::Up
+    } else {
+        DependencyStatus::Down
+    };
+
+    let ready = database == DependencyStatus::Up;
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(ReadinessResponse {
+            ready,
+            database,
+            redis,
+        }),
+    )
+}
+
 /// GET /api/corridors - List all corridors
 pub async fn list_corridors(
     State(app_state): State<AppState>,
     Query(params): Query<ListCorridorsQuery>,
 ) -> ApiResult<Json<ListCorridorsResponse>> {
-    let corridors = app_state.db.list_corridors(params.limit, params.offset).await?;
-    let total = corridors.len();
-    Ok(Json(ListCorridorsResponse { corridors, total }))
+    let page = params.page(app_state.cache_config.max_list_limit)?;
+    let sort = params.sort()?;
+    let filters = params.filters()?;
+    let corridors = app_state
+        .db
+        .list_corridors(page.limit, page.offset, sort.as_ref(), &filters)
+        .await?;
+    let total = app_state.db.count_filtered_corridors(&filters).await?;
+    Ok(Json(ListCorridorsResponse::page(
+        corridors,
+        total,
+        page.offset,
+    )))
 }
 
-/// POST /api/corridors - Create a new corridor
-pub async fn create_corridor(
-    State(app_state): State<AppState>,
-    Json(req): Json<CreateCorridorRequest>,
-) -> ApiResult<Json<Corridor>> {
+/// Shape checks shared by the corridor create endpoints
+pub fn validate_create_corridor(req: &CreateCorridorRequest) -> ApiResult<()> {
     if req.source_asset_code.is_empty() || req.dest_asset_code.is_empty() {
         return Err(ApiError::BadRequest(
             "Asset codes cannot be empty".to_string(),
@@ -262,6 +894,18 @@ pub async fn create_corridor(
             "Asset issuers cannot be empty".to_string(),
         ));
     }
+    for transaction in req.initial_transactions.iter().flatten() {
+        transaction.validate().map_err(ApiError::BadRequest)?;
+    }
+    Ok(())
+}
+
+/// POST /api/corridors - Create a new corridor
+pub async fn create_corridor(
+    State(app_state): State<AppState>,
+    Json(req): Json<CreateCorridorRequest>,
+) -> ApiResult<Json<Corridor>> {
+    validate_create_corridor(&req)?;
     let corridor = app_state.db.create_corridor(req).await?;
     
     // Broadcast the new corridor to WebSocket clients
@@ -271,18 +915,11 @@ pub async fn create_corridor(
 }
 
 /// PUT /api/corridors/:id/metrics-from-transactions - Compute metrics from transactions and persist
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct UpdateCorridorMetricsFromTxns {
     pub transactions: Vec<CorridorTransactionDto>,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct CorridorTransactionDto {
-    pub successful: bool,
-    pub settlement_latency_ms: Option<i32>,
-    pub amount_usd: f64,
-}
-
 pub async fn update_corridor_metrics_from_transactions(
     State(app_state): State<AppState>,
     Path(id): Path<Uuid>,
@@ -298,11 +935,7 @@ pub async fn update_corridor_metrics_from_transactions(
     let txs: Vec<CorridorTransaction> = req
         .transactions
         .into_iter()
-        .map(|t| CorridorTransaction {
-            successful: t.successful,
-            settlement_latency_ms: t.settlement_latency_ms,
-            amount_usd: t.amount_usd,
-        })
+        .map(CorridorTransaction::from)
         .collect();
 
     let metrics = compute_corridor_metrics(&txs, None, 1.0);
@@ -320,3 +953,206 @@ pub async fn ingestion_status(
     let status = app_state.ingestion.get_ingestion_status().await?;
     Ok(Json(status))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Anchor;
+
+    const USDC_ISSUER: &str = "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
+
+    fn rejection(account: &str) -> String {
+        match validate_stellar_account(account) {
+            Err(ApiError::BadRequest(message)) => message,
+            other => panic!("expected BadRequest for {:?}, got {:?}", account, other),
+        }
+    }
+
+    #[test]
+    fn test_valid_stellar_account_is_accepted() {
+        assert!(validate_stellar_account(USDC_ISSUER).is_ok());
+    }
+
+    #[test]
+    fn test_wrong_length_stellar_account_is_rejected() {
+        assert!(rejection("GABC").contains("expected 56 characters, got 4"));
+        assert!(rejection(&format!("{}A", USDC_ISSUER)).contains("got 57"));
+    }
+
+    #[test]
+    fn test_bad_checksum_stellar_account_is_rejected() {
+        let mut typo = USDC_ISSUER[..55].to_string();
+        typo.push('M');
+        assert!(rejection(&typo).contains("checksum mismatch"));
+    }
+
+    #[test]
+    fn test_non_account_strkeys_are_rejected() {
+        let secret_seed = format!("S{}", &USDC_ISSUER[1..]);
+        assert!(rejection(&secret_seed).contains("start with 'G'"));
+        assert!(rejection(&USDC_ISSUER.to_lowercase()).contains("start with 'G'"));
+        let lowercase_body = format!("G{}", USDC_ISSUER[1..].to_lowercase());
+        assert!(rejection(&lowercase_body).contains("only A-Z and 2-7"));
+        // Valid base32, but the version byte isn't an account's
+        let muxed_version = format!("GE{}", &USDC_ISSUER[2..]);
+        assert!(rejection(&muxed_version).contains("not an account public key"));
+    }
+
+    fn anchors(count: usize) -> Vec<Anchor> {
+        (0..count)
+            .map(|i| Anchor {
+                id: format!("anchor-{}", i),
+                name: format!("Anchor {}", i),
+                stellar_account: USDC_ISSUER.to_string(),
+                home_domain: None,
+                total_transactions: 0,
+                successful_transactions: 0,
+                failed_transactions: 0,
+                total_volume_usd: 0.0,
+                avg_settlement_time_ms: 0,
+                reliability_score: 0.0,
+                status: "green".to_string(),
+                is_active: true,
+                last_activity_at: None,
+                version: 0,
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_offset_pages_report_whether_more_follow() {
+        // 25 rows in pages of 10
+        let first = ListAnchorsResponse::offset_page(anchors(10), 25, 0);
+        assert!(first.has_more);
+        assert_eq!(first.next_offset, Some(10));
+
+        let middle = ListAnchorsResponse::offset_page(anchors(10), 25, 10);
+        assert!(middle.has_more);
+        assert_eq!(middle.next_offset, Some(20));
+
+        let last = ListAnchorsResponse::offset_page(anchors(5), 25, 20);
+        assert!(!last.has_more);
+        assert_eq!(last.next_offset, None);
+
+        let past_the_end = ListAnchorsResponse::offset_page(anchors(0), 25, 30);
+        assert!(!past_the_end.has_more);
+        assert_eq!(past_the_end.next_offset, None);
+    }
+
+    #[test]
+    fn test_corridor_page_metadata_is_serialized() {
+        let first = ListCorridorsResponse::page(Vec::new(), 0, 0);
+        assert!(!first.has_more);
+
+        let json = serde_json::to_value(ListCorridorsResponse::page(Vec::new(), 20, 20)).unwrap();
+        assert_eq!(json["has_more"], false);
+        assert!(json["next_offset"].is_null());
+
+        let json = serde_json::to_value(ListCorridorsResponse::page(Vec::new(), 20, 10)).unwrap();
+        assert_eq!(json["next_offset"], 10);
+    }
+
+    #[test]
+    fn test_oversized_limit_is_clamped() {
+        let uri: axum::http::Uri = "/api/anchors?limit=1000000&offset=20".parse().unwrap();
+        let Query(params) = Query::<ListAnchorsQuery>::try_from_uri(&uri).unwrap();
+        let page = params.page(500).unwrap();
+        assert_eq!((page.limit, page.offset), (500, 20));
+        assert_eq!(page.clamped_limit(), Some(500));
+
+        let page = PageBounds::new(500, 0, 500).unwrap();
+        assert_eq!(page.limit, 500);
+        assert_eq!(page.clamped_limit(), None);
+    }
+
+    #[test]
+    fn test_negative_limit_and_offset_are_rejected() {
+        let uri: axum::http::Uri = "/api/corridors?limit=-1".parse().unwrap();
+        let Query(params) = Query::<ListCorridorsQuery>::try_from_uri(&uri).unwrap();
+        assert!(matches!(params.page(500), Err(ApiError::BadRequest(m)) if m.contains("limit")));
+
+        let uri: axum::http::Uri = "/api/corridors?offset=-50".parse().unwrap();
+        let Query(params) = Query::<ListCorridorsQuery>::try_from_uri(&uri).unwrap();
+        assert!(matches!(params.page(500), Err(ApiError::BadRequest(m)) if m.contains("offset")));
+    }
+
+    fn metrics(total: i64, successful: i64, failed: i64) -> UpdateMetricsRequest {
+        UpdateMetricsRequest {
+            total_transactions: total,
+            successful_transactions: successful,
+            failed_transactions: failed,
+            avg_settlement_time_ms: Some(1200),
+            volume_usd: Some(5000.0),
+            expected_version: None,
+        }
+    }
+
+    fn metrics_rejection(req: &UpdateMetricsRequest) -> String {
+        match validate_metrics(req) {
+            Err(ApiError::BadRequest(message)) => message,
+            other => panic!("expected BadRequest for {:?}, got {:?}", req, other),
+        }
+    }
+
+    #[test]
+    fn test_consistent_metrics_are_accepted() {
+        assert!(validate_metrics(&metrics(100, 90, 10)).is_ok());
+        assert!(validate_metrics(&metrics(100, 80, 5)).is_ok());
+        assert!(validate_metrics(&UpdateMetricsRequest {
+            avg_settlement_time_ms: None,
+            volume_usd: None,
+            ..metrics(0, 0, 0)
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn test_negative_counts_are_rejected() {
+        assert_eq!(
+            metrics_rejection(&metrics(-1, 0, 0)),
+            "total_transactions cannot be negative, got -1"
+        );
+        assert_eq!(
+            metrics_rejection(&metrics(10, -2, 0)),
+            "successful_transactions cannot be negative, got -2"
+        );
+        assert_eq!(
+            metrics_rejection(&metrics(10, 0, -3)),
+            "failed_transactions cannot be negative, got -3"
+        );
+    }
+
+    #[test]
+    fn test_outcomes_exceeding_total_are_rejected() {
+        assert_eq!(
+            metrics_rejection(&metrics(100, 95, 10)),
+            "successful_transactions + failed_transactions (95 + 10) exceeds total_transactions (100)"
+        );
+        assert!(metrics_rejection(&metrics(100, i64::MAX, i64::MAX)).contains("exceeds"));
+    }
+
+    #[test]
+    fn test_negative_or_nan_volume_is_rejected() {
+        for volume in [-0.01, f64::NAN, f64::INFINITY] {
+            let req = UpdateMetricsRequest {
+                volume_usd: Some(volume),
+                ..metrics(100, 90, 10)
+            };
+            assert!(metrics_rejection(&req).starts_with("volume_usd must be a non-negative number"));
+        }
+    }
+
+    #[test]
+    fn test_negative_settlement_time_is_rejected() {
+        let req = UpdateMetricsRequest {
+            avg_settlement_time_ms: Some(-5),
+            ..metrics(100, 90, 10)
+        };
+        assert_eq!(
+            metrics_rejection(&req),
+            "Settlement latency cannot be negative, got -5ms"
+        );
+    }
+}
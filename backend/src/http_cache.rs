@@ -0,0 +1,422 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Query},
+    http::{header, request::Parts, HeaderMap, HeaderValue, StatusCode, Uri},
+    response::{IntoResponse, IntoResponseParts, Response, ResponseParts},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::convert::Infallible;
+use std::ops::Deref;
+
+use crate::cache::CacheStatus;
+use crate::handlers::ApiError;
+use crate::state::AppState;
+
+/// Debug header naming the cache tier that served a response
+pub const X_CACHE: &str = "x-cache";
+/// Seconds since the served value was computed, so clients can show "data as of"
+pub const X_CACHE_AGE: &str = "x-cache-age";
+/// Set on list responses whose `limit` was over the maximum, to the limit served
+pub const X_LIMIT_CLAMPED: &str = "x-limit-clamped";
+/// Set on list responses to the body's `total`, for table UIs that read the
+/// row count from a header
+pub const X_TOTAL_COUNT: &str = "x-total-count";
+/// Request header letting a client retry a create without inserting twice
+pub const IDEMPOTENCY_KEY: &str = "idempotency-key";
+/// Longest `Idempotency-Key` accepted
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// JSON response carrying HTTP caching headers. It sets
+/// `Cache-Control: public, max-age=<ttl>` and a weak `ETag` derived from the
+/// serialized body. When the request's `If-None-Match` already names that ETag,
+/// it answers `304 Not Modified` with an empty body instead.
+#[derive(Debug)]
+pub struct CachedJson<T> {
+    value: T,
+    max_age: usize,
+    if_none_match: Option<String>,
+    cache_status: Option<CacheStatus>,
+    cache_age: Option<i64>,
+    limit_clamped: Option<i64>,
+    total_count: Option<i64>,
+}
+
+impl<T> CachedJson<T> {
+    pub fn new(value: T, max_age: usize, request_headers: &HeaderMap) -> Self {
+        Self {
+            value,
+            max_age,
+            if_none_match: request_headers
+                .get(header::IF_NONE_MATCH)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string),
+            cache_status: None,
+            cache_age: None,
+            limit_clamped: None,
+            total_count: None,
+        }
+    }
+
+    /// Report `status` in an `X-Cache` header; `None` leaves the header off
+    pub fn with_cache_status(mut self, status: Option<CacheStatus>) -> Self {
+        self.cache_status = status;
+        self
+    }
+
+    /// Report how old the value `status` describes is in an `X-Cache-Age`
+    /// header; left off for hits whose write time is unknown
+    pub fn with_cache_age(mut self, status: CacheStatus) -> Self {
+        self.cache_age = status.age_secs(Utc::now());
+        self
+    }
+
+    /// Report in an `X-Limit-Clamped` header that the page was cut to `limit`;
+    /// `None` leaves the header off
+    pub fn with_limit_clamped(mut self, limit: Option<i64>) -> Self {
+        self.limit_clamped = limit;
+        self
+    }
+
+    /// Report a list's `total` in an `X-Total-Count` header
+    pub fn with_total_count(mut self, total: i64) -> Self {
+        self.total_count = Some(total);
+        self
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for CachedJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: Serialize> IntoResponse for CachedJson<T> {
+    fn into_response(self) -> Response {
+        let body = match serde_json::to_vec(&self.value) {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::error!("Failed to serialize response body: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+
+        let etag = weak_etag(&body);
+        let cache_control = format!("public, max-age={}", self.max_age);
+        let mut headers = HeaderMap::new();
+        if let Ok(value) = HeaderValue::from_str(&etag) {
+            headers.insert(header::ETAG, value);
+        }
+        if let Ok(value) = HeaderValue::from_str(&cache_control) {
+            headers.insert(header::CACHE_CONTROL, value);
+        }
+        if let Some(status) = self.cache_status {
+            headers.insert(X_CACHE, HeaderValue::from_static(status.header_value()));
+        }
+        if let Some(age) = self.cache_age {
+            headers.insert(X_CACHE_AGE, HeaderValue::from(age));
+        }
+        if let Some(limit) = self.limit_clamped {
+            headers.insert(X_LIMIT_CLAMPED, HeaderValue::from(limit));
+        }
+        if let Some(total) = self.total_count {
+            headers.insert(X_TOTAL_COUNT, HeaderValue::from(total));
+        }
+
+        if self
+            .if_none_match
+            .as_deref()
+            .is_some_and(|candidates| etag_matches(candidates, &etag))
+        {
+            return (StatusCode::NOT_MODIFIED, headers).into_response();
+        }
+
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        (StatusCode::OK, headers, body).into_response()
+    }
+}
+
+/// `X-Total-Count` for list handlers answering with plain `Json`, returned
+/// alongside the body as `(TotalCount(total), Json(body))`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TotalCount(pub i64);
+
+impl IntoResponseParts for TotalCount {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Infallible> {
+        res.headers_mut()
+            .insert(X_TOTAL_COUNT, HeaderValue::from(self.0));
+        Ok(res)
+    }
+}
+
+/// `?no_cache=true` on a cached GET: skip the cache read, load from the
+/// database and write the fresh value back for everyone else. Ignored unless
+/// `ALLOW_CACHE_BYPASS` is set, so it can't be used to hammer the database.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheBypass(pub bool);
+
+#[derive(Debug, Default, Deserialize)]
+struct BypassParams {
+    #[serde(default)]
+    no_cache: bool,
+}
+
+impl CacheBypass {
+    /// Whether `uri` asks to bypass the cache and `allowed` permits it
+    pub fn from_uri(uri: &Uri, allowed: bool) -> Self {
+        let requested = Query::<BypassParams>::try_from_uri(uri)
+            .map(|Query(params)| params.no_cache)
+            .unwrap_or_default();
+        if requested && !allowed {
+            tracing::debug!(
+                "Ignoring no_cache on {}: ALLOW_CACHE_BYPASS is off",
+                uri.path()
+            );
+        }
+        Self(requested && allowed)
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for CacheBypass {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Infallible> {
+        Ok(Self::from_uri(&parts.uri, state.cache_config.allow_bypass))
+    }
+}
+
+/// Optional `Idempotency-Key` request header. A missing or blank header is
+/// `None`; one that isn't printable ASCII or is longer than
+/// `MAX_IDEMPOTENCY_KEY_LEN` is rejected with 400.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdempotencyKey(pub Option<String>);
+
+impl IdempotencyKey {
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self, ApiError> {
+        let Some(value) = headers.get(IDEMPOTENCY_KEY) else {
+            return Ok(Self(None));
+        };
+        let key = value
+            .to_str()
+            .map_err(|_| ApiError::BadRequest("Idempotency-Key must be ASCII".to_string()))?
+            .trim();
+        if key.is_empty() {
+            return Ok(Self(None));
+        }
+        if key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+            return Err(ApiError::BadRequest(format!(
+                "Idempotency-Key must be at most {} characters",
+                MAX_IDEMPOTENCY_KEY_LEN
+            )));
+        }
+        Ok(Self(Some(key.to_string())))
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IdempotencyKey {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, ApiError> {
+        Self::from_headers(&parts.headers)
+    }
+}
+
+/// Weak validator over the exact response bytes
+fn weak_etag(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    format!("W/\"{}\"", hex::encode(&digest[..16]))
+}
+
+/// Weak comparison of an `If-None-Match` list against `etag`
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    if_none_match
+        .split(',')
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers_with_etag(etag: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(etag).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_sets_etag_and_cache_control() {
+        let response = CachedJson::new(vec![1, 2, 3], 600, &HeaderMap::new()).into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "public, max-age=600"
+        );
+        assert!(response.headers()[header::ETAG]
+            .to_str()
+            .unwrap()
+            .starts_with("W/\""));
+    }
+
+    #[test]
+    fn test_total_count_header_is_only_set_when_given() {
+        let with_total = CachedJson::new("page", 60, &HeaderMap::new())
+            .with_total_count(42)
+            .into_response();
+        assert_eq!(with_total.headers()[X_TOTAL_COUNT], "42");
+
+        let without = CachedJson::new("page", 60, &HeaderMap::new()).into_response();
+        assert!(!without.headers().contains_key(X_TOTAL_COUNT));
+
+        let plain = (TotalCount(7), "body").into_response();
+        assert_eq!(plain.headers()[X_TOTAL_COUNT], "7");
+    }
+
+    #[test]
+    fn test_matching_if_none_match_returns_not_modified() {
+        let first = CachedJson::new("payload", 60, &HeaderMap::new()).into_response();
+        let etag = first.headers()[header::ETAG].to_str().unwrap().to_string();
+
+        let second = CachedJson::new("payload", 60, &headers_with_etag(&etag)).into_response();
+        assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(second.headers()[header::ETAG], etag.as_str());
+
+        let changed = CachedJson::new("changed", 60, &headers_with_etag(&etag)).into_response();
+        assert_eq!(changed.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_etag_matching_is_weak_and_list_aware() {
+        assert!(etag_matches("\"abc\"", "W/\"abc\""));
+        assert!(etag_matches("W/\"zzz\", W/\"abc\"", "W/\"abc\""));
+        assert!(etag_matches("*", "W/\"abc\""));
+        assert!(!etag_matches("W/\"abd\"", "W/\"abc\""));
+    }
+
+    #[test]
+    fn test_cache_status_header_is_opt_in() {
+        let plain = CachedJson::new(1, 60, &HeaderMap::new()).into_response();
+        assert!(plain.headers().get(X_CACHE).is_none());
+
+        let debug = CachedJson::new(1, 60, &HeaderMap::new())
+            .with_cache_status(Some(CacheStatus::Miss))
+            .into_response();
+        assert_eq!(debug.headers()[X_CACHE], "MISS");
+    }
+
+    #[test]
+    fn test_limit_clamped_header_only_when_clamped() {
+        let unclamped = CachedJson::new(1, 60, &HeaderMap::new())
+            .with_limit_clamped(None)
+            .into_response();
+        assert!(unclamped.headers().get(X_LIMIT_CLAMPED).is_none());
+
+        let clamped = CachedJson::new(1, 60, &HeaderMap::new())
+            .with_limit_clamped(Some(500))
+            .into_response();
+        assert_eq!(clamped.headers()[X_LIMIT_CLAMPED], "500");
+    }
+
+    #[test]
+    fn test_cache_bypass_requires_flag_and_permission() {
+        let uri = |s: &str| s.parse::<Uri>().unwrap();
+        assert_eq!(
+            CacheBypass::from_uri(&uri("/api/anchors?no_cache=true&limit=5"), true),
+            CacheBypass(true)
+        );
+        assert_eq!(
+            CacheBypass::from_uri(&uri("/api/anchors?no_cache=true"), false),
+            CacheBypass(false)
+        );
+        assert_eq!(
+            CacheBypass::from_uri(&uri("/api/anchors?limit=5"), true),
+            CacheBypass(false)
+        );
+        assert_eq!(
+            CacheBypass::from_uri(&uri("/api/anchors?no_cache=maybe"), true),
+            CacheBypass(false)
+        );
+    }
+
+    #[test]
+    fn test_idempotency_key_header() {
+        let with_key = |key: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(IDEMPOTENCY_KEY, HeaderValue::from_str(key).unwrap());
+            IdempotencyKey::from_headers(&headers)
+        };
+
+        assert_eq!(
+            IdempotencyKey::from_headers(&HeaderMap::new()).unwrap(),
+            IdempotencyKey(None)
+        );
+        assert_eq!(with_key("  ").unwrap(), IdempotencyKey(None));
+        assert_eq!(
+            with_key(" retry-42 ").unwrap(),
+            IdempotencyKey(Some("retry-42".to_string()))
+        );
+        assert!(matches!(
+            with_key(&"k".repeat(MAX_IDEMPOTENCY_KEY_LEN + 1)),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cache_age_grows_between_reads_of_the_same_key() {
+        let cache = crate::cache::RedisCache::from_url("redis://127.0.0.1:1")
+            .await
+            .unwrap();
+        let read = || async {
+            let (value, status) = cache
+                .get_or_set_with_status("dashboard:stats", 60, 0.0, || async {
+                    Ok::<_, anyhow::Error>(1u32)
+                })
+                .await
+                .unwrap();
+            let response = CachedJson::new(value, 60, &HeaderMap::new())
+                .with_cache_age(status)
+                .into_response();
+            response.headers()[X_CACHE_AGE]
+                .to_str()
+                .unwrap()
+                .parse::<i64>()
+                .unwrap()
+        };
+
+        let first = read().await;
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        let second = read().await;
+
+        assert_eq!(first, 0);
+        assert!(second > first, "{} then {}", first, second);
+    }
+
+    #[test]
+    fn test_unknown_cache_age_leaves_header_off() {
+        let legacy_hit = CacheStatus::Hit {
+            tier: crate::cache::CacheTier::Redis,
+            cached_at: None,
+        };
+        let response = CachedJson::new(1, 60, &HeaderMap::new())
+            .with_cache_age(legacy_hit)
+            .into_response();
+        assert!(response.headers().get(X_CACHE_AGE).is_none());
+    }
+}
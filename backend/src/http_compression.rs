@@ -0,0 +1,180 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use flate2::{write::GzEncoder, Compression};
+use std::io::Write;
+
+/// Bodies smaller than this go out uncompressed; gzip framing would eat most
+/// of the saving
+pub const GZIP_MIN_BYTES: usize = 1024;
+
+/// Middleware gzipping `200` JSON responses on the HTTP hop for clients that
+/// send `Accept-Encoding: gzip`. This is separate from the cache's at-rest
+/// compression. `304`s, errors, bodies under `GZIP_MIN_BYTES` and responses
+/// that already carry a `Content-Encoding` are passed through untouched.
+pub async fn gzip_json_response(req: Request, next: Next) -> Response {
+    let accepts_gzip = accepts_gzip(req.headers());
+    let response = next.run(req).await;
+
+    if response.status() != StatusCode::OK
+        || !is_json(response.headers())
+        || response.headers().contains_key(header::CONTENT_ENCODING)
+    {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    // Whether or not this client gets gzip, the body depends on Accept-Encoding
+    parts
+        .headers
+        .append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    if !accepts_gzip {
+        return Response::from_parts(parts, body);
+    }
+
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Failed to buffer response body for compression: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    if bytes.len() < GZIP_MIN_BYTES {
+        return Response::from_parts(parts, Body::from(bytes));
+    }
+
+    match gzip(&bytes) {
+        Ok(compressed) => {
+            parts
+                .headers
+                .insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(compressed))
+        }
+        Err(e) => {
+            tracing::warn!(
+                "Failed to gzip response body, sending it uncompressed: {}",
+                e
+            );
+            Response::from_parts(parts, Body::from(bytes))
+        }
+    }
+}
+
+/// Whether an `Accept-Encoding` header allows gzip: listed (or `*`) without `q=0`
+fn accepts_gzip(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|coding| {
+            let mut params = coding.split(';').map(str::trim);
+            let name = params.next().unwrap_or_default();
+            let quality = params
+                .find_map(|param| param.strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.parse::<f32>().ok());
+            (name.eq_ignore_ascii_case("gzip") || name == "*") && quality.is_some_and(|q| q > 0.0)
+        })
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
+
+fn gzip(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::with_capacity(bytes.len() / 4), Compression::fast());
+    encoder.write_all(bytes)?;
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Json, Router};
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/large",
+                get(|| async {
+                    let items: Vec<_> = (0..500)
+                        .map(|i| serde_json::json!({ "id": i, "name": format!("Anchor {}", i) }))
+                        .collect();
+                    Json(serde_json::json!({ "anchors": items, "total": 500 }))
+                }),
+            )
+            .route(
+                "/small",
+                get(|| async { Json(serde_json::json!({ "total": 0 })) }),
+            )
+            .route("/not-modified", get(|| async { StatusCode::NOT_MODIFIED }))
+            .layer(middleware::from_fn(gzip_json_response))
+    }
+
+    async fn get_with(path: &str, accept_encoding: Option<&str>) -> Response {
+        let mut request = Request::builder().uri(path);
+        if let Some(accept_encoding) = accept_encoding {
+            request = request.header(header::ACCEPT_ENCODING, accept_encoding);
+        }
+        app()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_large_json_is_gzipped_when_accepted() {
+        let response = get_with("/large", Some("br;q=1.0, gzip;q=0.8")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(response.headers()[header::VARY], "accept-encoding");
+
+        let compressed = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let mut json = String::new();
+        GzDecoder::new(&compressed[..])
+            .read_to_string(&mut json)
+            .unwrap();
+        assert!(compressed.len() < json.len());
+        let body: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(body["total"], 500);
+    }
+
+    #[tokio::test]
+    async fn test_uncompressed_without_gzip_in_accept_encoding() {
+        for accept_encoding in [None, Some("br"), Some("gzip;q=0")] {
+            let response = get_with("/large", accept_encoding).await;
+            assert!(
+                !response.headers().contains_key(header::CONTENT_ENCODING),
+                "{:?}",
+                accept_encoding
+            );
+            assert_eq!(response.headers()[header::VARY], "accept-encoding");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_small_bodies_and_304s_are_not_compressed() {
+        let small = get_with("/small", Some("gzip")).await;
+        assert!(!small.headers().contains_key(header::CONTENT_ENCODING));
+        let body = to_bytes(small.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], br#"{"total":0}"#);
+
+        let not_modified = get_with("/not-modified", Some("gzip")).await;
+        assert_eq!(not_modified.status(), StatusCode::NOT_MODIFIED);
+        assert!(!not_modified
+            .headers()
+            .contains_key(header::CONTENT_ENCODING));
+    }
+}
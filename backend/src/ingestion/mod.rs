@@ -2,11 +2,13 @@
 pub mod ledger;
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 use std::sync::Arc;
 use tracing::{info, warn};
 
-use crate::database::Database;
+use crate::database::{AnchorFilters, Database};
+use crate::models::Anchor;
 use crate::rpc::StellarRpcClient;
 
 pub struct DataIngestionService {
@@ -33,10 +35,13 @@ impl DataIngestionService {
     pub async fn sync_anchor_metrics(&self) -> Result<()> {
         info!("Syncing anchor metrics from Stellar network");
 
-        let anchors = self.db.list_anchors(0, 100).await?;
+        let anchors = self
+            .db
+            .list_anchors(0, 100, None, false, &AnchorFilters::default())
+            .await?;
 
         for anchor in anchors {
-            match self.process_anchor_metrics(&anchor.stellar_account).await {
+            match self.process_anchor_metrics(&anchor).await {
                 Ok(_) => info!("Updated metrics for anchor: {}", anchor.name),
                 Err(e) => warn!("Failed to update anchor {}: {}", anchor.name, e),
             }
@@ -46,7 +51,8 @@ impl DataIngestionService {
     }
 
     /// Process metrics for a single anchor
-    async fn process_anchor_metrics(&self, account_id: &str) -> Result<()> {
+    async fn process_anchor_metrics(&self, anchor: &Anchor) -> Result<()> {
+        let account_id = anchor.stellar_account.as_str();
         let payments = self
             .rpc_client
             .fetch_account_payments(account_id, 100)
@@ -105,6 +111,18 @@ impl DataIngestionService {
             })
             .await?;
 
+        // Payments whose timestamp doesn't parse still prove the anchor is active
+        let last_activity = payments
+            .iter()
+            .filter_map(|payment| DateTime::parse_from_rfc3339(&payment.created_at).ok())
+            .map(|ts| ts.with_timezone(&Utc))
+            .max()
+            .unwrap_or_else(Utc::now);
+        let anchor_id = uuid::Uuid::parse_str(&anchor.id).context("Invalid anchor id")?;
+        self.db
+            .touch_anchor_last_seen(anchor_id, last_activity)
+            .await?;
+
         Ok(())
     }
 
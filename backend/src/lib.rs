@@ -3,18 +3,29 @@ pub mod api;
 pub mod auth;
 pub mod auth_middleware;
 pub mod broadcast;
+pub mod cache;
+pub mod cache_invalidation;
+pub mod cache_warmer;
+pub mod cached_handlers;
+pub mod http_cache;
+pub mod http_compression;
 pub mod database;
+pub mod events;
 pub mod db;
 pub mod handlers;
 pub mod ingestion;
 pub mod ml;
 pub mod ml_handlers;
 pub mod models;
+pub mod openapi;
 pub mod services;
 pub mod snapshot;
 pub mod rate_limit;
+pub mod request_id;
+pub mod routes;
 pub mod snapshot_handlers;
 pub mod state;
+pub mod statsd;
 pub mod websocket;
 
 pub mod rpc;
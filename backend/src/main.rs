@@ -1,6 +1,7 @@
 use anyhow::Result;
 use axum::{
-    routing::{get, put, post},
+    http::HeaderName,
+    routing::get,
     Router,
 };
 use dotenv::dotenv;
@@ -8,18 +9,23 @@ use std::sync::Arc;
 use tower_http::cors::{Any, CorsLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use stellar_insights_backend::api::anchors::get_anchors;
-use stellar_insights_backend::api::corridors::{get_corridor_detail, list_corridors};
 use stellar_insights_backend::api::metrics;
 use stellar_insights_backend::auth::AuthService;
 use stellar_insights_backend::auth_middleware::auth_middleware;
-use stellar_insights_backend::database::Database;
-use stellar_insights_backend::handlers::*;
+use stellar_insights_backend::cache::{CacheConfig, CacheKey, RedisCache};
+use stellar_insights_backend::cache_warmer::CacheWarmer;
+use stellar_insights_backend::database::{corridor_history_retention_days_from_env, Database};
 use stellar_insights_backend::ingestion::DataIngestionService;
 use stellar_insights_backend::rpc::StellarRpcClient;
 use stellar_insights_backend::rpc_handlers;
+use stellar_insights_backend::services::analytics::AnomalyThresholds;
+use stellar_insights_backend::http_cache::X_TOTAL_COUNT;
+use stellar_insights_backend::http_compression::gzip_json_response;
 use stellar_insights_backend::rate_limit::{RateLimiter, RateLimitConfig, rate_limit_middleware};
+use stellar_insights_backend::request_id::{request_id, X_REQUEST_ID};
+use stellar_insights_backend::routes::{protected_routes, public_routes};
 use stellar_insights_backend::state::AppState;
+use stellar_insights_backend::statsd::StatsdExporter;
 use stellar_insights_backend::websocket::{ws_handler, WsState};
 
 
@@ -94,11 +100,18 @@ async fn main() -> Result<()> {
         Arc::clone(&db),
     ));
 
+    // Initialize response cache (falls back to memory when Redis is unavailable)
+    let cache = Arc::new(RedisCache::new().await?);
+    tracing::info!("Cache initialized");
+
     // Create shared app state
     let app_state = AppState::new(
         Arc::clone(&db),
         Arc::clone(&ws_state),
         Arc::clone(&ingestion_service),
+        Arc::clone(&cache),
+        CacheConfig::from_env(),
+        AnomalyThresholds::from_env(),
     );
 
     // Ledger Ingestion initialization (commented out)
@@ -112,13 +125,77 @@ async fn main() -> Result<()> {
 
 
     // Start background sync task (metrics)
+    // Only one replica syncs per tick; the TTL frees the lock if its holder dies mid-run
     let ingestion_clone = Arc::clone(&ingestion_service);
+    let ingestion_cache = Arc::clone(&cache);
+    let ingestion_invalidation = Arc::clone(&app_state.cache_invalidation);
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(std::time::Duration::from_secs(300)); // 5 minutes
         loop {
             interval.tick().await;
-            if let Err(e) = ingestion_clone.sync_all_metrics().await {
-                tracing::error!("Metrics synchronization failed: {}", e);
+            let lock = match ingestion_cache
+                .try_lock(&CacheKey::metrics_ingestion_lock(), 600)
+                .await
+            {
+                Ok(Some(lock)) => Some(lock),
+                Ok(None) => {
+                    tracing::info!("Metrics synchronization running on another replica, skipping");
+                    continue;
+                }
+                Err(e) => {
+                    // Without Redis there's no one to coordinate with
+                    tracing::warn!("Syncing metrics without the ingestion lock: {}", e);
+                    None
+                }
+            };
+            match ingestion_clone.sync_all_metrics().await {
+                Ok(()) => {
+                    if let Err(e) = ingestion_invalidation.on_metrics_ingestion_complete().await {
+                        tracing::warn!("Failed to invalidate caches after ingestion: {}", e);
+                    }
+                }
+                Err(e) => tracing::error!("Metrics synchronization failed: {}", e),
+            }
+            if let Some(lock) = lock {
+                if let Err(e) = lock.release().await {
+                    tracing::warn!("Failed to release the ingestion lock: {}", e);
+                }
+            }
+        }
+    });
+
+    // Optionally pre-load the hottest cache keys so cold starts don't hit the database
+    let warm_on_start = std::env::var("CACHE_WARM_ON_START")
+        .unwrap_or_else(|_| "false".to_string())
+        .parse::<bool>()
+        .unwrap_or(false);
+    if warm_on_start {
+        let (db, cache, config) = (
+            Arc::clone(&db),
+            Arc::clone(&cache),
+            app_state.cache_config.clone(),
+        );
+        tokio::spawn(async move {
+            CacheWarmer::warm(db, cache, config).await;
+        });
+    }
+
+    // Push cache metrics to a StatsD agent when STATSD_ADDR is set
+    StatsdExporter::spawn_from_env(Arc::clone(&cache)).await;
+
+    // Drop corridor metrics snapshots past their retention once an hour
+    let retention_days = corridor_history_retention_days_from_env();
+    let db_clone = Arc::clone(&db);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            match db_clone.prune_corridor_metrics_snapshots(retention_days).await {
+                Ok(pruned) if pruned > 0 => {
+                    tracing::info!("Pruned {} corridor metrics snapshots older than {} days", pruned, retention_days);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to prune corridor metrics snapshots: {}", e),
             }
         }
     });
@@ -186,45 +263,37 @@ async fn main() -> Result<()> {
     tracing::info!("Running initial metrics synchronization...");
     let _ = ingestion_service.sync_all_metrics().await;
 
-    // Initialize rate limiter
-    let rate_limiter_result = RateLimiter::new().await;
-    let rate_limiter = match rate_limiter_result {
-        Ok(limiter) => {
-            tracing::info!("Rate limiter initialized successfully");
-            Arc::new(limiter)
-        },
-        Err(e) => {
-            tracing::warn!("Failed to initialize Redis rate limiter, creating with memory fallback: {}", e);
-            // Create a rate limiter that will use memory store only
-            Arc::new(RateLimiter::new().await.unwrap_or_else(|_| {
-                panic!("Failed to create rate limiter: critical error")
-            }))
-        }
-    };
+    // Initialize rate limiter (counts in the cache's Redis, memory when it is down)
+    let rate_limiter = Arc::new(RateLimiter::new(Arc::clone(&cache)));
 
     // Configure rate limits for endpoints
-    rate_limiter.register_endpoint("/health".to_string(), RateLimitConfig {
-        requests_per_minute: 1000, // Health checks can be more frequent
-        whitelist_ips: vec!["127.0.0.1".to_string()],
-    }).await;
+    for probe in ["/health", "/healthz", "/readyz"] {
+        rate_limiter.register_endpoint(probe.to_string(), RateLimitConfig {
+            requests_per_minute: 1000, // Health checks can be more frequent
+            whitelist_ips: vec!["127.0.0.1".to_string()],
+        }).await;
+    }
 
-    rate_limiter.register_endpoint("/api/anchors".to_string(), RateLimitConfig {
-        requests_per_minute: 100,
-        whitelist_ips: vec![],
-    }).await;
+    for listing in ["/api/anchors", "/api/v2/anchors", "/api/corridors", "/api/v2/corridors"] {
+        rate_limiter.register_endpoint(listing.to_string(), RateLimitConfig {
+            requests_per_minute: 100,
+            whitelist_ips: vec![],
+        }).await;
+    }
 
-    rate_limiter.register_endpoint("/api/corridors".to_string(), RateLimitConfig {
+    rate_limiter.register_endpoint("/api/rpc/payments".to_string(), RateLimitConfig {
         requests_per_minute: 100,
         whitelist_ips: vec![],
     }).await;
 
-    rate_limiter.register_endpoint("/api/rpc/payments".to_string(), RateLimitConfig {
+    rate_limiter.register_endpoint("/api/rpc/trades".to_string(), RateLimitConfig {
         requests_per_minute: 100,
         whitelist_ips: vec![],
     }).await;
 
-    rate_limiter.register_endpoint("/api/rpc/trades".to_string(), RateLimitConfig {
-        requests_per_minute: 100,
+    // Flushing the cache is expensive for every reader afterwards
+    rate_limiter.register_endpoint("/api/cache/clear".to_string(), RateLimitConfig {
+        requests_per_minute: 5,
         whitelist_ips: vec![],
     }).await;
 
@@ -232,7 +301,11 @@ async fn main() -> Result<()> {
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
-        .allow_headers(Any);
+        .allow_headers(Any)
+        .expose_headers([
+            HeaderName::from_static(X_TOTAL_COUNT),
+            HeaderName::from_static(X_REQUEST_ID),
+        ]);
 
     // Import middleware
     use tower::ServiceBuilder;
@@ -242,17 +315,7 @@ async fn main() -> Result<()> {
     let auth_routes = stellar_insights_backend::api::auth::routes(auth_service.clone());
 
     // Build anchor router with protected write endpoints
-    let anchor_routes = Router::new()
-        .route("/health", get(health_check))
-        .route("/api/anchors", get(get_anchors))
-        .route("/api/anchors/:id", get(get_anchor))
-        .route(
-            "/api/anchors/account/:stellar_account",
-            get(get_anchor_by_account),
-        )
-        .route("/api/anchors/:id/assets", get(get_anchor_assets))
-        .route("/api/corridors", get(list_corridors))
-        .route("/api/corridors/:corridor_key", get(get_corridor_detail))
+    let anchor_routes = public_routes()
         // .route("/api/ingestion/status", get(ingestion_status)) // Commented out due to missing handlers
         .with_state(app_state.clone())
         .layer(
@@ -261,19 +324,12 @@ async fn main() -> Result<()> {
                     rate_limiter.clone(),
                     rate_limit_middleware,
                 ))
+                .layer(middleware::from_fn(gzip_json_response))
         )
         .layer(cors.clone());
 
     // Build protected anchor routes (require authentication)
-    let protected_anchor_routes = Router::new()
-        .route("/api/anchors", axum::routing::post(create_anchor))
-        .route("/api/anchors/:id/metrics", put(update_anchor_metrics))
-        .route("/api/anchors/:id/assets", axum::routing::post(create_anchor_asset))
-        .route("/api/corridors", axum::routing::post(create_corridor))
-        .route(
-            "/api/corridors/:id/metrics-from-transactions",
-            put(update_corridor_metrics_from_transactions),
-        )
+    let protected_anchor_routes = protected_routes()
         .with_state(app_state.clone())
         .layer(
             ServiceBuilder::new()
@@ -322,7 +378,9 @@ async fn main() -> Result<()> {
         .merge(protected_anchor_routes)
         .merge(rpc_routes)
         .merge(metrics::routes())
-        .merge(ws_routes);
+        .merge(ws_routes)
+        // Outermost, so everything a request logs sits inside its span
+        .layer(middleware::from_fn(request_id));
 
     // Start server
     let host = std::env::var("SERVER_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
@@ -334,7 +392,42 @@ async fn main() -> Result<()> {
     axum::serve(
         listener, 
         app.into_make_service_with_connect_info::<std::net::SocketAddr>()
-    ).await?;
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await?;
+
+    // Requests have drained; let in-flight cache refreshes and writes finish
+    cache.shutdown().await;
 
     Ok(())
 }
+
+/// Resolves on Ctrl-C or, on Unix, SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    tracing::info!("Shutdown signal received, draining requests");
+}
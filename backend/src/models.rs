@@ -9,8 +9,10 @@ pub enum SortBy {
     #[default]
     SuccessRate,
     Volume,
+    HealthScore,
+    Liquidity,
 }
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, utoipa::ToSchema)]
 pub struct Anchor {
     pub id: String,
     pub name: String,
@@ -23,11 +25,25 @@ pub struct Anchor {
     pub avg_settlement_time_ms: i32,
     pub reliability_score: f64,
     pub status: String,
+    /// `false` once deactivated; the anchor is then left out of default listings
+    #[serde(default = "default_is_active")]
+    pub is_active: bool,
+    /// Most recent transaction seen during ingestion; `None` until the first
+    #[serde(default)]
+    pub last_activity_at: Option<DateTime<Utc>>,
+    /// Bumped by every metrics update; send it back as `expected_version` to
+    /// update only if nobody else has since
+    #[serde(default)]
+    pub version: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+fn default_is_active() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, utoipa::ToSchema)]
 pub struct Asset {
     pub id: String,
     pub anchor_id: String,
@@ -39,7 +55,25 @@ pub struct Asset {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+/// Totals over every payment recorded in one asset
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+pub struct AssetMetrics {
+    pub asset_code: String,
+    pub asset_issuer: String,
+    pub total_transactions: i64,
+    pub successful_transactions: i64,
+    pub failed_transactions: i64,
+    /// Successful transactions as a percentage of all transactions
+    pub success_rate: f64,
+    /// Summed amount of successful payments, in units of the asset
+    pub volume: f64,
+    pub avg_settlement_latency_ms: Option<i32>,
+    pub median_settlement_latency_ms: Option<i32>,
+    pub p95_settlement_latency_ms: Option<i32>,
+    pub p99_settlement_latency_ms: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, utoipa::ToSchema)]
 pub struct AnchorMetricsHistory {
     pub id: String,
     pub anchor_id: String,
@@ -101,11 +135,33 @@ pub struct AnchorWithAssets {
     pub assets: Vec<Asset>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct AnchorDetailResponse {
     pub anchor: Anchor,
     pub assets: Vec<Asset>,
     pub metrics_history: Vec<AnchorMetricsHistory>,
+    /// 0–100 score from `compute_anchor_reliability`
+    #[serde(default)]
+    pub reliability: f64,
+    /// Copied from `anchor.last_activity_at`
+    #[serde(default)]
+    pub last_activity_at: Option<DateTime<Utc>>,
+    /// No activity for `ANCHOR_STALE_AFTER_DAYS`; see `is_anchor_stale`
+    #[serde(default)]
+    pub is_stale: bool,
+    /// Copied from `anchor.version`
+    #[serde(default)]
+    pub version: i64,
+}
+
+/// Network-wide totals shown on the dashboard
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, sqlx::FromRow, utoipa::ToSchema)]
+pub struct DashboardStats {
+    pub total_anchors: i64,
+    pub total_corridors: i64,
+    /// Successful transactions as a percentage of all anchor transactions
+    pub overall_success_rate: f64,
+    pub total_volume_usd: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -117,10 +173,72 @@ pub struct CorridorRecord {
     pub destination_asset_issuer: String,
     pub reliability_score: f64,
     pub status: String,
+    /// Share of attempted USD volume that settled; `None` until a metrics run records it
+    #[sqlx(default)]
+    pub volume_weighted_success_rate: Option<f64>,
+    #[sqlx(default)]
+    pub median_settlement_latency_ms: Option<i32>,
+    #[sqlx(default)]
+    pub p95_settlement_latency_ms: Option<i32>,
+    #[sqlx(default)]
+    pub p99_settlement_latency_ms: Option<i32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Totals over a corridor's most recent daily metrics
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, sqlx::FromRow, utoipa::ToSchema)]
+pub struct CorridorTransactionSummary {
+    pub total_transactions: i64,
+    pub successful_transactions: i64,
+    pub failed_transactions: i64,
+    /// Successful transactions as a percentage of all transactions in the window
+    pub success_rate: f64,
+    pub volume_usd: f64,
+}
+
+/// A corridor's metrics as recorded by one metrics run
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, sqlx::FromRow, utoipa::ToSchema)]
+pub struct CorridorMetricsSnapshot {
+    pub id: String,
+    pub corridor_id: String,
+    pub total_transactions: i64,
+    pub successful_transactions: i64,
+    pub failed_transactions: i64,
+    pub success_rate: f64,
+    /// Share of attempted USD volume that settled; `None` on snapshots recorded
+    /// before it was tracked
+    pub volume_weighted_success_rate: Option<f64>,
+    pub volume_usd: f64,
+    pub median_settlement_latency_ms: Option<i32>,
+    pub p95_settlement_latency_ms: Option<i32>,
+    pub p99_settlement_latency_ms: Option<i32>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CorridorDetailResponse {
+    pub id: String,
+    pub corridor: crate::models::corridor::Corridor,
+    pub status: String,
+    pub reliability_score: f64,
+    /// Share of attempted USD volume that settled in the last metrics run
+    pub volume_weighted_success_rate: Option<f64>,
+    pub median_settlement_latency_ms: Option<i32>,
+    pub p95_settlement_latency_ms: Option<i32>,
+    pub p99_settlement_latency_ms: Option<i32>,
+    /// Days of daily metrics `recent_transactions` covers
+    pub recent_window_days: i64,
+    pub recent_transactions: CorridorTransactionSummary,
+}
+
+impl CorridorDetailResponse {
+    /// Average transactions per day over `recent_window_days`
+    pub fn daily_transactions(&self) -> f64 {
+        self.recent_transactions.total_transactions as f64 / self.recent_window_days.max(1) as f64
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct MetricRecord {
     pub id: String,
@@ -144,7 +262,7 @@ pub struct SnapshotRecord {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct CreateAnchorRequest {
     pub name: String,
     pub stellar_account: String,
@@ -155,13 +273,71 @@ pub struct CreateAnchorRequest {
 // Corridor domain (new)
 // =========================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct CreateCorridorRequest {
     pub name: Option<String>,
     pub source_asset_code: String,
     pub source_asset_issuer: String,
     pub dest_asset_code: String,
     pub dest_asset_issuer: String,
+    /// Transactions to compute the corridor's first metrics from. The corridor
+    /// and its metrics are written in one transaction, so a failure leaves no
+    /// corridor behind.
+    #[serde(default)]
+    pub initial_transactions: Option<Vec<CorridorTransactionDto>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CorridorTransactionDto {
+    pub successful: bool,
+    pub settlement_latency_ms: Option<i32>,
+    pub amount_usd: f64,
+    #[serde(default)]
+    pub occurred_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+impl CorridorTransactionDto {
+    /// Reject amounts and latencies no metrics computation can make sense of
+    pub fn validate(&self) -> Result<(), String> {
+        validate_amount("Transaction amount", self.amount_usd)?;
+        validate_latency_ms(self.settlement_latency_ms)
+    }
+}
+
+/// Reject a USD amount that is negative, NaN or infinite; `field` names it in the message
+pub fn validate_amount(field: &str, amount: f64) -> Result<(), String> {
+    if !amount.is_finite() || amount < 0.0 {
+        return Err(format!(
+            "{} must be a non-negative number, got {}",
+            field, amount
+        ));
+    }
+    Ok(())
+}
+
+/// Reject a negative settlement latency
+pub fn validate_latency_ms(latency_ms: Option<i32>) -> Result<(), String> {
+    if let Some(latency) = latency_ms.filter(|latency| *latency < 0) {
+        return Err(format!(
+            "Settlement latency cannot be negative, got {}ms",
+            latency
+        ));
+    }
+    Ok(())
+}
+
+impl From<CorridorTransactionDto> for crate::services::analytics::CorridorTransaction {
+    fn from(dto: CorridorTransactionDto) -> Self {
+        Self {
+            successful: dto.successful,
+            settlement_latency_ms: dto.settlement_latency_ms,
+            amount_usd: dto.amount_usd,
+            occurred_at: dto.occurred_at,
+            currency: dto.currency,
+        }
+    }
 }
 
 // =========================
@@ -2,7 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, sqlx::FromRow, utoipa::ToSchema)]
 pub struct Corridor {
     pub asset_a_code: String,
     pub asset_a_issuer: String,
@@ -58,13 +58,29 @@ pub struct CorridorMetrics {
     pub successful_transactions: i64,
     pub failed_transactions: i64,
     pub success_rate: f64,
+    /// Share of attempted USD volume that settled, as a percentage
+    #[sqlx(default)]
+    #[serde(default)]
+    pub volume_weighted_success_rate: f64,
     pub volume_usd: f64,
     pub avg_settlement_latency_ms: Option<i32>,
     /// Median settlement latency in milliseconds
     #[sqlx(default)]
     pub median_settlement_latency_ms: Option<i32>,
+    /// 95th percentile settlement latency in milliseconds
+    #[sqlx(default)]
+    #[serde(default)]
+    pub p95_settlement_latency_ms: Option<i32>,
+    /// 99th percentile settlement latency in milliseconds
+    #[sqlx(default)]
+    #[serde(default)]
+    pub p99_settlement_latency_ms: Option<i32>,
     #[serde(default)]
     pub liquidity_depth_usd: f64,
+    /// Transactions left out of volume because their currency had no FX rate
+    #[sqlx(default)]
+    #[serde(default)]
+    pub skipped_fx: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -147,6 +163,25 @@ pub fn compute_median(values: &mut [i64]) -> Option<i64> {
     }
 }
 
+/// Computes successful USD volume as a percentage of all attempted USD volume,
+/// returning 0 when nothing was attempted.
+pub fn compute_volume_weighted_success_rate(successful_volume: f64, total_volume: f64) -> f64 {
+    if total_volume <= 0.0 {
+        return 0.0;
+    }
+    (successful_volume / total_volume) * 100.0
+}
+
+/// Computes the nearest-rank percentile (0-100) of latency measurements already sorted ascending.
+pub fn compute_percentile(sorted_values: &[i64], percentile: f64) -> Option<i64> {
+    if sorted_values.is_empty() {
+        return None;
+    }
+    let rank = (percentile / 100.0 * sorted_values.len() as f64).ceil() as usize;
+    let idx = rank.clamp(1, sorted_values.len()) - 1;
+    Some(sorted_values[idx])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,207 @@
+use axum::{response::Html, Json};
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::cache::{
+    BreakerState, CacheMetricsSummary, FailedWrite, LatencyBucket, LatencyReport, LatencySummary,
+    MemoryCompaction, OperationLatencySummary, PrefixStats,
+};
+use crate::cached_handlers::{
+    self, BatchUpdateMetricsResponse, BatchUpdateMetricsResult, CacheFailuresResponse,
+    CacheKeyInspection, CacheStatsResponse, CorridorMetricsUpdateResponse, WarmCacheRequest,
+    WarmCacheResponse, WarmCacheResult,
+};
+use crate::handlers::{
+    BatchUpdateMetricsItem, CorridorHistoryResponse, CreateAssetRequest, ErrorResponse,
+    ListAnchorsResponse, ListCorridorsResponse, UpdateCorridorMetricsFromTxns,
+    UpdateMetricsRequest,
+};
+use crate::models::corridor::Corridor;
+use crate::models::{
+    Anchor, AnchorDetailResponse, AnchorMetricsHistory, Asset, AssetMetrics,
+    CorridorDetailResponse, CorridorMetricsSnapshot, CorridorTransactionDto,
+    CorridorTransactionSummary, CreateAnchorRequest, CreateCorridorRequest, DashboardStats,
+};
+use crate::services::analytics::{Anomaly, Trend};
+
+/// OpenAPI description of the cached anchor, corridor, dashboard and cache endpoints
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Stellar Insights API"),
+    paths(
+        cached_handlers::list_anchors_cached,
+        cached_handlers::get_anchor_cached,
+        cached_handlers::get_anchor_by_account_cached,
+        cached_handlers::create_anchor_cached,
+        cached_handlers::upsert_anchor_cached,
+        cached_handlers::update_anchor_metrics_cached,
+        cached_handlers::update_anchor_metrics_batch_cached,
+        cached_handlers::delete_anchor_cached,
+        cached_handlers::deactivate_anchor_cached,
+        cached_handlers::reactivate_anchor_cached,
+        cached_handlers::get_anchor_assets_cached,
+        cached_handlers::create_anchor_asset_cached,
+        cached_handlers::get_anchors_by_asset_cached,
+        cached_handlers::get_asset_metrics_cached,
+        cached_handlers::list_corridors_cached,
+        cached_handlers::get_corridors_by_asset_cached,
+        cached_handlers::get_corridor_cached,
+        cached_handlers::get_corridor_history_cached,
+        cached_handlers::create_corridor_cached,
+        cached_handlers::update_corridor_metrics_from_transactions_cached,
+        cached_handlers::get_dashboard_stats_cached,
+        cached_handlers::get_cache_stats,
+        cached_handlers::get_cache_metrics_prometheus,
+        cached_handlers::reset_cache_metrics,
+        cached_handlers::inspect_cache_key,
+        cached_handlers::get_cache_failures,
+        cached_handlers::compact_memory_cache,
+        cached_handlers::warm_cache,
+        cached_handlers::clear_cache,
+    ),
+    components(schemas(
+        Anchor,
+        AnchorDetailResponse,
+        AnchorMetricsHistory,
+        Anomaly,
+        Asset,
+        AssetMetrics,
+        BatchUpdateMetricsItem,
+        BatchUpdateMetricsResponse,
+        BatchUpdateMetricsResult,
+        BreakerState,
+        CacheFailuresResponse,
+        CacheKeyInspection,
+        CacheMetricsSummary,
+        CacheStatsResponse,
+        Corridor,
+        CorridorDetailResponse,
+        CorridorHistoryResponse,
+        CorridorMetricsSnapshot,
+        CorridorMetricsUpdateResponse,
+        CorridorTransactionDto,
+        CorridorTransactionSummary,
+        CreateAnchorRequest,
+        CreateAssetRequest,
+        CreateCorridorRequest,
+        DashboardStats,
+        ErrorResponse,
+        FailedWrite,
+        LatencyBucket,
+        LatencyReport,
+        LatencySummary,
+        MemoryCompaction,
+        ListAnchorsResponse,
+        ListCorridorsResponse,
+        OperationLatencySummary,
+        PrefixStats,
+        Trend,
+        UpdateCorridorMetricsFromTxns,
+        UpdateMetricsRequest,
+        WarmCacheRequest,
+        WarmCacheResponse,
+        WarmCacheResult,
+    )),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "anchors", description = "Anchors, their metrics and issued assets"),
+        (name = "corridors", description = "Payment corridors and their metrics history"),
+        (name = "dashboard", description = "Network-wide totals"),
+        (name = "cache", description = "Cache statistics and maintenance"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the `bearer_auth` scheme the protected routes refer to
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+/// `GET /api/openapi.json`
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Swagger UI loaded from a CDN, so the binary doesn't bundle its assets
+const SWAGGER_UI_HTML: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8" />
+  <title>Stellar Insights API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css" />
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js" crossorigin></script>
+  <script>
+    window.onload = () => {
+      window.ui = SwaggerUIBundle({ url: "/api/openapi.json", dom_id: "#swagger-ui" });
+    };
+  </script>
+</body>
+</html>
+"##;
+
+/// `GET /api/docs`
+pub async fn swagger_ui() -> Html<&'static str> {
+    Html(SWAGGER_UI_HTML)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::{to_bytes, Body},
+        http::{Request, StatusCode},
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_spec_documents_cached_endpoints() {
+        let app = Router::new().route("/api/openapi.json", get(openapi_json));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/openapi.json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let spec: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let paths = &spec["paths"];
+        assert!(paths["/api/v2/anchors"]["get"].is_object());
+        for (path, schema) in [
+            ("/api/v2/anchors", "ListAnchorsResponse"),
+            ("/api/v2/corridors", "ListCorridorsResponse"),
+        ] {
+            assert_eq!(
+                paths[path]["get"]["responses"]["200"]["content"]["application/json"]["schema"]
+                    ["$ref"],
+                format!("#/components/schemas/{}", schema)
+            );
+        }
+        assert!(paths["/api/corridors/{id}/history"]["get"].is_object());
+        assert_eq!(
+            paths["/api/anchors"]["post"]["security"][0]["bearer_auth"],
+            serde_json::json!([])
+        );
+        assert!(paths["/api/anchors"]["put"]["responses"]["201"].is_object());
+        assert!(paths["/api/anchors/{id}"]["get"]["responses"]["404"].is_object());
+        assert!(spec["components"]["schemas"]["ErrorResponse"].is_object());
+        assert!(spec["components"]["securitySchemes"]["bearer_auth"].is_object());
+    }
+}
@@ -1,15 +1,16 @@
 use axum::{
-    extract::{ConnectInfo, Request, State},
+    extract::{ConnectInfo, MatchedPath, Request, State},
     http::StatusCode,
     middleware::Next,
     response::{IntoResponse, Response},
 };
-use redis::aio::MultiplexedConnection;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::cache::RedisCache;
+
 /// Rate limit configuration for an endpoint
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RateLimitConfig {
@@ -26,41 +27,33 @@ impl Default for RateLimitConfig {
     }
 }
 
+/// Length of a fixed rate limit window in seconds
+const WINDOW_SECS: u64 = 60;
+
+/// Endpoint every request matching no route is counted under, so probing
+/// random paths can't mint a counter per path
+const UNMATCHED_ENDPOINT: &str = "<unmatched>";
+
 /// Rate limiter state
 pub struct RateLimiter {
-    redis_connection: Arc<RwLock<Option<MultiplexedConnection>>>,
+    cache: Arc<RedisCache>,
     endpoint_configs: Arc<RwLock<HashMap<String, RateLimitConfig>>>,
     fallback_memory_store: Arc<RwLock<HashMap<String, (u32, i64)>>>,
 }
 
 impl RateLimiter {
-    pub async fn new() -> anyhow::Result<Self> {
-        let redis_url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
-        
-        let connection = if let Ok(client) = redis::Client::open(redis_url.as_str()) {
-            match client.get_multiplexed_tokio_connection().await {
-                Ok(conn) => {
-                    tracing::info!("Connected to Redis for rate limiting");
-                    Some(conn)
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to connect to Redis ({}), using memory-only rate limiting", e);
-                    None
-                }
-            }
-        } else {
-            tracing::warn!("Invalid Redis URL, using memory-only rate limiting");
-            None
-        };
-
-        Ok(Self {
-            redis_connection: Arc::new(RwLock::new(connection)),
+    /// Counts requests in the cache's Redis, or in process memory while the
+    /// cache has no Redis connection
+    pub fn new(cache: Arc<RedisCache>) -> Self {
+        Self {
+            cache,
             endpoint_configs: Arc::new(RwLock::new(HashMap::new())),
             fallback_memory_store: Arc::new(RwLock::new(HashMap::new())),
-        })
+        }
     }
 
-    /// Register a rate limit config for an endpoint
+    /// Register a rate limit config for an endpoint, by its route template
+    /// (e.g. `/api/anchors/:id`)
     pub async fn register_endpoint(&self, path: String, config: RateLimitConfig) {
         self.endpoint_configs.write().await.insert(path, config);
     }
@@ -72,7 +65,9 @@ impl RateLimiter {
         })
     }
 
-    /// Check rate limit for an IP/endpoint combination
+    /// Check rate limit for an IP/endpoint combination. Requests are counted
+    /// per fixed `WINDOW_SECS` window under `ratelimit:<ip>:<window>:<endpoint>`.
+    /// If Redis is connected but the count fails, the request is let through.
     pub async fn check_rate_limit(
         &self,
         ip: &str,
@@ -85,106 +80,73 @@ impl RateLimiter {
             .cloned()
             .unwrap_or_default();
 
+        let now = unix_now();
+        let window = now / WINDOW_SECS;
+        let reset_after = (WINDOW_SECS - now % WINDOW_SECS) as u32;
+        let limit = config.requests_per_minute;
+
         // Check whitelist
         if self.is_whitelisted(ip, &config) {
             return (true, RateLimitInfo {
-                limit: config.requests_per_minute,
-                remaining: config.requests_per_minute,
-                reset_after: 60,
+                limit,
+                remaining: limit,
+                reset_after,
                 is_whitelisted: true,
             });
         }
 
-        let key = format!("ratelimit:{}:{}", endpoint, ip);
-        let limit = config.requests_per_minute;
+        let key = format!("ratelimit:{}:{}:{}", ip, window, endpoint);
 
-        // Try Redis first
-        if let Some(conn) = self.redis_connection.read().await.as_ref() {
-            let mut conn = conn.clone();
-            match self.check_redis_limit(&mut conn, &key, limit).await {
-                Ok((allowed, remaining, reset)) => {
-                    return (allowed, RateLimitInfo {
-                        limit,
-                        remaining,
-                        reset_after: reset,
-                        is_whitelisted: false,
-                    });
-                }
-                Err(_) => {
-                }
+        let count = match self.cache.incr_window(&key, WINDOW_SECS as usize).await {
+            Ok(Some(count)) => count,
+            Ok(None) => self.incr_memory(&key, now).await,
+            Err(e) => {
+                tracing::warn!("Rate limit check failed for {}, allowing request: {}", ip, e);
+                return (true, RateLimitInfo {
+                    limit,
+                    remaining: limit,
+                    reset_after,
+                    is_whitelisted: false,
+                });
             }
-        }
+        };
 
-        // Fall back to memory store
-        let (allowed, remaining, reset) = self.check_memory_limit(&key, limit).await;
+        let allowed = count <= u64::from(limit);
+        let remaining = u64::from(limit).saturating_sub(count) as u32;
         (
             allowed,
             RateLimitInfo {
                 limit,
                 remaining,
-                reset_after: reset,
+                reset_after,
                 is_whitelisted: false,
             },
         )
     }
 
-    /// Check rate limit in Redis
-    async fn check_redis_limit(
-        &self,
-        conn: &mut MultiplexedConnection,
-        key: &str,
-        limit: u32,
-    ) -> anyhow::Result<(bool, u32, u32), Box<dyn std::error::Error + Send + Sync>> {
-        use redis::AsyncCommands;
-
-        let current: u32 = conn.get(key).await.unwrap_or(0);
-        let ttl: i64 = conn.ttl(key).await.unwrap_or(-1);
-
-        if current >= limit {
-            return Ok((false, 0, if ttl > 0 { ttl as u32 } else { 60 }));
-        }
-
-        let new_count = current + 1;
-        conn.incr::<_, _, ()>(key, 1).await?;
-
-        if current == 0 {
-            conn.expire::<_, ()>(key, 60).await?;
-        }
-
-        let remaining = if new_count >= limit { 0 } else { limit - new_count };
-        Ok((new_count < limit, remaining, 60))
-    }
-
-    /// Check rate limit in memory (fallback)
-    async fn check_memory_limit(
-        &self,
-        key: &str,
-        limit: u32,
-    ) -> (bool, u32, u32) {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-
+    /// Count a request in memory (fallback), dropping counters from past windows
+    async fn incr_memory(&self, key: &str, now: u64) -> u64 {
+        let now = now as i64;
         let mut store = self.fallback_memory_store.write().await;
 
-        let (count, expiry) = store.get(key).copied().unwrap_or((0, now + 60));
-
-        if now > expiry {
-            // Reset counter
-            store.insert(key.to_string(), (1, now + 60));
-            (true, limit - 1, 60)
-        } else if count >= limit {
-            (false, 0, (expiry - now) as u32)
-        } else {
-            let new_count = count + 1;
-            store.insert(key.to_string(), (new_count, expiry));
-            let remaining = if new_count >= limit { 0 } else { limit - new_count };
-            (new_count < limit, remaining, (expiry - now) as u32)
+        if !store.contains_key(key) {
+            store.retain(|_, (_, expiry)| *expiry > now);
         }
+        let (count, _) = store
+            .entry(key.to_string())
+            .or_insert((0, now + WINDOW_SECS as i64));
+        *count = count.saturating_add(1);
+        u64::from(*count)
     }
 }
 
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
 /// Rate limit information in response
 #[derive(Debug, Clone)]
 pub struct RateLimitInfo {
@@ -211,6 +173,9 @@ impl IntoResponse for RateLimitError {
         (
             StatusCode::TOO_MANY_REQUESTS,
             [(
+                "Retry-After",
+                self.info.reset_after.to_string(),
+            ), (
                 "RateLimit-Limit",
                 self.info.limit.to_string(),
             ), (
@@ -226,7 +191,8 @@ impl IntoResponse for RateLimitError {
     }
 }
 
-/// Middleware for rate limiting
+/// Middleware for rate limiting. Requests are counted per route template, so
+/// `/api/anchors/1` and `/api/anchors/2` share the `/api/anchors/:id` limit.
 pub async fn rate_limit_middleware(
     State(limiter): State<Arc<RateLimiter>>,
     addr: ConnectInfo<std::net::SocketAddr>,
@@ -234,9 +200,13 @@ pub async fn rate_limit_middleware(
     next: Next,
 ) -> Response {
     let ip = addr.0.ip().to_string();
-    let path = req.uri().path().to_string();
+    let endpoint = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_ENDPOINT, |matched| matched.as_str())
+        .to_string();
 
-    let (allowed, info) = limiter.check_rate_limit(&ip, &path).await;
+    let (allowed, info) = limiter.check_rate_limit(&ip, &endpoint).await;
 
     if !allowed {
         return RateLimitError { info }.into_response();
@@ -258,3 +228,89 @@ pub async fn rate_limit_middleware(
 
     response
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body, extract::connect_info::MockConnectInfo, middleware, routing::get, Router,
+    };
+    use tower::ServiceExt;
+
+    async fn memory_limiter(requests_per_minute: u32) -> RateLimiter {
+        // Closed port so counting falls back to memory
+        let cache = RedisCache::from_url("redis://127.0.0.1:1").await.unwrap();
+        let limiter = RateLimiter::new(Arc::new(cache));
+        limiter
+            .register_endpoint("/api/cache/clear".to_string(), RateLimitConfig {
+                requests_per_minute,
+                whitelist_ips: vec![],
+            })
+            .await;
+        limiter
+    }
+
+    #[tokio::test]
+    async fn test_requests_under_the_limit_are_allowed() {
+        let limiter = memory_limiter(3).await;
+
+        for expected_remaining in [2, 1, 0] {
+            let (allowed, info) = limiter.check_rate_limit("10.0.0.1", "/api/cache/clear").await;
+            assert!(allowed);
+            assert_eq!(info.limit, 3);
+            assert_eq!(info.remaining, expected_remaining);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requests_over_the_limit_get_429_with_retry_after() {
+        let limiter = memory_limiter(2).await;
+
+        for _ in 0..2 {
+            assert!(limiter.check_rate_limit("10.0.0.2", "/api/cache/clear").await.0);
+        }
+        let (allowed, info) = limiter.check_rate_limit("10.0.0.2", "/api/cache/clear").await;
+        assert!(!allowed);
+        assert_eq!(info.remaining, 0);
+        assert!((1..=WINDOW_SECS as u32).contains(&info.reset_after));
+
+        // Other clients keep their own counters
+        assert!(limiter.check_rate_limit("10.0.0.3", "/api/cache/clear").await.0);
+
+        let response = RateLimitError { info: info.clone() }.into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(
+            response.headers()["retry-after"],
+            info.reset_after.to_string().as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_paths_of_one_route_share_its_limit() {
+        let limiter = Arc::new(memory_limiter(2).await);
+        limiter
+            .register_endpoint("/api/anchors/:id".to_string(), RateLimitConfig {
+                requests_per_minute: 2,
+                whitelist_ips: vec![],
+            })
+            .await;
+        let app = Router::new()
+            .route("/api/anchors/:id", get(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(limiter, rate_limit_middleware))
+            .layer(MockConnectInfo(std::net::SocketAddr::from(([10, 0, 0, 4], 0))));
+
+        let mut statuses = Vec::new();
+        for id in ["1", "2", "3"] {
+            let request = Request::builder()
+                .uri(format!("/api/anchors/{}", id))
+                .body(Body::empty())
+                .unwrap();
+            statuses.push(app.clone().oneshot(request).await.unwrap().status());
+        }
+
+        assert_eq!(
+            statuses,
+            [StatusCode::OK, StatusCode::OK, StatusCode::TOO_MANY_REQUESTS]
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use axum::{
+    extract::Request,
+    http::{HeaderMap, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+
+pub const X_REQUEST_ID: &str = "x-request-id";
+/// Client-supplied ids longer than this are replaced rather than logged
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Correlation id of the request being served, also available to handlers as
+/// an extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// Middleware tagging each request with an id: the client's `X-Request-Id` if
+/// it sent a usable one, a fresh UUID otherwise. The rest of the request runs
+/// inside a `request` span carrying it, so every log line it causes, cache
+/// operations included, can be tied back to the request. The id is echoed in
+/// the response's `X-Request-Id`.
+pub async fn request_id(mut req: Request, next: Next) -> Response {
+    let id = client_request_id(req.headers()).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %req.method(),
+        path = %req.uri().path(),
+    );
+    req.extensions_mut().insert(RequestId(id.clone()));
+
+    let mut response = next.run(req).instrument(span).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(X_REQUEST_ID, value);
+    }
+    response
+}
+
+/// The client's id if it's short, printable ASCII without spaces, so it can't
+/// break up a log line
+fn client_request_id(headers: &HeaderMap) -> Option<String> {
+    let id = headers.get(X_REQUEST_ID)?.to_str().ok()?.trim();
+    let usable = !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id.bytes().all(|b| b.is_ascii_graphic());
+    usable.then(|| id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::RedisCache;
+    use axum::{body::Body, middleware, routing::get, Extension, Router};
+    use std::sync::{Arc, Mutex};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/",
+                get(|Extension(id): Extension<RequestId>| async move { id.0 }),
+            )
+            .layer(middleware::from_fn(request_id))
+    }
+
+    async fn get_with(request_id: Option<&str>) -> Response {
+        let mut request = Request::builder().uri("/");
+        if let Some(request_id) = request_id {
+            request = request.header(X_REQUEST_ID, request_id);
+        }
+        app()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_response_carries_a_generated_request_id() {
+        let first = get_with(None).await;
+        let second = get_with(None).await;
+
+        let id = first.headers()[X_REQUEST_ID].to_str().unwrap().to_string();
+        assert!(uuid::Uuid::parse_str(&id).is_ok(), "{}", id);
+        assert_ne!(second.headers()[X_REQUEST_ID], id.as_str());
+        let body = axum::body::to_bytes(first.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], id.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_client_request_id_is_echoed_unchanged() {
+        for _ in 0..2 {
+            let response = get_with(Some("trace-abc-123")).await;
+            assert_eq!(response.headers()[X_REQUEST_ID], "trace-abc-123");
+        }
+
+        // Unusable ids are replaced instead of ending up in the logs
+        let long = "x".repeat(MAX_REQUEST_ID_LEN + 1);
+        for unusable in ["", "two words", long.as_str()] {
+            let response = get_with(Some(unusable)).await;
+            let id = response.headers()[X_REQUEST_ID].to_str().unwrap();
+            assert!(uuid::Uuid::parse_str(id).is_ok(), "{:?} kept", unusable);
+        }
+    }
+
+    /// `(cache.op, request_id of the enclosing span)` for each cache log line
+    #[derive(Clone, Default)]
+    struct CacheOpRequestIds(Arc<Mutex<Vec<(String, Option<String>)>>>);
+
+    /// Records the value of the field called `.0` into `.1`
+    struct FieldVisitor<'a>(&'static str, &'a mut Option<String>);
+
+    impl tracing::field::Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            if field.name() == self.0 {
+                *self.1 = Some(format!("{:?}", value));
+            }
+        }
+
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            if field.name() == self.0 {
+                *self.1 = Some(value.to_string());
+            }
+        }
+    }
+
+    impl<S> tracing_subscriber::Layer<S> for CacheOpRequestIds
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut request_id = None;
+            attrs.record(&mut FieldVisitor("request_id", &mut request_id));
+            if let (Some(request_id), Some(span)) = (request_id, ctx.span(id)) {
+                span.extensions_mut().insert(RequestId(request_id));
+            }
+        }
+
+        fn on_event(
+            &self,
+            event: &tracing::Event<'_>,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut op = None;
+            event.record(&mut FieldVisitor("cache.op", &mut op));
+            let Some(op) = op else {
+                return;
+            };
+            let request_id = ctx.event_scope(event).and_then(|scope| {
+                scope
+                    .from_root()
+                    .find_map(|span| span.extensions().get::<RequestId>().cloned())
+            });
+            self.0.lock().unwrap().push((op, request_id.map(|id| id.0)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_log_lines_carry_the_request_id() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let captured = CacheOpRequestIds::default();
+        let _default =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(captured.clone()));
+        let cache = Arc::new(RedisCache::from_url("redis://127.0.0.1:1").await.unwrap());
+        let app = Router::new()
+            .route(
+                "/",
+                get(move || {
+                    let cache = Arc::clone(&cache);
+                    async move {
+                        cache.set("anchor:detail:traced", &1, 60).await.unwrap();
+                        cache.get::<i32>("anchor:detail:traced").await.unwrap();
+                    }
+                }),
+            )
+            .layer(middleware::from_fn(request_id));
+
+        app.oneshot(
+            Request::builder()
+                .uri("/")
+                .header(X_REQUEST_ID, "trace-cache-1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+        let traced = Some("trace-cache-1".to_string());
+        assert_eq!(
+            *captured.0.lock().unwrap(),
+            [
+                ("set".to_string(), traced.clone()),
+                ("get".to_string(), traced)
+            ]
+        );
+    }
+}
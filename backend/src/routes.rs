@@ -0,0 +1,104 @@
+use axum::{
+    routing::{delete, get, post, put},
+    Router,
+};
+
+use crate::api::anchors::get_anchors;
+use crate::api::corridors::list_corridors;
+use crate::cached_handlers::{
+    clear_cache, compact_memory_cache, create_anchor_asset_cached, create_anchor_cached,
+    create_corridor_cached, deactivate_anchor_cached, delete_anchor_cached,
+    get_anchor_assets_cached, get_anchor_by_account_cached, get_anchor_cached,
+    get_anchors_by_asset_cached, get_asset_metrics_cached, get_cache_failures,
+    get_cache_metrics_prometheus, get_cache_stats, get_corridor_cached,
+    get_corridor_history_cached, get_corridors_by_asset_cached, get_dashboard_stats_cached,
+    inspect_cache_key, list_anchors_cached, list_corridors_cached, reactivate_anchor_cached,
+    reset_cache_metrics, update_anchor_metrics_batch_cached, update_anchor_metrics_cached,
+    update_corridor_metrics_from_transactions_cached, upsert_anchor_cached, warm_cache,
+};
+use crate::events::cache_events;
+use crate::handlers::{health_check, liveness_check, readiness_check};
+use crate::openapi::{openapi_json, swagger_ui};
+use crate::state::AppState;
+
+/// Read-only anchor, asset, corridor and cache routes, served without
+/// authentication. `main` adds the state and middleware; tests call this to
+/// exercise the same routing. `GET /api/anchors` and `/api/corridors` keep the
+/// shapes the frontend reads; the paginated, cached listings are under `/api/v2`.
+pub fn public_routes() -> Router<AppState> {
+    Router::new()
+        .route("/health", get(health_check))
+        .route("/healthz", get(liveness_check))
+        .route("/readyz", get(readiness_check))
+        .route("/api/anchors", get(get_anchors))
+        .route("/api/v2/anchors", get(list_anchors_cached))
+        .route("/api/anchors/:id", get(get_anchor_cached))
+        .route(
+            "/api/anchors/account/:stellar_account",
+            get(get_anchor_by_account_cached),
+        )
+        .route("/api/anchors/:id/assets", get(get_anchor_assets_cached))
+        .route(
+            "/api/assets/:code/anchors",
+            get(get_anchors_by_asset_cached),
+        )
+        .route(
+            "/api/assets/:code/:issuer/metrics",
+            get(get_asset_metrics_cached),
+        )
+        .route(
+            "/api/assets/:code/corridors",
+            get(get_corridors_by_asset_cached),
+        )
+        .route("/api/corridors", get(list_corridors))
+        .route("/api/v2/corridors", get(list_corridors_cached))
+        .route("/api/corridors/:corridor", get(get_corridor_cached))
+        .route(
+            "/api/corridors/:id/history",
+            get(get_corridor_history_cached),
+        )
+        .route("/api/dashboard/stats", get(get_dashboard_stats_cached))
+        .route("/api/cache/stats", get(get_cache_stats))
+        .route("/api/events", get(cache_events))
+        .route("/metrics", get(get_cache_metrics_prometheus))
+        .route("/api/openapi.json", get(openapi_json))
+        .route("/api/docs", get(swagger_ui))
+}
+
+/// Write and cache-admin routes; `main` puts them behind `auth_middleware`
+pub fn protected_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/anchors",
+            post(create_anchor_cached).put(upsert_anchor_cached),
+        )
+        .route("/api/anchors/:id", delete(delete_anchor_cached))
+        .route(
+            "/api/anchors/:id/metrics",
+            put(update_anchor_metrics_cached),
+        )
+        .route(
+            "/api/anchors/:id/deactivate",
+            post(deactivate_anchor_cached),
+        )
+        .route(
+            "/api/anchors/:id/reactivate",
+            post(reactivate_anchor_cached),
+        )
+        .route(
+            "/api/anchors/metrics:batch",
+            put(update_anchor_metrics_batch_cached),
+        )
+        .route("/api/anchors/:id/assets", post(create_anchor_asset_cached))
+        .route("/api/corridors", post(create_corridor_cached))
+        .route(
+            "/api/corridors/:id/metrics-from-transactions",
+            put(update_corridor_metrics_from_transactions_cached),
+        )
+        .route("/api/cache/clear", post(clear_cache))
+        .route("/api/cache/metrics/reset", post(reset_cache_metrics))
+        .route("/api/cache/memory/compact", post(compact_memory_cache))
+        .route("/api/cache/warm", post(warm_cache))
+        .route("/api/cache/inspect", get(inspect_cache_key))
+        .route("/api/cache/failures", get(get_cache_failures))
+}
@@ -1,11 +1,69 @@
-use crate::models::corridor::{compute_median, CorridorMetrics, PaymentRecord};
-use std::collections::HashMap;
+use crate::analytics::calculate_settlement_time_score;
+use crate::models::corridor::{
+    compute_median, compute_percentile, compute_volume_weighted_success_rate, CorridorMetrics,
+    PaymentRecord,
+};
+use crate::models::{Anchor, CorridorMetricsSnapshot};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Clone)]
 pub struct CorridorTransaction {
     pub successful: bool,
     pub settlement_latency_ms: Option<i32>,
+    /// Amount in `currency`, converted to USD before it counts towards volume
     pub amount_usd: f64,
+    /// When the transaction happened, if known; used to bucket time series
+    pub occurred_at: Option<DateTime<Utc>>,
+    /// ISO currency code of the amount; `None` means it is already USD
+    pub currency: Option<String>,
+}
+
+/// Currency every corridor volume is reported in
+pub const VOLUME_CURRENCY: &str = "USD";
+
+/// Source of exchange rates used to normalize transaction amounts
+pub trait FxRateProvider {
+    /// Units of `to` per unit of `from`, or `None` if the pair is unknown
+    fn rate(&self, from: &str, to: &str) -> Option<f64>;
+}
+
+/// Fixed in-memory rate table; every currency converts to itself at 1.0
+#[derive(Debug, Clone, Default)]
+pub struct StaticFxRates {
+    rates: HashMap<(String, String), f64>,
+}
+
+impl StaticFxRates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_rate(mut self, from: &str, to: &str, rate: f64) -> Self {
+        self.rates
+            .insert((from.to_ascii_uppercase(), to.to_ascii_uppercase()), rate);
+        self
+    }
+}
+
+impl FxRateProvider for StaticFxRates {
+    fn rate(&self, from: &str, to: &str) -> Option<f64> {
+        if from.eq_ignore_ascii_case(to) {
+            return Some(1.0);
+        }
+        self.rates
+            .get(&(from.to_ascii_uppercase(), to.to_ascii_uppercase()))
+            .copied()
+    }
+}
+
+/// The transaction's amount in USD, or `None` if its currency can't be converted
+fn amount_in_usd(t: &CorridorTransaction, fx: &dyn FxRateProvider) -> Option<f64> {
+    let currency = t.currency.as_deref().unwrap_or(VOLUME_CURRENCY);
+    fx.rate(currency, VOLUME_CURRENCY)
+        .filter(|rate| rate.is_finite() && *rate >= 0.0)
+        .map(|rate| t.amount_usd * rate)
 }
 
 /// Order book structures for computing liquidity depth
@@ -56,14 +114,188 @@ pub fn compute_liquidity_depth(order_book: &OrderBookSnapshot, max_slippage_perc
     buy_liquidity + sell_liquidity
 }
 
-/// Computes corridor metrics from transactions, calculating average and median settlement latency with optional liquidity depth.
+/// Computes corridor metrics from transactions, calculating average, median, p95 and p99 settlement latency with optional liquidity depth.
+/// Amounts are taken as USD; see [`compute_corridor_metrics_with_fx`] for other currencies.
 pub fn compute_corridor_metrics(
     txns: &[CorridorTransaction],
     order_book: Option<&OrderBookSnapshot>, // Optional snapshot for liquidity depth
     slippage_percent: f64,                  // e.g., 1.0 = 1% slippage
 ) -> CorridorMetrics {
-    if txns.is_empty() {
-        return CorridorMetrics {
+    compute_corridor_metrics_with_fx(txns, order_book, slippage_percent, &StaticFxRates::new())
+}
+
+/// Like [`compute_corridor_metrics`], converting each amount to USD through `fx`.
+/// Transactions whose currency has no rate are left out of volume and counted in `skipped_fx`.
+pub fn compute_corridor_metrics_with_fx(
+    txns: &[CorridorTransaction],
+    order_book: Option<&OrderBookSnapshot>,
+    slippage_percent: f64,
+    fx: &dyn FxRateProvider,
+) -> CorridorMetrics {
+    let mut accumulator = CorridorMetricsAccumulator::with_fx(fx);
+    if let Some(order_book) = order_book {
+        accumulator = accumulator.with_order_book(order_book, slippage_percent);
+    }
+    for t in txns {
+        accumulator.add(t);
+    }
+    accumulator.finalize()
+}
+
+/// Rates for the accumulator's default: amounts already in USD only
+struct SameCurrencyOnly;
+
+impl FxRateProvider for SameCurrencyOnly {
+    fn rate(&self, from: &str, to: &str) -> Option<f64> {
+        from.eq_ignore_ascii_case(to).then_some(1.0)
+    }
+}
+
+/// Folds transactions into [`CorridorMetrics`] one at a time, so a stream never
+/// has to be collected into a `Vec`. [`compute_corridor_metrics_with_fx`] is
+/// built on it, so both always agree on the same transactions.
+///
+/// Counts and volume sums take constant space, but the latency percentiles are
+/// exact, so every successful transaction's latency is kept (8 bytes apiece) and
+/// `finalize` sorts a copy of them. A streaming estimator such as P² or a
+/// t-digest would bound that memory, but its percentiles are approximate and
+/// would no longer match the batch result; window very long streams instead.
+///
+/// Accumulators filled on separate tasks can be combined with [`merge`](Self::merge).
+pub struct CorridorMetricsAccumulator<'a> {
+    fx: &'a dyn FxRateProvider,
+    liquidity_depth_usd: f64,
+    total_transactions: i64,
+    successful_transactions: i64,
+    failed_transactions: i64,
+    latency_sum: i64,
+    latency_values: Vec<i64>,
+    volume_usd: f64,
+    attempted_volume_usd: f64,
+    skipped_fx: i64,
+}
+
+impl Default for CorridorMetricsAccumulator<'static> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CorridorMetricsAccumulator<'static> {
+    /// Amounts are taken as USD, as in [`compute_corridor_metrics`]
+    pub fn new() -> Self {
+        Self::with_fx(&SameCurrencyOnly)
+    }
+}
+
+impl<'a> CorridorMetricsAccumulator<'a> {
+    /// Convert each amount to USD through `fx`, as in [`compute_corridor_metrics_with_fx`]
+    pub fn with_fx(fx: &'a dyn FxRateProvider) -> Self {
+        Self {
+            fx,
+            liquidity_depth_usd: 0.0,
+            total_transactions: 0,
+            successful_transactions: 0,
+            failed_transactions: 0,
+            latency_sum: 0,
+            latency_values: Vec::new(),
+            volume_usd: 0.0,
+            attempted_volume_usd: 0.0,
+            skipped_fx: 0,
+        }
+    }
+
+    /// Report liquidity depth from `order_book` within `slippage_percent`
+    pub fn with_order_book(
+        mut self,
+        order_book: &OrderBookSnapshot,
+        slippage_percent: f64,
+    ) -> Self {
+        self.liquidity_depth_usd = compute_liquidity_depth(order_book, slippage_percent);
+        self
+    }
+
+    pub fn add(&mut self, t: &CorridorTransaction) {
+        let amount_usd = match amount_in_usd(t, self.fx) {
+            Some(amount) => amount.max(0.0),
+            None => {
+                self.skipped_fx += 1;
+                0.0
+            }
+        };
+        self.total_transactions += 1;
+        self.attempted_volume_usd += amount_usd;
+        if t.successful {
+            self.successful_transactions += 1;
+            self.volume_usd += amount_usd;
+            if let Some(ms) = t.settlement_latency_ms {
+                self.latency_sum += ms as i64;
+                self.latency_values.push(ms as i64);
+            }
+        } else {
+            self.failed_transactions += 1;
+        }
+    }
+
+    /// Fold in transactions another accumulator has seen. Counts and latencies
+    /// combine exactly; volumes are summed in a different order than a single
+    /// pass would use, so they can differ from it by float rounding. The
+    /// liquidity depth stays this accumulator's.
+    pub fn merge(&mut self, other: CorridorMetricsAccumulator<'_>) {
+        self.total_transactions += other.total_transactions;
+        self.successful_transactions += other.successful_transactions;
+        self.failed_transactions += other.failed_transactions;
+        self.latency_sum += other.latency_sum;
+        self.latency_values.extend(other.latency_values);
+        self.volume_usd += other.volume_usd;
+        self.attempted_volume_usd += other.attempted_volume_usd;
+        self.skipped_fx += other.skipped_fx;
+    }
+
+    /// Metrics over every transaction added so far
+    pub fn finalize(&self) -> CorridorMetrics {
+        if self.total_transactions == 0 {
+            return CorridorMetrics {
+                id: uuid::Uuid::nil().to_string(),
+                corridor_key: String::new(),
+                asset_a_code: String::new(),
+                asset_a_issuer: String::new(),
+                asset_b_code: String::new(),
+                asset_b_issuer: String::new(),
+                date: chrono::Utc::now(),
+                success_rate: 0.0,
+                volume_weighted_success_rate: 0.0,
+                avg_settlement_latency_ms: None,
+                median_settlement_latency_ms: None,
+                p95_settlement_latency_ms: None,
+                p99_settlement_latency_ms: None,
+                liquidity_depth_usd: 0.0,
+                skipped_fx: 0,
+                volume_usd: 0.0,
+                total_transactions: 0,
+                successful_transactions: 0,
+                failed_transactions: 0,
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+            };
+        }
+
+        let success_rate =
+            (self.successful_transactions as f64 / self.total_transactions as f64) * 100.0;
+        let volume_weighted_success_rate =
+            compute_volume_weighted_success_rate(self.volume_usd, self.attempted_volume_usd);
+        let avg_settlement_latency_ms = if !self.latency_values.is_empty() {
+            Some((self.latency_sum / self.latency_values.len() as i64) as i32)
+        } else {
+            None
+        };
+        let mut latency_values = self.latency_values.clone();
+        let median_settlement_latency_ms = compute_median(&mut latency_values).map(|v| v as i32);
+        // compute_median leaves latency_values sorted
+        let p95_settlement_latency_ms = compute_percentile(&latency_values, 95.0).map(|v| v as i32);
+        let p99_settlement_latency_ms = compute_percentile(&latency_values, 99.0).map(|v| v as i32);
+
+        CorridorMetrics {
             id: uuid::Uuid::nil().to_string(),
             corridor_key: String::new(),
             asset_a_code: String::new(),
@@ -71,73 +303,123 @@ pub fn compute_corridor_metrics(
             asset_b_code: String::new(),
             asset_b_issuer: String::new(),
             date: chrono::Utc::now(),
-            success_rate: 0.0,
-            avg_settlement_latency_ms: None,
-            median_settlement_latency_ms: None,
-            liquidity_depth_usd: 0.0,
-            volume_usd: 0.0,
-            total_transactions: 0,
-            successful_transactions: 0,
-            failed_transactions: 0,
+            total_transactions: self.total_transactions,
+            successful_transactions: self.successful_transactions,
+            failed_transactions: self.failed_transactions,
+            success_rate,
+            volume_weighted_success_rate,
+            volume_usd: self.volume_usd,
+            avg_settlement_latency_ms,
+            median_settlement_latency_ms,
+            p95_settlement_latency_ms,
+            p99_settlement_latency_ms,
+            liquidity_depth_usd: self.liquidity_depth_usd,
+            skipped_fx: self.skipped_fx,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
-        };
+        }
     }
+}
 
-    let total_transactions = txns.len() as i64;
-    let mut successful_transactions = 0;
-    let mut failed_transactions = 0;
-    let mut latency_sum = 0i64;
-    let mut latency_values: Vec<i64> = Vec::new();
-    let mut volume_usd = 0.0;
+/// How sharp a change between two metric snapshots must be to count as an anomaly
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnomalyThresholds {
+    /// `ANOMALY_SUCCESS_RATE_DROP`: fall in success rate, in percentage points
+    pub success_rate_drop_pct: f64,
+    /// `ANOMALY_LATENCY_SPIKE_RATIO`: factor by which p95 latency must grow
+    pub latency_spike_ratio: f64,
+}
 
-    for t in txns {
-        if t.successful {
-            successful_transactions += 1;
-            volume_usd += t.amount_usd.max(0.0);
-            if let Some(ms) = t.settlement_latency_ms {
-                latency_sum += ms as i64;
-                latency_values.push(ms as i64);
-            }
-        } else {
-            failed_transactions += 1;
+impl Default for AnomalyThresholds {
+    fn default() -> Self {
+        Self {
+            success_rate_drop_pct: 10.0,
+            latency_spike_ratio: 2.0,
         }
     }
+}
 
-    let success_rate = (successful_transactions as f64 / total_transactions as f64) * 100.0;
-    let avg_settlement_latency_ms = if !latency_values.is_empty() {
-        Some((latency_sum / latency_values.len() as i64) as i32)
-    } else {
-        None
+impl AnomalyThresholds {
+    /// Thresholds from the environment, falling back to the defaults per value
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let threshold = |var: &str, default: f64| {
+            parse_threshold(var, std::env::var(var).ok().as_deref(), default)
+        };
+
+        Self {
+            success_rate_drop_pct: threshold(
+                "ANOMALY_SUCCESS_RATE_DROP",
+                defaults.success_rate_drop_pct,
+            ),
+            latency_spike_ratio: threshold(
+                "ANOMALY_LATENCY_SPIKE_RATIO",
+                defaults.latency_spike_ratio,
+            ),
+        }
+    }
+}
+
+/// A positive threshold from `raw`, or `default` if it is missing or invalid
+fn parse_threshold(var: &str, raw: Option<&str>, default: f64) -> f64 {
+    let Some(raw) = raw else {
+        return default;
     };
-    let median_settlement_latency_ms = compute_median(&mut latency_values).map(|v| v as i32);
-
-    // Compute liquidity depth using order book snapshot if provided
-    let liquidity_depth_usd = order_book
-        .map(|ob| compute_liquidity_depth(ob, slippage_percent))
-        .unwrap_or(0.0);
-
-    CorridorMetrics {
-        id: uuid::Uuid::nil().to_string(),
-        corridor_key: String::new(),
-        asset_a_code: String::new(),
-        asset_a_issuer: String::new(),
-        asset_b_code: String::new(),
-        asset_b_issuer: String::new(),
-        date: chrono::Utc::now(),
-        total_transactions,
-        successful_transactions,
-        failed_transactions,
-        success_rate,
-        volume_usd,
-        avg_settlement_latency_ms,
-        median_settlement_latency_ms,
-        liquidity_depth_usd,
-        created_at: chrono::Utc::now(),
-        updated_at: chrono::Utc::now(),
+    match raw.trim().parse::<f64>() {
+        Ok(value) if value.is_finite() && value > 0.0 => value,
+        _ => {
+            tracing::warn!(
+                "Ignoring invalid {}={:?} (expected a positive number), using {}",
+                var,
+                raw,
+                default
+            );
+            default
+        }
     }
 }
 
+/// A sharp change in corridor health between two metric snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Anomaly {
+    /// Success rate fell by `delta` percentage points
+    SuccessRateDrop { delta: f64 },
+    /// p95 settlement latency grew by `delta` milliseconds
+    LatencySpike { delta: i32 },
+}
+
+/// Compare `current` against `previous` and report every threshold it crosses.
+/// Nothing is reported when either snapshot has no transactions to compare.
+pub fn detect_anomaly(
+    previous: &CorridorMetrics,
+    current: &CorridorMetrics,
+    thresholds: &AnomalyThresholds,
+) -> Vec<Anomaly> {
+    let mut anomalies = Vec::new();
+    if previous.total_transactions == 0 || current.total_transactions == 0 {
+        return anomalies;
+    }
+
+    let drop = previous.success_rate - current.success_rate;
+    if drop > thresholds.success_rate_drop_pct {
+        anomalies.push(Anomaly::SuccessRateDrop { delta: drop });
+    }
+
+    if let (Some(before), Some(after)) = (
+        previous.p95_settlement_latency_ms,
+        current.p95_settlement_latency_ms,
+    ) {
+        if before > 0 && after as f64 > before as f64 * thresholds.latency_spike_ratio {
+            anomalies.push(Anomaly::LatencySpike {
+                delta: after - before,
+            });
+        }
+    }
+
+    anomalies
+}
+
 /// Computes corridor metrics from payment records, aggregating settlement latency (both average and median) per corridor.
 pub fn compute_metrics_from_payments(payments: &[PaymentRecord]) -> Vec<CorridorMetrics> {
     let mut corridor_map: HashMap<String, Vec<&PaymentRecord>> = HashMap::new();
@@ -161,10 +443,12 @@ pub fn compute_metrics_from_payments(payments: &[PaymentRecord]) -> Vec<Corridor
         let mut successful_transactions = 0;
         let mut failed_transactions = 0;
         let mut volume_usd = 0.0;
+        let mut attempted_volume_usd = 0.0;
         let mut latency_sum = 0i64;
         let mut latency_values: Vec<i64> = Vec::new();
 
         for p in &corridor_payments {
+            attempted_volume_usd += p.amount;
             if p.successful {
                 successful_transactions += 1;
                 volume_usd += p.amount; // Assuming amount is already USD or normalized.
@@ -183,6 +467,8 @@ pub fn compute_metrics_from_payments(payments: &[PaymentRecord]) -> Vec<Corridor
         } else {
             0.0
         };
+        let volume_weighted_success_rate =
+            compute_volume_weighted_success_rate(volume_usd, attempted_volume_usd);
 
         let avg_settlement_latency_ms = if !latency_values.is_empty() {
             Some((latency_sum / latency_values.len() as i64) as i32)
@@ -190,6 +476,8 @@ pub fn compute_metrics_from_payments(payments: &[PaymentRecord]) -> Vec<Corridor
             None
         };
         let median_settlement_latency_ms = compute_median(&mut latency_values).map(|v| v as i32);
+        let p95_settlement_latency_ms = compute_percentile(&latency_values, 95.0).map(|v| v as i32);
+        let p99_settlement_latency_ms = compute_percentile(&latency_values, 99.0).map(|v| v as i32);
 
         results.push(CorridorMetrics {
             id: uuid::Uuid::new_v4().to_string(), // Generate new ID for this snapshot
@@ -203,10 +491,14 @@ pub fn compute_metrics_from_payments(payments: &[PaymentRecord]) -> Vec<Corridor
             successful_transactions,
             failed_transactions,
             success_rate,
+            volume_weighted_success_rate,
             volume_usd,
             avg_settlement_latency_ms,
             median_settlement_latency_ms,
+            p95_settlement_latency_ms,
+            p99_settlement_latency_ms,
             liquidity_depth_usd: 0.0, // Needs order book
+            skipped_fx: 0,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         });
@@ -215,6 +507,73 @@ pub fn compute_metrics_from_payments(payments: &[PaymentRecord]) -> Vec<Corridor
     results
 }
 
+/// Success rate and settled volume for one time window
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SuccessRateBucket {
+    pub window_start: DateTime<Utc>,
+    /// Percentage of the window's transactions that succeeded
+    pub success_rate: f64,
+    /// USD volume of the window's successful transactions
+    pub volume_usd: f64,
+}
+
+/// Per-window reliability of a corridor, plus the totals for transactions
+/// that carry no timestamp and so can't be placed in a window
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SuccessRateSeries {
+    /// Non-empty windows in chronological order
+    pub buckets: Vec<SuccessRateBucket>,
+    /// `None` when every transaction had a timestamp
+    pub unbucketed_success_rate: Option<f64>,
+    pub unbucketed_volume_usd: f64,
+}
+
+/// Bucket transactions into consecutive windows of `bucket_size` (aligned to
+/// the Unix epoch) and compute each window's success rate and volume
+pub fn compute_success_rate_series(
+    txns: &[CorridorTransaction],
+    bucket_size: chrono::Duration,
+) -> SuccessRateSeries {
+    let bucket_ms = bucket_size.num_milliseconds().max(1);
+
+    // (successful, total, settled volume) per window start in milliseconds
+    let mut windows: BTreeMap<i64, (u64, u64, f64)> = BTreeMap::new();
+    let mut unbucketed = (0u64, 0u64, 0.0f64);
+
+    for t in txns {
+        let totals = match t.occurred_at {
+            Some(at) => {
+                let start = at.timestamp_millis().div_euclid(bucket_ms) * bucket_ms;
+                windows.entry(start).or_insert((0, 0, 0.0))
+            }
+            None => &mut unbucketed,
+        };
+        totals.1 += 1;
+        if t.successful {
+            totals.0 += 1;
+            totals.2 += t.amount_usd.max(0.0);
+        }
+    }
+
+    let rate = |successful: u64, total: u64| successful as f64 / total as f64 * 100.0;
+    let buckets = windows
+        .into_iter()
+        .filter_map(|(start_ms, (successful, total, volume_usd))| {
+            Some(SuccessRateBucket {
+                window_start: DateTime::<Utc>::from_timestamp_millis(start_ms)?,
+                success_rate: rate(successful, total),
+                volume_usd,
+            })
+        })
+        .collect();
+
+    SuccessRateSeries {
+        buckets,
+        unbucketed_success_rate: (unbucketed.1 > 0).then(|| rate(unbucketed.0, unbucketed.1)),
+        unbucketed_volume_usd: unbucketed.2,
+    }
+}
+
 /// Filter payments by time window and compute metrics
 pub fn compute_metrics_by_window(
     payments: &[PaymentRecord],
@@ -230,6 +589,139 @@ pub fn compute_metrics_by_window(
     compute_metrics_from_payments(&filtered)
 }
 
+/// Volume at which an anchor earns the whole volume share of its reliability score
+pub const RELIABILITY_FULL_VOLUME_USD: f64 = 1_000_000.0;
+
+/// Single 0–100 reliability score for an anchor, weighted as:
+///
+/// - 70% success rate (successful / total transactions)
+/// - 20% settlement latency: full credit at or under 1s, none at 10s or more,
+///   linear in between (the curve `compute_anchor_metrics` uses)
+/// - 10% volume, log-scaled so `RELIABILITY_FULL_VOLUME_USD` or more earns it all
+///
+/// An anchor with no transactions scores 0.
+pub fn compute_anchor_reliability(anchor: &Anchor) -> f64 {
+    if anchor.total_transactions <= 0 {
+        return 0.0;
+    }
+
+    let success =
+        (anchor.successful_transactions as f64 / anchor.total_transactions as f64).clamp(0.0, 1.0);
+    let latency = calculate_settlement_time_score(Some(anchor.avg_settlement_time_ms)) / 100.0;
+    let volume = ((anchor.total_volume_usd.max(0.0) + 1.0).log10()
+        / (RELIABILITY_FULL_VOLUME_USD + 1.0).log10())
+    .min(1.0);
+
+    (70.0 * success + 20.0 * latency + 10.0 * volume).clamp(0.0, 100.0)
+}
+
+/// Corridors with fewer transactions than this grade `'?'`
+pub const MIN_GRADE_TRANSACTIONS: i64 = 10;
+
+/// `(grade, minimum success rate %, maximum settlement latency ms)`, best first
+const GRADE_BANDS: [(char, f64, i32); 4] = [
+    ('A', 99.0, 5_000),
+    ('B', 97.0, 10_000),
+    ('C', 93.0, 30_000),
+    ('D', 85.0, 60_000),
+];
+
+/// Letter grade for a corridor's health. A corridor earns the best band whose
+/// floor on success rate and ceiling on latency it meets, both inclusive:
+///
+/// | Grade | Success rate | Latency   |
+/// |-------|--------------|-----------|
+/// | A     | ≥ 99%        | ≤ 5s      |
+/// | B     | ≥ 97%        | ≤ 10s     |
+/// | C     | ≥ 93%        | ≤ 30s     |
+/// | D     | ≥ 85%        | ≤ 60s     |
+/// | F     | < 85%        | or > 60s  |
+///
+/// Latency is the p95 settlement time, falling back to the average; with neither
+/// the grade rests on success rate alone. Fewer than `MIN_GRADE_TRANSACTIONS`
+/// transactions grade `'?'` (insufficient data).
+pub fn grade_corridor(metrics: &CorridorMetrics) -> char {
+    if metrics.total_transactions < MIN_GRADE_TRANSACTIONS {
+        return '?';
+    }
+    let latency_ms = metrics
+        .p95_settlement_latency_ms
+        .or(metrics.avg_settlement_latency_ms);
+
+    GRADE_BANDS
+        .iter()
+        .find(|(_, min_success_rate, max_latency_ms)| {
+            metrics.success_rate >= *min_success_rate
+                && latency_ms.is_none_or(|latency| latency <= *max_latency_ms)
+        })
+        .map_or('F', |(grade, ..)| *grade)
+}
+
+/// Fitted change in volume across the window, as a percentage of its mean,
+/// inside which the trend is `Flat` rather than `Up` or `Down`. Keeps ordinary
+/// run-to-run noise from flipping the arrow.
+pub const FLAT_TREND_BAND_PCT: f64 = 5.0;
+
+/// Which way a corridor's volume is heading
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Trend {
+    Up,
+    Down,
+    Flat,
+    /// Fewer than two snapshots, or all taken at the same instant
+    Unknown,
+}
+
+/// Volume trend over `snapshots`, from the least-squares slope of volume
+/// against `recorded_at`. The slope is scaled to the change it predicts across
+/// the whole window; within `FLAT_TREND_BAND_PCT` of the mean volume that's
+/// `Flat`. A window with no volume at all is `Flat` too.
+pub fn volume_trend(snapshots: &[CorridorMetricsSnapshot]) -> Trend {
+    if snapshots.len() < 2 {
+        return Trend::Unknown;
+    }
+
+    let start = snapshots[0].recorded_at;
+    let points: Vec<(f64, f64)> = snapshots
+        .iter()
+        .map(|snapshot| {
+            let hours = (snapshot.recorded_at - start).num_milliseconds() as f64 / 3_600_000.0;
+            (hours, snapshot.volume_usd)
+        })
+        .collect();
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let spread: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if spread == 0.0 {
+        return Trend::Unknown;
+    }
+    if mean_y.abs() < f64::EPSILON {
+        return Trend::Flat;
+    }
+
+    let slope = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum::<f64>()
+        / spread;
+    let (min_x, max_x) = points
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(lo, hi), (x, _)| {
+            (lo.min(*x), hi.max(*x))
+        });
+    let change_pct = slope * (max_x - min_x) / mean_y.abs() * 100.0;
+
+    if change_pct > FLAT_TREND_BAND_PCT {
+        Trend::Up
+    } else if change_pct < -FLAT_TREND_BAND_PCT {
+        Trend::Down
+    } else {
+        Trend::Flat
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -332,16 +824,22 @@ mod tests {
                 successful: true,
                 settlement_latency_ms: Some(1000),
                 amount_usd: 100.0,
+                occurred_at: None,
+                currency: None,
             },
             CorridorTransaction {
                 successful: true,
                 settlement_latency_ms: Some(3000),
                 amount_usd: 200.0,
+                occurred_at: None,
+                currency: None,
             },
             CorridorTransaction {
                 successful: false,
                 settlement_latency_ms: None,
                 amount_usd: 50.0,
+                occurred_at: None,
+                currency: None,
             },
         ];
 
@@ -395,11 +893,15 @@ mod tests {
                 successful: false,
                 settlement_latency_ms: None,
                 amount_usd: 10.0,
+                occurred_at: None,
+                currency: None,
             },
             CorridorTransaction {
                 successful: false,
                 settlement_latency_ms: None,
                 amount_usd: 20.0,
+                occurred_at: None,
+                currency: None,
             },
         ];
         let metrics = compute_corridor_metrics(&txns, None, 1.0);
@@ -409,6 +911,133 @@ mod tests {
         assert_eq!(metrics.liquidity_depth_usd, 0.0);
     }
 
+    fn successful_txn(latency_ms: i32) -> CorridorTransaction {
+        CorridorTransaction {
+            successful: true,
+            settlement_latency_ms: Some(latency_ms),
+            amount_usd: 10.0,
+            occurred_at: None,
+            currency: None,
+        }
+    }
+
+    #[test]
+    fn test_latency_percentiles_known_distribution() {
+        // Latencies 10ms, 20ms, ... 1000ms
+        let txns: Vec<CorridorTransaction> = (1..=100).map(|i| successful_txn(i * 10)).collect();
+
+        let metrics = compute_corridor_metrics(&txns, None, 1.0);
+        assert_eq!(metrics.median_settlement_latency_ms, Some(505)); // (500 + 510) / 2
+        assert_eq!(metrics.p95_settlement_latency_ms, Some(950));
+        assert_eq!(metrics.p99_settlement_latency_ms, Some(990));
+    }
+
+    #[test]
+    fn test_latency_percentiles_capture_tail() {
+        let mut txns: Vec<CorridorTransaction> = (0..90).map(|_| successful_txn(100)).collect();
+        txns.extend((0..10).map(|_| successful_txn(5000)));
+        // Transactions without a recorded latency are ignored
+        txns.push(CorridorTransaction {
+            successful: true,
+            settlement_latency_ms: None,
+            amount_usd: 10.0,
+            occurred_at: None,
+            currency: None,
+        });
+
+        let metrics = compute_corridor_metrics(&txns, None, 1.0);
+        assert_eq!(metrics.median_settlement_latency_ms, Some(100));
+        assert_eq!(metrics.p95_settlement_latency_ms, Some(5000));
+        assert_eq!(metrics.p99_settlement_latency_ms, Some(5000));
+        assert_eq!(metrics.avg_settlement_latency_ms, Some(590));
+    }
+
+    #[test]
+    fn test_latency_percentiles_single_transaction() {
+        let metrics = compute_corridor_metrics(&[successful_txn(1234)], None, 1.0);
+        assert_eq!(metrics.median_settlement_latency_ms, Some(1234));
+        assert_eq!(metrics.p95_settlement_latency_ms, Some(1234));
+        assert_eq!(metrics.p99_settlement_latency_ms, Some(1234));
+    }
+
+    #[test]
+    fn test_latency_percentiles_none_without_latencies() {
+        let empty = compute_corridor_metrics(&[], None, 1.0);
+        assert_eq!(empty.p95_settlement_latency_ms, None);
+        assert_eq!(empty.p99_settlement_latency_ms, None);
+
+        let failed_only = compute_corridor_metrics(
+            &[CorridorTransaction {
+                successful: false,
+                settlement_latency_ms: None,
+                amount_usd: 10.0,
+                occurred_at: None,
+                currency: None,
+            }],
+            None,
+            1.0,
+        );
+        assert_eq!(failed_only.p95_settlement_latency_ms, None);
+        assert_eq!(failed_only.p99_settlement_latency_ms, None);
+    }
+
+    #[test]
+    fn test_volume_weighted_success_rate_mixed_magnitudes() {
+        let txn = |successful: bool, amount_usd: f64| CorridorTransaction {
+            successful,
+            settlement_latency_ms: None,
+            amount_usd,
+            occurred_at: None,
+            currency: None,
+        };
+
+        // One failed $1M transfer next to a thousand successful $5 ones
+        let mut big_failure = vec![txn(false, 1_000_000.0)];
+        big_failure.extend((0..1000).map(|_| txn(true, 5.0)));
+        let m = compute_corridor_metrics(&big_failure, None, 1.0);
+        assert!(m.success_rate > 99.9);
+        assert!((m.volume_weighted_success_rate - 5_000.0 / 1_005_000.0 * 100.0).abs() < 1e-9);
+
+        // The reverse: the $1M transfer settles and the small ones fail
+        let mut big_success = vec![txn(true, 1_000_000.0)];
+        big_success.extend((0..1000).map(|_| txn(false, 5.0)));
+        let m = compute_corridor_metrics(&big_success, None, 1.0);
+        assert!(m.success_rate < 0.1);
+        assert!(m.volume_weighted_success_rate > 99.5);
+    }
+
+    #[test]
+    fn test_volume_weighted_success_rate_zero_volume() {
+        let txns = vec![CorridorTransaction {
+            successful: true,
+            settlement_latency_ms: Some(100),
+            amount_usd: 0.0,
+            occurred_at: None,
+            currency: None,
+        }];
+        let m = compute_corridor_metrics(&txns, None, 1.0);
+        assert_eq!(m.success_rate, 100.0);
+        assert_eq!(m.volume_weighted_success_rate, 0.0);
+
+        assert_eq!(
+            compute_corridor_metrics(&[], None, 1.0).volume_weighted_success_rate,
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_volume_weighted_success_rate_from_payments() {
+        let now = Utc::now();
+        let payments = vec![
+            create_test_payment_record("USDC", "EURC", 300.0, true, now),
+            create_test_payment_record("USDC", "EURC", 100.0, false, now),
+        ];
+
+        let metrics = compute_metrics_from_payments(&payments);
+        assert_eq!(metrics[0].success_rate, 50.0);
+        assert_eq!(metrics[0].volume_weighted_success_rate, 75.0);
+    }
+
     #[test]
     fn test_median_latency_from_payments() {
         let now = Utc::now();
@@ -445,4 +1074,486 @@ mod tests {
         assert_eq!(m.avg_settlement_latency_ms, Some(2500)); // (1000 + 2000 + 3000 + 4000) / 4
         assert_eq!(m.median_settlement_latency_ms, Some(2500)); // (2000 + 3000) / 2
     }
+
+    #[test]
+    fn test_success_rate_series_buckets_by_window() {
+        let at = |minute: u32| {
+            Some(
+                chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 1, 1, 10, minute, 0).unwrap(),
+            )
+        };
+        let txn = |successful: bool, amount_usd: f64, occurred_at| CorridorTransaction {
+            successful,
+            settlement_latency_ms: None,
+            amount_usd,
+            occurred_at,
+            currency: None,
+        };
+        let txns = vec![
+            txn(true, 100.0, at(5)),
+            txn(false, 50.0, at(20)),
+            txn(true, 10.0, at(40)),
+            txn(true, 30.0, at(55)),
+            txn(false, 1_000.0, None),
+            txn(true, 7.0, None),
+            // Out of order input lands in the earliest window
+            txn(true, 1.0, at(0)),
+        ];
+
+        let series = compute_success_rate_series(&txns, chrono::Duration::minutes(30));
+
+        assert_eq!(series.buckets.len(), 2);
+        let first = &series.buckets[0];
+        assert_eq!(first.window_start, at(0).unwrap());
+        assert!((first.success_rate - 2.0 / 3.0 * 100.0).abs() < 1e-9);
+        assert_eq!(first.volume_usd, 101.0);
+        let second = &series.buckets[1];
+        assert_eq!(second.window_start, at(30).unwrap());
+        assert_eq!(second.success_rate, 100.0);
+        assert_eq!(second.volume_usd, 40.0);
+
+        assert_eq!(series.unbucketed_success_rate, Some(50.0));
+        assert_eq!(series.unbucketed_volume_usd, 7.0);
+    }
+
+    #[test]
+    fn test_success_rate_series_without_untimestamped_transactions() {
+        let txns = vec![CorridorTransaction {
+            successful: true,
+            settlement_latency_ms: None,
+            amount_usd: 5.0,
+            occurred_at: Some(Utc::now()),
+            currency: None,
+        }];
+        let series = compute_success_rate_series(&txns, chrono::Duration::hours(1));
+        assert_eq!(series.buckets.len(), 1);
+        assert_eq!(series.unbucketed_success_rate, None);
+
+        let empty = compute_success_rate_series(&[], chrono::Duration::hours(1));
+        assert!(empty.buckets.is_empty());
+        assert_eq!(empty.unbucketed_success_rate, None);
+    }
+
+    fn metrics_with(success_rate: f64, p95_latency_ms: Option<i32>) -> CorridorMetrics {
+        let txns = vec![CorridorTransaction {
+            successful: true,
+            settlement_latency_ms: None,
+            amount_usd: 1.0,
+            occurred_at: None,
+            currency: None,
+        }];
+        CorridorMetrics {
+            success_rate,
+            p95_settlement_latency_ms: p95_latency_ms,
+            ..compute_corridor_metrics(&txns, None, 1.0)
+        }
+    }
+
+    #[test]
+    fn test_detect_anomaly_success_rate_drop() {
+        let previous = metrics_with(98.0, Some(1_000));
+        let current = metrics_with(80.0, Some(1_100));
+
+        let anomalies = detect_anomaly(&previous, &current, &AnomalyThresholds::default());
+        assert_eq!(anomalies, vec![Anomaly::SuccessRateDrop { delta: 18.0 }]);
+
+        // A drop within the threshold is not reported
+        let current = metrics_with(90.0, Some(1_100));
+        assert!(detect_anomaly(&previous, &current, &AnomalyThresholds::default()).is_empty());
+    }
+
+    #[test]
+    fn test_detect_anomaly_latency_spike() {
+        let previous = metrics_with(95.0, Some(1_000));
+        let current = metrics_with(95.0, Some(2_500));
+
+        let anomalies = detect_anomaly(&previous, &current, &AnomalyThresholds::default());
+        assert_eq!(anomalies, vec![Anomaly::LatencySpike { delta: 1_500 }]);
+
+        let lenient = AnomalyThresholds {
+            latency_spike_ratio: 3.0,
+            ..AnomalyThresholds::default()
+        };
+        assert!(detect_anomaly(&previous, &current, &lenient).is_empty());
+    }
+
+    #[test]
+    fn test_detect_anomaly_reports_both_and_ignores_missing_latency() {
+        let previous = metrics_with(99.0, Some(500));
+        let current = metrics_with(50.0, Some(5_000));
+        assert_eq!(
+            detect_anomaly(&previous, &current, &AnomalyThresholds::default()),
+            vec![
+                Anomaly::SuccessRateDrop { delta: 49.0 },
+                Anomaly::LatencySpike { delta: 4_500 },
+            ]
+        );
+
+        let previous = metrics_with(99.0, None);
+        let current = metrics_with(99.0, Some(5_000));
+        assert!(detect_anomaly(&previous, &current, &AnomalyThresholds::default()).is_empty());
+    }
+
+    #[test]
+    fn test_detect_anomaly_without_previous_data() {
+        let previous = compute_corridor_metrics(&[], None, 1.0);
+        let current = metrics_with(10.0, Some(10_000));
+        assert!(detect_anomaly(&previous, &current, &AnomalyThresholds::default()).is_empty());
+    }
+
+    #[test]
+    fn test_parse_threshold_falls_back_on_invalid_values() {
+        let parse = |raw| parse_threshold("ANOMALY_LATENCY_SPIKE_RATIO", raw, 2.0);
+        assert_eq!(parse(None), 2.0);
+        assert_eq!(parse(Some("1.5")), 1.5);
+        assert_eq!(parse(Some(" 3 ")), 3.0);
+        assert_eq!(parse(Some("0")), 2.0);
+        assert_eq!(parse(Some("NaN")), 2.0);
+        assert_eq!(parse(Some("x")), 2.0);
+    }
+
+    #[test]
+    fn test_compute_corridor_metrics_converts_volume_to_usd() {
+        let txn = |successful: bool, amount: f64, currency: Option<&str>| CorridorTransaction {
+            successful,
+            settlement_latency_ms: None,
+            amount_usd: amount,
+            occurred_at: None,
+            currency: currency.map(str::to_string),
+        };
+        let txns = vec![
+            txn(true, 100.0, None),
+            txn(true, 100.0, Some("eur")),
+            txn(false, 50.0, Some("USD")),
+            txn(true, 1_000.0, Some("NGN")),
+        ];
+        let fx = StaticFxRates::new().with_rate("EUR", "USD", 1.1);
+
+        let metrics = compute_corridor_metrics_with_fx(&txns, None, 1.0, &fx);
+
+        assert!((metrics.volume_usd - 210.0).abs() < 1e-9);
+        assert_eq!(metrics.skipped_fx, 1);
+        // Unconvertible transactions still count towards the success rate
+        assert_eq!(metrics.total_transactions, 4);
+        assert_eq!(metrics.success_rate, 75.0);
+        assert!((metrics.volume_weighted_success_rate - 210.0 / 260.0 * 100.0).abs() < 1e-9);
+    }
+
+    fn assert_same_metrics(actual: &CorridorMetrics, expected: &CorridorMetrics) {
+        assert_eq!(actual.total_transactions, expected.total_transactions);
+        assert_eq!(
+            actual.successful_transactions,
+            expected.successful_transactions
+        );
+        assert_eq!(actual.failed_transactions, expected.failed_transactions);
+        assert_eq!(actual.success_rate, expected.success_rate);
+        assert_eq!(
+            actual.volume_weighted_success_rate,
+            expected.volume_weighted_success_rate
+        );
+        assert_eq!(actual.volume_usd, expected.volume_usd);
+        assert_eq!(
+            actual.avg_settlement_latency_ms,
+            expected.avg_settlement_latency_ms
+        );
+        assert_eq!(
+            actual.median_settlement_latency_ms,
+            expected.median_settlement_latency_ms
+        );
+        assert_eq!(
+            actual.p95_settlement_latency_ms,
+            expected.p95_settlement_latency_ms
+        );
+        assert_eq!(
+            actual.p99_settlement_latency_ms,
+            expected.p99_settlement_latency_ms
+        );
+        assert_eq!(actual.liquidity_depth_usd, expected.liquidity_depth_usd);
+        assert_eq!(actual.skipped_fx, expected.skipped_fx);
+    }
+
+    /// Random mix of outcomes, latencies and currencies; whole-dollar amounts so
+    /// volume sums are exact in any order
+    fn random_transactions(rng: &mut rand::rngs::StdRng) -> Vec<CorridorTransaction> {
+        use rand::Rng;
+
+        let len = rng.gen_range(0..300);
+        (0..len)
+            .map(|_| CorridorTransaction {
+                successful: rng.gen_bool(0.8),
+                settlement_latency_ms: rng.gen_bool(0.9).then(|| rng.gen_range(0..60_000)),
+                amount_usd: rng.gen_range(0..10_000) as f64,
+                occurred_at: None,
+                currency: [None, Some("EUR"), Some("NGN")][rng.gen_range(0..3)].map(str::to_string),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_accumulator_matches_batch_metrics_on_random_streams() {
+        use rand::{Rng, SeedableRng};
+
+        let fx = StaticFxRates::new().with_rate("EUR", "USD", 2.0);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(54);
+
+        for _ in 0..200 {
+            let txns = random_transactions(&mut rng);
+            let batch = compute_corridor_metrics_with_fx(&txns, None, 1.0, &fx);
+
+            let mut streamed = CorridorMetricsAccumulator::with_fx(&fx);
+            for t in &txns {
+                streamed.add(t);
+            }
+            assert_same_metrics(&streamed.finalize(), &batch);
+
+            // Two halves filled separately and merged
+            let split = rng.gen_range(0..=txns.len());
+            let mut first = CorridorMetricsAccumulator::with_fx(&fx);
+            let mut second = CorridorMetricsAccumulator::with_fx(&fx);
+            txns[..split].iter().for_each(|t| first.add(t));
+            txns[split..].iter().for_each(|t| second.add(t));
+            first.merge(second);
+            assert_same_metrics(&first.finalize(), &batch);
+        }
+    }
+
+    #[test]
+    fn test_accumulator_defaults_to_usd_and_reports_liquidity() {
+        let order_book = OrderBookSnapshot {
+            bids: vec![OrderBookEntry {
+                price: 99.5,
+                amount_usd: 150.0,
+            }],
+            asks: vec![OrderBookEntry {
+                price: 100.5,
+                amount_usd: 200.0,
+            }],
+        };
+        let txns = vec![CorridorTransaction {
+            successful: true,
+            settlement_latency_ms: Some(1500),
+            amount_usd: 100.0,
+            occurred_at: None,
+            currency: Some("EUR".to_string()),
+        }];
+
+        let mut accumulator = CorridorMetricsAccumulator::new().with_order_book(&order_book, 1.0);
+        accumulator.add(&txns[0]);
+
+        assert_same_metrics(
+            &accumulator.finalize(),
+            &compute_corridor_metrics(&txns, Some(&order_book), 1.0),
+        );
+        assert_eq!(accumulator.finalize().liquidity_depth_usd, 350.0);
+    }
+
+    #[test]
+    fn test_compute_corridor_metrics_without_rates_skips_foreign_currency() {
+        let txns = vec![CorridorTransaction {
+            successful: true,
+            settlement_latency_ms: None,
+            amount_usd: 100.0,
+            occurred_at: None,
+            currency: Some("EUR".to_string()),
+        }];
+
+        let metrics = compute_corridor_metrics(&txns, None, 1.0);
+        assert_eq!(metrics.volume_usd, 0.0);
+        assert_eq!(metrics.skipped_fx, 1);
+    }
+
+    #[test]
+    fn test_static_fx_rates_lookup() {
+        let fx = StaticFxRates::new().with_rate("eur", "usd", 1.1);
+        assert_eq!(fx.rate("EUR", "USD"), Some(1.1));
+        assert_eq!(fx.rate("USD", "EUR"), None);
+        assert_eq!(fx.rate("ngn", "NGN"), Some(1.0));
+    }
+
+    fn anchor_with(total: i64, successful: i64, avg_settlement_ms: i32, volume_usd: f64) -> Anchor {
+        Anchor {
+            id: Uuid::new_v4().to_string(),
+            name: "Test Anchor".to_string(),
+            stellar_account: "GTEST".to_string(),
+            home_domain: None,
+            total_transactions: total,
+            successful_transactions: successful,
+            failed_transactions: total - successful,
+            total_volume_usd: volume_usd,
+            avg_settlement_time_ms: avg_settlement_ms,
+            reliability_score: 0.0,
+            status: "green".to_string(),
+            is_active: true,
+            last_activity_at: None,
+            version: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_anchor_reliability_pinned_scores() {
+        let cases = [
+            // Perfect on every axis
+            (anchor_with(1000, 1000, 1000, 1_000_000.0), 100.0),
+            // 95% success (66.5), 5.5s settlement (10), $10k volume (~6.667)
+            (anchor_with(1000, 950, 5500, 10_000.0), 83.1667),
+            // Volume past the cap earns no more than the cap
+            (anchor_with(100, 50, 800, 1e9), 65.0),
+            // Nothing succeeds, settlement past 10s, no volume
+            (anchor_with(100, 0, 20_000, 0.0), 0.0),
+        ];
+        for (anchor, expected) in cases {
+            let score = compute_anchor_reliability(&anchor);
+            assert!(
+                (score - expected).abs() < 1e-3,
+                "expected {} got {} for {:?}",
+                expected,
+                score,
+                anchor
+            );
+        }
+    }
+
+    #[test]
+    fn test_anchor_reliability_without_transactions_is_zero() {
+        let score = compute_anchor_reliability(&anchor_with(0, 0, 0, 0.0));
+        assert_eq!(score, 0.0);
+    }
+
+    fn graded(success_rate: f64, p95_latency_ms: Option<i32>) -> CorridorMetrics {
+        CorridorMetrics {
+            total_transactions: 100,
+            ..metrics_with(success_rate, p95_latency_ms)
+        }
+    }
+
+    #[test]
+    fn test_grade_success_rate_boundaries() {
+        let cases = [
+            (100.0, 'A'),
+            (99.0, 'A'),
+            (98.99, 'B'),
+            (97.0, 'B'),
+            (96.99, 'C'),
+            (93.0, 'C'),
+            (92.99, 'D'),
+            (85.0, 'D'),
+            (84.99, 'F'),
+            (0.0, 'F'),
+        ];
+        for (success_rate, expected) in cases {
+            assert_eq!(
+                grade_corridor(&graded(success_rate, Some(1_000))),
+                expected,
+                "success rate {}",
+                success_rate
+            );
+        }
+    }
+
+    #[test]
+    fn test_grade_latency_boundaries() {
+        let cases = [
+            (5_000, 'A'),
+            (5_001, 'B'),
+            (10_000, 'B'),
+            (10_001, 'C'),
+            (30_000, 'C'),
+            (30_001, 'D'),
+            (60_000, 'D'),
+            (60_001, 'F'),
+        ];
+        for (latency_ms, expected) in cases {
+            assert_eq!(
+                grade_corridor(&graded(100.0, Some(latency_ms))),
+                expected,
+                "latency {}ms",
+                latency_ms
+            );
+        }
+    }
+
+    #[test]
+    fn test_grade_takes_the_worse_of_success_and_latency() {
+        assert_eq!(grade_corridor(&graded(99.5, Some(20_000))), 'C');
+        assert_eq!(grade_corridor(&graded(90.0, Some(500))), 'D');
+
+        // Average latency stands in for a missing p95; no latency at all grades on success
+        let averaged = CorridorMetrics {
+            avg_settlement_latency_ms: Some(12_000),
+            ..graded(100.0, None)
+        };
+        assert_eq!(grade_corridor(&averaged), 'C');
+        assert_eq!(grade_corridor(&graded(97.5, None)), 'B');
+    }
+
+    #[test]
+    fn test_grade_needs_a_minimum_sample() {
+        let sparse = CorridorMetrics {
+            total_transactions: MIN_GRADE_TRANSACTIONS - 1,
+            ..graded(100.0, Some(1_000))
+        };
+        assert_eq!(grade_corridor(&sparse), '?');
+
+        let enough = CorridorMetrics {
+            total_transactions: MIN_GRADE_TRANSACTIONS,
+            ..sparse
+        };
+        assert_eq!(grade_corridor(&enough), 'A');
+    }
+
+    fn snapshots_with_volumes(volumes: &[f64]) -> Vec<CorridorMetricsSnapshot> {
+        let start = Utc::now();
+        volumes
+            .iter()
+            .enumerate()
+            .map(|(hour, &volume_usd)| CorridorMetricsSnapshot {
+                id: Uuid::new_v4().to_string(),
+                corridor_id: "corridor".to_string(),
+                total_transactions: 10,
+                successful_transactions: 10,
+                failed_transactions: 0,
+                success_rate: 100.0,
+                volume_weighted_success_rate: Some(100.0),
+                volume_usd,
+                median_settlement_latency_ms: None,
+                p95_settlement_latency_ms: None,
+                p99_settlement_latency_ms: None,
+                recorded_at: start + chrono::Duration::hours(hour as i64),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_volume_trend_up_and_down() {
+        let rising = snapshots_with_volumes(&[100.0, 140.0, 130.0, 180.0, 220.0]);
+        assert_eq!(volume_trend(&rising), Trend::Up);
+
+        let falling = snapshots_with_volumes(&[500.0, 420.0, 450.0, 300.0, 250.0]);
+        assert_eq!(volume_trend(&falling), Trend::Down);
+    }
+
+    #[test]
+    fn test_volume_trend_noisy_but_level_series_is_flat() {
+        let noisy = snapshots_with_volumes(&[1000.0, 1030.0, 970.0, 1020.0, 985.0, 1005.0]);
+        assert_eq!(volume_trend(&noisy), Trend::Flat);
+        assert_eq!(
+            volume_trend(&snapshots_with_volumes(&[0.0, 0.0])),
+            Trend::Flat
+        );
+    }
+
+    #[test]
+    fn test_volume_trend_needs_two_distinct_snapshots() {
+        assert_eq!(volume_trend(&[]), Trend::Unknown);
+        assert_eq!(
+            volume_trend(&snapshots_with_volumes(&[100.0])),
+            Trend::Unknown
+        );
+
+        let mut simultaneous = snapshots_with_volumes(&[100.0, 300.0]);
+        simultaneous[1].recorded_at = simultaneous[0].recorded_at;
+        assert_eq!(volume_trend(&simultaneous), Trend::Unknown);
+    }
 }
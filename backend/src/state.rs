@@ -1,14 +1,21 @@
 use std::sync::Arc;
+use crate::cache::{CacheConfig, RedisCache};
+use crate::cache_invalidation::CacheInvalidationService;
 use crate::database::Database;
 use crate::websocket::WsState;
 use crate::ingestion::DataIngestionService;
+use crate::services::analytics::AnomalyThresholds;
 
-/// Shared application state for handlers
-#[derive(Clone)]
-pub struct AppState {
+/// Shared application state for handlers. Handlers generic over the `Cache`
+/// can be served from an `AppState` holding a test double (see `with_cache`).
+pub struct AppState<C = RedisCache> {
     pub db: Arc<Database>,
     pub ws_state: Arc<WsState>,
     pub ingestion: Arc<DataIngestionService>,
+    pub cache: Arc<C>,
+    pub cache_config: CacheConfig,
+    pub anomaly_thresholds: AnomalyThresholds,
+    pub cache_invalidation: Arc<CacheInvalidationService>,
 }
 
 impl AppState {
@@ -16,11 +23,53 @@ impl AppState {
         db: Arc<Database>,
         ws_state: Arc<WsState>,
         ingestion: Arc<DataIngestionService>,
+        cache: Arc<RedisCache>,
+        cache_config: CacheConfig,
+        anomaly_thresholds: AnomalyThresholds,
     ) -> Self {
+        let cache_invalidation = Arc::new(
+            CacheInvalidationService::new(Arc::clone(&cache))
+                .with_double_delete(cache_config.double_delete_delay),
+        );
         Self {
             db,
             ws_state,
             ingestion,
+            cache,
+            cache_config,
+            anomaly_thresholds,
+            cache_invalidation,
+        }
+    }
+}
+
+impl<C> AppState<C> {
+    /// The same state reading and writing through `cache` instead. Invalidation
+    /// still goes through the original service.
+    pub fn with_cache<D>(self, cache: Arc<D>) -> AppState<D> {
+        AppState {
+            db: self.db,
+            ws_state: self.ws_state,
+            ingestion: self.ingestion,
+            cache,
+            cache_config: self.cache_config,
+            anomaly_thresholds: self.anomaly_thresholds,
+            cache_invalidation: self.cache_invalidation,
+        }
+    }
+}
+
+// Not derived: that would require `C: Clone`, which the `Arc` makes unnecessary
+impl<C> Clone for AppState<C> {
+    fn clone(&self) -> Self {
+        Self {
+            db: Arc::clone(&self.db),
+            ws_state: Arc::clone(&self.ws_state),
+            ingestion: Arc::clone(&self.ingestion),
+            cache: Arc::clone(&self.cache),
+            cache_config: self.cache_config.clone(),
+            anomaly_thresholds: self.anomaly_thresholds,
+            cache_invalidation: Arc::clone(&self.cache_invalidation),
         }
     }
 }
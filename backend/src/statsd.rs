@@ -0,0 +1,306 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+
+use crate::cache::{BreakerState, CacheMetricsSummary, LatencySummary, RedisCache};
+
+/// Metric names are prefixed with this unless `STATSD_PREFIX` says otherwise
+pub const DEFAULT_STATSD_PREFIX: &str = "stellar.cache";
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+/// Keeps each datagram under a typical 1500-byte MTU once IP/UDP headers are added
+const MAX_PACKET_BYTES: usize = 1432;
+
+/// Where and how often cache metrics are pushed to a StatsD/Datadog agent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatsdConfig {
+    /// `host:port` of the agent
+    pub addr: String,
+    pub flush_interval: Duration,
+    pub prefix: String,
+}
+
+impl StatsdConfig {
+    /// `None` unless `STATSD_ADDR` is set; `STATSD_FLUSH_INTERVAL_SECS` (default 10)
+    /// and `STATSD_PREFIX` tune the rest
+    pub fn from_env() -> Option<Self> {
+        let addr = std::env::var("STATSD_ADDR")
+            .ok()
+            .map(|addr| addr.trim().to_string())
+            .filter(|addr| !addr.is_empty())?;
+        let flush_interval = std::env::var("STATSD_FLUSH_INTERVAL_SECS")
+            .ok()
+            .and_then(|secs| secs.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .map_or(DEFAULT_FLUSH_INTERVAL, Duration::from_secs);
+        let prefix = std::env::var("STATSD_PREFIX")
+            .ok()
+            .filter(|prefix| !prefix.is_empty())
+            .unwrap_or_else(|| DEFAULT_STATSD_PREFIX.to_string());
+
+        Some(Self {
+            addr,
+            flush_interval,
+            prefix,
+        })
+    }
+}
+
+/// Lifetime totals at the previous flush, so counters go out as deltas
+#[derive(Debug, Default, Clone, Copy)]
+struct Totals {
+    hits: u64,
+    misses: u64,
+    invalidations: u64,
+    errors: u64,
+}
+
+/// Pushes `CacheMetrics` summaries to StatsD over UDP. Sends are fire-and-forget:
+/// an unreachable agent costs a debug log line, never an error.
+pub struct StatsdExporter {
+    socket: UdpSocket,
+    prefix: String,
+    flush_interval: Duration,
+    last: Totals,
+}
+
+impl StatsdExporter {
+    /// Bind a local socket aimed at the agent. Only name resolution can fail here;
+    /// whether anything listens shows up (and is ignored) on send.
+    pub async fn connect(config: &StatsdConfig) -> std::io::Result<Self> {
+        let local = if config.addr.starts_with('[') {
+            "[::]:0"
+        } else {
+            "0.0.0.0:0"
+        };
+        let socket = UdpSocket::bind(local).await?;
+        socket.connect(config.addr.as_str()).await?;
+
+        Ok(Self {
+            socket,
+            prefix: config.prefix.clone(),
+            flush_interval: config.flush_interval,
+            last: Totals::default(),
+        })
+    }
+
+    /// Start flushing `cache`'s metrics every interval if `STATSD_ADDR` is set;
+    /// otherwise, or if the agent's address can't be resolved, do nothing
+    pub async fn spawn_from_env(cache: Arc<RedisCache>) -> Option<JoinHandle<()>> {
+        let config = StatsdConfig::from_env()?;
+        match Self::connect(&config).await {
+            Ok(exporter) => {
+                tracing::info!(
+                    "Exporting cache metrics to StatsD at {} every {:?}",
+                    config.addr,
+                    config.flush_interval
+                );
+                Some(exporter.spawn(cache))
+            }
+            Err(e) => {
+                tracing::warn!("StatsD export disabled, can't reach {}: {}", config.addr, e);
+                None
+            }
+        }
+    }
+
+    pub fn spawn(mut self, cache: Arc<RedisCache>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.flush_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                self.flush(&cache.get_metrics()).await;
+            }
+        })
+    }
+
+    /// Send one summary's worth of metrics, packed into as few datagrams as fit
+    pub async fn flush(&mut self, summary: &CacheMetricsSummary) {
+        let lines = self.lines(summary);
+        for packet in pack(&lines) {
+            if let Err(e) = self.socket.send(packet.as_bytes()).await {
+                tracing::debug!("Dropped StatsD packet: {}", e);
+                return;
+            }
+        }
+    }
+
+    /// StatsD lines for `summary`: counters carry the change since the previous
+    /// flush, gauges the current value
+    fn lines(&mut self, summary: &CacheMetricsSummary) -> Vec<String> {
+        let totals = Totals {
+            hits: summary.hits,
+            misses: summary.misses,
+            invalidations: summary.invalidations,
+            errors: summary.errors,
+        };
+        let last = std::mem::replace(&mut self.last, totals);
+        let prefix = &self.prefix;
+        let counter = |name: &str, now: u64, before: u64| {
+            // A metrics reset drops the totals below the last flush; everything since is new
+            let delta = if now >= before { now - before } else { now };
+            format!("{}.{}:{}|c", prefix, name, delta)
+        };
+        let gauge = |name: &str, value: f64| format!("{}.{}:{}|g", prefix, name, value);
+
+        let mut lines = vec![
+            counter("hits", totals.hits, last.hits),
+            counter("misses", totals.misses, last.misses),
+            counter("invalidations", totals.invalidations, last.invalidations),
+            counter("errors", totals.errors, last.errors),
+            gauge("hit_rate", summary.hit_rate),
+            gauge("recent_hit_rate", summary.recent_hit_rate),
+            gauge(
+                "redis_circuit_open",
+                f64::from(u8::from(summary.redis_breaker == BreakerState::Open)),
+            ),
+            gauge("memory_entries", summary.memory_cache_entries as f64),
+            gauge("memory_bytes", summary.memory_cache_bytes as f64),
+        ];
+        for (tier, latency) in [
+            ("redis", &summary.latency.redis),
+            ("memory", &summary.latency.memory),
+        ] {
+            for (op, stats) in [
+                ("get", &latency.get),
+                ("set", &latency.set),
+                ("delete", &latency.delete),
+            ] {
+                lines.extend(latency_gauges(&gauge, tier, op, stats));
+            }
+        }
+        lines
+    }
+}
+
+fn latency_gauges(
+    gauge: &impl Fn(&str, f64) -> String,
+    tier: &str,
+    op: &str,
+    stats: &LatencySummary,
+) -> Vec<String> {
+    if stats.count == 0 {
+        return Vec::new();
+    }
+    [
+        ("p50", stats.p50_ms),
+        ("p95", stats.p95_ms),
+        ("p99", stats.p99_ms),
+    ]
+    .into_iter()
+    .map(|(quantile, ms)| gauge(&format!("latency.{}.{}.{}_ms", tier, op, quantile), ms))
+    .collect()
+}
+
+/// Newline-join lines into datagrams of at most `MAX_PACKET_BYTES`
+fn pack(lines: &[String]) -> Vec<String> {
+    let mut packets = Vec::new();
+    let mut packet = String::new();
+    for line in lines {
+        if !packet.is_empty() && packet.len() + 1 + line.len() > MAX_PACKET_BYTES {
+            packets.push(std::mem::take(&mut packet));
+        }
+        if !packet.is_empty() {
+            packet.push('\n');
+        }
+        packet.push_str(line);
+    }
+    if !packet.is_empty() {
+        packets.push(packet);
+    }
+    packets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheMetrics;
+
+    async fn exporter_to(agent: &UdpSocket) -> StatsdExporter {
+        StatsdExporter::connect(&StatsdConfig {
+            addr: agent.local_addr().unwrap().to_string(),
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            prefix: DEFAULT_STATSD_PREFIX.to_string(),
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn receive(agent: &UdpSocket) -> String {
+        let mut buf = [0u8; MAX_PACKET_BYTES];
+        let len = tokio::time::timeout(Duration::from_secs(2), agent.recv(&mut buf))
+            .await
+            .expect("no StatsD packet arrived")
+            .unwrap();
+        String::from_utf8(buf[..len].to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_flush_sends_cache_metrics_to_the_agent() {
+        let agent = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut exporter = exporter_to(&agent).await;
+        let metrics = CacheMetrics::default();
+        metrics.record_hit("anchor:data:1");
+        metrics.record_hit("anchor:data:2");
+        metrics.record_miss("anchor:data:3");
+
+        exporter.flush(&metrics.summary()).await;
+
+        let packet = receive(&agent).await;
+        let lines: Vec<&str> = packet.lines().collect();
+        assert!(lines.contains(&"stellar.cache.hits:2|c"), "{}", packet);
+        assert!(lines.contains(&"stellar.cache.misses:1|c"), "{}", packet);
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("stellar.cache.hit_rate:66.6")));
+        assert!(lines.contains(&"stellar.cache.redis_circuit_open:0|g"));
+    }
+
+    #[tokio::test]
+    async fn test_counters_are_sent_as_deltas_between_flushes() {
+        let agent = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut exporter = exporter_to(&agent).await;
+        let metrics = CacheMetrics::default();
+        metrics.record_hit("anchor:data:1");
+        exporter.flush(&metrics.summary()).await;
+        receive(&agent).await;
+
+        metrics.record_hit("anchor:data:1");
+        metrics.record_hit("anchor:data:1");
+        exporter.flush(&metrics.summary()).await;
+        assert!(receive(&agent).await.contains("stellar.cache.hits:2|c"));
+
+        metrics.reset();
+        metrics.record_hit("anchor:data:1");
+        exporter.flush(&metrics.summary()).await;
+        assert!(receive(&agent).await.contains("stellar.cache.hits:1|c"));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_agent_is_ignored() {
+        let agent = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut exporter = exporter_to(&agent).await;
+        drop(agent);
+
+        for _ in 0..3 {
+            exporter.flush(&CacheMetrics::default().summary()).await;
+        }
+    }
+
+    #[test]
+    fn test_lines_are_packed_under_the_packet_limit() {
+        let lines: Vec<String> = (0..200)
+            .map(|i| format!("stellar.cache.metric_{}:{}|g", i, i))
+            .collect();
+
+        let packets = pack(&lines);
+
+        assert!(packets.len() > 1);
+        assert!(packets
+            .iter()
+            .all(|packet| packet.len() <= MAX_PACKET_BYTES));
+        assert_eq!(packets.join("\n").lines().count(), lines.len());
+    }
+}
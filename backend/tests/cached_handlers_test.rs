@@ -0,0 +1,2724 @@
+use axum::body::Body;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, HeaderValue, Request, StatusCode};
+use axum::response::IntoResponse;
+use axum::Json;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+use stellar_insights_backend::cache::{
+    Cache, CacheConfig, CacheError, CacheKey, RedisCache, TtlRegistry,
+};
+use stellar_insights_backend::cache_warmer::CacheWarmer;
+use stellar_insights_backend::cached_handlers::{
+    anchors_batch_through_cache, create_anchor_asset_cached, create_anchor_cached,
+    create_corridor_cached, deactivate_anchor_cached, delete_anchor_cached,
+    get_anchor_assets_cached, get_anchor_by_account_cached, get_anchor_cached,
+    get_anchors_by_asset_cached, get_asset_metrics_cached, get_cache_failures, get_corridor_cached,
+    get_corridor_history_cached, get_corridors_by_asset_cached, get_dashboard_stats_cached,
+    inspect_cache_key, list_anchors_cached, list_corridors_cached, reactivate_anchor_cached,
+    update_anchor_metrics_batch_cached, update_anchor_metrics_cached,
+    update_corridor_metrics_from_transactions_cached, upsert_anchor_cached, warm_cache,
+    CacheInspectQuery, WarmCacheRequest,
+};
+use stellar_insights_backend::database::{
+    AnchorCursor, AnchorFilters, CorridorFilters, Database, Upserted,
+};
+use stellar_insights_backend::handlers::{
+    validate_stellar_account, AnchorAssetsQuery, ApiError, AssetCorridorsQuery,
+    BatchUpdateMetricsItem, CorridorHistoryQuery, CorridorHistoryResponse, CorridorTransactionDto,
+    CreateAssetRequest, DeleteAnchorQuery, ListAnchorsQuery, ListAnchorsResponse,
+    ListCorridorsQuery, ListCorridorsResponse, UpdateCorridorMetricsFromTxns, UpdateMetricsRequest,
+};
+use stellar_insights_backend::http_cache::{
+    CacheBypass, IdempotencyKey, X_CACHE, X_LIMIT_CLAMPED, X_TOTAL_COUNT,
+};
+use stellar_insights_backend::ingestion::DataIngestionService;
+use stellar_insights_backend::models::corridor::Corridor;
+use stellar_insights_backend::models::{
+    Anchor, AnchorDetailResponse, AssetMetrics, CorridorDetailResponse, CorridorMetricsSnapshot,
+    CreateAnchorRequest, CreateCorridorRequest,
+};
+use stellar_insights_backend::routes::public_routes;
+use stellar_insights_backend::rpc::StellarRpcClient;
+use stellar_insights_backend::services::analytics::{Anomaly, AnomalyThresholds, Trend};
+use stellar_insights_backend::state::AppState;
+use stellar_insights_backend::websocket::WsState;
+
+async fn setup_test_state() -> AppState {
+    setup_test_state_with(CacheConfig::default()).await
+}
+
+async fn setup_test_state_with(cache_config: CacheConfig) -> AppState {
+    // Closed port so the cache runs against its memory tier
+    let cache = RedisCache::from_url("redis://127.0.0.1:1").await.unwrap();
+    setup_test_state_on(cache, cache_config).await
+}
+
+/// A replica of the server on a cache reaching `REDIS_URL`, or `None` when no
+/// server is reachable
+async fn setup_redis_replica() -> Option<AppState> {
+    let cache = RedisCache::new().await.unwrap();
+    if !cache.is_redis_connected().await {
+        return None;
+    }
+    Some(setup_test_state_on(cache, CacheConfig::default()).await)
+}
+
+async fn setup_test_state_on(cache: RedisCache, cache_config: CacheConfig) -> AppState {
+    let database_url =
+        std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for integration tests");
+    let pool = PgPool::connect(&database_url).await.unwrap();
+
+    // Run migrations
+    sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+    let db = Arc::new(Database::new(pool));
+    let rpc_client = Arc::new(StellarRpcClient::new_with_defaults(true));
+    let ingestion = Arc::new(DataIngestionService::new(rpc_client, Arc::clone(&db)));
+    let cache = Arc::new(cache);
+
+    AppState::new(
+        db,
+        Arc::new(WsState::new()),
+        ingestion,
+        cache,
+        cache_config,
+        AnomalyThresholds::default(),
+    )
+}
+
+/// A fresh, checksum-valid `G...` account: random key bytes strkey-encoded
+fn random_stellar_account() -> String {
+    let mut payload = vec![6 << 3];
+    payload.extend_from_slice(uuid::Uuid::new_v4().as_bytes());
+    payload.extend_from_slice(uuid::Uuid::new_v4().as_bytes());
+    let mut crc = 0u16;
+    for &byte in &payload {
+        crc ^= u16::from(byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    payload.extend_from_slice(&crc.to_le_bytes());
+
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let (mut account, mut buffer, mut bits) = (String::new(), 0u32, 0u32);
+    for byte in payload {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            account.push(ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    account
+}
+
+async fn create_test_anchor(state: &AppState, name: &str) -> Anchor {
+    state
+        .db
+        .create_anchor(CreateAnchorRequest {
+            name: name.to_string(),
+            stellar_account: random_stellar_account(),
+            home_domain: None,
+        })
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_list_anchors_total_reports_row_count_not_page_length() {
+    let state = setup_test_state().await;
+    for i in 0..5 {
+        create_test_anchor(&state, &format!("Paged Anchor {}", i)).await;
+    }
+
+    let response = list_anchors_cached(
+        State(state.clone()),
+        Query(ListAnchorsQuery {
+            limit: 2,
+            offset: 0,
+            q: None,
+            sort_by: None,
+            order: None,
+            after: None,
+            include_inactive: false,
+            min_volume: None,
+            min_success_rate: None,
+        }),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.anchors.len(), 2);
+    assert!(response.total > response.anchors.len() as i64);
+}
+
+#[tokio::test]
+async fn test_oversized_list_limit_is_clamped_and_flagged() {
+    let state = setup_test_state_with(CacheConfig {
+        max_list_limit: 3,
+        ..CacheConfig::default()
+    })
+    .await;
+    for i in 0..4 {
+        create_test_anchor(&state, &format!("Clamped Anchor {}", i)).await;
+    }
+
+    let response = list_anchors_cached(
+        State(state),
+        Query(ListAnchorsQuery {
+            limit: 1_000_000,
+            offset: 0,
+            q: None,
+            sort_by: None,
+            order: None,
+            after: None,
+            include_inactive: false,
+            min_volume: None,
+            min_success_rate: None,
+        }),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.anchors.len(), 3);
+    assert!(response.has_more);
+    let response = response.into_response();
+    assert_eq!(response.headers()[X_LIMIT_CLAMPED], "3");
+}
+
+#[tokio::test]
+async fn test_negative_list_offset_is_rejected() {
+    let state = setup_test_state().await;
+    let err = list_corridors_cached(
+        State(state),
+        Query(ListCorridorsQuery {
+            limit: 10,
+            offset: -1,
+            sort_by: None,
+            order: None,
+            source_asset: None,
+            dest_asset: None,
+            min_success_rate: None,
+        }),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(matches!(err, ApiError::BadRequest(_)), "{:?}", err);
+}
+
+#[tokio::test]
+async fn test_anchor_list_filters_exclude_anchors_below_the_floors() {
+    let state = setup_test_state().await;
+    let busy = create_test_anchor(&state, "Floor Anchor Busy").await;
+    let unreliable = create_test_anchor(&state, "Floor Anchor Unreliable").await;
+    let quiet = create_test_anchor(&state, "Floor Anchor Quiet").await;
+    for (anchor, successful, volume) in [
+        (&busy, 990, 2_000_000.0),
+        (&unreliable, 900, 2_000_000.0),
+        (&quiet, 990, 50_000.0),
+    ] {
+        state
+            .db
+            .update_anchor_metrics(
+                anchor.id.parse().unwrap(),
+                1000,
+                successful,
+                1000 - successful,
+                Some(1000),
+                Some(volume),
+            )
+            .await
+            .unwrap();
+    }
+
+    let response = list_anchors_cached(
+        State(state.clone()),
+        Query(ListAnchorsQuery {
+            limit: 500,
+            offset: 0,
+            q: None,
+            sort_by: None,
+            order: None,
+            after: None,
+            include_inactive: false,
+            min_volume: Some(1_000_000.0),
+            min_success_rate: Some(95.0),
+        }),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap();
+
+    let ids: Vec<&str> = response.anchors.iter().map(|a| a.id.as_str()).collect();
+    assert!(ids.contains(&busy.id.as_str()));
+    assert!(!ids.contains(&unreliable.id.as_str()));
+    assert!(!ids.contains(&quiet.id.as_str()));
+    assert_eq!(response.total, response.anchors.len() as i64);
+    assert!(response
+        .anchors
+        .iter()
+        .all(|a| a.total_volume_usd >= 1_000_000.0));
+
+    // The filtered page has its own entry; the unfiltered key stays empty
+    let unfiltered: Option<ListAnchorsResponse> = state
+        .cache
+        .get(&CacheKey::anchor_list(500, 0, "default", false))
+        .await
+        .unwrap();
+    assert!(unfiltered.is_none());
+}
+
+#[tokio::test]
+async fn test_cached_anchor_page_keeps_pagination_metadata() {
+    let state = setup_test_state_with(CacheConfig {
+        cacheable_list_limits: vec![2],
+        ..CacheConfig::default()
+    })
+    .await;
+    for i in 0..3 {
+        create_test_anchor(&state, &format!("Metadata Anchor {}", i)).await;
+    }
+    let page = |offset: i64| {
+        list_anchors_cached(
+            State(state.clone()),
+            Query(ListAnchorsQuery {
+                limit: 2,
+                offset,
+                q: None,
+                sort_by: None,
+                order: None,
+                after: None,
+                include_inactive: false,
+                min_volume: None,
+                min_success_rate: None,
+            }),
+            HeaderMap::new(),
+            CacheBypass::default(),
+        )
+    };
+
+    let first = page(0).await.unwrap();
+    assert!(first.has_more);
+    assert_eq!(first.next_offset, Some(2));
+
+    // The metadata is stored alongside the rows, so a hit reports the same
+    let cached: ListAnchorsResponse = state
+        .cache
+        .get(&CacheKey::anchor_list(2, 0, "default", false))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!((cached.has_more, cached.next_offset), (true, Some(2)));
+    let hit = page(0).await.unwrap();
+    assert_eq!((hit.has_more, hit.next_offset), (true, Some(2)));
+
+    let last_offset = first.total - 1;
+    let last = page(last_offset).await.unwrap();
+    assert_eq!(last.anchors.len(), 1);
+    assert!(!last.has_more);
+    assert_eq!(last.next_offset, None);
+}
+
+#[tokio::test]
+async fn test_uncommon_list_page_is_served_but_not_cached() {
+    let state = setup_test_state_with(CacheConfig {
+        debug_headers: true,
+        ..CacheConfig::default()
+    })
+    .await;
+    for i in 0..3 {
+        create_test_anchor(&state, &format!("Odd Offset Anchor {}", i)).await;
+    }
+    let page = |offset: i64| {
+        list_anchors_cached(
+            State(state.clone()),
+            Query(ListAnchorsQuery {
+                limit: 50,
+                offset,
+                q: None,
+                sort_by: None,
+                order: None,
+                after: None,
+                include_inactive: false,
+                min_volume: None,
+                min_success_rate: None,
+            }),
+            HeaderMap::new(),
+            CacheBypass::default(),
+        )
+    };
+
+    let aligned = page(0).await.unwrap();
+    let odd = page(1).await.unwrap();
+    assert_eq!(
+        odd.anchors.first().map(|a| &a.id),
+        aligned.anchors.get(1).map(|a| &a.id)
+    );
+    assert_eq!(odd.total, aligned.total);
+
+    let stored: Option<ListAnchorsResponse> = state
+        .cache
+        .get(&CacheKey::anchor_list(50, 1, "default", false))
+        .await
+        .unwrap();
+    assert!(stored.is_none());
+    let again = page(1).await.unwrap().into_response();
+    assert_eq!(again.headers()[X_CACHE], "MISS");
+    assert_eq!(
+        page(0).await.unwrap().into_response().headers()[X_CACHE],
+        "HIT-MEMORY"
+    );
+}
+
+#[tokio::test]
+async fn test_list_anchors_search_filters_by_name_and_account_prefix() {
+    let state = setup_test_state().await;
+    let marker = uuid::Uuid::new_v4().simple().to_string();
+    create_test_anchor(&state, &format!("Circle {}", marker)).await;
+    create_test_anchor(&state, &format!("Other {}", marker)).await;
+
+    let search = |q: &str| {
+        list_anchors_cached(
+            State(state.clone()),
+            Query(ListAnchorsQuery {
+                limit: 50,
+                offset: 0,
+                q: Some(q.to_string()),
+                sort_by: None,
+                order: None,
+                after: None,
+                include_inactive: false,
+                min_volume: None,
+                min_success_rate: None,
+            }),
+            HeaderMap::new(),
+            CacheBypass::default(),
+        )
+    };
+
+    let response = search(&format!("circle {}", marker.to_uppercase()))
+        .await
+        .unwrap();
+    assert_eq!(response.total, 1);
+    assert!(response.anchors[0].name.starts_with("Circle"));
+
+    let by_marker = search(&marker).await.unwrap();
+    assert_eq!(by_marker.total, 2);
+
+    let account = by_marker.anchors[0].stellar_account.clone();
+    let by_account = search(&account[..12]).await.unwrap();
+    assert!(by_account
+        .anchors
+        .iter()
+        .any(|a| a.stellar_account == account));
+
+    // Wildcards in the query are matched literally
+    assert_eq!(search("%").await.unwrap().total, 0);
+}
+
+#[tokio::test]
+async fn test_list_anchors_sorts_by_allowlisted_column() {
+    let state = setup_test_state().await;
+    for name in ["Sort C", "Sort A", "Sort B"] {
+        create_test_anchor(&state, name).await;
+    }
+
+    let list = |sort_by: &str, order: &str| {
+        list_anchors_cached(
+            State(state.clone()),
+            Query(ListAnchorsQuery {
+                limit: 1000,
+                offset: 0,
+                q: Some("Sort ".to_string()),
+                sort_by: Some(sort_by.to_string()),
+                order: Some(order.to_string()),
+                after: None,
+                include_inactive: false,
+                min_volume: None,
+                min_success_rate: None,
+            }),
+            HeaderMap::new(),
+            CacheBypass::default(),
+        )
+    };
+
+    let asc = list("name", "asc").await.unwrap();
+    let names: Vec<&str> = asc.anchors.iter().map(|a| a.name.as_str()).collect();
+    let mut sorted = names.clone();
+    sorted.sort();
+    assert_eq!(names, sorted);
+
+    let desc = list("name", "desc").await.unwrap();
+    assert_eq!(
+        desc.anchors.first().map(|a| &a.name),
+        asc.anchors.last().map(|a| &a.name)
+    );
+
+    let err = list("reliability_score; --", "asc").await.unwrap_err();
+    assert!(matches!(err, ApiError::BadRequest(_)));
+}
+
+#[tokio::test]
+async fn test_keyset_pagination_neither_skips_nor_repeats_rows() {
+    let state = setup_test_state().await;
+    for i in 0..7 {
+        create_test_anchor(&state, &format!("Keyset Anchor {}", i)).await;
+    }
+    let before: std::collections::HashSet<String> = state
+        .db
+        .list_anchors_after(None, 100_000, true)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|a| a.id)
+        .collect();
+
+    let mut seen = Vec::new();
+    let mut cursor: Option<AnchorCursor> = None;
+    let mut inserted_mid_pagination = false;
+    loop {
+        let page = state
+            .db
+            .list_anchors_after(cursor.as_ref(), 3, true)
+            .await
+            .unwrap();
+        if page.is_empty() {
+            break;
+        }
+        seen.extend(page.iter().map(|a| a.id.clone()));
+        cursor = page.last().map(AnchorCursor::from_anchor);
+
+        if !inserted_mid_pagination {
+            create_test_anchor(&state, "Keyset Anchor inserted").await;
+            inserted_mid_pagination = true;
+        }
+    }
+
+    let unique: std::collections::HashSet<&String> = seen.iter().collect();
+    assert_eq!(unique.len(), seen.len(), "a row was returned twice");
+    assert!(
+        before.iter().all(|id| unique.contains(id)),
+        "a row was skipped"
+    );
+}
+
+#[tokio::test]
+async fn test_list_anchors_cached_returns_next_cursor() {
+    let state = setup_test_state().await;
+    for i in 0..3 {
+        create_test_anchor(&state, &format!("Cursor Anchor {}", i)).await;
+    }
+
+    let page = |after: &str| {
+        list_anchors_cached(
+            State(state.clone()),
+            Query(ListAnchorsQuery {
+                limit: 2,
+                offset: 0,
+                q: None,
+                sort_by: None,
+                order: None,
+                after: Some(after.to_string()),
+                include_inactive: false,
+                min_volume: None,
+                min_success_rate: None,
+            }),
+            HeaderMap::new(),
+            CacheBypass::default(),
+        )
+    };
+
+    let first = page("").await.unwrap();
+    assert_eq!(first.anchors.len(), 2);
+    let next = first
+        .next_cursor
+        .clone()
+        .expect("full page should carry a cursor");
+
+    let second = page(&next).await.unwrap();
+    assert!(second
+        .anchors
+        .iter()
+        .all(|a| first.anchors.iter().all(|b| a.id != b.id)));
+
+    assert!(matches!(
+        page("definitely-not-a-cursor").await.unwrap_err(),
+        ApiError::BadRequest(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_cached_get_returns_304_for_matching_etag() {
+    let state = setup_test_state().await;
+    create_test_anchor(&state, "ETag Anchor").await;
+
+    let query = || {
+        Query(ListAnchorsQuery {
+            limit: 10,
+            offset: 0,
+            q: None,
+            sort_by: None,
+            order: None,
+            after: None,
+            include_inactive: false,
+            min_volume: None,
+            min_success_rate: None,
+        })
+    };
+
+    let first = list_anchors_cached(
+        State(state.clone()),
+        query(),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap()
+    .into_response();
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(
+        first.headers()[header::CACHE_CONTROL],
+        "public, max-age=600"
+    );
+    let etag = first.headers()[header::ETAG].clone();
+
+    let mut conditional = HeaderMap::new();
+    conditional.insert(header::IF_NONE_MATCH, etag.clone());
+    let second = list_anchors_cached(
+        State(state.clone()),
+        query(),
+        conditional,
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap()
+    .into_response();
+    assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(second.headers()[header::ETAG], etag);
+
+    let mut stale = HeaderMap::new();
+    stale.insert(
+        header::IF_NONE_MATCH,
+        HeaderValue::from_static("W/\"stale\""),
+    );
+    let third = list_anchors_cached(State(state), query(), stale, CacheBypass::default())
+        .await
+        .unwrap()
+        .into_response();
+    assert_eq!(third.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_dashboard_stats_aggregates_and_is_served_from_cache() {
+    let state = setup_test_state().await;
+    create_test_anchor(&state, "Dashboard Anchor A").await;
+    create_test_anchor(&state, "Dashboard Anchor B").await;
+
+    let first = get_dashboard_stats_cached(
+        State(state.clone()),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap()
+    .into_inner();
+    assert!(first.total_anchors >= 2);
+    assert!(first.total_corridors >= 0);
+    assert!((0.0..=100.0).contains(&first.overall_success_rate));
+    assert!(first.total_volume_usd >= 0.0);
+
+    // Data written within the TTL is not visible until invalidation
+    create_test_anchor(&state, "Dashboard Anchor C").await;
+    let second = get_dashboard_stats_cached(
+        State(state.clone()),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap()
+    .into_inner();
+    assert_eq!(second, first);
+    assert_eq!(
+        state.cache.get_metrics().per_prefix["dashboard:stats"].hits,
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_anchor_update_keeps_unrelated_anchor_details_cached() {
+    let state = setup_test_state().await;
+    let updated = create_test_anchor(&state, "Tagged Anchor Updated").await;
+    let untouched = create_test_anchor(&state, "Tagged Anchor Untouched").await;
+
+    for anchor in [&updated, &untouched] {
+        let id = anchor.id.parse().unwrap();
+        get_anchor_cached(
+            State(state.clone()),
+            Path(id),
+            HeaderMap::new(),
+            CacheBypass::default(),
+        )
+        .await
+        .unwrap();
+    }
+
+    let Json(anchor) = update_anchor_metrics_cached(
+        State(state.clone()),
+        Path(updated.id.parse().unwrap()),
+        Json(UpdateMetricsRequest {
+            total_transactions: 10,
+            successful_transactions: 9,
+            failed_transactions: 1,
+            avg_settlement_time_ms: Some(1200),
+            volume_usd: Some(500.0),
+            expected_version: None,
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(anchor.total_transactions, 10);
+
+    let cached = |id: &str| {
+        let key = CacheKey::anchor_detail(id);
+        let cache = Arc::clone(&state.cache);
+        async move { cache.get::<AnchorDetailResponse>(&key).await.unwrap() }
+    };
+    // The updated anchor's detail was rewritten in place rather than dropped
+    let rewritten = cached(&updated.id).await.unwrap();
+    assert_eq!(rewritten.anchor.total_transactions, 10);
+    assert!(cached(&untouched.id).await.is_some());
+}
+
+fn metrics_update(total_transactions: i64) -> Json<UpdateMetricsRequest> {
+    Json(UpdateMetricsRequest {
+        total_transactions,
+        successful_transactions: total_transactions,
+        failed_transactions: 0,
+        avg_settlement_time_ms: None,
+        volume_usd: None,
+        expected_version: None,
+    })
+}
+
+#[tokio::test]
+async fn test_metrics_update_refreshes_the_asset_anchor_list() {
+    let state = setup_test_state().await;
+    let anchor = create_test_anchor(&state, "Asset Listed Anchor").await;
+    let id: uuid::Uuid = anchor.id.parse().unwrap();
+    let code = format!("M{}", &uuid::Uuid::new_v4().simple().to_string()[..8]).to_uppercase();
+    state
+        .db
+        .create_asset(id, code.clone(), random_stellar_account())
+        .await
+        .unwrap();
+    let uri = format!("/api/assets/{}/anchors", code);
+
+    let (_, _, before) = get_json(&state, &uri).await;
+    assert_eq!(before[0]["total_transactions"], 0);
+    let Json(_) = update_anchor_metrics_cached(State(state.clone()), Path(id), metrics_update(42))
+        .await
+        .unwrap();
+
+    let (_, _, after) = get_json(&state, &uri).await;
+    assert_eq!(after[0]["total_transactions"], 42);
+}
+
+#[tokio::test]
+async fn test_metrics_update_never_overwrites_a_newer_cached_row() {
+    let state = setup_test_state().await;
+    let anchor = create_test_anchor(&state, "Out Of Order Anchor").await;
+    let id: uuid::Uuid = anchor.id.parse().unwrap();
+    // A concurrent update that finished first and cached a later version
+    let newer = Anchor {
+        version: anchor.version + 10,
+        total_transactions: 999,
+        ..anchor.clone()
+    };
+    let data_key = CacheKey::anchor_data(&anchor.id);
+    state.cache.set(&data_key, &newer, 60).await.unwrap();
+
+    let Json(updated) =
+        update_anchor_metrics_cached(State(state.clone()), Path(id), metrics_update(5))
+            .await
+            .unwrap();
+    assert!(updated.version < newer.version);
+
+    let cached: Anchor = state.cache.get(&data_key).await.unwrap().unwrap();
+    assert_eq!(cached.version, newer.version);
+    assert_eq!(cached.total_transactions, 999);
+}
+
+#[tokio::test]
+async fn test_metrics_update_writes_single_anchor_keys_through() {
+    let state = setup_test_state().await;
+    let anchor = create_test_anchor(&state, "Write Through Anchor").await;
+    let id: uuid::Uuid = anchor.id.parse().unwrap();
+    let list_key = CacheKey::anchor_list(10, 0, "", false);
+    state.cache.set(&list_key, &"stale page", 60).await.unwrap();
+
+    let Json(updated) = update_anchor_metrics_cached(
+        State(state.clone()),
+        Path(id),
+        Json(UpdateMetricsRequest {
+            total_transactions: 50,
+            successful_transactions: 45,
+            failed_transactions: 5,
+            avg_settlement_time_ms: Some(700),
+            volume_usd: Some(2_500.0),
+            expected_version: None,
+        }),
+    )
+    .await
+    .unwrap();
+
+    let detail: AnchorDetailResponse = state
+        .cache
+        .get(&CacheKey::anchor_detail(&anchor.id))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(detail.anchor.total_transactions, 50);
+    assert_eq!(detail.version, updated.version);
+    assert_eq!(detail.metrics_history[0].total_transactions, 50);
+    let data: Anchor = state
+        .cache
+        .get(&CacheKey::anchor_data(&anchor.id))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(data.successful_transactions, 45);
+    // List pages still need recomputing
+    assert!(state
+        .cache
+        .get::<String>(&list_key)
+        .await
+        .unwrap()
+        .is_none());
+
+    // With the row gone from the database, the detail can only come from the cache
+    state.db.delete_anchor(id).await.unwrap();
+    let served = get_anchor_cached(
+        State(state.clone()),
+        Path(id),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap()
+    .into_inner();
+    assert_eq!(served.anchor.total_transactions, 50);
+}
+
+#[tokio::test]
+async fn test_cache_warmer_populates_first_anchor_page() {
+    let state = setup_test_state().await;
+    create_test_anchor(&state, "Warm Anchor").await;
+
+    let populated = CacheWarmer::warm(
+        Arc::clone(&state.db),
+        Arc::clone(&state.cache),
+        state.cache_config,
+    )
+    .await;
+    assert_eq!(populated, 5);
+
+    let page: Option<ListAnchorsResponse> = state
+        .cache
+        .get(&CacheKey::anchor_list(50, 0, "default", false))
+        .await
+        .unwrap();
+    assert!(page.is_some_and(|page| page.total >= 1));
+}
+
+#[tokio::test]
+async fn test_refresh_corridors_populates_corridor_list() {
+    let state = setup_test_state().await;
+    let key = CacheKey::corridor_list(50, 0, "default");
+    assert!(state
+        .cache
+        .get::<ListCorridorsResponse>(&key)
+        .await
+        .unwrap()
+        .is_none());
+
+    let refreshed = state
+        .cache_invalidation
+        .refresh_corridors(&state.db, &state.cache_config)
+        .await;
+    assert_eq!(refreshed, 3);
+
+    let page: Option<ListCorridorsResponse> = state.cache.get(&key).await.unwrap();
+    assert!(page.is_some());
+    assert!(state
+        .cache
+        .get::<i64>(&CacheKey::corridor_count())
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test]
+async fn test_delete_anchor_returns_404_for_unknown_anchor() {
+    let state = setup_test_state().await;
+
+    let err = delete_anchor_cached(
+        State(state),
+        Path(uuid::Uuid::new_v4()),
+        Query(DeleteAnchorQuery::default()),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, ApiError::NotFound(_)));
+}
+
+#[tokio::test]
+async fn test_delete_anchor_with_assets_requires_force() {
+    let state = setup_test_state().await;
+    let anchor = create_test_anchor(&state, "Delete Anchor With Assets").await;
+    let id: uuid::Uuid = anchor.id.parse().unwrap();
+    state
+        .db
+        .create_asset(id, "DEL".to_string(), anchor.stellar_account.clone())
+        .await
+        .unwrap();
+
+    let err = delete_anchor_cached(
+        State(state.clone()),
+        Path(id),
+        Query(DeleteAnchorQuery::default()),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, ApiError::Conflict(_)));
+    assert!(state.db.get_anchor_by_id(id).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_forced_delete_removes_anchor_assets_and_cache_entries() {
+    let state = setup_test_state().await;
+    let anchor = create_test_anchor(&state, "Delete Anchor Forced").await;
+    let id: uuid::Uuid = anchor.id.parse().unwrap();
+    state
+        .db
+        .create_asset(id, "FRC".to_string(), anchor.stellar_account.clone())
+        .await
+        .unwrap();
+    get_anchor_cached(
+        State(state.clone()),
+        Path(id),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap();
+
+    let status = delete_anchor_cached(
+        State(state.clone()),
+        Path(id),
+        Query(DeleteAnchorQuery { force: true }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    assert!(state.db.get_anchor_by_id(id).await.unwrap().is_none());
+    assert_eq!(state.db.count_assets_by_anchor(id).await.unwrap(), 0);
+    let cached: Option<AnchorDetailResponse> = state
+        .cache
+        .get(&CacheKey::anchor_detail(&anchor.id))
+        .await
+        .unwrap();
+    assert!(cached.is_none());
+}
+
+#[tokio::test]
+async fn test_batch_metrics_update_reports_unknown_anchors_per_item() {
+    let state = setup_test_state().await;
+    let first = create_test_anchor(&state, "Batch Anchor A").await;
+    let second = create_test_anchor(&state, "Batch Anchor B").await;
+    let unknown = uuid::Uuid::new_v4();
+
+    let item = |id: uuid::Uuid, total: i64| BatchUpdateMetricsItem {
+        id,
+        metrics: UpdateMetricsRequest {
+            total_transactions: total,
+            successful_transactions: total - 1,
+            failed_transactions: 1,
+            avg_settlement_time_ms: Some(900),
+            volume_usd: Some(1_000.0),
+            expected_version: None,
+        },
+    };
+
+    let Json(response) = update_anchor_metrics_batch_cached(
+        State(state.clone()),
+        Json(vec![
+            item(first.id.parse().unwrap(), 10),
+            item(unknown, 20),
+            item(second.id.parse().unwrap(), 30),
+        ]),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.updated, 2);
+    assert_eq!(response.failed, 1);
+    assert_eq!(response.results[1].id, unknown);
+    assert!(response.results[1].error.is_some());
+
+    let second_after = state
+        .db
+        .get_anchor_by_id(second.id.parse().unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(second_after.total_transactions, 30);
+}
+
+#[tokio::test]
+async fn test_batch_metrics_update_rejects_inconsistent_counts_before_writing() {
+    let state = setup_test_state().await;
+    let valid = create_test_anchor(&state, "Batch Valid Anchor").await;
+    let invalid = create_test_anchor(&state, "Batch Invalid Anchor").await;
+
+    let item = |id: &str, successful: i64| BatchUpdateMetricsItem {
+        id: id.parse().unwrap(),
+        metrics: UpdateMetricsRequest {
+            total_transactions: 10,
+            successful_transactions: successful,
+            failed_transactions: 1,
+            avg_settlement_time_ms: None,
+            volume_usd: None,
+            expected_version: None,
+        },
+    };
+
+    let result = update_anchor_metrics_batch_cached(
+        State(state.clone()),
+        Json(vec![item(&valid.id, 9), item(&invalid.id, 10)]),
+    )
+    .await;
+
+    assert!(
+        matches!(result, Err(ApiError::BadRequest(ref m)) if m.contains(&invalid.id) && m.contains("exceeds"))
+    );
+    let valid_after = state
+        .db
+        .get_anchor_by_id(valid.id.parse().unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(valid_after.total_transactions, valid.total_transactions);
+}
+
+#[tokio::test]
+async fn test_debug_header_reports_memory_hit_when_redis_is_down() {
+    let state = setup_test_state_with(CacheConfig {
+        debug_headers: true,
+        ..CacheConfig::default()
+    })
+    .await;
+    let anchor = create_test_anchor(&state, "X-Cache Anchor").await;
+    let id: uuid::Uuid = anchor.id.parse().unwrap();
+
+    let x_cache = || async {
+        let response = get_anchor_cached(
+            State(state.clone()),
+            Path(id),
+            HeaderMap::new(),
+            CacheBypass::default(),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        response.headers()["x-cache"].clone()
+    };
+
+    assert_eq!(x_cache().await, "MISS");
+    assert_eq!(x_cache().await, "HIT-MEMORY");
+}
+
+#[tokio::test]
+async fn test_anchors_by_asset_code_is_case_insensitive_and_invalidated_on_create() {
+    let state = setup_test_state().await;
+    let code = format!("A{}", &uuid::Uuid::new_v4().simple().to_string()[..8]).to_uppercase();
+    let first = create_test_anchor(&state, "Asset Issuer A").await;
+    let second = create_test_anchor(&state, "Asset Issuer B").await;
+
+    let add_asset = |anchor: &Anchor| {
+        create_anchor_asset_cached(
+            State(state.clone()),
+            Path(anchor.id.parse().unwrap()),
+            IdempotencyKey::default(),
+            Json(CreateAssetRequest {
+                asset_code: code.clone(),
+                asset_issuer: anchor.stellar_account.clone(),
+            }),
+        )
+    };
+    let issuers = |code: String| {
+        let state = state.clone();
+        async move {
+            get_anchors_by_asset_cached(
+                State(state),
+                Path(code),
+                HeaderMap::new(),
+                CacheBypass::default(),
+            )
+            .await
+            .unwrap()
+            .into_inner()
+        }
+    };
+
+    let Json(asset) = add_asset(&first).await.unwrap();
+    assert_eq!(asset.asset_code, code);
+    let lowercase = issuers(code.to_lowercase()).await;
+    assert_eq!(lowercase.len(), 1);
+    assert_eq!(lowercase[0].id, first.id);
+
+    // The cached list is keyed on the normalized code, so a new issuer clears it
+    let Json(_) = add_asset(&second).await.unwrap();
+    assert_eq!(issuers(code.clone()).await.len(), 2);
+
+    let err = get_anchors_by_asset_cached(
+        State(state.clone()),
+        Path("US*".to_string()),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, ApiError::BadRequest(_)));
+}
+
+#[tokio::test]
+async fn test_adding_asset_invalidates_every_per_anchor_key() {
+    let state = setup_test_state().await;
+    let anchor = create_test_anchor(&state, "Asset Invalidation Anchor").await;
+    let mut keys = CacheKey::all_keys_for_anchor(&anchor.id);
+    assert_eq!(keys.len(), 2);
+    keys.push(CacheKey::anchor_assets(&anchor.id, 50, 0));
+    keys.push(CacheKey::anchor_assets(&anchor.id, 50, 50));
+    for key in &keys {
+        state.cache.set(key, &"stale", 600).await.unwrap();
+    }
+
+    let Json(_) = create_anchor_asset_cached(
+        State(state.clone()),
+        Path(anchor.id.parse().unwrap()),
+        IdempotencyKey::default(),
+        Json(CreateAssetRequest {
+            asset_code: "INV".to_string(),
+            asset_issuer: anchor.stellar_account.clone(),
+        }),
+    )
+    .await
+    .unwrap();
+
+    for key in &keys {
+        assert_eq!(
+            state.cache.get::<String>(key).await.unwrap(),
+            None,
+            "{} survived",
+            key
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_asset_metrics_aggregate_payments_and_are_invalidated_with_the_asset() {
+    let state = setup_test_state().await;
+    let anchor = create_test_anchor(&state, "Asset Metrics Anchor").await;
+    let code = format!("M{}", &uuid::Uuid::new_v4().simple().to_string()[..8]).to_uppercase();
+    let issuer = anchor.stellar_account.clone();
+    let cache_key = CacheKey::asset_metrics(&code, &issuer);
+    let metrics = || {
+        get_asset_metrics_cached(
+            State(state.clone()),
+            Path((code.clone(), issuer.clone())),
+            HeaderMap::new(),
+            CacheBypass::default(),
+        )
+    };
+
+    // The 404 for an unknown asset is remembered until the asset is created
+    assert!(matches!(metrics().await, Err(ApiError::NotFound(_))));
+    for amount in [100.0_f32, 250.5, 49.5] {
+        sqlx::query(
+            r#"
+            INSERT INTO payments (
+                id, transaction_hash, source_account, destination_account,
+                asset_type, asset_code, asset_issuer, amount, created_at
+            )
+            VALUES ($1, 'hash', $2, $2, 'credit_alphanum4', $3, $2, $4, '2024-01-01T00:00:00Z')
+            "#,
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(&issuer)
+        .bind(&code)
+        .bind(amount)
+        .execute(state.db.pool())
+        .await
+        .unwrap();
+    }
+    let Json(_) = create_anchor_asset_cached(
+        State(state.clone()),
+        Path(anchor.id.parse().unwrap()),
+        IdempotencyKey::default(),
+        Json(CreateAssetRequest {
+            asset_code: code.clone(),
+            asset_issuer: issuer.clone(),
+        }),
+    )
+    .await
+    .unwrap();
+
+    let body = metrics().await.unwrap().into_inner();
+    assert_eq!(body.total_transactions, 3);
+    assert_eq!(body.success_rate, 100.0);
+    assert_eq!(body.volume, 400.0);
+    let cached: Option<AssetMetrics> = state.cache.get(&cache_key).await.unwrap();
+    assert_eq!(cached, Some(body));
+
+    delete_anchor_cached(
+        State(state.clone()),
+        Path(anchor.id.parse().unwrap()),
+        Query(DeleteAnchorQuery { force: true }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        state.cache.get::<AssetMetrics>(&cache_key).await.unwrap(),
+        None
+    );
+    assert!(matches!(metrics().await, Err(ApiError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn test_no_cache_reloads_from_database_and_refreshes_entry() {
+    let state = setup_test_state_with(CacheConfig {
+        allow_bypass: true,
+        ..CacheConfig::default()
+    })
+    .await;
+    let anchor = create_test_anchor(&state, "Bypass Anchor").await;
+    let id: uuid::Uuid = anchor.id.parse().unwrap();
+    let detail = |bypass: bool| {
+        let state = state.clone();
+        async move {
+            get_anchor_cached(
+                State(state),
+                Path(id),
+                HeaderMap::new(),
+                CacheBypass(bypass),
+            )
+            .await
+            .unwrap()
+            .into_inner()
+        }
+    };
+
+    assert_eq!(detail(false).await.anchor.total_transactions, 0);
+    // Write behind the cache's back, so only a real reload can see it
+    state
+        .db
+        .update_anchor_metrics(id, 40, 38, 2, Some(800), None)
+        .await
+        .unwrap();
+    assert_eq!(detail(false).await.anchor.total_transactions, 0);
+
+    assert_eq!(detail(true).await.anchor.total_transactions, 40);
+    // The bypassing read wrote the fresh value back for normal readers
+    assert_eq!(detail(false).await.anchor.total_transactions, 40);
+}
+
+#[test]
+fn test_cache_errors_map_to_http_status() {
+    let unavailable = ApiError::from(CacheError::Connection("refused".to_string()));
+    assert_eq!(
+        unavailable.into_response().status(),
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+
+    let corrupt = ApiError::from(CacheError::Deserialization("bad json".to_string()));
+    assert_eq!(
+        corrupt.into_response().status(),
+        StatusCode::INTERNAL_SERVER_ERROR
+    );
+}
+
+fn anchor_request(name: &str) -> CreateAnchorRequest {
+    CreateAnchorRequest {
+        name: name.to_string(),
+        stellar_account: random_stellar_account(),
+        home_domain: None,
+    }
+}
+
+#[tokio::test]
+async fn test_repeated_idempotency_key_returns_original_anchor() {
+    let state = setup_test_state().await;
+    let key = IdempotencyKey(Some(uuid::Uuid::new_v4().to_string()));
+    let before = state.db.count_anchors().await.unwrap();
+
+    let request = anchor_request("Idempotent Anchor");
+
+    let Json(first) =
+        create_anchor_cached(State(state.clone()), key.clone(), Json(request.clone()))
+            .await
+            .unwrap();
+    let Json(retry) = create_anchor_cached(State(state.clone()), key, Json(request))
+        .await
+        .unwrap();
+
+    assert_eq!(retry.id, first.id);
+    assert_eq!(state.db.count_anchors().await.unwrap(), before + 1);
+}
+
+#[tokio::test]
+async fn test_idempotency_key_reused_for_a_different_request_conflicts() {
+    let state = setup_test_state().await;
+    let key = IdempotencyKey(Some(uuid::Uuid::new_v4().to_string()));
+    let before = state.db.count_anchors().await.unwrap();
+
+    let Json(_) = create_anchor_cached(
+        State(state.clone()),
+        key.clone(),
+        Json(anchor_request("Idempotent Anchor")),
+    )
+    .await
+    .unwrap();
+    let err = create_anchor_cached(
+        State(state.clone()),
+        key.clone(),
+        Json(anchor_request("Idempotent Anchor Retry")),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, ApiError::Conflict(_)), "{:?}", err);
+
+    // The same asset body under the same key, but for another anchor's path
+    let issuer = random_stellar_account();
+    let asset = |anchor: Anchor| {
+        create_anchor_asset_cached(
+            State(state.clone()),
+            Path(anchor.id.parse().unwrap()),
+            key.clone(),
+            Json(CreateAssetRequest {
+                asset_code: "USDC".to_string(),
+                asset_issuer: issuer.clone(),
+            }),
+        )
+    };
+    let Json(_) = asset(create_test_anchor(&state, "Idempotent Asset A").await)
+        .await
+        .unwrap();
+    let err = asset(create_test_anchor(&state, "Idempotent Asset B").await)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Conflict(_)), "{:?}", err);
+
+    assert_eq!(state.db.count_anchors().await.unwrap(), before + 3);
+}
+
+#[tokio::test]
+async fn test_concurrent_duplicate_create_waits_for_first_result() {
+    let state = setup_test_state().await;
+    let key = IdempotencyKey(Some(uuid::Uuid::new_v4().to_string()));
+    let before = state.db.count_anchors().await.unwrap();
+
+    let (a, b) = tokio::join!(
+        create_anchor_cached(
+            State(state.clone()),
+            key.clone(),
+            Json(anchor_request("Concurrent Anchor")),
+        ),
+        create_anchor_cached(
+            State(state.clone()),
+            key,
+            Json(anchor_request("Concurrent Anchor")),
+        ),
+    );
+    let (Json(a), Json(b)) = (a.unwrap(), b.unwrap());
+
+    assert_eq!(a.id, b.id);
+    assert_eq!(state.db.count_anchors().await.unwrap(), before + 1);
+}
+
+#[tokio::test]
+async fn test_duplicate_create_on_another_replica_replays_the_first() {
+    // Each replica has its own memory tier, so only Redis is shared
+    let (Some(first), Some(second)) = (setup_redis_replica().await, setup_redis_replica().await)
+    else {
+        return;
+    };
+    let key = IdempotencyKey(Some(uuid::Uuid::new_v4().to_string()));
+    let request = anchor_request("Replicated Anchor");
+    let before = first.db.count_anchors().await.unwrap();
+
+    let (a, b) = tokio::join!(
+        create_anchor_cached(State(first.clone()), key.clone(), Json(request.clone())),
+        create_anchor_cached(State(second.clone()), key, Json(request)),
+    );
+    let (Json(a), Json(b)) = (a.unwrap(), b.unwrap());
+
+    assert_eq!(a.id, b.id);
+    assert_eq!(first.db.count_anchors().await.unwrap(), before + 1);
+}
+
+#[test]
+fn test_generated_accounts_pass_validation() {
+    for _ in 0..20 {
+        validate_stellar_account(&random_stellar_account()).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_create_anchor_rejects_malformed_stellar_account() {
+    let state = setup_test_state().await;
+    let request = CreateAnchorRequest {
+        stellar_account: "GNOTAREALACCOUNT".to_string(),
+        ..anchor_request("Typo Anchor")
+    };
+
+    let err = create_anchor_cached(State(state), IdempotencyKey::default(), Json(request))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, ApiError::BadRequest(_)), "{:?}", err);
+}
+
+#[tokio::test]
+async fn test_duplicate_stellar_account_returns_conflict() {
+    let state = setup_test_state().await;
+    let first = anchor_request("Original Anchor");
+    let duplicate = CreateAnchorRequest {
+        name: "Duplicate Anchor".to_string(),
+        ..first.clone()
+    };
+
+    let Json(_) =
+        create_anchor_cached(State(state.clone()), IdempotencyKey::default(), Json(first))
+            .await
+            .unwrap();
+    let err = create_anchor_cached(State(state), IdempotencyKey::default(), Json(duplicate))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, ApiError::Conflict(_)), "{:?}", err);
+    assert_eq!(err.into_response().status(), StatusCode::CONFLICT);
+}
+
+fn corridor_request(
+    issuer: &str,
+    initial_transactions: Option<Vec<CorridorTransactionDto>>,
+) -> CreateCorridorRequest {
+    CreateCorridorRequest {
+        name: None,
+        source_asset_code: "USDC".to_string(),
+        source_asset_issuer: issuer.to_string(),
+        dest_asset_code: "EURC".to_string(),
+        dest_asset_issuer: issuer.to_string(),
+        initial_transactions,
+    }
+}
+
+async fn corridor_id_for_issuer(state: &AppState, issuer: &str) -> Option<uuid::Uuid> {
+    let id: Option<(String,)> = sqlx::query_as(
+        "SELECT id FROM corridors WHERE source_asset_code = 'USDC' AND source_asset_issuer = $1",
+    )
+    .bind(issuer)
+    .fetch_optional(state.db.pool())
+    .await
+    .unwrap();
+    id.map(|(id,)| id.parse().unwrap())
+}
+
+async fn create_test_corridor_id(state: &AppState) -> uuid::Uuid {
+    let issuer = format!("G{}", uuid::Uuid::new_v4().simple());
+    state
+        .db
+        .create_corridor(corridor_request(&issuer, None))
+        .await
+        .unwrap();
+
+    corridor_id_for_issuer(state, &issuer).await.unwrap()
+}
+
+async fn corridors_by_asset(state: &AppState, code: &str, issuer: Option<&str>) -> Vec<Corridor> {
+    get_corridors_by_asset_cached(
+        State(state.clone()),
+        Path(code.to_string()),
+        Query(AssetCorridorsQuery {
+            issuer: issuer.map(str::to_string),
+        }),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap()
+    .into_inner()
+}
+
+#[tokio::test]
+async fn test_corridors_by_asset_match_code_alone_or_code_and_issuer() {
+    let state = setup_test_state().await;
+    let code = format!("C{}", &uuid::Uuid::new_v4().simple().to_string()[..8]).to_uppercase();
+    let first_issuer = random_stellar_account();
+    let second_issuer = random_stellar_account();
+    let with_source = |issuer: &str| CreateCorridorRequest {
+        source_asset_code: code.clone(),
+        ..corridor_request(issuer, None)
+    };
+
+    let Json(_) = create_corridor_cached(
+        State(state.clone()),
+        IdempotencyKey::default(),
+        Json(with_source(&first_issuer)),
+    )
+    .await
+    .unwrap();
+    // The same code on the destination side, from another issuer
+    let Json(_) = create_corridor_cached(
+        State(state.clone()),
+        IdempotencyKey::default(),
+        Json(CreateCorridorRequest {
+            dest_asset_code: code.to_lowercase(),
+            ..corridor_request(&second_issuer, None)
+        }),
+    )
+    .await
+    .unwrap();
+
+    let by_code = corridors_by_asset(&state, &code.to_lowercase(), None).await;
+    assert_eq!(by_code.len(), 2);
+
+    let by_issuer = corridors_by_asset(&state, &code, Some(&first_issuer)).await;
+    assert_eq!(by_issuer.len(), 1);
+    assert_eq!(by_issuer[0].asset_a_issuer, first_issuer);
+    assert_eq!(
+        corridors_by_asset(&state, &code, Some(&random_stellar_account()))
+            .await
+            .len(),
+        0
+    );
+
+    // Both cached lists are dropped when another corridor with the code appears
+    let third_issuer = random_stellar_account();
+    let Json(_) = create_corridor_cached(
+        State(state.clone()),
+        IdempotencyKey::default(),
+        Json(with_source(&third_issuer)),
+    )
+    .await
+    .unwrap();
+    for issuer in [None, Some(first_issuer.as_str())] {
+        let key = CacheKey::asset_corridors(&code, issuer);
+        assert_eq!(
+            state.cache.get::<Vec<Corridor>>(&key).await.unwrap(),
+            None,
+            "{} survived",
+            key
+        );
+    }
+    assert_eq!(corridors_by_asset(&state, &code, None).await.len(), 3);
+}
+
+#[tokio::test]
+async fn test_corridor_detail_returns_404_for_unknown_id() {
+    let state = setup_test_state().await;
+
+    let err = get_corridor_cached(
+        State(state),
+        Path(uuid::Uuid::new_v4().to_string()),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, ApiError::NotFound(_)), "{:?}", err);
+}
+
+#[tokio::test]
+async fn test_corridor_detail_is_served_from_cache() {
+    let state = setup_test_state_with(CacheConfig {
+        debug_headers: true,
+        ..CacheConfig::default()
+    })
+    .await;
+    let id = create_test_corridor_id(&state).await;
+
+    let x_cache = || async {
+        let response = get_corridor_cached(
+            State(state.clone()),
+            Path(id.to_string()),
+            HeaderMap::new(),
+            CacheBypass::default(),
+        )
+        .await
+        .unwrap();
+        response.headers()["x-cache"].clone()
+    };
+
+    assert_eq!(x_cache().await, "MISS");
+    assert_eq!(x_cache().await, "HIT-MEMORY");
+
+    let cached = state
+        .cache
+        .get::<CorridorDetailResponse>(&CacheKey::corridor_detail(&id.to_string()))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(cached.id, id.to_string());
+    assert_eq!(cached.recent_transactions.total_transactions, 0);
+}
+
+#[tokio::test]
+async fn test_deactivated_anchor_is_listed_only_with_include_inactive() {
+    let state = setup_test_state().await;
+    let anchor = create_test_anchor(&state, "Deactivated Anchor").await;
+    let id: uuid::Uuid = anchor.id.parse().unwrap();
+
+    let list = |include_inactive: bool| {
+        list_anchors_cached(
+            State(state.clone()),
+            Query(ListAnchorsQuery {
+                limit: 1000,
+                offset: 0,
+                q: None,
+                sort_by: None,
+                order: None,
+                after: None,
+                include_inactive,
+                min_volume: None,
+                min_success_rate: None,
+            }),
+            HeaderMap::new(),
+            CacheBypass::default(),
+        )
+    };
+    let listed =
+        |response: &ListAnchorsResponse| response.anchors.iter().any(|a| a.id == anchor.id);
+
+    // Populate the default view so deactivation has to invalidate it
+    assert!(listed(&list(false).await.unwrap()));
+
+    let Json(deactivated) = deactivate_anchor_cached(State(state.clone()), Path(id))
+        .await
+        .unwrap();
+    assert!(!deactivated.is_active);
+
+    let active = list(false).await.unwrap().into_inner();
+    let all = list(true).await.unwrap().into_inner();
+    assert!(!listed(&active));
+    assert!(listed(&all));
+    assert!(all.total > active.total);
+
+    let Json(reactivated) = reactivate_anchor_cached(State(state.clone()), Path(id))
+        .await
+        .unwrap();
+    assert!(reactivated.is_active);
+    assert!(listed(&list(false).await.unwrap()));
+}
+
+#[tokio::test]
+async fn test_deactivated_anchor_is_hidden_from_search_and_cursor_listings() {
+    let state = setup_test_state().await;
+    let marker = uuid::Uuid::new_v4().simple().to_string();
+    let anchor = create_test_anchor(&state, &format!("Hidden {}", marker)).await;
+    let id: uuid::Uuid = anchor.id.parse().unwrap();
+    let Json(_) = deactivate_anchor_cached(State(state.clone()), Path(id))
+        .await
+        .unwrap();
+
+    for include_inactive in [false, true] {
+        let uri = format!(
+            "/api/v2/anchors?q={}&include_inactive={}",
+            marker, include_inactive
+        );
+        let (_, _, json) = get_json(&state, &uri).await;
+        let searched = json["anchors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|a| a["id"] == anchor.id.as_str());
+        assert_eq!(searched, include_inactive);
+        assert_eq!(json["total"], i64::from(include_inactive));
+
+        let walked = state
+            .db
+            .list_anchors_after(None, 100_000, include_inactive)
+            .await
+            .unwrap()
+            .iter()
+            .any(|a| a.id == anchor.id);
+        assert_eq!(walked, include_inactive);
+    }
+
+    // The cursor page total counts the same rows the page walks
+    let (_, _, json) = get_json(&state, "/api/v2/anchors?after=").await;
+    let active = state
+        .db
+        .count_listed_anchors(false, &AnchorFilters::default())
+        .await
+        .unwrap();
+    assert_eq!(json["total"].as_i64(), Some(active));
+}
+
+async fn record_corridor_run(state: &AppState, id: uuid::Uuid, transactions: usize) {
+    let transactions = (0..transactions)
+        .map(|_| settled_transaction(100.0))
+        .collect();
+    let Json(_) = update_corridor_metrics_from_transactions_cached(
+        State(state.clone()),
+        Path(id),
+        Json(UpdateCorridorMetricsFromTxns { transactions }),
+    )
+    .await
+    .unwrap();
+}
+
+async fn corridor_history(state: &AppState, id: uuid::Uuid) -> CorridorHistoryResponse {
+    get_corridor_history_cached(
+        State(state.clone()),
+        Path(id),
+        Query(CorridorHistoryQuery { hours: 24 }),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap()
+    .into_inner()
+}
+
+#[tokio::test]
+async fn test_corridor_history_is_oldest_first_and_invalidated_by_new_runs() {
+    let state = setup_test_state().await;
+    let id = create_test_corridor_id(&state).await;
+
+    record_corridor_run(&state, id, 1).await;
+    record_corridor_run(&state, id, 2).await;
+    let first = corridor_history(&state, id).await.snapshots;
+    assert_eq!(first.len(), 2);
+
+    // A new run must replace the cached two-snapshot series
+    record_corridor_run(&state, id, 3).await;
+    let response = corridor_history(&state, id).await;
+    assert_eq!(response.trend, Trend::Up);
+    let history = response.snapshots;
+    let totals: Vec<i64> = history.iter().map(|s| s.total_transactions).collect();
+    assert_eq!(totals, vec![1, 2, 3]);
+    assert!(history
+        .windows(2)
+        .all(|pair| pair[0].recorded_at <= pair[1].recorded_at));
+
+    let key = CacheKey::corridor_metrics_history(&id.to_string(), 24);
+    assert!(state
+        .cache
+        .get::<Vec<CorridorMetricsSnapshot>>(&key)
+        .await
+        .unwrap()
+        .is_some());
+    state
+        .cache_invalidation
+        .on_metrics_ingestion_complete()
+        .await
+        .unwrap();
+    assert!(state
+        .cache
+        .get::<Vec<CorridorMetricsSnapshot>>(&key)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_corridor_anomalies_compare_against_the_last_persisted_run() {
+    let state = setup_test_state().await;
+    let id = create_test_corridor_id(&state).await;
+    record_corridor_run(&state, id, 4).await;
+
+    // Clearing the cache must not drop the baseline the next run compares to
+    state.cache.clear_all().await.unwrap();
+
+    let failed = CorridorTransactionDto {
+        successful: false,
+        ..settled_transaction(100.0)
+    };
+    let Json(response) = update_corridor_metrics_from_transactions_cached(
+        State(state.clone()),
+        Path(id),
+        Json(UpdateCorridorMetricsFromTxns {
+            transactions: vec![failed.clone(), failed],
+        }),
+    )
+    .await
+    .unwrap();
+
+    assert!(matches!(
+        response.warnings.as_slice(),
+        [Anomaly::SuccessRateDrop { .. }]
+    ));
+}
+
+fn settled_transaction(amount_usd: f64) -> CorridorTransactionDto {
+    CorridorTransactionDto {
+        successful: true,
+        settlement_latency_ms: Some(1000),
+        amount_usd,
+        occurred_at: None,
+        currency: None,
+    }
+}
+
+#[tokio::test]
+async fn test_corridor_created_with_initial_transactions_has_metrics() {
+    let state = setup_test_state().await;
+    let issuer = format!("G{}", uuid::Uuid::new_v4().simple());
+    let transactions = vec![settled_transaction(100.0), settled_transaction(50.0)];
+
+    state
+        .db
+        .create_corridor(corridor_request(&issuer, Some(transactions)))
+        .await
+        .unwrap();
+
+    let id = corridor_id_for_issuer(&state, &issuer).await.unwrap();
+    let detail = state.db.get_corridor_detail(id).await.unwrap().unwrap();
+    assert_eq!(detail.median_settlement_latency_ms, Some(1000));
+    let history = corridor_history(&state, id).await;
+    assert_eq!(history.trend, Trend::Unknown);
+    let history = history.snapshots;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].total_transactions, 2);
+}
+
+#[tokio::test]
+async fn test_failed_initial_metrics_leave_no_corridor_row() {
+    let state = setup_test_state().await;
+    let issuer = format!("G{}", uuid::Uuid::new_v4().simple());
+    // The corridor row is inserted before metrics are computed from these
+    let transactions = vec![settled_transaction(100.0), settled_transaction(f64::NAN)];
+
+    let result = state
+        .db
+        .create_corridor(corridor_request(&issuer, Some(transactions)))
+        .await;
+
+    assert!(result.is_err());
+    assert_eq!(corridor_id_for_issuer(&state, &issuer).await, None);
+}
+
+#[tokio::test]
+async fn test_corridor_metrics_persist_the_volume_weighted_success_rate() {
+    let state = setup_test_state().await;
+    let issuer = format!("G{}", uuid::Uuid::new_v4().simple());
+    let failed = CorridorTransactionDto {
+        successful: false,
+        ..settled_transaction(100.0)
+    };
+    let transactions = vec![settled_transaction(300.0), failed];
+
+    state
+        .db
+        .create_corridor(corridor_request(&issuer, Some(transactions)))
+        .await
+        .unwrap();
+
+    let id = corridor_id_for_issuer(&state, &issuer).await.unwrap();
+    let detail = state.db.get_corridor_detail(id).await.unwrap().unwrap();
+    assert_eq!(detail.volume_weighted_success_rate, Some(75.0));
+    let history = corridor_history(&state, id).await.snapshots;
+    assert_eq!(history[0].volume_weighted_success_rate, Some(75.0));
+}
+
+async fn anchor_by_account(state: &AppState, stellar_account: &str) -> Result<Anchor, ApiError> {
+    get_anchor_by_account_cached(
+        State(state.clone()),
+        Path(stellar_account.to_string()),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .map(|json| json.into_inner())
+}
+
+#[tokio::test]
+async fn test_repeated_lookup_of_missing_anchor_is_served_from_cache() {
+    let state = setup_test_state().await;
+    let request = anchor_request("Late Anchor");
+
+    let err = anchor_by_account(&state, &request.stellar_account)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::NotFound(_)), "{:?}", err);
+
+    // Inserted behind the cache's back, so only a database read would find it
+    state.db.create_anchor(request.clone()).await.unwrap();
+    let err = anchor_by_account(&state, &request.stellar_account)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::NotFound(_)), "{:?}", err);
+
+    let key = CacheKey::not_found(&CacheKey::anchor_by_account(&request.stellar_account));
+    assert!(state.cache.get::<String>(&key).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_create_replaces_cached_not_found_with_the_anchor() {
+    let state = setup_test_state().await;
+    let request = anchor_request("Created Anchor");
+
+    assert!(anchor_by_account(&state, &request.stellar_account)
+        .await
+        .is_err());
+
+    let Json(created) = create_anchor_cached(
+        State(state.clone()),
+        IdempotencyKey::default(),
+        Json(request.clone()),
+    )
+    .await
+    .unwrap();
+
+    let found = anchor_by_account(&state, &request.stellar_account)
+        .await
+        .unwrap();
+    assert_eq!(found.id, created.id);
+}
+
+#[tokio::test]
+async fn test_anchor_batch_queries_the_db_once_for_uncached_ids() {
+    let state = setup_test_state().await;
+    let mut ids = Vec::new();
+    for i in 0..4 {
+        let anchor = create_test_anchor(&state, &format!("Batch Anchor {}", i)).await;
+        ids.push(uuid::Uuid::parse_str(&anchor.id).unwrap());
+    }
+    let unknown = uuid::Uuid::new_v4();
+    let ttl = state.cache_config.anchor_data_ttl;
+    let db = &state.db;
+
+    // Warm the first two through the batch itself
+    anchors_batch_through_cache(&state.cache, ttl, &ids[..2], |missing| async move {
+        Ok(db.get_anchors_batch(&missing).await?)
+    })
+    .await
+    .unwrap();
+
+    let queries = std::sync::atomic::AtomicUsize::new(0);
+    let requested = std::sync::Mutex::new(Vec::new());
+    let mut wanted = ids.clone();
+    wanted.extend([ids[3], unknown]);
+    let anchors = anchors_batch_through_cache(&state.cache, ttl, &wanted, |missing| {
+        queries.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        *requested.lock().unwrap() = missing.clone();
+        async move { Ok(db.get_anchors_batch(&missing).await?) }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(queries.load(std::sync::atomic::Ordering::SeqCst), 1);
+    let mut expected = vec![ids[2], ids[3], unknown];
+    expected.sort_unstable();
+    assert_eq!(*requested.lock().unwrap(), expected);
+    assert_eq!(anchors.len(), 4);
+    assert!(ids.iter().all(|id| anchors[id].id == id.to_string()));
+
+    // The misses were backfilled, so nothing known is left to load
+    let anchors = anchors_batch_through_cache(&state.cache, ttl, &ids, |_| async {
+        panic!("every anchor should have been cached")
+    })
+    .await
+    .unwrap();
+    assert_eq!(anchors.len(), 4);
+}
+
+#[tokio::test]
+async fn test_endpoint_ttl_override_applies_only_to_that_endpoint() {
+    let state = setup_test_state_with(CacheConfig {
+        ttl_overrides: TtlRegistry::parse("anchor.by_account=45"),
+        ..CacheConfig::default()
+    })
+    .await;
+    let anchor = create_test_anchor(&state, "Override Anchor").await;
+
+    let by_account = get_anchor_by_account_cached(
+        State(state.clone()),
+        Path(anchor.stellar_account.clone()),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap()
+    .into_response();
+    assert_eq!(
+        by_account.headers()[header::CACHE_CONTROL],
+        "public, max-age=45"
+    );
+
+    let by_id = get_anchor_cached(
+        State(state.clone()),
+        Path(uuid::Uuid::parse_str(&anchor.id).unwrap()),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap()
+    .into_response();
+    assert_eq!(
+        by_id.headers()[header::CACHE_CONTROL],
+        "public, max-age=600"
+    );
+}
+
+#[tokio::test]
+async fn test_total_count_header_matches_body_total_fresh_and_cached() {
+    let state = setup_test_state_with(CacheConfig {
+        debug_headers: true,
+        cacheable_list_limits: vec![2],
+        ..CacheConfig::default()
+    })
+    .await;
+    for i in 0..3 {
+        create_test_anchor(&state, &format!("Counted Anchor {}", i)).await;
+    }
+
+    for expected_cache in ["MISS", "HIT-MEMORY"] {
+        let page = list_anchors_cached(
+            State(state.clone()),
+            Query(ListAnchorsQuery {
+                limit: 2,
+                offset: 0,
+                q: None,
+                sort_by: None,
+                order: None,
+                after: None,
+                include_inactive: false,
+                min_volume: None,
+                min_success_rate: None,
+            }),
+            HeaderMap::new(),
+            CacheBypass::default(),
+        )
+        .await
+        .unwrap();
+        let total = page.total;
+        assert!(total >= 3);
+
+        let response = page.into_response();
+        assert_eq!(response.headers()[X_CACHE], expected_cache);
+        assert_eq!(
+            response.headers()[X_TOTAL_COUNT],
+            total.to_string().as_str()
+        );
+    }
+
+    let corridors = list_corridors_cached(
+        State(state.clone()),
+        Query(ListCorridorsQuery {
+            limit: 10,
+            offset: 0,
+            sort_by: None,
+            order: None,
+            source_asset: None,
+            dest_asset: None,
+            min_success_rate: None,
+        }),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap();
+    let total = corridors.total;
+    assert_eq!(
+        corridors.into_response().headers()[X_TOTAL_COUNT],
+        total.to_string().as_str()
+    );
+}
+
+#[tokio::test]
+async fn test_anchor_without_recent_activity_reports_stale() {
+    let state = setup_test_state().await;
+    let dormant = create_test_anchor(&state, "Dormant Anchor").await;
+    let active = create_test_anchor(&state, "Active Anchor").await;
+    let dormant_id = uuid::Uuid::parse_str(&dormant.id).unwrap();
+    let active_id = uuid::Uuid::parse_str(&active.id).unwrap();
+    let long_ago = chrono::Utc::now() - chrono::Duration::days(60);
+    state
+        .db
+        .touch_anchor_last_seen(dormant_id, long_ago)
+        .await
+        .unwrap();
+    state
+        .db
+        .touch_anchor_last_seen(active_id, chrono::Utc::now())
+        .await
+        .unwrap();
+    // Older activity never rewinds the timestamp
+    state
+        .db
+        .touch_anchor_last_seen(active_id, long_ago)
+        .await
+        .unwrap();
+
+    let detail = |id| {
+        get_anchor_cached(
+            State(state.clone()),
+            Path(id),
+            HeaderMap::new(),
+            CacheBypass::default(),
+        )
+    };
+    let dormant_detail = detail(dormant_id).await.unwrap().into_inner();
+    assert!(dormant_detail.is_stale);
+    assert_eq!(
+        dormant_detail.last_activity_at.map(|ts| ts.timestamp()),
+        Some(long_ago.timestamp())
+    );
+
+    let active_detail = detail(active_id).await.unwrap().into_inner();
+    assert!(!active_detail.is_stale);
+    assert!(active_detail.last_activity_at.unwrap() > long_ago);
+
+    // Served from the cache with the same fields
+    assert!(detail(dormant_id).await.unwrap().into_inner().is_stale);
+}
+
+#[tokio::test]
+async fn test_warm_cache_populates_detail_keys_and_reports_failures() {
+    let state = setup_test_state().await;
+    let first = create_test_anchor(&state, "Warm Anchor 1").await;
+    let second = create_test_anchor(&state, "Warm Anchor 2").await;
+    let corridor_id = create_test_corridor_id(&state).await;
+    let unknown = uuid::Uuid::new_v4();
+    let anchor_ids: Vec<uuid::Uuid> = [&first, &second]
+        .iter()
+        .map(|anchor| uuid::Uuid::parse_str(&anchor.id).unwrap())
+        .collect();
+
+    let Json(response) = warm_cache(
+        State(state.clone()),
+        Json(WarmCacheRequest {
+            anchor_ids: [anchor_ids.clone(), vec![unknown]].concat(),
+            corridor_ids: vec![corridor_id],
+        }),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.warmed, 3);
+    assert_eq!(response.failed, 1);
+    let failure = response.anchors.iter().find(|r| r.id == unknown).unwrap();
+    assert!(!failure.ok);
+    assert!(failure.error.as_deref().unwrap().contains("not found"));
+    for anchor in [&first, &second] {
+        let detail: Option<AnchorDetailResponse> = state
+            .cache
+            .get(&CacheKey::anchor_detail(&anchor.id))
+            .await
+            .unwrap();
+        assert_eq!(detail.unwrap().anchor.id, anchor.id);
+        let assets: Option<Vec<stellar_insights_backend::models::Asset>> = state
+            .cache
+            .get(&CacheKey::anchor_assets(&anchor.id, 50, 0))
+            .await
+            .unwrap();
+        assert!(assets.is_some());
+    }
+    let corridor: Option<CorridorDetailResponse> = state
+        .cache
+        .get(&CacheKey::corridor_detail(&corridor_id.to_string()))
+        .await
+        .unwrap();
+    assert!(corridor.is_some());
+}
+
+#[tokio::test]
+async fn test_metrics_update_with_expected_version_bumps_or_conflicts() {
+    let state = setup_test_state().await;
+    let anchor = create_test_anchor(&state, "Versioned Anchor").await;
+    let id: uuid::Uuid = anchor.id.parse().unwrap();
+    let update = |total: i64, expected_version: Option<i64>| {
+        update_anchor_metrics_cached(
+            State(state.clone()),
+            Path(id),
+            Json(UpdateMetricsRequest {
+                total_transactions: total,
+                successful_transactions: total,
+                failed_transactions: 0,
+                avg_settlement_time_ms: None,
+                volume_usd: None,
+                expected_version,
+            }),
+        )
+    };
+
+    let Json(updated) = update(20, Some(anchor.version)).await.unwrap();
+    assert_eq!(updated.version, anchor.version + 1);
+
+    let detail = get_anchor_cached(
+        State(state.clone()),
+        Path(id),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap()
+    .into_inner();
+    assert_eq!(detail.version, updated.version);
+
+    // A writer still holding the old version loses instead of clobbering
+    let err = update(5, Some(anchor.version)).await.unwrap_err();
+    assert!(matches!(err, ApiError::Conflict(_)), "{:?}", err);
+    let current = state.db.get_anchor_by_id(id).await.unwrap().unwrap();
+    assert_eq!(current.total_transactions, 20);
+    assert_eq!(current.version, updated.version);
+
+    // Without an expected version the write always applies
+    let Json(unconditional) = update(30, None).await.unwrap();
+    assert_eq!(unconditional.version, updated.version + 1);
+}
+
+#[tokio::test]
+async fn test_inspect_reports_existence_and_remaining_ttl() {
+    let state = setup_test_state().await;
+    let key = CacheKey::anchor_detail(&uuid::Uuid::new_v4().to_string());
+    state.cache.set(&key, &1, 90).await.unwrap();
+    let inspect = |key: &str| {
+        inspect_cache_key(
+            State(state.clone()),
+            Query(CacheInspectQuery {
+                key: key.to_string(),
+            }),
+        )
+    };
+
+    let Json(found) = inspect(&key).await.unwrap();
+    assert!(found.exists);
+    let remaining = found.remaining_ttl_ms.unwrap();
+    assert!(remaining <= 90_000 && remaining > 80_000, "{}", remaining);
+
+    let Json(missing) = inspect("anchor:detail:nope").await.unwrap();
+    assert!(!missing.exists);
+    assert_eq!(missing.remaining_ttl_ms, None);
+
+    assert!(matches!(
+        inspect("  ").await.unwrap_err(),
+        ApiError::BadRequest(_)
+    ));
+}
+
+/// `Cache` over a plain map that records each operation it's asked for
+#[derive(Default)]
+struct RecordingCache {
+    entries: std::sync::Mutex<std::collections::HashMap<String, serde_json::Value>>,
+    calls: std::sync::Mutex<Vec<String>>,
+}
+
+impl RecordingCache {
+    fn record(&self, op: &str, key: &str) {
+        self.calls.lock().unwrap().push(format!("{} {}", op, key));
+    }
+
+    fn calls(&self, op: &str) -> Vec<String> {
+        let prefix = format!("{} ", op);
+        self.calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|call| call.strip_prefix(&prefix).map(str::to_string))
+            .collect()
+    }
+}
+
+impl Cache for RecordingCache {
+    async fn get<T>(&self, key: &str) -> Result<Option<T>, CacheError>
+    where
+        T: serde::de::DeserializeOwned + Send,
+    {
+        self.record("get", key);
+        let entry = self.entries.lock().unwrap().get(key).cloned();
+        Ok(entry.and_then(|value| serde_json::from_value(value).ok()))
+    }
+
+    async fn set<T>(&self, key: &str, value: &T, _ttl_secs: usize) -> Result<(), CacheError>
+    where
+        T: serde::Serialize + Sync,
+    {
+        self.record("set", key);
+        let value =
+            serde_json::to_value(value).map_err(|e| CacheError::Serialization(e.to_string()))?;
+        self.entries.lock().unwrap().insert(key.to_string(), value);
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), CacheError> {
+        self.record("delete", key);
+        self.entries.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn delete_pattern(&self, pattern: &str) -> Result<usize, CacheError> {
+        self.record("delete_pattern", pattern);
+        let prefix = pattern.trim_end_matches('*');
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|key, _| !key.starts_with(prefix));
+        Ok(before - entries.len())
+    }
+}
+
+#[tokio::test]
+async fn test_anchor_handlers_run_against_a_mock_cache() {
+    let state = setup_test_state().await;
+    let anchor = create_test_anchor(&state, "Mock Cache Anchor").await;
+    let cache = Arc::new(RecordingCache::default());
+    let state = state.with_cache(Arc::clone(&cache));
+    let id = uuid::Uuid::parse_str(&anchor.id).unwrap();
+    let detail_key = CacheKey::anchor_detail(&anchor.id);
+
+    let request = || {
+        get_anchor_cached(
+            State(state.clone()),
+            Path(id),
+            HeaderMap::new(),
+            CacheBypass::default(),
+        )
+    };
+
+    // A miss loads the detail and stores it
+    let first = request().await.unwrap();
+    assert_eq!(first.anchor.id, anchor.id);
+    assert_eq!(cache.calls("set"), [detail_key.as_str()]);
+
+    // The next request is answered from the mock without another write
+    let second = request().await.unwrap();
+    assert_eq!(second.anchor.id, anchor.id);
+    assert_eq!(cache.calls("set"), [detail_key.as_str()]);
+    assert!(
+        cache
+            .calls("get")
+            .iter()
+            .filter(|key| **key == detail_key)
+            .count()
+            >= 2
+    );
+}
+
+#[tokio::test]
+async fn test_cache_failures_is_empty_while_writes_fall_back_to_memory() {
+    let state = setup_test_state().await;
+    create_test_anchor(&state, "Failure Log Anchor").await;
+    get_dashboard_stats_cached(
+        State(state.clone()),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap();
+
+    // Redis is unreachable here, so nothing was ever rejected by it
+    let Json(response) = get_cache_failures(State(state.clone())).await;
+    assert!(response.failures.is_empty());
+    assert_eq!(response.capacity, state.cache.failed_writes_capacity());
+}
+
+#[tokio::test]
+async fn test_stored_timestamp_round_trips_as_rfc3339_utc() {
+    let state = setup_test_state().await;
+    let anchor = create_test_anchor(&state, "Timezone Anchor").await;
+    let id: uuid::Uuid = anchor.id.parse().unwrap();
+    sqlx::query("UPDATE anchors SET created_at = '2024-03-01 12:30:45.123456+02' WHERE id = $1")
+        .bind(&anchor.id)
+        .execute(state.db.pool())
+        .await
+        .unwrap();
+
+    let stored = state.db.get_anchor_by_id(id).await.unwrap().unwrap();
+
+    let json = serde_json::to_value(&stored).unwrap();
+    assert_eq!(json["created_at"], "2024-03-01T10:30:45.123456Z");
+}
+
+#[tokio::test]
+async fn test_upsert_anchor_creates_then_updates_by_account() {
+    let state = setup_test_state().await;
+    let stellar_account = random_stellar_account();
+    let request = |name: &str, home_domain: Option<&str>| CreateAnchorRequest {
+        name: name.to_string(),
+        stellar_account: stellar_account.clone(),
+        home_domain: home_domain.map(str::to_string),
+    };
+
+    let (created, upserted) = state
+        .db
+        .upsert_anchor(request("Upserted Anchor", None))
+        .await
+        .unwrap();
+    assert_eq!(upserted, Upserted::Created);
+    assert_eq!(created.name, "Upserted Anchor");
+
+    let (updated, upserted) = state
+        .db
+        .upsert_anchor(request("Renamed Anchor", Some("anchor.example")))
+        .await
+        .unwrap();
+    assert_eq!(upserted, Upserted::Updated);
+    assert_eq!(updated.id, created.id);
+    assert_eq!(updated.name, "Renamed Anchor");
+    assert_eq!(updated.home_domain.as_deref(), Some("anchor.example"));
+    assert_eq!(updated.created_at, created.created_at);
+}
+
+#[tokio::test]
+async fn test_put_anchor_reports_created_or_updated_and_invalidates_cache() {
+    let state = setup_test_state().await;
+    let request = |name: &str, stellar_account: &str| {
+        Json(CreateAnchorRequest {
+            name: name.to_string(),
+            stellar_account: stellar_account.to_string(),
+            home_domain: None,
+        })
+    };
+
+    let stellar_account = random_stellar_account();
+    let (status, Json(created)) = upsert_anchor_cached(
+        State(state.clone()),
+        request("Put Anchor", &stellar_account),
+    )
+    .await
+    .unwrap();
+    assert_eq!(status, StatusCode::CREATED);
+    let cached = anchor_by_account(&state, &stellar_account).await.unwrap();
+    assert_eq!(cached.name, "Put Anchor");
+
+    let (status, Json(updated)) = upsert_anchor_cached(
+        State(state.clone()),
+        request("Put Anchor Renamed", &stellar_account),
+    )
+    .await
+    .unwrap();
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated.id, created.id);
+
+    // The cached lookup from before the update was dropped
+    let cached = anchor_by_account(&state, &stellar_account).await.unwrap();
+    assert_eq!(cached.name, "Put Anchor Renamed");
+}
+
+/// An anchor issuing `PGA`, `PGB` and `PGC`, created out of order
+async fn create_paged_assets_anchor(state: &AppState) -> uuid::Uuid {
+    let anchor = create_test_anchor(state, "Paged Assets Anchor").await;
+    let id: uuid::Uuid = anchor.id.parse().unwrap();
+    for code in ["PGC", "PGA", "PGB"] {
+        state
+            .db
+            .create_asset(id, code.to_string(), anchor.stellar_account.clone())
+            .await
+            .unwrap();
+    }
+    id
+}
+
+async fn anchor_asset_codes(
+    state: &AppState,
+    id: uuid::Uuid,
+    query: AnchorAssetsQuery,
+) -> Vec<String> {
+    get_anchor_assets_cached(
+        State(state.clone()),
+        Path(id),
+        Query(query),
+        HeaderMap::new(),
+        CacheBypass::default(),
+    )
+    .await
+    .unwrap()
+    .into_inner()
+    .into_iter()
+    .map(|asset| asset.asset_code)
+    .collect()
+}
+
+#[tokio::test]
+async fn test_anchor_assets_are_served_a_page_at_a_time() {
+    let state = setup_test_state().await;
+    let id = create_paged_assets_anchor(&state).await;
+
+    // No params: the first page of the default size, which holds all three
+    let uri = format!("/api/anchors/{}/assets", id).parse().unwrap();
+    let Query(default_query) = Query::<AnchorAssetsQuery>::try_from_uri(&uri).unwrap();
+    assert_eq!(default_query.limit, 50);
+    assert_eq!(default_query.offset, 0);
+    assert_eq!(
+        anchor_asset_codes(&state, id, default_query).await,
+        ["PGA", "PGB", "PGC"]
+    );
+
+    let page = |offset| AnchorAssetsQuery { limit: 2, offset };
+    assert_eq!(
+        anchor_asset_codes(&state, id, page(0)).await,
+        ["PGA", "PGB"]
+    );
+    assert_eq!(anchor_asset_codes(&state, id, page(2)).await, ["PGC"]);
+    assert!(anchor_asset_codes(&state, id, page(4)).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_anchor_asset_pages_are_cached_under_their_own_keys() {
+    let state = setup_test_state_with(CacheConfig {
+        cacheable_list_limits: vec![2],
+        ..CacheConfig::default()
+    })
+    .await;
+    let id = create_paged_assets_anchor(&state).await;
+
+    for offset in [0, 2] {
+        anchor_asset_codes(&state, id, AnchorAssetsQuery { limit: 2, offset }).await;
+    }
+
+    for (offset, cached_len) in [(0, Some(2)), (2, Some(1)), (4, None)] {
+        let cached: Option<Vec<stellar_insights_backend::models::Asset>> = state
+            .cache
+            .get(&CacheKey::anchor_assets(&id.to_string(), 2, offset))
+            .await
+            .unwrap();
+        assert_eq!(
+            cached.map(|assets| assets.len()),
+            cached_len,
+            "offset {}",
+            offset
+        );
+    }
+}
+
+/// Send `request` through the server's public routes, as `main` mounts them
+async fn serve(state: &AppState, request: Request<Body>) -> axum::response::Response {
+    public_routes()
+        .with_state(state.clone())
+        .oneshot(request)
+        .await
+        .unwrap()
+}
+
+/// GET `uri` through the public routes: the status, headers and JSON body
+/// (`null` when there is none)
+async fn get_json(state: &AppState, uri: &str) -> (StatusCode, HeaderMap, serde_json::Value) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let (parts, body) = serve(state, request).await.into_parts();
+    let body = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+    let json = if body.is_empty() {
+        serde_json::Value::Null
+    } else {
+        serde_json::from_slice(&body).unwrap()
+    };
+    (parts.status, parts.headers, json)
+}
+
+#[tokio::test]
+async fn test_list_routes_are_served_by_the_cached_handlers() {
+    let state = setup_test_state().await;
+    for i in 0..3 {
+        create_test_anchor(&state, &format!("Routed Anchor {}", i)).await;
+    }
+
+    let (status, headers, json) = get_json(&state, "/api/v2/anchors?limit=2").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["anchors"].as_array().unwrap().len(), 2);
+    assert_eq!(headers[X_TOTAL_COUNT], json["total"].to_string().as_str());
+    // The COUNT behind `total` is cached for the next page
+    let count: Option<i64> = state
+        .cache
+        .get(&CacheKey::active_anchor_count())
+        .await
+        .unwrap();
+    assert_eq!(Some(json["total"].as_i64().unwrap()), count);
+
+    let (status, headers, json) = get_json(&state, "/api/v2/corridors").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(json["corridors"].is_array());
+    assert_eq!(headers[X_TOTAL_COUNT], json["total"].to_string().as_str());
+}
+
+#[tokio::test]
+async fn test_legacy_list_routes_keep_the_frontend_shapes() {
+    let state = setup_test_state().await;
+    let marker = uuid::Uuid::new_v4().simple().to_string();
+    let anchor = create_test_anchor(&state, &format!("Legacy {}", marker)).await;
+
+    let (status, headers, json) = get_json(&state, &format!("/api/anchors?q={}", marker)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers[X_TOTAL_COUNT], "1");
+    assert_eq!(json["total"], 1);
+    assert_eq!(json["anchors"][0]["id"], anchor.id.as_str());
+    assert_eq!(json["anchors"][0]["asset_coverage"], 0);
+    assert_eq!(json["anchors"][0]["failure_rate"], 0.0);
+
+    // The frontend's filters and sort columns, not the v2 allowlist
+    let (status, _, json) = get_json(
+        &state,
+        "/api/corridors?success_rate_min=50&volume_min=0&asset_code=usdc&sort_by=health_score",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(json.is_array());
+}
+
+#[tokio::test]
+async fn test_list_routes_clamp_limit_and_reject_negative_bounds() {
+    let state = setup_test_state_with(CacheConfig {
+        max_list_limit: 5,
+        ..CacheConfig::default()
+    })
+    .await;
+
+    for path in ["/api/v2/anchors", "/api/v2/corridors"] {
+        let (status, headers, _) = get_json(&state, &format!("{}?limit=1000000", path)).await;
+        assert_eq!(status, StatusCode::OK, "{}", path);
+        assert_eq!(headers[X_LIMIT_CLAMPED], "5", "{}", path);
+
+        for bounds in ["limit=-1", "offset=-1"] {
+            let (status, _, _) = get_json(&state, &format!("{}?{}", path, bounds)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}?{}", path, bounds);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_list_routes_report_the_following_page() {
+    let state = setup_test_state().await;
+    let marker = uuid::Uuid::new_v4().simple().to_string();
+    for i in 0..3 {
+        create_test_anchor(&state, &format!("Paged {} {}", marker, i)).await;
+    }
+    for _ in 0..2 {
+        create_test_corridor_id(&state).await;
+    }
+
+    let (_, _, json) = get_json(&state, &format!("/api/v2/anchors?q={}&limit=2", marker)).await;
+    assert_eq!(json["has_more"], true);
+    assert_eq!(json["next_offset"], 2);
+    let (_, _, json) = get_json(
+        &state,
+        &format!("/api/v2/anchors?q={}&limit=2&offset=2", marker),
+    )
+    .await;
+    assert_eq!(json["has_more"], false);
+    assert!(json["next_offset"].is_null());
+
+    let (_, _, json) = get_json(&state, "/api/v2/corridors?limit=1").await;
+    assert_eq!(json["has_more"], true);
+    assert_eq!(json["next_offset"], 1);
+}
+
+#[tokio::test]
+async fn test_corridor_list_route_applies_filters_under_their_own_key() {
+    let state = setup_test_state().await;
+    let issuer = format!("G{}", uuid::Uuid::new_v4().simple());
+    state
+        .db
+        .create_corridor(corridor_request(&issuer, None))
+        .await
+        .unwrap();
+    create_test_corridor_id(&state).await;
+
+    let (status, _, json) = get_json(
+        &state,
+        &format!("/api/v2/corridors?source_asset=usdc:{}", issuer),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["total"], 1);
+    assert_eq!(json["corridors"][0]["asset_a_issuer"], issuer.as_str());
+    let filters = CorridorFilters {
+        source_asset: Some(format!("USDC:{}", issuer)),
+        ..CorridorFilters::default()
+    };
+    let key = CacheKey::corridor_list(50, 0, &filters.cache_token(None));
+    let cached: Option<ListCorridorsResponse> = state.cache.get(&key).await.unwrap();
+    assert_eq!(cached.unwrap().total, 1);
+}
+
+#[tokio::test]
+async fn test_corridor_list_route_sorts_by_allowlisted_columns_only() {
+    let state = setup_test_state().await;
+    create_test_corridor_id(&state).await;
+
+    let (status, _, json) =
+        get_json(&state, "/api/v2/corridors?sort_by=created_at&order=desc").await;
+    assert_eq!(status, StatusCode::OK);
+    let cached: Option<ListCorridorsResponse> = state
+        .cache
+        .get(&CacheKey::corridor_list(50, 0, "created_at:desc"))
+        .await
+        .unwrap();
+    assert_eq!(cached.unwrap().total, json["total"].as_i64().unwrap());
+
+    let (status, _, _) = get_json(&state, "/api/v2/corridors?sort_by=asset_a_issuer").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_list_routes_answer_304_for_a_matching_etag() {
+    let state = setup_test_state().await;
+    let marker = uuid::Uuid::new_v4().simple().to_string();
+    create_test_anchor(&state, &format!("ETag {}", marker)).await;
+    let issuer = format!("G{}", uuid::Uuid::new_v4().simple());
+    state
+        .db
+        .create_corridor(corridor_request(&issuer, None))
+        .await
+        .unwrap();
+
+    // Filtered so rows other tests create can't change the body in between
+    for uri in [
+        format!("/api/v2/anchors?q={}", marker),
+        format!("/api/v2/corridors?source_asset=USDC:{}", issuer),
+    ] {
+        let (status, headers, _) = get_json(&state, &uri).await;
+        assert_eq!(status, StatusCode::OK, "{}", uri);
+        let etag = headers[header::ETAG].clone();
+
+        let request = Request::builder()
+            .uri(uri.as_str())
+            .header(header::IF_NONE_MATCH, etag)
+            .body(Body::empty())
+            .unwrap();
+        let response = serve(&state, request).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED, "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn test_list_routes_serve_the_documented_response_shapes() {
+    let state = setup_test_state().await;
+    create_test_anchor(&state, "Documented Shape Anchor").await;
+    create_test_corridor_id(&state).await;
+
+    // The schemas openapi.rs documents for these paths
+    let (_, _, anchors) = get_json(&state, "/api/v2/anchors").await;
+    let anchors: ListAnchorsResponse = serde_json::from_value(anchors).unwrap();
+    assert!(!anchors.anchors.is_empty());
+    let (_, _, corridors) = get_json(&state, "/api/v2/corridors").await;
+    let corridors: ListCorridorsResponse = serde_json::from_value(corridors).unwrap();
+    assert!(!corridors.corridors.is_empty());
+}
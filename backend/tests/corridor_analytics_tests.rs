@@ -7,16 +7,22 @@ fn test_corridor_metrics_basic() {
             successful: true,
             settlement_latency_ms: Some(1000),
             amount_usd: 100.0,
+            occurred_at: None,
+            currency: None,
         },
         CorridorTransaction {
             successful: true,
             settlement_latency_ms: Some(3000),
             amount_usd: 200.0,
+            occurred_at: None,
+            currency: None,
         },
         CorridorTransaction {
             successful: false,
             settlement_latency_ms: None,
             amount_usd: 50.0,
+            occurred_at: None,
+            currency: None,
         },
     ];
 
@@ -46,11 +52,15 @@ fn test_corridor_metrics_all_success_no_latency() {
             successful: true,
             settlement_latency_ms: None,
             amount_usd: 10.0,
+            occurred_at: None,
+            currency: None,
         },
         CorridorTransaction {
             successful: true,
             settlement_latency_ms: None,
             amount_usd: 20.0,
+            occurred_at: None,
+            currency: None,
         },
     ];
     let m = compute_corridor_metrics(&txns, None, 1.0);
@@ -0,0 +1,63 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use sqlx::postgres::PgPoolOptions;
+use std::sync::Arc;
+use std::time::Duration;
+
+use stellar_insights_backend::cache::{CacheConfig, RedisCache};
+use stellar_insights_backend::database::Database;
+use stellar_insights_backend::handlers::{readiness_check, DependencyStatus};
+use stellar_insights_backend::ingestion::DataIngestionService;
+use stellar_insights_backend::rpc::StellarRpcClient;
+use stellar_insights_backend::services::analytics::AnomalyThresholds;
+use stellar_insights_backend::state::AppState;
+use stellar_insights_backend::websocket::WsState;
+
+async fn state_for_database(database_url: &str) -> AppState {
+    // Lazy so a dead database only shows up when readiness queries it
+    let pool = PgPoolOptions::new()
+        .acquire_timeout(Duration::from_millis(500))
+        .connect_lazy(database_url)
+        .unwrap();
+
+    let db = Arc::new(Database::new(pool));
+    let rpc_client = Arc::new(StellarRpcClient::new_with_defaults(true));
+    let ingestion = Arc::new(DataIngestionService::new(rpc_client, Arc::clone(&db)));
+    // Closed port so the cache runs against its memory tier
+    let cache = Arc::new(RedisCache::from_url("redis://127.0.0.1:1").await.unwrap());
+
+    AppState::new(
+        db,
+        Arc::new(WsState::new()),
+        ingestion,
+        cache,
+        CacheConfig::default(),
+        AnomalyThresholds::default(),
+    )
+}
+
+#[tokio::test]
+async fn test_ready_when_database_answers_even_without_redis() {
+    let database_url =
+        std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for integration tests");
+    let state = state_for_database(&database_url).await;
+
+    let (status, Json(body)) = readiness_check(State(state)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.ready);
+    assert_eq!(body.database, DependencyStatus::Up);
+    assert_eq!(body.redis, DependencyStatus::Down);
+}
+
+#[tokio::test]
+async fn test_not_ready_when_database_is_down() {
+    let state = state_for_database("postgres://postgres@127.0.0.1:1/stellar_insights").await;
+
+    let (status, Json(body)) = readiness_check(State(state)).await;
+
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(!body.ready);
+    assert_eq!(body.database, DependencyStatus::Down);
+}